@@ -5,6 +5,7 @@
 , mold
 , lib
 , openssl
+, alsa-lib
 , wayland
 , libGL
 , libxkbcommon
@@ -27,6 +28,7 @@ rustPlatform.buildRustPackage {
   buildInputs = [
     openssl
     wayland
+    alsa-lib
   ];
   postInstall = ''
     mkdir -p $out/share/applications
@@ -1092,6 +1092,638 @@ pub async fn sharepoint_download_file(
     }
 }
 
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn channel_files_folder(
+    token: &AccessToken,
+    group_id: &str,
+    channel_id: &str,
+) -> Result<DriveItem, Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://graph.microsoft.com/v1.0/teams/{}/channels/{}/filesFolder",
+        group_id, channel_id
+    );
+
+    if LOG_REQUESTS {
+        println!("Log: GET {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = reqwest::header::HeaderMap::new();
+
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.get(url).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        let body = res.text().await?;
+        let parsed_body: Value = serde_json::from_str(&body).expect("Invalid JSON");
+        let pretty_json =
+            serde_json::to_string_pretty(&parsed_body).expect("Failed to format JSON");
+        let result: Result<DriveItem, serde_json::Error> = serde_json::from_str(&pretty_json);
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                eprintln!("Error occurred while serializing: {}", err);
+                let line_content = pretty_json.lines().nth(err.line() - 1).unwrap();
+                eprintln!("Line: {}", line_content);
+
+                Err(err.into())
+            }
+        }
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn drive_item_children(
+    token: &AccessToken,
+    drive_id: &str,
+    item_id: &str,
+) -> Result<DriveItemCollection, Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://graph.microsoft.com/v1.0/drives/{}/items/{}/children?$top=999",
+        drive_id, item_id
+    );
+
+    if LOG_REQUESTS {
+        println!("Log: GET {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = reqwest::header::HeaderMap::new();
+
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.get(url).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        let body = res.text().await?;
+        let parsed_body: Value = serde_json::from_str(&body).expect("Invalid JSON");
+        let pretty_json =
+            serde_json::to_string_pretty(&parsed_body).expect("Failed to format JSON");
+        let result: Result<DriveItemCollection, serde_json::Error> =
+            serde_json::from_str(&pretty_json);
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                eprintln!("Error occurred while serializing: {}", err);
+                let line_content = pretty_json.lines().nth(err.line() - 1).unwrap();
+                eprintln!("Line: {}", line_content);
+
+                Err(err.into())
+            }
+        }
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+// Only supports files up to 250 MB, larger files need an upload session
+pub async fn upload_drive_item(
+    token: &AccessToken,
+    drive_id: &str,
+    parent_id: &str,
+    file_name: &str,
+    bytes: Vec<u8>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://graph.microsoft.com/v1.0/drives/{}/items/{}:/{}:/content",
+        drive_id,
+        parent_id,
+        urlencoding::encode(file_name)
+    );
+
+    if LOG_REQUESTS {
+        println!("Log: PUT {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = reqwest::header::HeaderMap::new();
+
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+    headers.insert(
+        reqwest::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.put(url).body(bytes).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        Ok(())
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn channel_tabs(
+    token: &AccessToken,
+    group_id: &str,
+    channel_id: &str,
+) -> Result<TeamsTabCollection, Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://graph.microsoft.com/v1.0/teams/{}/channels/{}/tabs?$expand=teamsApp",
+        group_id, channel_id
+    );
+
+    if LOG_REQUESTS {
+        println!("Log: GET {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = reqwest::header::HeaderMap::new();
+
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.get(url).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        let body = res.text().await?;
+        let parsed_body: Value = serde_json::from_str(&body).expect("Invalid JSON");
+        let pretty_json =
+            serde_json::to_string_pretty(&parsed_body).expect("Failed to format JSON");
+        let result: Result<TeamsTabCollection, serde_json::Error> =
+            serde_json::from_str(&pretty_json);
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                eprintln!("Error occurred while serializing: {}", err);
+                let line_content = pretty_json.lines().nth(err.line() - 1).unwrap();
+                eprintln!("Line: {}", line_content);
+
+                Err(err.into())
+            }
+        }
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn plan_tasks(
+    token: &AccessToken,
+    plan_id: &str,
+) -> Result<PlannerTaskCollection, Box<dyn std::error::Error>> {
+    let url = format!("https://graph.microsoft.com/v1.0/planner/plans/{}/tasks", plan_id);
+
+    if LOG_REQUESTS {
+        println!("Log: GET {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = reqwest::header::HeaderMap::new();
+
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.get(url).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        let body = res.text().await?;
+        let parsed_body: Value = serde_json::from_str(&body).expect("Invalid JSON");
+        let pretty_json =
+            serde_json::to_string_pretty(&parsed_body).expect("Failed to format JSON");
+        let result: Result<PlannerTaskCollection, serde_json::Error> =
+            serde_json::from_str(&pretty_json);
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                eprintln!("Error occurred while serializing: {}", err);
+                let line_content = pretty_json.lines().nth(err.line() - 1).unwrap();
+                eprintln!("Line: {}", line_content);
+
+                Err(err.into())
+            }
+        }
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn section_onenote_pages(
+    token: &AccessToken,
+    group_id: &str,
+    section_id: &str,
+) -> Result<OnenotePageCollection, Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://graph.microsoft.com/v1.0/groups/{}/onenote/sections/{}/pages?$top=50&$orderby=lastModifiedDateTime%20desc",
+        group_id, section_id
+    );
+
+    if LOG_REQUESTS {
+        println!("Log: GET {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = reqwest::header::HeaderMap::new();
+
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.get(url).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        let body = res.text().await?;
+        let parsed_body: Value = serde_json::from_str(&body).expect("Invalid JSON");
+        let pretty_json =
+            serde_json::to_string_pretty(&parsed_body).expect("Failed to format JSON");
+        let result: Result<OnenotePageCollection, serde_json::Error> =
+            serde_json::from_str(&pretty_json);
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                eprintln!("Error occurred while serializing: {}", err);
+                let line_content = pretty_json.lines().nth(err.line() - 1).unwrap();
+                eprintln!("Line: {}", line_content);
+
+                Err(err.into())
+            }
+        }
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn create_channel(
+    token: &AccessToken,
+    group_id: &str,
+    body: String,
+) -> Result<Channel, Box<dyn std::error::Error>> {
+    let url = format!("https://graph.microsoft.com/v1.0/teams/{}/channels", group_id);
+
+    if LOG_REQUESTS {
+        println!("Log: POST {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+    headers.insert(
+        HeaderName::from_static("content-type"),
+        HeaderValue::from_static("application/json"),
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.post(url).body(body).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        let body = res.text().await?;
+        let parsed_body: Value = serde_json::from_str(&body).expect("Invalid JSON");
+        let pretty_json =
+            serde_json::to_string_pretty(&parsed_body).expect("Failed to format JSON");
+        let result: Result<Channel, serde_json::Error> = serde_json::from_str(&pretty_json);
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                eprintln!("Error occurred while serializing: {}", err);
+                let line_content = pretty_json.lines().nth(err.line() - 1).unwrap();
+                eprintln!("Line: {}", line_content);
+
+                Err(err.into())
+            }
+        }
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn update_channel(
+    token: &AccessToken,
+    group_id: &str,
+    channel_id: &str,
+    body: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://graph.microsoft.com/v1.0/teams/{}/channels/{}",
+        group_id, channel_id
+    );
+
+    if LOG_REQUESTS {
+        println!("Log: PATCH {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+    headers.insert(
+        HeaderName::from_static("content-type"),
+        HeaderValue::from_static("application/json"),
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.patch(url).body(body).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        Ok(())
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn delete_channel(
+    token: &AccessToken,
+    group_id: &str,
+    channel_id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://graph.microsoft.com/v1.0/teams/{}/channels/{}",
+        group_id, channel_id
+    );
+
+    if LOG_REQUESTS {
+        println!("Log: DELETE {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.delete(url).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        Ok(())
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn team_members(
+    token: &AccessToken,
+    group_id: &str,
+) -> Result<TeamMemberCollection, Box<dyn std::error::Error>> {
+    let url = format!("https://graph.microsoft.com/v1.0/teams/{}/members", group_id);
+
+    if LOG_REQUESTS {
+        println!("Log: GET {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.get(url).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        let body = res.text().await?;
+        let parsed_body: Value = serde_json::from_str(&body).expect("Invalid JSON");
+        let pretty_json =
+            serde_json::to_string_pretty(&parsed_body).expect("Failed to format JSON");
+        let result: Result<TeamMemberCollection, serde_json::Error> =
+            serde_json::from_str(&pretty_json);
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                eprintln!("Error occurred while serializing: {}", err);
+                let line_content = pretty_json.lines().nth(err.line() - 1).unwrap();
+                eprintln!("Line: {}", line_content);
+
+                Err(err.into())
+            }
+        }
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn update_team_member(
+    token: &AccessToken,
+    group_id: &str,
+    membership_id: &str,
+    body: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://graph.microsoft.com/v1.0/teams/{}/members/{}",
+        group_id, membership_id
+    );
+
+    if LOG_REQUESTS {
+        println!("Log: PATCH {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+    headers.insert(
+        HeaderName::from_static("content-type"),
+        HeaderValue::from_static("application/json"),
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.patch(url).body(body).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        Ok(())
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: Graph
+// Scope: https://graph.microsoft.com/.default
+pub async fn remove_team_member(
+    token: &AccessToken,
+    group_id: &str,
+    membership_id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://graph.microsoft.com/v1.0/teams/{}/members/{}",
+        group_id, membership_id
+    );
+
+    if LOG_REQUESTS {
+        println!("Log: DELETE {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.delete(url).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        Ok(())
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
 // Api: Emea v2
 // Scope: https://chatsvcagg.teams.microsoft.com/.default
 pub async fn team_conversations(
@@ -1344,13 +1976,19 @@ pub async fn authorize_team_picture(
     }
 }
 
+pub enum PictureResponse {
+    NotModified,
+    Modified(Bytes, Option<String>), // The picture and its etag
+}
+
 // Api: Emea v2
 // Scope: https://api.spaces.skype.com/Authorization.ReadWrite
 pub async fn authorize_profile_picture(
     token: &AccessToken,
     user_id: &str,
     display_name: &str,
-) -> Result<Bytes, Box<dyn std::error::Error>> {
+    etag: Option<String>,
+) -> Result<PictureResponse, Box<dyn std::error::Error>> {
     let url = format!(
         "https://teams.microsoft.com/api/mt/part/emea-02/beta/users/{}/profilepicturev2",
         user_id
@@ -1374,6 +2012,12 @@ pub async fn authorize_profile_picture(
             .as_str(),
         )?,
     );
+    if let Some(etag) = etag {
+        headers.insert(
+            HeaderName::from_static("if-none-match"),
+            HeaderValue::from_str(&etag)?,
+        );
+    }
 
     let params = [("displayname", display_name), ("size", "HR64x64")];
 
@@ -1388,9 +2032,16 @@ pub async fn authorize_profile_picture(
         .send()
         .await?;
 
-    if res.status().is_success() {
+    if res.status() == StatusCode::NOT_MODIFIED {
+        Ok(PictureResponse::NotModified)
+    } else if res.status().is_success() {
+        let etag = res
+            .headers()
+            .get("etag")
+            .and_then(|etag| etag.to_str().ok())
+            .map(|etag| etag.to_string());
         let bytes = res.bytes().await?;
-        Ok(bytes)
+        Ok(PictureResponse::Modified(bytes, etag))
     } else {
         let error_message = format!(
             "Status code: {}, Response body: {}",
@@ -1439,6 +2090,203 @@ pub async fn authorize_image(
     }
 }
 
+// Api: AMS
+// Scope: Skype
+pub async fn create_ams_object(
+    token: &AccessToken,
+    body: String,
+) -> Result<AmsObject, Box<dyn std::error::Error>> {
+    let url = "https://eu-api.asm.skype.com/v1/objects";
+    if LOG_REQUESTS {
+        println!("Log: POST {}", url);
+    }
+
+    let access_token = format!("skype_token {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+    headers.insert(
+        HeaderName::from_static("content-type"),
+        HeaderValue::from_static("application/json"),
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.post(url).body(body).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        let body = res.text().await?;
+        let parsed_body: Value = serde_json::from_str(&body).expect("Invalid JSON");
+        let pretty_json =
+            serde_json::to_string_pretty(&parsed_body).expect("Failed to format JSON");
+        let result: Result<AmsObject, serde_json::Error> = serde_json::from_str(&pretty_json);
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                eprintln!("Error occurred while serializing: {}", err);
+                let line_content = pretty_json.lines().nth(err.line() - 1).unwrap();
+                eprintln!("Line: {}", line_content);
+
+                Err(err.into())
+            }
+        }
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+// Api: AMS
+// Scope: Skype
+pub async fn upload_ams_image(
+    token: &AccessToken,
+    object_id: &str,
+    bytes: Vec<u8>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://eu-api.asm.skype.com/v1/objects/{}/content/imgpsh",
+        object_id
+    );
+    if LOG_REQUESTS {
+        println!("Log: PUT {}", url);
+    }
+
+    let access_token = format!("skype_token {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+    headers.insert(
+        HeaderName::from_static("content-type"),
+        HeaderValue::from_static("application/octet-stream"),
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.put(url).body(bytes).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        Ok(())
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
+const MAX_PAGE_BYTES: usize = 2_000_000;
+
+// Api: Any website, the page is read for link previews
+// Scope: None
+pub async fn fetch_page_html(url: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if LOG_REQUESTS {
+        println!("Log: GET {}", url);
+    }
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "User-Agent",
+        "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0"
+            .parse()
+            .unwrap(),
+    );
+    headers.insert(
+        HeaderName::from_static("accept"),
+        HeaderValue::from_static("text/html"),
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::limited(5))
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?;
+
+    let mut res = client.get(url).headers(headers).send().await?;
+
+    let is_html = res
+        .headers()
+        .get("content-type")
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.contains("text/html"));
+
+    if !res.status().is_success() || !is_html {
+        return Err(format!("Status code: {}, not an html page", res.status()).into());
+    }
+
+    // Open Graph tags are in the head, only the start of large pages is downloaded
+    let mut bytes = vec![];
+    while let Some(chunk) = res.chunk().await? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() >= MAX_PAGE_BYTES {
+            bytes.truncate(MAX_PAGE_BYTES);
+            break;
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+// Api: AMS
+// Scope: Skype
+pub async fn upload_ams_audio(
+    token: &AccessToken,
+    object_id: &str,
+    bytes: Vec<u8>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://eu-api.asm.skype.com/v1/objects/{}/content/audio",
+        object_id
+    );
+    if LOG_REQUESTS {
+        println!("Log: PUT {}", url);
+    }
+
+    let access_token = format!("skype_token {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+    headers.insert(
+        HeaderName::from_static("content-type"),
+        HeaderValue::from_static("application/octet-stream"),
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.put(url).body(bytes).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        Ok(())
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
 // Api: Emea v2
 // Scope: Skype
 pub async fn authorize_merged_profile_picture(
@@ -1579,6 +2427,48 @@ pub async fn delete_message(
     }
 }
 
+// Api: Emea v1
+// Scope: https://ic3.teams.office.com/.default
+pub async fn edit_message(
+    token: &AccessToken,
+    conversation_id: &str,
+    message_id: &str,
+    body: String,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://teams.microsoft.com/api/chatsvc/emea/v1/users/ME/conversations/{}/messages/{}",
+        conversation_id, message_id,
+    );
+    if LOG_REQUESTS {
+        println!("Log: PUT {}", url);
+    }
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.put(url).body(body).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        let body = res.text().await?;
+        Ok(body)
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
+
 // Api: Emea v1
 // Scope: https://ic3.teams.office.com/.default
 pub async fn start_thread(
@@ -1656,3 +2546,55 @@ pub async fn add_member(
         Err(error_message.into())
     }
 }
+
+// Api: Emea v1
+// Scope: https://chatsvcagg.teams.microsoft.com/.default
+pub async fn custom_emojis(
+    token: &AccessToken,
+) -> Result<CustomEmojiMetadata, Box<dyn std::error::Error>> {
+    let url = "https://teams.microsoft.com/api/csa/emea/api/v1/customemoji/metadata";
+    if LOG_REQUESTS {
+        println!("Log: GET {}", url);
+    }
+
+    let access_token = format!("Bearer {}", token.value);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&access_token)?,
+    );
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let res = client.get(url).headers(headers).send().await?;
+
+    if res.status().is_success() {
+        let body = res.text().await?;
+        let parsed_body: Value = serde_json::from_str(&body).expect("Invalid JSON");
+        let pretty_json =
+            serde_json::to_string_pretty(&parsed_body).expect("Failed to format JSON");
+        let result: Result<CustomEmojiMetadata, serde_json::Error> =
+            serde_json::from_str(&pretty_json);
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                eprintln!("Error occurred while serializing: {}", err);
+                let line_content = pretty_json.lines().nth(err.line() - 1).unwrap();
+                eprintln!("Line: {}", line_content);
+
+                Err(err.into())
+            }
+        }
+    } else {
+        let error_message = format!(
+            "Status code: {}, Response body: {}",
+            res.status(),
+            res.text().await?
+        );
+        Err(error_message.into())
+    }
+}
@@ -165,6 +165,12 @@ pub struct MessageProperties {
     #[serde(deserialize_with = "deserialize_cards")]
     pub cards: Option<Vec<Card>>,
     #[serde(default)]
+    #[serde(deserialize_with = "deserialize_links")]
+    pub links: Option<Vec<Link>>,
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_mentions")]
+    pub mentions: Option<Vec<Mention>>,
+    #[serde(default)]
     #[serde(deserialize_with = "string_to_i64")]
     pub deletetime: i64,
     #[serde(default)]
@@ -209,6 +215,40 @@ pub struct Card {
     pub preview_hidden: Option<bool>,
 }
 
+// Teams unfurls urls into these, the preview is shown as a card below the message
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    #[serde(rename = "@type")]
+    pub link_type: String,
+    #[serde(default)]
+    #[serde(deserialize_with = "string_to_i64")]
+    pub itemid: i64,
+    pub url: String,
+    #[serde(default)]
+    pub previewenabled: bool,
+    pub preview: Option<UrlPreview>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Mention {
+    pub mri: String,
+    pub display_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UrlPreview {
+    pub previewurl: Option<String>,
+    pub previewfilename: Option<String>,
+    #[serde(rename = "type")]
+    pub preview_type: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "isLinkUnsafe")]
+    #[serde(default)]
+    pub is_link_unsafe: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Message {
@@ -505,6 +545,152 @@ where
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveItem {
+    pub id: String,
+    pub name: String,
+    pub size: Option<i64>,
+    pub web_url: Option<String>,
+    pub last_modified_date_time: Option<String>,
+    pub last_modified_by: Option<IdentitySet>,
+    pub folder: Option<DriveFolder>,
+    pub file: Option<DriveFile>,
+    pub parent_reference: Option<ItemReference>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveItemCollection {
+    pub value: Vec<DriveItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentitySet {
+    pub user: Option<Identity>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Identity {
+    pub id: Option<String>,
+    pub display_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveFolder {
+    pub child_count: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveFile {
+    pub mime_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemReference {
+    pub drive_id: Option<String>,
+    pub id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamsTabCollection {
+    pub value: Vec<TeamsTab>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamsTab {
+    pub id: String,
+    pub display_name: String,
+    pub web_url: Option<String>,
+    pub configuration: Option<TeamsTabConfiguration>,
+    pub teams_app: Option<TeamsApp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamsTabConfiguration {
+    pub entity_id: Option<String>,
+    pub content_url: Option<String>,
+    pub website_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamsApp {
+    pub id: String,
+    pub display_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannerTaskCollection {
+    pub value: Vec<PlannerTask>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannerTask {
+    pub id: String,
+    pub title: String,
+    pub percent_complete: Option<i64>,
+    pub due_date_time: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OnenotePageCollection {
+    pub value: Vec<OnenotePage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OnenotePage {
+    pub id: String,
+    pub title: Option<String>,
+    pub last_modified_date_time: Option<String>,
+    pub links: Option<OnenotePageLinks>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OnenotePageLinks {
+    pub one_note_web_url: Option<ExternalLink>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalLink {
+    pub href: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamMemberCollection {
+    pub value: Vec<TeamMember>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamMember {
+    pub id: String, // Membership id
+    pub display_name: Option<String>,
+    pub user_id: Option<String>,
+    pub roles: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AmsObject {
+    pub id: String,
+}
+
 fn deserialize_cards<'de, D>(deserializer: D) -> Result<Option<Vec<Card>>, D::Error>
 where
     D: Deserializer<'de>,
@@ -523,3 +709,54 @@ where
         None => Ok(None),
     }
 }
+
+// Links in an unexpected shape are left out instead of failing the whole message
+fn deserialize_links<'de, D>(deserializer: D) -> Result<Option<Vec<Link>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<Value>::deserialize(deserializer)?;
+    let links = match value {
+        Some(Value::String(json_str)) => serde_json::from_str::<Vec<Link>>(&json_str).ok(),
+        Some(value @ Value::Array(_)) => serde_json::from_value::<Vec<Link>>(value).ok(),
+        _ => None,
+    };
+    Ok(links.filter(|links| !links.is_empty()))
+}
+
+fn deserialize_mentions<'de, D>(deserializer: D) -> Result<Option<Vec<Mention>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<Value>::deserialize(deserializer)?;
+    let mentions = match value {
+        Some(Value::String(json_str)) => serde_json::from_str::<Vec<Mention>>(&json_str).ok(),
+        Some(value @ Value::Array(_)) => serde_json::from_value::<Vec<Mention>>(value).ok(),
+        _ => None,
+    };
+    Ok(mentions.filter(|mentions| !mentions.is_empty()))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomEmojiMetadata {
+    pub categories: Vec<CustomEmojiCategory>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomEmojiCategory {
+    pub id: String,
+    pub title: Option<String>,
+    pub emoticons: Vec<CustomEmoji>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomEmoji {
+    pub id: String, // Used as the reaction key
+    pub document_id: String, // AMS object id of the image
+    pub description: Option<String>,
+    #[serde(default)]
+    pub shortcuts: Vec<String>,
+}
@@ -1,60 +1,286 @@
-use crate::Message;
-use crate::widgets::{gif::Gif, viewport::ViewportHandler};
+use crate::{Message, style};
+use crate::widgets::{
+    gif::Gif,
+    viewport::{ViewportHandler, forget_identifier},
+};
 use bytes::Bytes;
 use directories::ProjectDirs;
 use iced::widget::{container, image, space};
-use iced::{Color, ContentFit, Element};
-use std::str::FromStr;
+use iced::{ContentFit, Element};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
 use std::{
-    fs::{File, create_dir_all},
-    io::Write,
-    path::Path,
+    fs::{self, File, create_dir_all, read_dir, remove_dir_all, remove_file, rename},
+    io::{Read, Write},
+    path::PathBuf,
 };
-pub fn save_cached_image(identifier: String, extension: &str, bytes: Bytes) {
+
+// Extensions the cache writes, "jpeg" is from before the content was sniffed
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "png", "gif", "webp", "bmp", "jpeg"];
+
+struct CachedImage {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+    touched: bool, // Whether the modified time was already updated this session
+}
+
+// Index of the cache directory, file modified times carry the last use across sessions
+static IMAGE_CACHE: LazyLock<Mutex<HashMap<String, CachedImage>>> =
+    LazyLock::new(|| Mutex::new(scan_image_cache()));
+
+static IMAGE_CACHE_LIMIT: AtomicU64 = AtomicU64::new(0); // In bytes, 0 is no limit
+
+pub fn image_cache_dir() -> PathBuf {
     let project_dirs = ProjectDirs::from("", "ianterzo", "squads");
+    project_dirs.unwrap().cache_dir().join("image-cache")
+}
+
+fn sniff_image_extension(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("gif")
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else if bytes.starts_with(b"BM") {
+        Some("bmp")
+    } else {
+        None
+    }
+}
+
+// Removes interrupted writes and files that aren't images, like error pages
+fn scan_image_cache() -> HashMap<String, CachedImage> {
+    let mut images = HashMap::new();
+
+    let Ok(entries) = read_dir(image_cache_dir()) else {
+        return images;
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        if file_name.ends_with(".tmp") {
+            let _ = remove_file(&path);
+            continue;
+        }
+
+        let Some((identifier, extension)) = file_name.rsplit_once('.') else {
+            continue;
+        };
+        if !IMAGE_EXTENSIONS.contains(&extension) {
+            continue;
+        }
+
+        let mut header = [0; 12];
+        let is_valid = File::open(&path)
+            .and_then(|mut file| file.read_exact(&mut header))
+            .is_ok()
+            && sniff_image_extension(&header).is_some();
+
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+
+        if !is_valid {
+            let _ = remove_file(&path);
+            let _ = remove_file(path.with_extension("etag"));
+            continue;
+        }
+
+        images.insert(
+            identifier.to_string(),
+            CachedImage {
+                size: metadata.len(),
+                last_used: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                path,
+                touched: false,
+            },
+        );
+    }
+
+    images
+}
+
+// Total size of the cached images in bytes
+pub fn image_cache_size() -> u64 {
+    IMAGE_CACHE
+        .lock()
+        .unwrap()
+        .values()
+        .map(|image| image.size)
+        .sum()
+}
+
+pub fn clear_image_cache() {
+    let mut images = IMAGE_CACHE.lock().unwrap();
+    // Images that are still being attached to a message are kept
+    images.retain(|identifier, image| {
+        if identifier.starts_with("pending") {
+            return true;
+        }
+        let _ = remove_file(&image.path);
+        let _ = remove_file(image.path.with_extension("etag"));
+        forget_identifier(identifier);
+        false
+    });
+
+    if images.is_empty() {
+        let _ = remove_dir_all(image_cache_dir());
+    }
+}
+
+pub fn set_image_cache_limit(limit_mb: u64) {
+    IMAGE_CACHE_LIMIT.store(limit_mb * 1_000_000, Ordering::Relaxed);
+    evict_image_cache();
+}
+
+// Removes the least recently used images until the cache is 10% below its limit, an evicted
+// image that comes back on screen is downloaded again
+fn evict_image_cache() {
+    let limit = IMAGE_CACHE_LIMIT.load(Ordering::Relaxed);
+    if limit == 0 {
+        return;
+    }
+
+    let mut images = IMAGE_CACHE.lock().unwrap();
+    let mut total_size: u64 = images.values().map(|image| image.size).sum();
+    if total_size <= limit {
+        return;
+    }
+
+    let mut by_last_use: Vec<(String, SystemTime)> = images
+        .iter()
+        .filter(|(identifier, _)| !identifier.starts_with("pending"))
+        .map(|(identifier, image)| (identifier.clone(), image.last_used))
+        .collect();
+    by_last_use.sort_by_key(|(_, last_used)| *last_used);
+
+    for (identifier, _) in by_last_use {
+        if total_size <= limit / 10 * 9 {
+            break;
+        }
+        if let Some(image) = images.remove(&identifier) {
+            let _ = remove_file(&image.path);
+            let _ = remove_file(image.path.with_extension("etag"));
+            forget_identifier(&identifier);
+            total_size -= image.size;
+        }
+    }
+}
+
+// The path of a complete cached image
+pub fn cached_image_path(identifier: &str) -> Option<PathBuf> {
+    let images = IMAGE_CACHE.lock().unwrap();
+    images.get(identifier).map(|image| image.path.clone())
+}
+
+// Marks a cached image as used when it enters the screen, returns whether it is cached
+pub fn mark_image_used(identifier: &str) -> bool {
+    let mut images = IMAGE_CACHE.lock().unwrap();
+    let Some(image) = images.get_mut(identifier) else {
+        return false;
+    };
+
+    image.last_used = SystemTime::now();
+    if !image.touched {
+        image.touched = true;
+        if let Ok(file) = File::options().append(true).open(&image.path) {
+            let _ = file.set_modified(image.last_used);
+        }
+    }
+
+    true
+}
+
+pub fn cached_image_etag(identifier: &str) -> Option<String> {
+    let path = cached_image_path(identifier)?;
+    fs::read_to_string(path.with_extension("etag")).ok()
+}
 
-    let mut cache_dir = project_dirs.unwrap().cache_dir().to_path_buf();
-    cache_dir.push("image-cache");
+// Written to a temporary file first so a crash never leaves a partial image behind
+pub fn save_cached_image(identifier: String, bytes: Bytes) {
+    let Some(extension) = sniff_image_extension(&bytes) else {
+        eprintln!("Not caching {}, the content is not a supported image", identifier);
+        return;
+    };
+
+    let cache_dir = image_cache_dir();
 
     if !cache_dir.exists() {
         create_dir_all(&cache_dir).expect("Failed to create image-cache directory");
     }
 
-    cache_dir.push(format!("{}.{}", identifier, extension));
-    if !cache_dir.exists() {
-        let mut file = File::create(cache_dir).unwrap();
-        let _ = file.write_all(&bytes);
+    let path = cache_dir.join(format!("{}.{}", identifier, extension));
+    let temp_path = cache_dir.join(format!("{}.{}.tmp", identifier, rand::random::<u64>()));
+
+    let written = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(&bytes)?;
+            file.sync_all()
+        })
+        .and_then(|_| rename(&temp_path, &path));
+
+    if let Err(err) = written {
+        eprintln!("Failed to cache {}: {}", identifier, err);
+        let _ = remove_file(&temp_path);
+        return;
+    }
+
+    let previous = IMAGE_CACHE.lock().unwrap().insert(
+        identifier,
+        CachedImage {
+            path: path.clone(),
+            size: bytes.len() as u64,
+            last_used: SystemTime::now(),
+            touched: true,
+        },
+    );
+
+    // The image changed type
+    if let Some(previous) = previous
+        && previous.path != path
+    {
+        let _ = remove_file(previous.path);
+    }
+
+    evict_image_cache();
+}
+
+pub fn save_cached_image_etag(identifier: &str, etag: &str) {
+    if let Some(path) = cached_image_path(identifier) {
+        let _ = fs::write(path.with_extension("etag"), etag);
     }
 }
 
 pub fn c_cached_image<'a>(
+    theme: &style::Theme,
     identifier: String,
     on_enter_unique: Message,
     image_width: f32,
     image_height: f32,
     border_radius: f32,
 ) -> Element<'a, Message> {
+    let placeholder = theme.colors.image_placeholder;
     let mut team_picture = container(
         ViewportHandler::new(space()).on_enter_unique(identifier.clone(), on_enter_unique.clone()),
     )
-    .style(|_| container::Style {
-        background: Some(
-            Color::from_str("#b8b4b4")
-                .expect("Background color is invalid.")
-                .into(),
-        ),
-
+    .style(move |_| container::Style {
+        background: Some(placeholder.into()),
         ..Default::default()
     })
     .width(image_width)
     .height(image_height);
 
-    let project_dirs = ProjectDirs::from("", "ianterzo", "squads");
-    let mut image_path = project_dirs.unwrap().cache_dir().to_path_buf();
-    image_path.push("image-cache");
-    image_path.push(format!("{}.jpeg", identifier));
-
-    if Path::new(&image_path).exists() {
+    if let Some(image_path) = cached_image_path(&identifier) {
         team_picture = container(
             ViewportHandler::new(
                 image(image_path)
@@ -71,31 +297,24 @@ pub fn c_cached_image<'a>(
 }
 
 pub fn c_cached_gif<'a>(
+    theme: &style::Theme,
     identifier: String,
     on_enter_unique: Message,
     image_width: f32,
     image_height: f32,
 ) -> Element<'a, Message> {
+    let placeholder = theme.colors.image_placeholder;
     let mut team_picture = container(
         ViewportHandler::new(space()).on_enter_unique(identifier.clone(), on_enter_unique.clone()),
     )
-    .style(|_| container::Style {
-        background: Some(
-            Color::from_str("#b8b4b4")
-                .expect("Background color is invalid.")
-                .into(),
-        ),
-
+    .style(move |_| container::Style {
+        background: Some(placeholder.into()),
         ..Default::default()
     })
     .width(image_width)
     .height(image_height);
-    let project_dirs = ProjectDirs::from("", "ianterzo", "squads");
 
-    let mut image_path = project_dirs.unwrap().cache_dir().to_path_buf();
-    image_path.push("image-cache");
-    image_path.push(format!("{}.gif", identifier));
-    if Path::new(&image_path).exists() {
+    if let Some(image_path) = cached_image_path(&identifier) {
         team_picture = container(
             ViewportHandler::new(
                 Gif::new(image_path)
@@ -103,6 +322,7 @@ pub fn c_cached_gif<'a>(
                     .width(image_width.into())
                     .height(image_height.into()),
             )
+            .pause_when_hidden()
             .on_enter_unique(identifier, on_enter_unique),
         )
     }
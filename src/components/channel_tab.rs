@@ -0,0 +1,194 @@
+use crate::api::{OnenotePage, PlannerTask, TeamsTab};
+use crate::utils::{self, is_notes_tab, tab_plan_id, tab_url, truncate_name};
+use crate::widgets::click_area::click_area;
+use crate::{Message, style};
+use iced::widget::{Column, Id, column, container, row, scrollable, text};
+use iced::{Alignment, Element, Font, Length, Padding, border, font};
+
+fn date_of(time: &Option<String>) -> String {
+    time.as_ref()
+        .and_then(|time| time.split("T").next())
+        .map(|date| date.replace("-", "/"))
+        .unwrap_or("".to_string())
+}
+
+fn list_item<'a>(
+    theme: &'a style::Theme,
+    title: String,
+    details: String,
+    on_press: Option<Message>,
+) -> Element<'a, Message> {
+    let item = container(
+        row![
+            container(text(truncate_name(title, 70))).width(Length::Fill),
+            text(details).size(14).color(theme.colors.demo_text)
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center),
+    )
+    .width(Length::Fill)
+    .padding(Padding {
+        top: 9.0,
+        bottom: 9.0,
+        left: 8.0,
+        right: 8.0,
+    })
+    .style(|_| container::Style {
+        background: Some(theme.colors.foreground.into()),
+        border: border::rounded(4),
+        ..Default::default()
+    });
+
+    if let Some(on_press) = on_press {
+        click_area(item)
+            .on_press(on_press)
+            .interaction(iced::mouse::Interaction::Pointer)
+            .into()
+    } else {
+        item.into()
+    }
+}
+
+pub fn c_channel_tab<'a>(
+    theme: &'a style::Theme,
+    tab: &TeamsTab,
+    planner_tasks: Option<&Vec<PlannerTask>>,
+    notes: Option<&Vec<OnenotePage>>,
+) -> Element<'a, Message> {
+    let app_name = tab
+        .teams_app
+        .as_ref()
+        .and_then(|app| app.display_name.clone())
+        .unwrap_or("Tab".to_string());
+
+    let mut card = row![
+        column![
+            text(truncate_name(tab.display_name.clone(), 60)).font(Font {
+                weight: font::Weight::Bold,
+                ..Default::default()
+            }),
+            text(app_name).size(14).color(theme.colors.demo_text)
+        ]
+        .spacing(2)
+        .width(Length::Fill)
+    ]
+    .align_y(Alignment::Center);
+
+    if let Some(url) = tab_url(tab) {
+        card = card.push(
+            click_area(
+                container(
+                    row![
+                        utils::icon(theme, "external-link.svg")
+                            .width(17)
+                            .height(17),
+                        text("Open in Browser")
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(6),
+                )
+                .padding(Padding {
+                    top: 5.0,
+                    bottom: 5.0,
+                    left: 10.0,
+                    right: 10.0,
+                })
+                .style(|_| container::Style {
+                    background: Some(theme.colors.foreground_button.into()),
+                    border: border::rounded(4),
+                    ..Default::default()
+                }),
+            )
+            .on_press(Message::LinkClicked(url))
+            .interaction(iced::mouse::Interaction::Pointer),
+        );
+    }
+
+    let card = container(card)
+        .padding(12)
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            border: border::rounded(6),
+            ..Default::default()
+        });
+
+    let mut body: Column<Message> = column![].spacing(4).padding(Padding {
+        top: 0.0,
+        bottom: 8.0,
+        left: 8.0,
+        right: 8.0,
+    });
+
+    if tab_plan_id(tab).is_some() {
+        body = body.push(text("Tasks").size(14).color(theme.colors.demo_text));
+
+        if let Some(planner_tasks) = planner_tasks {
+            if planner_tasks.is_empty() {
+                body = body.push(text("No tasks in this plan."));
+            }
+
+            for task in planner_tasks {
+                let status = match task.percent_complete.unwrap_or(0) {
+                    100 => "Completed".to_string(),
+                    0 => "Not started".to_string(),
+                    _ => "In progress".to_string(),
+                };
+
+                let due = date_of(&task.due_date_time);
+                let details = if due.is_empty() {
+                    status
+                } else {
+                    format!("{} · Due {}", status, due)
+                };
+
+                body = body.push(list_item(theme, task.title.clone(), details, None));
+            }
+        } else {
+            body = body.push(text("Loading tasks...").color(theme.colors.demo_text));
+        }
+    } else if is_notes_tab(tab) {
+        body = body.push(text("Pages").size(14).color(theme.colors.demo_text));
+
+        if let Some(notes) = notes {
+            if notes.is_empty() {
+                body = body.push(text("No pages in this notebook."));
+            }
+
+            for page in notes {
+                let on_press = page
+                    .links
+                    .as_ref()
+                    .and_then(|links| links.one_note_web_url.as_ref())
+                    .map(|link| Message::LinkClicked(link.href.clone()));
+
+                body = body.push(list_item(
+                    theme,
+                    page.title.clone().unwrap_or("Untitled Page".to_string()),
+                    date_of(&page.last_modified_date_time),
+                    on_press,
+                ));
+            }
+        } else {
+            body = body.push(text("Loading pages...").color(theme.colors.demo_text));
+        }
+    } else {
+        body = body.push(
+            text("This tab can only be viewed in the browser.").color(theme.colors.demo_text),
+        );
+    }
+
+    column![
+        container(card).padding(8),
+        scrollable(body)
+            .direction(scrollable::Direction::Vertical(
+                scrollable::Scrollbar::new()
+                    .width(4)
+                    .spacing(0)
+                    .scroller_width(4),
+            ))
+            .style(|_, _| theme.stylesheet.scrollable)
+            .id(Id::new("channel_tab_column"))
+            .height(Length::Fill),
+    ]
+    .into()
+}
@@ -1,13 +1,15 @@
 use crate::Message;
 use crate::api::Profile;
 use crate::components::cached_image::c_cached_image;
-use crate::components::emoji_picker::c_emoji_picker;
+use crate::components::emoji_picker::{c_emoji, c_emoji_picker};
+use crate::components::link_preview::c_link_preview;
+use crate::components::reactions::{MANY_REACTIONS, c_reaction_users};
 use crate::components::more_options::c_more_options;
 use crate::components::picture_and_status::c_picture_and_status;
 use crate::components::toooltip::c_tooltip;
-use crate::parsing::{parse_card_html, parse_message_html};
+use crate::parsing::{is_media_message, parse_card_html, parse_message_html};
 use crate::style;
-use crate::types::Emoji;
+use crate::types::{Emoji, EmojiUsage, OutboxMessage, OutboxState, Settings};
 use crate::utils;
 use crate::websockets::Presence;
 use crate::widgets::anchored_overlay::anchored_overlay;
@@ -16,7 +18,7 @@ use crate::widgets::selectable_text::selectable_text;
 use iced::alignment::Vertical;
 use iced::widget::tooltip::Position;
 use crate::widgets::click_area::click_area;
-use iced::widget::{column, container, row, space, stack, svg, text, tooltip};
+use iced::widget::{column, container, row, space, stack, text, tooltip};
 use iced::{Alignment, Border, Element, Font, Length, Padding, border, font, padding};
 use indexmap::IndexMap;
 use std::collections::HashMap;
@@ -24,6 +26,35 @@ use std::time::Duration;
 
 const LOG_THREAD_ACTIVITY: bool = false;
 
+pub fn c_outbox_status<'a>(
+    theme: &'a style::Theme,
+    outbox_message: &OutboxMessage,
+) -> Option<Element<'a, Message>> {
+    match outbox_message.state {
+        OutboxState::Pending => Some(text("Sending...").size(14).color(theme.colors.demo_text).into()),
+        OutboxState::Sent => None,
+        OutboxState::Failed => Some(
+            row![
+                text("Failed —").size(14).color(theme.colors.status_busy),
+                click_area(text("Retry").size(14).color(theme.colors.text_link))
+                    .on_press(Message::RetryOutboxMessage(
+                        outbox_message.client_message_id.clone()
+                    ))
+                    .interaction(iced::mouse::Interaction::Pointer),
+                text("/").size(14).color(theme.colors.demo_text),
+                click_area(text("Delete").size(14).color(theme.colors.text_link))
+                    .on_press(Message::DeleteOutboxMessage(
+                        outbox_message.client_message_id.clone()
+                    ))
+                    .interaction(iced::mouse::Interaction::Pointer),
+            ]
+            .spacing(4)
+            .align_y(Alignment::Center)
+            .into(),
+        ),
+    }
+}
+
 pub fn c_chat_message<'a>(
     theme: &'a style::Theme,
     message: crate::api::Message,
@@ -33,12 +64,15 @@ pub fn c_chat_message<'a>(
     users: &HashMap<String, Profile>,
     me: &Profile,
     user_presences: &'a HashMap<String, Presence>,
+    outbox: &IndexMap<String, OutboxMessage>,
     show_more_options: &'a bool,
     more_menu_message_id: &'a Option<String>,
     show_message_emoji_picker: &'a bool,
     show_plus_emoji_picker: &'a bool,
     emoji_picker_message_id: &'a Option<String>,
     search_emojis_input_value: &String,
+    settings: &Settings,
+    emoji_usage: &HashMap<String, EmojiUsage>,
     window_size: &(f32, f32),
 ) -> Option<Element<'a, Message>> {
     if let Some(message_type) = message.message_type.clone() {
@@ -56,7 +90,10 @@ pub fn c_chat_message<'a>(
     let mut message_info = row![].spacing(10).align_y(Alignment::Center);
 
     if let Some(message_type) = message.message_type.clone() {
-        if message_type == "RichText/Html" || message_type == "Text" {
+        if message_type == "RichText/Html"
+            || message_type == "Text"
+            || is_media_message(&message_type)
+        {
             if let Some(ref user_id) = message.from {
                 let display_name =
                     if let Some(profile) = users.get(&user_id.replace("8:orgid:", "")) {
@@ -78,6 +115,7 @@ pub fn c_chat_message<'a>(
                 let identifier = user_id.clone().replace(":", "");
 
                 let user_picture = c_cached_image(
+                    theme,
                     identifier.clone(),
                     Message::FetchUserImage(identifier, user_id.clone(), display_name.clone()),
                     31.0,
@@ -121,6 +159,13 @@ pub fn c_chat_message<'a>(
         message_info = message_info.push(text(time).size(14).color(theme.colors.demo_text));
     }
 
+    if let Some(client_message_id) = &message.client_message_id
+        && let Some(outbox_message) = outbox.get(client_message_id)
+        && let Some(outbox_status) = c_outbox_status(theme, outbox_message)
+    {
+        message_info = message_info.push(outbox_status);
+    }
+
     contents_column = contents_column.push(message_info.wrap());
 
     // Message content
@@ -137,7 +182,7 @@ pub fn c_chat_message<'a>(
             ..Font::default()
         }));
     } else if let Some(message_type) = message.message_type.clone() {
-        if message_type == "RichText/Html" {
+        if message_type == "RichText/Html" || is_media_message(&message_type) {
             if let Some(content) = message.content.clone() {
                 match parse_message_html(theme, content) {
                     Ok(result) => {
@@ -180,6 +225,19 @@ pub fn c_chat_message<'a>(
         }
     }
 
+    // Link previews
+
+    if !deleted
+        && let Some(properties) = &message.properties
+        && let Some(links) = &properties.links
+    {
+        for link in links {
+            if let Some(link_preview) = c_link_preview(theme, link) {
+                contents_column = contents_column.push(link_preview);
+            }
+        }
+    }
+
     // Files
 
     if !deleted {
@@ -191,7 +249,7 @@ pub fn c_chat_message<'a>(
                     let file_container = click_area(
                         container(
                             row![
-                                svg(utils::get_image_dir().join("paperclip.svg"))
+                                utils::icon(theme, "paperclip.svg")
                                     .width(16)
                                     .height(16),
                                 text(file.file_name.clone().unwrap_or("File".to_string()))
@@ -232,12 +290,10 @@ pub fn c_chat_message<'a>(
                         continue;
                     }
 
-                    let mut reaction_text = text("(?)");
-
-                    let reaction_val = emoji_map.get(&reaction.key);
-                    if let Some(reaction_val) = reaction_val {
-                        reaction_text = text(reaction_val.unicode.clone());
-                    }
+                    let reaction_emoji = match emoji_map.get(&reaction.key) {
+                        Some(reaction_val) => c_emoji(theme, reaction_val, 16.0),
+                        None => text("(?)").into(),
+                    };
 
                     let mut self_has_reacted = false;
                     for reactor in &reaction.users {
@@ -248,27 +304,9 @@ pub fn c_chat_message<'a>(
                         }
                     }
 
-                    let reaction_string = reaction
-                        .users
-                        .iter()
-                        .filter_map(|reactor| {
-                            if let Some(reactor_id) = reactor.mri.split(":").nth(2) {
-                                users.get(reactor_id).map(|profile| {
-                                    profile
-                                        .display_name
-                                        .clone()
-                                        .unwrap_or("Unknown User".to_string())
-                                })
-                            } else {
-                                Some("Unknown User".to_string())
-                            }
-                        })
-                        .collect::<Vec<String>>()
-                        .join(", ");
-
                     let reaction_container = tooltip(
                         click_area(
-                            container(row![reaction_text, text(reacters)].spacing(4))
+                            container(row![reaction_emoji, text(reacters)].spacing(4))
                                 .style(move |_| {
                                     if self_has_reacted {
                                         container::Style {
@@ -303,21 +341,7 @@ pub fn c_chat_message<'a>(
                             reaction.clone(),
                         ))
                         .interaction(iced::mouse::Interaction::Pointer),
-                        container(text(format!(
-                            "{} \"{}\" reacted by {}",
-                            reaction_val
-                                .map(|emoji| emoji.unicode.clone())
-                                .unwrap_or("(?)".to_string()),
-                            reaction.key.clone(),
-                            reaction_string
-                        )))
-                        .padding(12)
-                        .max_width(200)
-                        .style(|_| container::Style {
-                            background: Some(theme.colors.foreground_surface.into()),
-                            border: border::rounded(4),
-                            ..Default::default()
-                        }),
+                        c_reaction_users(theme, reaction, emoji_map, users),
                         tooltip::Position::Top,
                     )
                     .delay(Duration::from_millis(400));
@@ -326,6 +350,33 @@ pub fn c_chat_message<'a>(
 
                     are_reactions = true;
                 }
+
+                // Long reaction lists are easier to go through grouped in an overlay
+                let total_reacters: usize =
+                    reactions.iter().map(|reaction| reaction.users.len()).sum();
+                if total_reacters >= MANY_REACTIONS {
+                    reactions_row = reactions_row.push(
+                        click_area(
+                            container(text("All reactions").size(14))
+                                .style(|_| container::Style {
+                                    background: Some(theme.colors.foreground_surface.into()),
+                                    border: border::rounded(4),
+                                    ..Default::default()
+                                })
+                                .padding(Padding {
+                                    top: 3.0,
+                                    right: 5.0,
+                                    bottom: 3.0,
+                                    left: 5.0,
+                                }),
+                        )
+                        .on_press(Message::ToggleReactions(
+                            chat_thread_id.clone(),
+                            message.id.clone().unwrap(),
+                        ))
+                        .interaction(iced::mouse::Interaction::Pointer),
+                    );
+                }
             }
         }
     }
@@ -337,7 +388,7 @@ pub fn c_chat_message<'a>(
             let content = tooltip(
                 click_area(
                     container(
-                        svg(utils::get_image_dir().join("plus.svg"))
+                        utils::icon(theme, "plus.svg")
                             .width(19)
                             .height(19),
                     )
@@ -366,6 +417,8 @@ pub fn c_chat_message<'a>(
                     c_emoji_picker(
                         theme,
                         search_emojis_input_value,
+                        &settings.skin_tone,
+                        emoji_usage,
                         emoji_map,
                         move |emoji_id, emoji_unicode| {
                             Message::EmojiPickerReaction(
@@ -417,7 +470,7 @@ pub fn c_chat_message<'a>(
                         {
                             container(row![
                                 tooltip(
-                                    svg(utils::get_image_dir().join("pencil.svg"))
+                                    utils::icon(theme, "pencil.svg")
                                         .width(17)
                                         .height(17),
                                     c_tooltip(theme, "Edit"),
@@ -431,7 +484,7 @@ pub fn c_chat_message<'a>(
                         },
                         tooltip(
                             click_area(
-                                svg(utils::get_image_dir().join("reply.svg"))
+                                utils::icon(theme, "reply.svg")
                                     .width(21)
                                     .height(21)
                             )
@@ -448,7 +501,7 @@ pub fn c_chat_message<'a>(
                         {
                             let content = tooltip(
                                 click_area(
-                                    svg(utils::get_image_dir().join("plus.svg"))
+                                    utils::icon(theme, "plus.svg")
                                         .width(21)
                                         .height(21),
                                 )
@@ -465,6 +518,8 @@ pub fn c_chat_message<'a>(
                                     c_emoji_picker(
                                         theme,
                                         search_emojis_input_value,
+                                        &settings.skin_tone,
+                                        emoji_usage,
                                         emoji_map,
                                         move |emoji_id, emoji_unicode| {
                                             Message::EmojiPickerReaction(
@@ -488,7 +543,7 @@ pub fn c_chat_message<'a>(
                         {
                             let content = tooltip(
                                 click_area(
-                                    svg(utils::get_image_dir().join("ellipsis.svg"))
+                                    utils::icon(theme, "ellipsis.svg")
                                         .width(21)
                                         .height(21),
                                 )
@@ -503,7 +558,7 @@ pub fn c_chat_message<'a>(
                             if *show_more_options {
                                 container(anchored_overlay(
                                     content,
-                                    c_more_options(theme, message.clone(), &me),
+                                    c_more_options(theme, message.clone(), chat_thread_id, me),
                                     crate::widgets::anchored_overlay::Position::Left,
                                     (2.0, 0.0),
                                     true,
@@ -553,9 +608,9 @@ pub fn c_chat_message<'a>(
                     ..Default::default()
                 })
                 .padding(Padding {
-                    top: 4.0,
+                    top: 4.0 * settings.message_density.scale(),
                     right: 1.0,
-                    bottom: 4.0,
+                    bottom: 4.0 * settings.message_density.scale(),
                     left: 3.0,
                 }),
             action_container
@@ -0,0 +1,99 @@
+use crate::Message;
+use crate::highlight::{code_language_label, highlight_code};
+use crate::style;
+use crate::utils;
+use crate::widgets::click_area::click_area;
+use crate::widgets::selectable_rich_text::selectable_rich_text;
+use crate::widgets::selectable_text;
+use iced::widget::{column, container, row, scrollable, span, text};
+use iced::{Alignment, Element, Font, Length, border};
+
+// Long lines scroll horizontally instead of wrapping, like in an editor
+pub fn c_code_block(
+    theme: &style::Theme,
+    language: Option<String>,
+    code: String,
+) -> Element<'static, Message> {
+    let background = theme.colors.foreground_surface;
+    let text_color = theme.colors.text;
+    let demo_text = theme.colors.demo_text;
+    let selection_color = theme.colors.text_selection;
+    let scrollable_style = theme.stylesheet.scrollable;
+
+    let dark_background = 0.299 * background.r + 0.587 * background.g + 0.114 * background.b < 0.5;
+
+    let highlighted = language
+        .as_ref()
+        .and_then(|language| highlight_code(language, &code, dark_background))
+        .unwrap_or_else(|| {
+            code.lines()
+                .map(|line| vec![(line.to_string(), text_color)])
+                .collect()
+        });
+
+    let lines = highlighted.into_iter().map(|line| {
+        let mut spans: Vec<_> = line
+            .into_iter()
+            .map(|(content, color)| span::<(), Font>(content).font(Font::MONOSPACE).color(color))
+            .collect();
+        // Empty lines would collapse
+        if spans.iter().all(|span| span.text.is_empty()) {
+            spans.push(span(" ").font(Font::MONOSPACE));
+        }
+
+        selectable_rich_text(spans)
+            .style(move |_| selectable_text::Style {
+                color: None,
+                selection_color,
+            })
+            .into()
+    });
+
+    let header = row![
+        container(
+            text(language.map(|language| code_language_label(&language)).unwrap_or_default())
+                .size(13)
+                .color(demo_text)
+        )
+        .width(Length::Fill),
+        click_area(
+            row![
+                utils::icon(theme, "copy.svg")
+                    .width(14)
+                    .height(14),
+                text("Copy").size(13).color(demo_text)
+            ]
+            .spacing(4)
+            .align_y(Alignment::Center)
+        )
+        .on_press(Message::CopyText(code))
+        .interaction(iced::mouse::Interaction::Pointer)
+    ]
+    .align_y(Alignment::Center);
+
+    container(
+        container(
+            column![
+                header,
+                scrollable(column(lines).padding(iced::padding::bottom(8)))
+                    .direction(scrollable::Direction::Horizontal(
+                        scrollable::Scrollbar::new()
+                            .width(4)
+                            .spacing(0)
+                            .scroller_width(4),
+                    ))
+                    .style(move |_, _| scrollable_style)
+            ]
+            .spacing(6),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(move |_| container::Style {
+            background: Some(background.into()),
+            border: border::rounded(5),
+            ..Default::default()
+        }),
+    )
+    .padding(iced::padding::vertical(6))
+    .into()
+}
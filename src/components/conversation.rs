@@ -4,7 +4,7 @@ use iced::{Element, border};
 use indexmap::IndexMap;
 
 use crate::Message;
-use crate::types::Emoji;
+use crate::types::{Emoji, EmojiUsage, OutboxMessage, Settings};
 use crate::websockets::Presence;
 use crate::{api, style};
 use std::collections::HashMap;
@@ -20,14 +20,18 @@ pub fn c_conversation<'a>(
     show_replies: bool,
     emoji_map: &'a IndexMap<String, Emoji>,
     search_emojis_input_value: &String,
+    settings: &Settings,
+    emoji_usage: &HashMap<String, EmojiUsage>,
     users: &HashMap<String, Profile>,
     me: &Profile,
     user_presences: &'a HashMap<String, Presence>,
+    outbox: &IndexMap<String, OutboxMessage>,
     show_plus_emoji_picker: &bool,
     emoji_picker_message_id: &Option<String>,
     window_size: &(f32, f32),
 ) -> Option<Element<'a, Message>> {
-    let mut message_chain = column![].spacing(20);
+    let density = settings.message_density.scale();
+    let mut message_chain = column![].spacing(20.0 * density);
 
     let first_message = messages.get(0).unwrap().clone();
     if let Some(message_element) = c_message(
@@ -36,9 +40,12 @@ pub fn c_conversation<'a>(
         first_message,
         emoji_map,
         search_emojis_input_value,
+        settings,
+        emoji_usage,
         users,
         me,
         user_presences,
+        outbox,
         show_plus_emoji_picker,
         emoji_picker_message_id,
         window_size,
@@ -72,9 +79,12 @@ pub fn c_conversation<'a>(
                 message,
                 emoji_map,
                 search_emojis_input_value,
+                settings,
+                emoji_usage,
                 users,
                 me,
                 user_presences,
+                outbox,
                 show_plus_emoji_picker,
                 emoji_picker_message_id,
                 window_size,
@@ -91,7 +101,7 @@ pub fn c_conversation<'a>(
                 ..Default::default()
             })
             .width(iced::Length::Fill)
-            .padding(20)
+            .padding(20.0 * density)
             .into(),
     )
 }
@@ -1,209 +1,267 @@
+use crate::components::cached_image::c_cached_image;
 use crate::components::horizontal_line::c_horizontal_line;
 use crate::style::Theme;
-use crate::types::Emoji;
+use crate::types::{Emoji, EmojiUsage, SkinTone};
+use crate::utils::{ams_image_identifier, apply_skin_tone, frequent_emojis, search_emojis};
 use crate::{Message, utils};
 use crate::widgets::click_area::click_area;
-use iced::widget::{Id, column, container, row, scrollable, svg, text, text_input};
-use iced::{Border, Element, Length, Padding};
+use iced::widget::{Column, Id, Row, column, container, row, scrollable, text, text_input};
+use iced::{Alignment, Border, Element, Length, Padding, border};
 use indexmap::IndexMap;
+use std::collections::HashMap;
+
+// Display order of the categories, with their sidebar icon
+const CATEGORIES: [(&str, &str); 9] = [
+    ("Custom", "sparkles.svg"),
+    ("Smileys & Emotion", "smile.svg"),
+    ("People & Body", "user-round.svg"),
+    ("Animals & Nature", "leaf.svg"),
+    ("Food & Drink", "pizza.svg"),
+    ("Activities", "gamepad-2.svg"),
+    ("Travel & Places", "bike.svg"),
+    ("Objects", "lamp.svg"),
+    ("Symbols", "heart.svg"),
+];
+
+const EMOJIS_PER_ROW: usize = 10;
+const EMOJI_SIZE: f32 = 42.0;
+const CATEGORY_LABEL_HEIGHT: f32 = 20.0;
+const SCROLLABLE_HEIGHT: f32 = 400.0;
+const FREQUENT_EMOJIS_LIMIT: usize = 20;
+
+pub fn c_emoji<'a>(theme: &Theme, emoji: &Emoji, size: f32) -> Element<'a, Message> {
+    if let Some(image_url) = &emoji.image_url {
+        let identifier = ams_image_identifier(image_url);
+        c_cached_image(
+            theme,
+            identifier.clone(),
+            Message::AuthorizeImage(image_url.clone(), identifier),
+            size,
+            size,
+            2.0,
+        )
+    } else {
+        text(emoji.unicode.clone()).size(size).into()
+    }
+}
+
+fn emoji_item<'a, F>(
+    theme: &Theme,
+    emoji_id: &String,
+    emoji: &Emoji,
+    skin_tone: &SkinTone,
+    on_pick: &F,
+) -> Element<'a, Message>
+where
+    F: Fn(String, String) -> Message + 'a,
+{
+    let (unicode, emoji_element) = if emoji.image_url.is_some() {
+        (emoji.unicode.clone(), c_emoji(theme, emoji, 34.0))
+    } else {
+        let unicode = apply_skin_tone(&emoji.unicode, skin_tone);
+        (unicode.clone(), text(unicode).size(34).into())
+    };
+
+    click_area(container(container(emoji_element).width(38).height(38)).padding(2))
+        .interaction(iced::mouse::Interaction::Pointer)
+        .on_press(on_pick(emoji_id.to_string(), unicode))
+        .into()
+}
+
+fn skin_tone_selector<'a>(theme: &'a Theme, skin_tone: &SkinTone) -> Element<'a, Message> {
+    let mut selector = row![].spacing(2).align_y(Alignment::Center);
+
+    for option in SkinTone::ALL {
+        let is_selected = &option == skin_tone;
+
+        selector = selector.push(
+            click_area(
+                container(text(apply_skin_tone("✋", &option)).size(18))
+                    .padding(3)
+                    .style(move |_| container::Style {
+                        background: if is_selected {
+                            Some(theme.colors.foreground_surface.into())
+                        } else {
+                            None
+                        },
+                        border: border::rounded(4),
+                        ..Default::default()
+                    }),
+            )
+            .interaction(iced::mouse::Interaction::Pointer)
+            .on_press(Message::SetSkinTone(option)),
+        );
+    }
+
+    selector.into()
+}
 
 pub fn c_emoji_picker<'a, F>(
     theme: &'a Theme,
     search_emojis_input_value: &String,
+    skin_tone: &SkinTone,
+    emoji_usage: &HashMap<String, EmojiUsage>,
     emoji_map: &'a IndexMap<String, Emoji>,
     on_pick: F,
 ) -> Element<'a, Message>
 where
     F: Fn(String, String) -> Message + 'a,
 {
+    let mut category_rows: Vec<Row<Message>> = CATEGORIES.iter().map(|_| row![]).collect();
+    let mut category_counts = [0usize; CATEGORIES.len()];
+    let mut frequent_count = 0usize;
+
+    let emoji_content: Column<Message> = if search_emojis_input_value.is_empty() {
+        let fallback_index = CATEGORIES
+            .iter()
+            .position(|(name, _)| *name == "Activities")
+            .unwrap();
+
+        for (emoji_id, emoji) in emoji_map {
+            let index = CATEGORIES
+                .iter()
+                .position(|(name, _)| *name == emoji.category)
+                .unwrap_or(fallback_index);
+
+            let category_row = std::mem::replace(&mut category_rows[index], row![]);
+            category_rows[index] =
+                category_row.push(emoji_item(theme, emoji_id, emoji, skin_tone, &on_pick));
+            category_counts[index] += 1;
+        }
+
+        let mut emoji_column = column![];
+
+        let frequent = frequent_emojis(emoji_map, emoji_usage, FREQUENT_EMOJIS_LIMIT);
+        if !frequent.is_empty() {
+            let mut frequent_row = row![];
+            for (emoji_id, emoji) in &frequent {
+                frequent_row =
+                    frequent_row.push(emoji_item(theme, emoji_id, emoji, skin_tone, &on_pick));
+            }
+            emoji_column = emoji_column
+                .push(text("Frequently used"))
+                .push(frequent_row.wrap());
+        }
+        frequent_count = frequent.len();
+
+        for (index, category_row) in category_rows.into_iter().enumerate() {
+            // Tenants without custom emojis don't get an empty category
+            if category_counts[index] == 0 && CATEGORIES[index].0 == "Custom" {
+                continue;
+            }
+            emoji_column = emoji_column
+                .push(text(CATEGORIES[index].0))
+                .push(category_row.wrap());
+        }
+        emoji_column
+    } else {
+        let mut emoji_row = row![];
+
+        for (emoji_id, emoji) in
+            search_emojis(emoji_map, emoji_usage, search_emojis_input_value, None)
+        {
+            emoji_row = emoji_row.push(emoji_item(theme, emoji_id, emoji, skin_tone, &on_pick));
+        }
+        column![emoji_row.wrap()]
+    };
+
     let emoji_scrollable = container(
-        scrollable(if search_emojis_input_value == "" {
-            let mut emoji_cat1 = row![];
-            let mut emoji_cat2 = row![];
-            let mut emoji_cat3 = row![];
-            let mut emoji_cat4 = row![];
-            let mut emoji_cat5 = row![];
-            let mut emoji_cat6 = row![];
-            let mut emoji_cat7 = row![];
-            let mut emoji_cat8 = row![];
-
-            for (_emoji_id, emoji) in emoji_map {
-                let emoji_component = click_area(
-                    container(
-                        container(text(emoji.unicode.clone()).size(34))
-                            .width(38)
-                            .height(38),
-                    )
-                    .padding(2),
-                )
-                .interaction(iced::mouse::Interaction::Pointer)
-                .on_press(on_pick(_emoji_id.to_string(), emoji.unicode.clone()));
-
-                match emoji.category.as_str() {
-                    "People & Body" => emoji_cat1 = emoji_cat1.push(emoji_component),
-                    "Animals & Nature" => emoji_cat2 = emoji_cat2.push(emoji_component),
-                    "Symbols" => emoji_cat3 = emoji_cat3.push(emoji_component),
-                    "Smileys & Emotion" => emoji_cat4 = emoji_cat4.push(emoji_component),
-                    "Objects" => emoji_cat5 = emoji_cat5.push(emoji_component),
-                    "Food & Drink" => emoji_cat6 = emoji_cat6.push(emoji_component),
-                    "Travel & Places" => emoji_cat7 = emoji_cat7.push(emoji_component),
-                    _ => emoji_cat8 = emoji_cat8.push(emoji_component),
-                };
+        scrollable(emoji_content)
+            .height(SCROLLABLE_HEIGHT)
+            .direction(scrollable::Direction::Vertical(
+                scrollable::Scrollbar::new()
+                    .width(4)
+                    .spacing(0)
+                    .scroller_width(4),
+            ))
+            .width(420)
+            .id(Id::new("emoji_column"))
+            .style(|_, _| theme.stylesheet.scrollable),
+    )
+    .padding(5);
+
+    // Relative scroll offsets are estimated from the number of rows in each category
+
+    let section_heights: Vec<f32> = category_counts
+        .iter()
+        .enumerate()
+        .map(|(index, count)| {
+            if *count == 0 && CATEGORIES[index].0 == "Custom" {
+                0.0
+            } else {
+                CATEGORY_LABEL_HEIGHT + count.div_ceil(EMOJIS_PER_ROW) as f32 * EMOJI_SIZE
             }
+        })
+        .collect();
+
+    let mut categories = column![
+        click_area(
+            utils::icon(theme, "clock.svg")
+                .width(24)
+                .height(24)
+        )
+        .interaction(iced::mouse::Interaction::Pointer)
+        .on_press(Message::EmojiPickerScrollTo(0.0))
+    ]
+    .height(Length::Fill)
+    .spacing(8);
+
+    let mut offset = if frequent_count != 0 {
+        CATEGORY_LABEL_HEIGHT + frequent_count.div_ceil(EMOJIS_PER_ROW) as f32 * EMOJI_SIZE
+    } else {
+        0.0
+    };
+    let scrollable_range =
+        (offset + section_heights.iter().sum::<f32>() - SCROLLABLE_HEIGHT).max(1.0);
 
-            column![
-                text("Smileys & Emotion"),
-                emoji_cat4.wrap(),
-                text("People & Body"),
-                emoji_cat1.wrap(),
-                text("Animals & Nature"),
-                emoji_cat2.wrap(),
-                text("Food & Drink"),
-                emoji_cat6.wrap(),
-                text("Activities"),
-                emoji_cat8.wrap(),
-                text("Travel & Places"),
-                emoji_cat7.wrap(),
-                text("Objects"),
-                emoji_cat5.wrap(),
-                text("Symbols"),
-                emoji_cat3.wrap(),
-            ]
-        } else {
-            let mut emoji_row = row![];
-
-            for (_emoji_id, emoji) in emoji_map {
-                if emoji.keywords.iter().any(|s| {
-                    s.to_lowercase()
-                        .contains(search_emojis_input_value.to_lowercase().as_str())
-                }) {
-                    let emoji_component = click_area(
-                        container(
-                            container(text(emoji.unicode.clone()).size(34))
-                                .width(38)
-                                .height(38),
-                        )
-                        .padding(2),
-                    )
+    for ((_, icon), height) in CATEGORIES.iter().zip(&section_heights) {
+        if *height != 0.0 {
+            categories = categories.push(
+                click_area(utils::icon(theme, icon).width(24).height(24))
                     .interaction(iced::mouse::Interaction::Pointer)
-                    .on_press(on_pick(_emoji_id.to_string(), emoji.unicode.clone()));
+                    .on_press(Message::EmojiPickerScrollTo(
+                        (offset / scrollable_range).min(1.0),
+                    )),
+            );
+        }
+        offset += height;
+    }
 
-                    emoji_row = emoji_row.push(emoji_component);
-                }
-            }
-            column![emoji_row.wrap()]
+    let categories = container(categories)
+        .padding(Padding {
+            top: 5.0,
+            right: 9.0,
+            left: 9.0,
+            bottom: 6.0,
         })
-        .height(400)
-        .direction(scrollable::Direction::Vertical(
-            scrollable::Scrollbar::new()
-                .width(4)
-                .spacing(0)
-                .scroller_width(4),
-        ))
-        .width(420)
-        .id(Id::new("emoji_column"))
-        .style(|_, _| theme.stylesheet.scrollable),
-    )
-    .padding(5);
+        .style(|_| container::Style {
+            background: Some(theme.colors.background.into()),
 
-    let categories = container(
-        column![
-            click_area(
-                svg(utils::get_image_dir().join("clock.svg"))
-                    .width(24)
-                    .height(24)
-            )
-            .interaction(iced::mouse::Interaction::Pointer)
-            .on_press(Message::EmojiPickerScrollTo(0.0)),
-            click_area(
-                svg(utils::get_image_dir().join("smile.svg"))
-                    .width(24)
-                    .height(24)
-            )
-            .interaction(iced::mouse::Interaction::Pointer)
-            .on_press(Message::EmojiPickerScrollTo(0.0)),
-            click_area(
-                svg(utils::get_image_dir().join("user-round.svg"))
-                    .width(24)
-                    .height(24)
-            )
-            .interaction(iced::mouse::Interaction::Pointer)
-            .on_press(Message::EmojiPickerScrollTo(0.1261)),
-            click_area(
-                svg(utils::get_image_dir().join("leaf.svg"))
-                    .width(24)
-                    .height(24)
-            )
-            .interaction(iced::mouse::Interaction::Pointer)
-            .on_press(Message::EmojiPickerScrollTo(0.342)),
-            click_area(
-                svg(utils::get_image_dir().join("pizza.svg"))
-                    .width(24)
-                    .height(24)
-            )
-            .interaction(iced::mouse::Interaction::Pointer)
-            .on_press(Message::EmojiPickerScrollTo(0.45)),
-            click_area(
-                svg(utils::get_image_dir().join("gamepad-2.svg"))
-                    .width(24)
-                    .height(24)
-            )
-            .interaction(iced::mouse::Interaction::Pointer)
-            .on_press(Message::EmojiPickerScrollTo(0.53)),
-            click_area(
-                svg(utils::get_image_dir().join("bike.svg"))
-                    .width(24)
-                    .height(24)
-            )
-            .interaction(iced::mouse::Interaction::Pointer)
-            .on_press(Message::EmojiPickerScrollTo(0.59)),
-            click_area(
-                svg(utils::get_image_dir().join("lamp.svg"))
-                    .width(24)
-                    .height(24)
-            )
-            .interaction(iced::mouse::Interaction::Pointer)
-            .on_press(Message::EmojiPickerScrollTo(0.734)),
-            click_area(
-                svg(utils::get_image_dir().join("heart.svg"))
-                    .width(24)
-                    .height(24)
-            )
-            .interaction(iced::mouse::Interaction::Pointer)
-            .on_press(Message::EmojiPickerScrollTo(0.9)),
-        ]
-        .height(Length::Fill)
-        .spacing(8),
-    )
-    .padding(Padding {
-        top: 5.0,
-        right: 9.0,
-        left: 9.0,
-        bottom: 6.0,
-    })
-    .style(|_| container::Style {
-        background: Some(theme.colors.background.into()),
-
-        ..Default::default()
-    });
+            ..Default::default()
+        });
 
     let top_part = container(
-        text_input("Find your emoji...", search_emojis_input_value)
-            .id(Id::new("search_emojis_input"))
-            .padding(8)
-            .on_input(Message::SearchEmojisContentChanged)
-            .style(|_, _| text_input::Style {
-                background: theme.colors.background.into(),
-                border: Border {
-                    color: theme.colors.line,
-                    width: 1.0,
-                    radius: 6.into(),
-                },
-                icon: theme.colors.not_set,
-                placeholder: theme.colors.demo_text,
-                value: theme.colors.text,
-                selection: theme.colors.text_selection,
-            }),
+        row![
+            text_input("Find your emoji...", search_emojis_input_value)
+                .id(Id::new("search_emojis_input"))
+                .padding(8)
+                .on_input(Message::SearchEmojisContentChanged)
+                .style(|_, _| text_input::Style {
+                    background: theme.colors.background.into(),
+                    border: Border {
+                        color: theme.colors.line,
+                        width: 1.0,
+                        radius: 6.into(),
+                    },
+                    icon: theme.colors.not_set,
+                    placeholder: theme.colors.demo_text,
+                    value: theme.colors.text,
+                    selection: theme.colors.text_selection,
+                }),
+            skin_tone_selector(theme, skin_tone)
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center),
     )
     .padding(12);
 
@@ -0,0 +1,40 @@
+use crate::widgets::click_area::click_area;
+use crate::{Message, style::Theme};
+use iced::{
+    Border, Element, Length,
+    alignment::{Horizontal, Vertical},
+    widget::{container, row, text},
+};
+
+// Failed background requests are shown at the bottom of the window until dismissed
+pub fn c_error_notice<'a>(theme: &'a Theme, error: &'a str) -> Element<'a, Message> {
+    container(
+        container(
+            row![
+                text(error).width(Length::Fill),
+                click_area(text("Dismiss").color(theme.colors.demo_text))
+                    .on_press(Message::DismissError)
+                    .interaction(iced::mouse::Interaction::Pointer)
+            ]
+            .spacing(12)
+            .align_y(Vertical::Center),
+        )
+        .padding(10)
+        .max_width(520)
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground_alt.into()),
+            border: Border {
+                color: theme.colors.status_busy,
+                width: 1.0,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        }),
+    )
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .padding(16)
+    .align_x(Horizontal::Center)
+    .align_y(Vertical::Bottom)
+    .into()
+}
@@ -1,15 +1,14 @@
 use crate::Message;
+use crate::components::cached_image::cached_image_path;
 use crate::widgets::gif::Gif;
-use directories::ProjectDirs;
 use iced::Element;
 use crate::widgets::click_area::click_area;
-use iced::widget::{container, image};
+use iced::widget::{container, image, space};
 
 pub fn c_expanded_image<'a>(identifier: &String, image_type: &String) -> Element<'a, Message> {
-    let project_dirs = ProjectDirs::from("", "ianterzo", "squads");
-    let mut image_path = project_dirs.unwrap().cache_dir().to_path_buf();
-    image_path.push("image-cache");
-    image_path.push(format!("{}.{}", identifier, image_type));
+    let Some(image_path) = cached_image_path(identifier) else {
+        return space().into();
+    };
 
     click_area(if image_type == "gif" {
         container(Gif::new(image_path))
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use crate::pages::page_chat::get_chat_title;
+use crate::utils::truncate_name;
+use crate::widgets::click_area::click_area;
+use crate::{
+    Message,
+    api::{Chat, Profile, Team},
+    style::Theme,
+};
+use iced::{
+    Border, Element, Length, Padding,
+    alignment::Vertical,
+    border,
+    widget::{Column, Id, column, container, scrollable, text, text_input},
+};
+
+fn target_item<'a>(theme: &'a Theme, label: String, on_press: Message) -> Element<'a, Message> {
+    click_area(
+        container(text(label))
+            .width(Length::Fill)
+            .align_y(Vertical::Center)
+            .style(|_| container::Style {
+                background: Some(theme.colors.foreground.into()),
+                border: border::rounded(4),
+                ..Default::default()
+            })
+            .padding(Padding {
+                top: 9.0,
+                right: 6.0,
+                bottom: 9.0,
+                left: 6.0,
+            }),
+    )
+    .interaction(iced::mouse::Interaction::Pointer)
+    .on_press(on_press)
+    .into()
+}
+
+pub fn c_forward_message<'a>(
+    theme: &'a Theme,
+    chats: &'a Vec<Chat>,
+    teams: &'a Vec<Team>,
+    users: &'a HashMap<String, Profile>,
+    me: &'a Profile,
+    search_users_input_value: &str,
+) -> Element<'a, Message> {
+    let search = search_users_input_value.to_lowercase();
+
+    let mut target_column: Column<Message> = column![].spacing(4).padding(Padding {
+        left: 8.0,
+        right: 6.0,
+        top: 6.0,
+        bottom: 6.0,
+    });
+
+    // Chats
+
+    target_column = target_column.push(text("Chats").size(14).color(theme.colors.demo_text));
+
+    for chat in chats {
+        // Drafts don't exist on the server yet
+        if chat.id.starts_with("draft:") {
+            continue;
+        }
+
+        let chat_title = get_chat_title(chat, &me.id, users);
+        if !chat_title.to_lowercase().starts_with(&search) {
+            continue;
+        }
+
+        target_column = target_column.push(target_item(
+            theme,
+            truncate_name(chat_title, 48),
+            Message::ForwardMessage(chat.id.clone()),
+        ));
+    }
+
+    // Channels
+
+    target_column = target_column.push(text("Channels").size(14).color(theme.colors.demo_text));
+
+    for team in teams {
+        for channel in &team.channels {
+            let channel_title = format!("{} > {}", team.display_name, channel.display_name);
+            if !team.display_name.to_lowercase().starts_with(&search)
+                && !channel.display_name.to_lowercase().starts_with(&search)
+            {
+                continue;
+            }
+
+            target_column = target_column.push(target_item(
+                theme,
+                truncate_name(channel_title, 48),
+                Message::ForwardMessage(channel.id.clone()),
+            ));
+        }
+    }
+
+    // People, same filtering as in c_start_chat
+
+    target_column = target_column.push(text("People").size(14).color(theme.colors.demo_text));
+
+    for user in users {
+        // Hotfix, this removes all non "human" users
+        if user.1.surname.is_none() || user.1.display_name.is_none() {
+            continue;
+        }
+
+        if !user
+            .1
+            .display_name
+            .as_ref()
+            .unwrap()
+            .to_lowercase()
+            .starts_with(&search)
+        {
+            continue;
+        }
+
+        if *user.0 == me.id {
+            continue;
+        }
+
+        target_column = target_column.push(target_item(
+            theme,
+            user.1
+                .display_name
+                .clone()
+                .unwrap_or("Unknown User".to_string()),
+            Message::ForwardMessageToUser(user.0.clone()),
+        ));
+    }
+
+    click_area(
+        container(
+            column![
+                container(
+                    text_input("Forward to...", search_users_input_value)
+                        .on_input(Message::SearchUsersContentChanged)
+                        .padding(10)
+                        .id("search_users_input")
+                        .style(|_, _| theme.stylesheet.input)
+                )
+                .align_y(Vertical::Center)
+                .padding(Padding {
+                    top: 6.0,
+                    bottom: 0.0,
+                    left: 8.0,
+                    right: 8.0
+                }),
+                scrollable(target_column)
+                    .direction(scrollable::Direction::Vertical(
+                        scrollable::Scrollbar::new()
+                            .width(4)
+                            .spacing(0)
+                            .scroller_width(4),
+                    ))
+                    .style(|_, _| theme.stylesheet.scrollable)
+                    .id(Id::new("forward_column"))
+            ]
+            .spacing(6),
+        )
+        .width(450)
+        .height(500)
+        .padding(1) // Otherwise the border is bugging
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            border: Border {
+                color: theme.colors.line,
+                width: 1.0,
+                radius: 4.into(),
+            },
+            ..Default::default()
+        }),
+    )
+    .on_enter(Message::EnterCenteredOverlay)
+    .on_exit(Message::ExitCenteredOverlay)
+    .into()
+}
@@ -0,0 +1,91 @@
+use crate::Message;
+use crate::api::Link;
+use crate::components::cached_image::c_cached_image;
+use crate::style;
+use crate::utils::{ams_image_identifier, truncate_name};
+use crate::widgets::click_area::click_area;
+use iced::widget::{column, container, row, text};
+use iced::{Alignment, Element, Font, border, font};
+use xxhash_rust::xxh3::xxh3_64;
+
+const TITLE_LENGTH: usize = 100;
+const DESCRIPTION_LENGTH: usize = 200;
+
+// Links without a preview, or with one Teams flagged as unsafe, are only shown in the text
+pub fn c_link_preview(theme: &style::Theme, link: &Link) -> Option<Element<'static, Message>> {
+    let preview = link.preview.as_ref()?;
+    if !link.previewenabled || preview.is_link_unsafe {
+        return None;
+    }
+
+    let title = preview.title.clone().filter(|title| !title.trim().is_empty())?;
+
+    let background = theme.colors.foreground_surface;
+    let demo_text = theme.colors.demo_text;
+
+    let mut details = column![text(truncate_name(title, TITLE_LENGTH)).font(Font {
+        weight: font::Weight::Bold,
+        ..Default::default()
+    })]
+    .spacing(2);
+
+    if let Some(description) = &preview.description
+        && !description.trim().is_empty()
+    {
+        details = details.push(
+            text(truncate_name(description.clone(), DESCRIPTION_LENGTH)).size(14),
+        );
+    }
+
+    let host = url::Url::parse(&link.url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_string()))
+        .unwrap_or(link.url.clone());
+    details = details.push(text(host).size(14).color(demo_text));
+
+    let mut content = row![].spacing(10).align_y(Alignment::Center);
+
+    if let Some(image_url) = &preview.previewurl {
+        // Previews Teams generated are stored in AMS, the ones we send point to the website
+        let image = if image_url.contains("asm.skype.com") {
+            let identifier = ams_image_identifier(image_url);
+            c_cached_image(
+                theme,
+                identifier.clone(),
+                Message::AuthorizeImage(image_url.clone(), identifier),
+                80.0,
+                80.0,
+                4.0,
+            )
+        } else {
+            let identifier = xxh3_64(image_url.as_bytes()).to_string();
+            c_cached_image(
+                theme,
+                identifier.clone(),
+                Message::DownloadImage(image_url.clone(), identifier),
+                80.0,
+                80.0,
+                4.0,
+            )
+        };
+        content = content.push(image);
+    }
+
+    content = content.push(details);
+
+    Some(
+        click_area(
+            container(content)
+                .padding(8)
+                .max_width(480)
+                .style(move |_| container::Style {
+                    background: Some(background.into()),
+                    border: border::rounded(6),
+                    ..Default::default()
+                }),
+        )
+        .on_press(Message::LinkClicked(link.url.clone()))
+        .interaction(iced::mouse::Interaction::Pointer)
+        .into(),
+    )
+}
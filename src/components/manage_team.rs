@@ -0,0 +1,284 @@
+use crate::api::{Profile, Team, TeamMember};
+use crate::utils::truncate_name;
+use crate::widgets::click_area::click_area;
+use crate::{Message, style::Theme};
+use iced::{
+    Border, Element, Font, Length, Padding,
+    alignment::Vertical,
+    border, font,
+    widget::{Column, Id, column, container, row, scrollable, text, text_input},
+};
+
+fn action_button<'a>(theme: &'a Theme, label: &'a str, on_press: Message) -> Element<'a, Message> {
+    click_area(
+        container(text(label).size(14))
+            .padding(Padding {
+                top: 4.0,
+                bottom: 4.0,
+                left: 8.0,
+                right: 8.0,
+            })
+            .style(|_| container::Style {
+                background: Some(theme.colors.foreground_surface.into()),
+                border: border::rounded(4),
+                ..Default::default()
+            }),
+    )
+    .interaction(iced::mouse::Interaction::Pointer)
+    .on_press(on_press)
+    .into()
+}
+
+// Deleting, removing and leaving can't be undone, so they are confirmed in place
+fn confirmed_action_button<'a>(
+    theme: &'a Theme,
+    label: &'a str,
+    id: &String,
+    confirming_team_action: &Option<String>,
+    on_confirm: Message,
+) -> Element<'a, Message> {
+    if confirming_team_action.as_ref() != Some(id) {
+        return action_button(theme, label, Message::ConfirmTeamAction(Some(id.clone())));
+    }
+
+    row![
+        action_button(theme, "Cancel", Message::ConfirmTeamAction(None)),
+        click_area(
+            container(text(format!("Confirm {}", label.to_lowercase())).size(14))
+                .padding(Padding {
+                    top: 4.0,
+                    bottom: 4.0,
+                    left: 8.0,
+                    right: 8.0,
+                })
+                .style(|_| container::Style {
+                    background: Some(theme.colors.status_busy.into()),
+                    border: border::rounded(4),
+                    ..Default::default()
+                }),
+        )
+        .interaction(iced::mouse::Interaction::Pointer)
+        .on_press(on_confirm)
+    ]
+    .spacing(6)
+    .into()
+}
+
+fn list_row<'a>(
+    theme: &'a Theme,
+    title: String,
+    details: Option<String>,
+    actions: Element<'a, Message>,
+) -> Element<'a, Message> {
+    let mut title_column = column![text(truncate_name(title, 36))].spacing(2);
+
+    if let Some(details) = details {
+        title_column = title_column.push(text(details).size(14).color(theme.colors.demo_text));
+    }
+
+    container(
+        row![
+            title_column,
+            container(actions).align_right(Length::Fill)
+        ]
+        .align_y(Vertical::Center),
+    )
+    .width(Length::Fill)
+    .padding(Padding {
+        top: 7.0,
+        right: 6.0,
+        bottom: 7.0,
+        left: 6.0,
+    })
+    .style(|_| container::Style {
+        background: Some(theme.colors.foreground.into()),
+        border: border::rounded(4),
+        ..Default::default()
+    })
+    .into()
+}
+
+pub fn c_manage_team<'a>(
+    theme: &'a Theme,
+    team: &'a Team,
+    members: Option<&'a Vec<TeamMember>>,
+    channel_name_input_value: &str,
+    renaming_channel: &Option<String>,
+    confirming_team_action: &Option<String>,
+    me: &Profile,
+) -> Element<'a, Message> {
+    let mut manage_column: Column<Message> = column![].spacing(4).padding(Padding {
+        left: 8.0,
+        right: 6.0,
+        top: 6.0,
+        bottom: 6.0,
+    });
+
+    // Channels
+
+    manage_column = manage_column.push(text("Channels").size(14).color(theme.colors.demo_text));
+
+    let mut channel_input_row = row![
+        text_input(
+            if renaming_channel.is_some() {
+                "New channel name"
+            } else {
+                "Create a channel"
+            },
+            channel_name_input_value
+        )
+        .on_input(Message::ChannelNameInputChanged)
+        .on_submit(Message::SubmitChannelName)
+        .padding(8)
+        .id("channel_name_input")
+        .style(|_, _| theme.stylesheet.input),
+        action_button(
+            theme,
+            if renaming_channel.is_some() {
+                "Rename"
+            } else {
+                "Create"
+            },
+            Message::SubmitChannelName
+        ),
+    ]
+    .align_y(Vertical::Center)
+    .spacing(6);
+
+    if renaming_channel.is_some() {
+        channel_input_row =
+            channel_input_row.push(action_button(theme, "Cancel", Message::RenameChannel(None)));
+    }
+
+    manage_column = manage_column.push(channel_input_row);
+
+    for channel in &team.channels {
+        let mut actions = row![action_button(
+            theme,
+            "Rename",
+            Message::RenameChannel(Some(channel.id.clone()))
+        )]
+        .spacing(6);
+
+        // The general channel shares the team id and can't be deleted
+        if channel.id != team.id {
+            actions = actions.push(confirmed_action_button(
+                theme,
+                "Delete",
+                &channel.id,
+                confirming_team_action,
+                Message::DeleteChannel(team.id.clone(), channel.id.clone()),
+            ));
+        }
+
+        manage_column = manage_column.push(list_row(
+            theme,
+            channel.display_name.clone(),
+            None,
+            actions.into(),
+        ));
+    }
+
+    // Members
+
+    manage_column = manage_column.push(
+        container(text("Members").size(14).color(theme.colors.demo_text)).padding(Padding {
+            top: 8.0,
+            bottom: 0.0,
+            left: 0.0,
+            right: 0.0,
+        }),
+    );
+
+    if let Some(members) = members {
+        for member in members {
+            let is_owner = member.roles.iter().any(|role| role == "owner");
+            let is_me = member.user_id.as_ref() == Some(&me.id);
+
+            let mut actions = row![].spacing(6);
+
+            if !is_me {
+                actions = actions.push(action_button(
+                    theme,
+                    if is_owner { "Make member" } else { "Make owner" },
+                    Message::SetTeamMemberRole(team.id.clone(), member.id.clone(), !is_owner),
+                ));
+                actions = actions.push(confirmed_action_button(
+                    theme,
+                    "Remove",
+                    &member.id,
+                    confirming_team_action,
+                    Message::RemoveTeamMember(team.id.clone(), member.id.clone()),
+                ));
+            }
+
+            manage_column = manage_column.push(list_row(
+                theme,
+                member
+                    .display_name
+                    .clone()
+                    .unwrap_or("Unknown User".to_string()),
+                Some(if is_owner { "Owner" } else { "Member" }.to_string()),
+                actions.into(),
+            ));
+        }
+    } else {
+        manage_column =
+            manage_column.push(text("Loading members...").color(theme.colors.demo_text));
+    }
+
+    click_area(
+        container(
+            column![
+                container(
+                    row![
+                        text(truncate_name(team.display_name.clone(), 30)).font(Font {
+                            weight: font::Weight::Bold,
+                            ..Default::default()
+                        }),
+                        container(confirmed_action_button(
+                            theme,
+                            "Leave team",
+                            &team.id,
+                            confirming_team_action,
+                            Message::LeaveTeam(team.id.clone())
+                        ))
+                        .align_right(Length::Fill)
+                    ]
+                    .align_y(Vertical::Center)
+                )
+                .padding(Padding {
+                    top: 8.0,
+                    bottom: 0.0,
+                    left: 8.0,
+                    right: 8.0
+                }),
+                scrollable(manage_column)
+                    .direction(scrollable::Direction::Vertical(
+                        scrollable::Scrollbar::new()
+                            .width(4)
+                            .spacing(0)
+                            .scroller_width(4),
+                    ))
+                    .style(|_, _| theme.stylesheet.scrollable)
+                    .id(Id::new("manage_team_column"))
+            ]
+            .spacing(6),
+        )
+        .width(500)
+        .height(520)
+        .padding(1) // Otherwise the border is bugging
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            border: Border {
+                color: theme.colors.line,
+                width: 1.0,
+                radius: 4.into(),
+            },
+            ..Default::default()
+        }),
+    )
+    .on_enter(Message::EnterCenteredOverlay)
+    .on_exit(Message::ExitCenteredOverlay)
+    .into()
+}
@@ -0,0 +1,62 @@
+use crate::Message;
+use crate::components::cached_image::c_cached_image;
+use crate::media::format_duration;
+use crate::style;
+use crate::types::{MediaAttachment, MediaKind};
+use crate::utils::{self, ams_image_identifier};
+use crate::widgets::click_area::click_area;
+use iced::widget::{column, container, row, text};
+use iced::{Alignment, Element, border};
+
+// Played by the system player, see Message::PlayMedia
+pub fn c_media_attachment(
+    theme: &style::Theme,
+    media: MediaAttachment,
+) -> Element<'static, Message> {
+    let background = theme.colors.foreground_surface;
+    let demo_text = theme.colors.demo_text;
+
+    let preview: Element<Message> = match (&media.kind, &media.thumbnail_url) {
+        (MediaKind::Video, Some(thumbnail_url)) => {
+            let identifier = ams_image_identifier(thumbnail_url);
+            c_cached_image(
+                theme,
+                identifier.clone(),
+                Message::AuthorizeImage(thumbnail_url.clone(), identifier),
+                160.0,
+                90.0,
+                4.0,
+            )
+        }
+        _ => utils::icon(theme, "play.svg")
+            .width(24)
+            .height(24)
+            .into(),
+    };
+
+    let title = match media.kind {
+        MediaKind::Audio => "Voice message",
+        MediaKind::Video => "Video",
+    };
+
+    let mut details = column![text(media.name.clone().unwrap_or(title.to_string()))].spacing(2);
+
+    let mut info = vec!["Play".to_string()];
+    if let Some(duration_ms) = media.duration_ms {
+        info.insert(0, format_duration(duration_ms));
+    }
+    details = details.push(text(info.join(" · ")).size(14).color(demo_text));
+
+    click_area(
+        container(row![preview, details].spacing(10).align_y(Alignment::Center))
+            .padding(8)
+            .style(move |_| container::Style {
+                background: Some(background.into()),
+                border: border::rounded(6),
+                ..Default::default()
+            }),
+    )
+    .on_press(Message::PlayMedia(media))
+    .interaction(iced::mouse::Interaction::Pointer)
+    .into()
+}
@@ -1,15 +1,18 @@
 use crate::Message;
 use crate::api::Profile;
 use crate::components::cached_image::c_cached_image;
-use crate::components::emoji_picker::c_emoji_picker;
+use crate::components::chat_message::c_outbox_status;
+use crate::components::emoji_picker::{c_emoji, c_emoji_picker};
+use crate::components::link_preview::c_link_preview;
+use crate::components::reactions::{MANY_REACTIONS, c_reaction_users};
 use crate::components::picture_and_status::c_picture_and_status;
-use crate::parsing::{parse_card_html, parse_message_html};
-use crate::types::Emoji;
+use crate::parsing::{is_media_message, parse_card_html, parse_message_html};
+use crate::types::{Emoji, EmojiUsage, OutboxMessage, Settings};
 use crate::websockets::Presence;
 use crate::widgets::anchored_overlay::anchored_overlay;
 use crate::{style, utils};
 use crate::widgets::click_area::click_area;
-use iced::widget::{column, container, row, svg, text, tooltip};
+use iced::widget::{column, container, row, text, tooltip};
 use iced::{Alignment, Border, Element, Font, Padding, border, font};
 use indexmap::IndexMap;
 use std::collections::HashMap;
@@ -23,9 +26,12 @@ pub fn c_message<'a>(
     message: crate::api::Message,
     emoji_map: &'a IndexMap<String, Emoji>,
     search_emojis_input_value: &String,
+    settings: &Settings,
+    emoji_usage: &HashMap<String, EmojiUsage>,
     users: &HashMap<String, Profile>,
     me: &Profile,
     user_presences: &'a HashMap<String, Presence>,
+    outbox: &IndexMap<String, OutboxMessage>,
     show_plus_emoji_picker: &bool,
     emoji_picker_message_id: &Option<String>,
     window_size: &(f32, f32),
@@ -43,7 +49,10 @@ pub fn c_message<'a>(
     // Message info bar
 
     if let Some(message_type) = message.message_type.clone() {
-        if message_type == "RichText/Html" || message_type == "Text" {
+        if message_type == "RichText/Html"
+            || message_type == "Text"
+            || is_media_message(&message_type)
+        {
             // The message.im_display_name value is useless. Some messages don't have it and it can be set completely arbitrarily by the client. Instead, Teams matches the displayname from the user id.
             if let Some(user_id) = message.from {
                 let profile = users.get(&user_id.replace("8:orgid:", ""));
@@ -56,6 +65,7 @@ pub fn c_message<'a>(
                     let identifier = user_id.clone().replace(":", "");
 
                     let user_picture = c_cached_image(
+                        theme,
                         identifier.clone(),
                         Message::FetchUserImage(identifier, user_id.clone(), display_name.clone()),
                         31.0,
@@ -94,6 +104,13 @@ pub fn c_message<'a>(
         message_info = message_info.push(text(time).size(14).color(theme.colors.demo_text));
     }
 
+    if let Some(client_message_id) = &message.client_message_id
+        && let Some(outbox_message) = outbox.get(client_message_id)
+        && let Some(outbox_status) = c_outbox_status(theme, outbox_message)
+    {
+        message_info = message_info.push(outbox_status);
+    }
+
     message_column = message_column.push(message_info.wrap());
 
     // Message subject
@@ -170,7 +187,7 @@ pub fn c_message<'a>(
             ..Font::default()
         }));
     } else if let Some(message_type) = message.message_type.clone() {
-        if message_type == "RichText/Html" {
+        if message_type == "RichText/Html" || is_media_message(&message_type) {
             if let Some(content) = message.content {
                 match parse_message_html(theme, content) {
                     Ok(result) => {
@@ -203,6 +220,19 @@ pub fn c_message<'a>(
         }
     }
 
+    // Link previews
+
+    if !deleted
+        && let Some(properties) = &message.properties
+        && let Some(links) = &properties.links
+    {
+        for link in links {
+            if let Some(link_preview) = c_link_preview(theme, link) {
+                message_column = message_column.push(link_preview);
+            }
+        }
+    }
+
     // Message reactions
 
     if !deleted {
@@ -216,12 +246,10 @@ pub fn c_message<'a>(
                         continue;
                     }
 
-                    let mut reaction_text = text("(?)");
-
-                    let reaction_val = emoji_map.get(&reaction.key);
-                    if let Some(reaction_val) = reaction_val {
-                        reaction_text = text(reaction_val.unicode.clone());
-                    }
+                    let reaction_emoji = match emoji_map.get(&reaction.key) {
+                        Some(reaction_val) => c_emoji(theme, reaction_val, 16.0),
+                        None => text("(?)").into(),
+                    };
 
                     let mut self_has_reacted = false;
                     for reactor in &reaction.users {
@@ -232,27 +260,9 @@ pub fn c_message<'a>(
                         }
                     }
 
-                    let reaction_string = reaction
-                        .users
-                        .iter()
-                        .filter_map(|reactor| {
-                            if let Some(reactor_id) = reactor.mri.split(":").nth(2) {
-                                users.get(reactor_id).map(|profile| {
-                                    profile
-                                        .display_name
-                                        .clone()
-                                        .unwrap_or("Unknown User".to_string())
-                                })
-                            } else {
-                                Some("Unknown User".to_string())
-                            }
-                        })
-                        .collect::<Vec<String>>()
-                        .join(", ");
-
                     let reaction_container = tooltip(
                         click_area(
-                            container(row![reaction_text, text(reacters)].spacing(4))
+                            container(row![reaction_emoji, text(reacters)].spacing(4))
                                 .style(move |_| {
                                     if self_has_reacted {
                                         container::Style {
@@ -287,27 +297,40 @@ pub fn c_message<'a>(
                             reaction.clone(),
                         ))
                         .interaction(iced::mouse::Interaction::Pointer),
-                        container(text(format!(
-                            "{} \"{}\" reacted by {}",
-                            reaction_val
-                                .map(|emoji| emoji.unicode.clone())
-                                .unwrap_or("(?)".to_string()),
-                            reaction.key.clone(),
-                            reaction_string
-                        )))
-                        .padding(12)
-                        .max_width(200)
-                        .style(|_| container::Style {
-                            background: Some(theme.colors.foreground_surface.into()),
-                            border: border::rounded(4),
-                            ..Default::default()
-                        }),
+                        c_reaction_users(theme, reaction, emoji_map, users),
                         tooltip::Position::Top,
                     )
                     .delay(Duration::from_millis(400));
 
                     reactions_row = reactions_row.push(reaction_container);
                 }
+
+                // Long reaction lists are easier to go through grouped in an overlay
+                let total_reacters: usize =
+                    reactions.iter().map(|reaction| reaction.users.len()).sum();
+                if total_reacters >= MANY_REACTIONS {
+                    reactions_row = reactions_row.push(
+                        click_area(
+                            container(text("All reactions").size(14))
+                                .style(|_| container::Style {
+                                    background: Some(theme.colors.foreground_surface.into()),
+                                    border: border::rounded(4),
+                                    ..Default::default()
+                                })
+                                .padding(Padding {
+                                    top: 3.0,
+                                    right: 5.0,
+                                    bottom: 3.0,
+                                    left: 5.0,
+                                }),
+                        )
+                        .on_press(Message::ToggleReactions(
+                            source_thread_id.clone(),
+                            message.id.clone().unwrap(),
+                        ))
+                        .interaction(iced::mouse::Interaction::Pointer),
+                    );
+                }
             }
         }
 
@@ -332,6 +355,8 @@ pub fn c_message<'a>(
                     c_emoji_picker(
                         theme,
                         search_emojis_input_value,
+                        &settings.skin_tone,
+                        emoji_usage,
                         emoji_map,
                         move |emoji_id, emoji_unicode| {
                             Message::EmojiPickerReaction(
@@ -368,7 +393,7 @@ pub fn c_message<'a>(
                     let file_container = click_area(
                         container(
                             row![
-                                svg(utils::get_image_dir().join("paperclip.svg"))
+                                utils::icon(theme, "paperclip.svg")
                                     .width(16)
                                     .height(16),
                                 text(file.file_name.clone().unwrap_or("File".to_string()))
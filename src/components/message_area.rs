@@ -1,34 +1,593 @@
-use iced::widget::text_editor::Content;
+use iced::keyboard::{Key, key::Named};
+use iced::widget::text_editor::{Binding, Content};
 use crate::widgets::click_area::click_area;
 use iced::widget::{
-    column, container, rich_text, row, space, span, svg, text, text_editor, text_input,
-    tooltip,
+    column, container, pick_list, rich_text, row, scrollable, space, span, text,
+    text_editor, text_input, tooltip,
 };
 use iced::{Alignment, Border, Element, Font, Length, Padding, border, font, padding};
 use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::ops::Range;
 
-use crate::components::emoji_picker::c_emoji_picker;
+use crate::api::Link;
+use crate::components::cached_image::c_cached_image;
+use crate::components::emoji_picker::{c_emoji, c_emoji_picker};
+use crate::components::link_preview::c_link_preview;
 use crate::components::toooltip::c_tooltip;
-use crate::types::{Emoji, MessageAreaAction};
+use crate::highlight::CODE_LANGUAGES;
+use crate::parsing::parse_message_html;
+use crate::rich_text::{HighlightSettings, RichDocument, RichHighlighter, to_format};
+use crate::spelling::SpellingMenu;
+use crate::types::{
+    Emoji, EmojiUsage, MessageAreaAction, PendingImage, ReplyTarget, Settings, SkinTone,
+};
 use crate::widgets::anchored_overlay::anchored_overlay;
+use crate::widgets::spelling_underline::spelling_underline;
 use crate::{Message, Page};
+use crate::utils::{apply_skin_tone, search_emojis};
 use crate::{style, utils};
 
-pub fn c_message_area<'a>(
+fn c_pending_images<'a>(
+    theme: &'a style::Theme,
+    pending_images: &Vec<PendingImage>,
+) -> Element<'a, Message> {
+    let mut images_row = row![].spacing(8);
+
+    for pending_image in pending_images {
+        // Keep the aspect ratio of the thumbnail
+        let scale = 64.0 / pending_image.width.max(pending_image.height).max(1) as f32;
+
+        images_row = images_row.push(
+            container(
+                row![
+                    c_cached_image(
+                        theme,
+                        pending_image.identifier.clone(),
+                        Message::DoNothing(()),
+                        pending_image.width as f32 * scale,
+                        pending_image.height as f32 * scale,
+                        4.0,
+                    ),
+                    tooltip(
+                        click_area(
+                            utils::icon(theme, "trash.svg")
+                                .width(16)
+                                .height(16)
+                        )
+                        .on_press(Message::RemovePendingImage(
+                            pending_image.identifier.clone()
+                        ))
+                        .interaction(iced::mouse::Interaction::Pointer),
+                        c_tooltip(theme, "Remove"),
+                        tooltip::Position::Top
+                    )
+                ]
+                .align_y(Alignment::Start)
+                .spacing(4),
+            )
+            .padding(4)
+            .style(|_| container::Style {
+                background: Some(theme.colors.foreground_surface.into()),
+                border: border::rounded(4),
+                ..Default::default()
+            }),
+        );
+    }
+
+    container(images_row.wrap()).padding(padding::bottom(6)).into()
+}
+
+// The preview is only attached to the message that is being written
+fn c_pending_link_preview<'a>(
+    theme: &'a style::Theme,
+    link_preview: &Option<Link>,
+) -> Element<'a, Message> {
+    let Some(link_preview) = link_preview.as_ref().and_then(|link| c_link_preview(theme, link))
+    else {
+        return space().into();
+    };
+
+    container(
+        row![
+            link_preview,
+            tooltip(
+                click_area(
+                    utils::icon(theme, "trash.svg")
+                        .width(16)
+                        .height(16)
+                )
+                .on_press(Message::RemoveLinkPreview)
+                .interaction(iced::mouse::Interaction::Pointer),
+                c_tooltip(theme, "Remove preview"),
+                tooltip::Position::Top
+            )
+        ]
+        .align_y(Alignment::Start)
+        .spacing(4),
+    )
+    .padding(padding::bottom(6))
+    .into()
+}
+
+fn c_reply_target<'a>(theme: &'a style::Theme, reply_target: &ReplyTarget) -> Element<'a, Message> {
+    container(
+        row![
+            container(
+                column![
+                    text(format!("Replying to {}", reply_target.name))
+                        .size(14)
+                        .font(Font {
+                            weight: font::Weight::Bold,
+                            ..Default::default()
+                        }),
+                    text(utils::truncate_name(reply_target.preview.clone(), 80))
+                        .size(14)
+                        .color(theme.colors.demo_text),
+                ]
+                .spacing(2),
+            )
+            .padding(6)
+            .width(Length::Fill)
+            .style(|_| container::Style {
+                background: Some(theme.colors.foreground_alt.into()),
+                border: border::rounded(4),
+                ..Default::default()
+            }),
+            tooltip(
+                click_area(
+                    utils::icon(theme, "trash.svg")
+                        .width(16)
+                        .height(16)
+                )
+                .on_press(Message::CancelReply)
+                .interaction(iced::mouse::Interaction::Pointer),
+                c_tooltip(theme, "Cancel reply"),
+                tooltip::Position::Top
+            )
+        ]
+        .align_y(Alignment::Start)
+        .spacing(4),
+    )
+    .padding(padding::bottom(6))
+    .into()
+}
+
+// Picking a language inserts a code block for it
+fn c_code_language_picker<'a>(theme: &'a style::Theme) -> Element<'a, Message> {
+    let labels: Vec<&str> = CODE_LANGUAGES.iter().map(|(label, _, _)| *label).collect();
+
+    pick_list(labels, None::<&str>, |label| {
+        let language = CODE_LANGUAGES
+            .iter()
+            .find(|(language_label, _, _)| *language_label == label)
+            .map(|(_, class, _)| class.to_string())
+            .unwrap_or_default();
+        Message::MessageAreaAction(MessageAreaAction::CodeBlock(language))
+    })
+    .placeholder("Code block")
+    .text_size(14)
+    .padding(padding::horizontal(6))
+    .style(|_, _| pick_list::Style {
+        text_color: theme.colors.text,
+        placeholder_color: theme.colors.text,
+        handle_color: theme.colors.demo_text,
+        background: theme.colors.foreground_alt.into(),
+        border: border::rounded(4),
+    })
+    .menu_style(|iced_theme| iced::overlay::menu::Style {
+        background: theme.colors.foreground_alt.into(),
+        text_color: theme.colors.text,
+        selected_text_color: theme.colors.text,
+        selected_background: theme.colors.foreground_surface.into(),
+        border: border::rounded(4),
+        ..iced::overlay::menu::default(iced_theme)
+    })
+    .into()
+}
+
+const PREVIEW_MAX_HEIGHT: f32 = 300.0;
+
+const PREVIEW_IMAGE_SIZE: f32 = 200.0;
+
+// Shows the draft rendered like a sent message, subject and attachments included
+fn c_message_preview<'a>(
     theme: &'a style::Theme,
-    message_area_content: &'a Content,
     subject_input_content: &Option<String>,
+    preview_html: &str,
+    pending_images: &Vec<PendingImage>,
+    link_preview: &Option<Link>,
+    message_area_height: f32,
+) -> Element<'a, Message> {
+    let mut preview_column = column![].spacing(4);
+
+    if let Some(subject) = subject_input_content
+        && !subject.trim().is_empty()
+    {
+        preview_column = preview_column.push(text(subject.trim_start().to_string()).font(Font {
+            weight: font::Weight::Bold,
+            ..Default::default()
+        }));
+    }
+
+    if preview_html.is_empty() && pending_images.is_empty() {
+        preview_column =
+            preview_column.push(text("Nothing to preview").color(theme.colors.demo_text));
+    } else if !preview_html.is_empty() {
+        match parse_message_html(theme, preview_html.to_string()) {
+            Ok(result) => {
+                preview_column = preview_column.push(result);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+            }
+        }
+    }
+
+    let mut images_row = row![].spacing(8);
+    for pending_image in pending_images {
+        let scale = (PREVIEW_IMAGE_SIZE
+            / pending_image.width.max(pending_image.height).max(1) as f32)
+            .min(1.0);
+        images_row = images_row.push(c_cached_image(
+            theme,
+            pending_image.identifier.clone(),
+            Message::DoNothing(()),
+            pending_image.width as f32 * scale,
+            pending_image.height as f32 * scale,
+            8.0,
+        ));
+    }
+    preview_column = preview_column.push(images_row.wrap());
+
+    if let Some(link_preview) = link_preview.as_ref().and_then(|link| c_link_preview(theme, link)) {
+        preview_column = preview_column.push(link_preview);
+    }
+
+    container(
+        scrollable(container(preview_column).width(Length::Fill).padding(Padding {
+            top: 5.0,
+            right: 10.0,
+            bottom: 5.0,
+            left: 5.0,
+        }))
+        .height(Length::Shrink)
+        .style(|_, _| theme.stylesheet.scrollable),
+    )
+    .width(Length::Fill)
+    .height(Length::Shrink)
+    .max_height(PREVIEW_MAX_HEIGHT.max(message_area_height))
+    .padding(padding::left(5))
+    .style(|_| container::Style {
+        background: Some(theme.colors.foreground_alt.into()),
+        border: border::rounded(4),
+        ..Default::default()
+    })
+    .into()
+}
+
+// Asks for the url of the link that is added to the selection
+fn c_rich_link_input<'a>(theme: &'a style::Theme, url: &str) -> Element<'a, Message> {
+    container(
+        text_input("Paste a link and press Enter", url)
+            .id("rich_link_input")
+            .on_input(Message::RichLinkInputChanged)
+            .on_submit(Message::SubmitRichLink)
+            .padding(6)
+            .style(|_, _| text_input::Style {
+                background: theme.colors.foreground_alt.into(),
+                border: border::rounded(6),
+                icon: theme.colors.not_set,
+                placeholder: theme.colors.demo_text,
+                value: theme.colors.text,
+                selection: theme.colors.text_selection,
+            }),
+    )
+    .padding(padding::bottom(6))
+    .into()
+}
+
+fn highlight_settings(
+    theme: &style::Theme,
+    rich_document: Option<&RichDocument>,
+    misspelled: Option<&Vec<Vec<Range<usize>>>>,
+    text: &str,
+) -> HighlightSettings {
+    HighlightSettings {
+        // The document catches up on the next edit when the editor text was replaced
+        spans: match rich_document {
+            Some(rich_document) if rich_document.text() == text => rich_document.line_spans(),
+            _ => vec![],
+        },
+        link_color: theme.colors.text_link,
+        underline_color: theme.colors.accent,
+        strikethrough_color: theme.colors.demo_text,
+        quote_color: theme.colors.demo_text,
+        misspelled: misspelled.cloned().unwrap_or_default(),
+        misspelled_color: theme.colors.status_busy,
+    }
+}
+
+fn c_spelling_menu<'a>(theme: &'a style::Theme, menu: &SpellingMenu) -> Element<'a, Message> {
+    let item_background = theme.colors.foreground;
+    let item = move |label: Element<'a, Message>, message: Message| {
+        click_area(
+            container(label)
+                .width(Length::Fill)
+                .padding(Padding {
+                    top: 4.0,
+                    bottom: 4.0,
+                    left: 6.0,
+                    right: 6.0,
+                })
+                .style(move |_| container::Style {
+                    background: Some(item_background.into()),
+                    border: border::rounded(4),
+                    ..Default::default()
+                }),
+        )
+        .on_press(message)
+        .interaction(iced::mouse::Interaction::Pointer)
+    };
+
+    let mut menu_column = column![].spacing(2);
+
+    if menu.suggestions.is_empty() {
+        menu_column = menu_column.push(
+            container(text("No suggestions").color(theme.colors.demo_text)).padding(6),
+        );
+    }
+
+    for suggestion in &menu.suggestions {
+        menu_column = menu_column.push(item(
+            text(suggestion.clone())
+                .font(Font {
+                    weight: font::Weight::Bold,
+                    ..Default::default()
+                })
+                .into(),
+            Message::ReplaceMisspelledWord(suggestion.clone()),
+        ));
+    }
+
+    menu_column = menu_column.push(item(
+        text!("Add \"{}\" to dictionary", menu.word).into(),
+        Message::AddToDictionary,
+    ));
+
+    container(menu_column)
+        .width(240)
+        .padding(4)
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground_alt.into()),
+            border: Border {
+                color: theme.colors.line,
+                width: 1.0,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+}
+
+const EMOJI_SUGGESTIONS_LIMIT: usize = 8;
+
+fn c_emoji_suggestions<'a>(
+    theme: &'a style::Theme,
+    suggestions: Vec<(&String, &Emoji)>,
+    skin_tone: &SkinTone,
+) -> Element<'a, Message> {
+    let mut suggestions_column = column![].spacing(2);
+
+    for (emoji_id, emoji) in suggestions {
+        let emoji_unicode = if emoji.image_url.is_some() {
+            emoji.unicode.clone()
+        } else {
+            apply_skin_tone(&emoji.unicode, skin_tone)
+        };
+
+        let name = emoji.keywords.first().cloned().unwrap_or(emoji_id.clone());
+
+        suggestions_column = suggestions_column.push(
+            click_area(
+                container(
+                    row![
+                        if emoji.image_url.is_some() {
+                            c_emoji(theme, emoji, 20.0)
+                        } else {
+                            text(emoji_unicode.clone()).size(20).into()
+                        },
+                        text(name)
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(8),
+                )
+                .width(Length::Fill)
+                .padding(Padding {
+                    top: 4.0,
+                    bottom: 4.0,
+                    left: 6.0,
+                    right: 6.0,
+                })
+                .style(|_| container::Style {
+                    background: Some(theme.colors.foreground.into()),
+                    border: border::rounded(4),
+                    ..Default::default()
+                }),
+            )
+            .on_press(Message::PickEmojiSuggestion(emoji_id.clone(), emoji_unicode))
+            .interaction(iced::mouse::Interaction::Pointer),
+        );
+    }
+
+    container(suggestions_column)
+        .width(280)
+        .padding(4)
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground_alt.into()),
+            border: Border {
+                color: theme.colors.line,
+                width: 1.0,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+}
+
+// The composer state of the chat and team pages
+#[derive(Clone, Copy)]
+pub struct MessageAreaState<'a> {
+    pub content: &'a Content,
+    pub subject: &'a Option<String>,
+    pub height: &'a f32,
+    pub pending_images: &'a Vec<PendingImage>,
+    pub reply_target: &'a Option<ReplyTarget>,
+    pub link_preview: &'a Option<Link>,
+    pub preview: &'a Option<String>,
+    pub rich_document: Option<&'a RichDocument>,
+    pub rich_link_input: Option<&'a String>,
+    pub misspelled: Option<&'a Vec<Vec<Range<usize>>>>, // Set when spell checking is on
+    pub spelling_menu: &'a Option<SpellingMenu>,
+    pub is_recording_voice: bool,
+    pub show_emoji_picker: &'a bool,
+    pub search_emojis_input_value: &'a String,
+    pub emoji_usage: &'a HashMap<String, EmojiUsage>,
+    pub emoji_autocomplete: &'a Option<String>,
+}
+
+pub fn c_message_area<'a>(
+    theme: &'a style::Theme,
+    state: MessageAreaState<'a>,
     page: Page,
-    message_area_height: &f32,
-    show_emoji_picker: &bool,
-    search_emojis_input_value: &String,
+    settings: &Settings,
     emoji_map: &'a IndexMap<String, Emoji>,
     window_size: &(f32, f32),
 ) -> Element<'a, Message> {
+    let MessageAreaState {
+        content: message_area_content,
+        subject: subject_input_content,
+        height: message_area_height,
+        pending_images,
+        reply_target,
+        link_preview,
+        preview: message_area_preview,
+        rich_document,
+        rich_link_input,
+        misspelled,
+        spelling_menu,
+        is_recording_voice,
+        show_emoji_picker,
+        search_emojis_input_value,
+        emoji_usage,
+        emoji_autocomplete,
+    } = state;
+
+    let editor = text_editor(message_area_content)
+        .height(*message_area_height)
+        .on_action(Message::MessageAreaEdit)
+        .key_binding(|key_press| {
+            // The editor drops Tab as a control character, so it is bound here
+            if key_press.key == Key::Named(Named::Tab)
+                && matches!(key_press.status, text_editor::Status::Focused { .. })
+            {
+                return Some(Binding::Custom(Message::MessageAreaIndent(
+                    key_press.modifiers.shift(),
+                )));
+            }
+            Binding::from_key_press(key_press)
+        })
+        .placeholder("Type your message...")
+        .style(|_, _| text_editor::Style {
+            background: theme.colors.foreground_alt.into(),
+            border: border::rounded(4),
+            placeholder: theme.colors.demo_text,
+            value: theme.colors.text,
+            selection: theme.colors.text_selection,
+        })
+        .id("message_area");
+
+    // Formatting is shown in the editor itself in the rich text composer. The underlines of
+    // misspelled words can't follow the editor once it scrolls, at its full height they are colored
+    let is_full_height = *message_area_height >= 0.5 * window_size.1;
+    let editor = if rich_document.is_some() || misspelled.is_some() {
+        let text = message_area_content.text();
+        let settings = highlight_settings(
+            theme,
+            rich_document,
+            misspelled.filter(|_| is_full_height),
+            &text,
+        );
+        let highlighted =
+            Element::from(editor.highlight_with::<RichHighlighter>(settings.clone(), to_format));
+
+        match misspelled.filter(|_| !is_full_height) {
+            Some(misspelled) => spelling_underline(
+                highlighted,
+                text,
+                settings,
+                misspelled.clone(),
+                theme.colors.status_busy,
+            )
+            .into(),
+            None => highlighted,
+        }
+    } else {
+        editor.into()
+    };
+
+    // Right clicking a misspelled word shows suggestions for it
+    let message_editor = container(if misspelled.is_some() {
+        Element::from(click_area(editor).on_right_press(Message::OpenSpellingMenu))
+    } else {
+        editor
+    });
+
+    // Typing :shortcode shows matching emojis above the editor
+    let emoji_suggestions = emoji_autocomplete
+        .as_ref()
+        .map(|query| {
+            search_emojis(emoji_map, emoji_usage, query, Some(EMOJI_SUGGESTIONS_LIMIT))
+        })
+        .filter(|suggestions| !suggestions.is_empty());
+
+    let message_editor: Element<Message> = if let Some(preview_html) = message_area_preview {
+        c_message_preview(
+            theme,
+            subject_input_content,
+            preview_html,
+            pending_images,
+            link_preview,
+            *message_area_height,
+        )
+    } else if let Some(emoji_suggestions) = emoji_suggestions {
+        anchored_overlay(
+            message_editor,
+            c_emoji_suggestions(theme, emoji_suggestions, &settings.skin_tone),
+            crate::widgets::anchored_overlay::Position::Top,
+            (0.0, 4.0),
+            true,
+            *window_size,
+        )
+    } else if let Some(spelling_menu) = spelling_menu {
+        anchored_overlay(
+            message_editor,
+            c_spelling_menu(theme, spelling_menu),
+            crate::widgets::anchored_overlay::Position::Top,
+            (0.0, 4.0),
+            true,
+            *window_size,
+        )
+    } else {
+        message_editor.into()
+    };
+
     container(
         container(
             column![
-                if let Some(subject_input_content) = subject_input_content {
+                if let Some(subject_input_content) = subject_input_content
+                    && message_area_preview.is_none()
+                {
                     container(
                         text_input("Subject", &subject_input_content)
                             .font(Font {
@@ -50,27 +609,35 @@ pub fn c_message_area<'a>(
                 } else {
                     container(space())
                 },
-                container(
-                    text_editor(message_area_content)
-                        .height(*message_area_height)
-                        .on_action(move |action| Message::MessageAreaEdit(action))
-                        .placeholder("Type your message...")
-                        .style(|_, _| text_editor::Style {
-                            background: theme.colors.foreground_alt.into(),
-                            border: border::rounded(4),
-                            placeholder: theme.colors.demo_text,
-                            value: theme.colors.text,
-                            selection: theme.colors.text_selection,
-                        })
-                        .id("message_area")
-                ),
+                if let Some(reply_target) = reply_target {
+                    c_reply_target(theme, reply_target)
+                } else {
+                    space().into()
+                },
+                // The preview shows the attachments as they will be sent
+                if !pending_images.is_empty() && message_area_preview.is_none() {
+                    c_pending_images(theme, pending_images)
+                } else {
+                    space().into()
+                },
+                if message_area_preview.is_none() {
+                    c_pending_link_preview(theme, link_preview)
+                } else {
+                    space().into()
+                },
+                if let Some(url) = rich_link_input {
+                    c_rich_link_input(theme, url)
+                } else {
+                    space().into()
+                },
+                message_editor,
                 row![
                     row![
                         row![
                             {
                                 let content = tooltip(
                                     click_area(
-                                        svg(utils::get_image_dir().join("smile.svg"))
+                                        utils::icon(theme, "smile.svg")
                                             .width(19)
                                             .height(19),
                                     )
@@ -85,6 +652,8 @@ pub fn c_message_area<'a>(
                                         c_emoji_picker(
                                             theme,
                                             search_emojis_input_value,
+                                            &settings.skin_tone,
+                                            emoji_usage,
                                             emoji_map,
                                             |emoji_id, emoji_unicode| {
                                                 Message::EmojiPickerSend(emoji_id, emoji_unicode)
@@ -101,7 +670,7 @@ pub fn c_message_area<'a>(
                             },
                             tooltip(
                                 click_area(
-                                    svg(utils::get_image_dir().join("upload.svg"))
+                                    utils::icon(theme, "upload.svg")
                                         .width(19)
                                         .height(19)
                                 )
@@ -174,7 +743,7 @@ pub fn c_message_area<'a>(
                                 row![
                                     tooltip(
                                         click_area(
-                                            svg(utils::get_image_dir().join("list.svg"))
+                                            utils::icon(theme, "list.svg")
                                                 .width(23)
                                                 .height(23)
                                         )
@@ -187,7 +756,7 @@ pub fn c_message_area<'a>(
                                     ),
                                     tooltip(
                                         click_area(
-                                            svg(utils::get_image_dir().join("list-ordered.svg"))
+                                            utils::icon(theme, "list-ordered.svg")
                                                 .width(23)
                                                 .height(23)
                                         )
@@ -204,7 +773,7 @@ pub fn c_message_area<'a>(
                                 row![
                                     tooltip(
                                         click_area(
-                                            svg(utils::get_image_dir().join("code.svg"))
+                                            utils::icon(theme, "code.svg")
                                                 .width(23)
                                                 .height(23)
                                         )
@@ -215,9 +784,10 @@ pub fn c_message_area<'a>(
                                         c_tooltip(theme, "Code"),
                                         tooltip::Position::Top
                                     ),
+                                    c_code_language_picker(theme),
                                     tooltip(
                                         click_area(
-                                            svg(utils::get_image_dir().join("text-quote.svg"))
+                                            utils::icon(theme, "text-quote.svg")
                                                 .width(23)
                                                 .height(23)
                                         )
@@ -230,7 +800,7 @@ pub fn c_message_area<'a>(
                                     ),
                                     tooltip(
                                         click_area(
-                                            svg(utils::get_image_dir().join("link.svg"))
+                                            utils::icon(theme, "link.svg")
                                                 .width(19)
                                                 .height(19)
                                         )
@@ -243,7 +813,7 @@ pub fn c_message_area<'a>(
                                     ),
                                     tooltip(
                                         click_area(
-                                            svg(utils::get_image_dir().join("image.svg"))
+                                            utils::icon(theme, "image.svg")
                                                 .width(19)
                                                 .height(19)
                                         )
@@ -254,8 +824,36 @@ pub fn c_message_area<'a>(
                                         c_tooltip(theme, "Image"),
                                         tooltip::Position::Top
                                     ),
+                                    if let Page::Chat(_, _) = page {
+                                        container(tooltip(
+                                            click_area(
+                                                utils::icon(theme,
+                                                    if is_recording_voice {
+                                                        "square.svg"
+                                                    } else {
+                                                        "mic.svg"
+                                                    }
+                                                )
+                                                .width(19)
+                                                .height(19)
+                                            )
+                                            .on_press(Message::ToggleVoiceRecording)
+                                            .interaction(iced::mouse::Interaction::Pointer),
+                                            c_tooltip(
+                                                theme,
+                                                if is_recording_voice {
+                                                    "Stop and send"
+                                                } else {
+                                                    "Voice message"
+                                                }
+                                            ),
+                                            tooltip::Position::Top
+                                        ))
+                                    } else {
+                                        container(space())
+                                    },
                                     tooltip(
-                                        svg(utils::get_image_dir().join("at-sign.svg"))
+                                        utils::icon(theme, "at-sign.svg")
                                             .width(19)
                                             .height(19),
                                         c_tooltip(theme, "Mention"),
@@ -271,13 +869,40 @@ pub fn c_message_area<'a>(
                     .align_y(Alignment::Center)
                     .spacing(28),
                     container(row![
-                        if let Page::Team(_, _) = page {
+                        container(
+                            click_area(
+                                container(
+                                    row![
+                                        utils::icon(theme,
+                                            if message_area_preview.is_some() {
+                                                "pencil.svg"
+                                            } else {
+                                                "eye.svg"
+                                            }
+                                        )
+                                        .width(19)
+                                        .height(19),
+                                        text(if message_area_preview.is_some() {
+                                            "Edit"
+                                        } else {
+                                            "Preview"
+                                        })
+                                    ]
+                                    .align_y(Alignment::Center)
+                                    .spacing(6),
+                                )
+                                .padding(4),
+                            )
+                            .on_press(Message::ToggleMessageAreaPreview)
+                            .interaction(iced::mouse::Interaction::Pointer),
+                        ),
+                        if let Page::Team(_, _, _) = page {
                             if subject_input_content.is_none() {
                                 container(
                                     click_area(
                                         container(
                                             row![
-                                                svg(utils::get_image_dir().join("plus.svg"))
+                                                utils::icon(theme, "plus.svg")
                                                     .width(19)
                                                     .height(19),
                                                 text("Add subject")
@@ -295,7 +920,7 @@ pub fn c_message_area<'a>(
                                     click_area(
                                         container(
                                             row![
-                                                svg(utils::get_image_dir().join("minus.svg"))
+                                                utils::icon(theme, "minus.svg")
                                                     .width(19)
                                                     .height(19),
                                                 text("Remove Subject")
@@ -313,16 +938,54 @@ pub fn c_message_area<'a>(
                         } else {
                             container(space())
                         },
-                        if let Page::Team(_, _) = page {
-                            space().width(12)
+                        if let Page::Team(_, _, _) = page {
+                            container(
+                                click_area(
+                                    container(
+                                        row![
+                                            utils::icon(theme, "megaphone.svg")
+                                                .width(19)
+                                                .height(19),
+                                            text("Post options")
+                                        ]
+                                        .align_y(Alignment::Center)
+                                        .spacing(6),
+                                    )
+                                    .padding(4),
+                                )
+                                .on_press(Message::TogglePostOptions)
+                                .interaction(iced::mouse::Interaction::Pointer),
+                            )
+                        } else {
+                            container(space())
+                        },
+                        if let Page::Chat(_, _) | Page::Team(_, _, _) = page {
+                            container(
+                                click_area(
+                                    container(
+                                        row![
+                                            utils::icon(theme, "clock.svg")
+                                                .width(19)
+                                                .height(19),
+                                            text("Send later")
+                                        ]
+                                        .align_y(Alignment::Center)
+                                        .spacing(6),
+                                    )
+                                    .padding(4),
+                                )
+                                .on_press(Message::ToggleSendLater)
+                                .interaction(iced::mouse::Interaction::Pointer),
+                            )
                         } else {
-                            space()
+                            container(space())
                         },
+                        space().width(12),
                         container(
                             click_area(
                                 container(
                                     row![
-                                        svg(utils::get_image_dir().join("corner-down-right.svg"))
+                                        utils::icon(theme, "corner-down-right.svg")
                                             .width(19)
                                             .height(19),
                                         text("Enter to send")
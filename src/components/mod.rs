@@ -1,17 +1,31 @@
 pub mod add_users;
 pub mod cached_image;
+pub mod channel_tab;
 pub mod chat_message;
+pub mod code_block;
 pub mod conversation;
 pub mod emoji_picker;
+pub mod error_notice;
 pub mod expanded_image;
+pub mod forward_message;
 pub mod horizontal_line;
+pub mod link_preview;
+pub mod manage_team;
+pub mod media_attachment;
 pub mod message;
 pub mod message_area;
 pub mod more_options;
 pub mod picture_and_status;
+pub mod post_options;
 pub mod preview_message;
 pub mod profile;
+pub mod quick_switcher;
+pub mod reactions;
+pub mod scheduled_messages;
+pub mod send_later;
+pub mod shortcuts;
 pub mod sidebar;
 pub mod start_chat;
+pub mod team_files;
 pub mod toooltip;
 pub mod vertical_line;
@@ -5,12 +5,13 @@ use crate::utils;
 use crate::{Message, style};
 use iced::alignment::Vertical;
 use crate::widgets::click_area::click_area;
-use iced::widget::{column, container, row, space, svg, text};
+use iced::widget::{column, container, row, space, text};
 use iced::{Border, Element};
 
 pub fn c_more_options<'a>(
     theme: &'a style::Theme,
     message: crate::api::Message,
+    thread_id: &str,
     me: &Profile,
 ) -> Element<'a, Message> {
     click_area(
@@ -18,7 +19,7 @@ pub fn c_more_options<'a>(
             column![
                 click_area(
                     row![
-                        svg(utils::get_image_dir().join("smile.svg"))
+                        utils::icon(theme, "smile.svg")
                             .width(19)
                             .height(19),
                         text("Add reaction")
@@ -33,7 +34,7 @@ pub fn c_more_options<'a>(
                 c_horizontal_line(theme, 200.into()),
                 click_area(
                     row![
-                        svg(utils::get_image_dir().join("reply.svg"))
+                        utils::icon(theme, "reply.svg")
                             .width(19)
                             .height(19),
                         text("Reply")
@@ -49,7 +50,7 @@ pub fn c_more_options<'a>(
                 )),
                 click_area(
                     row![
-                        svg(utils::get_image_dir().join("copy.svg"))
+                        utils::icon(theme, "copy.svg")
                             .width(19)
                             .height(19),
                         text("Copy Text")
@@ -73,6 +74,33 @@ pub fn c_more_options<'a>(
                         "".to_string()
                     }
                 )),
+                click_area(
+                    row![
+                        utils::icon(theme, "link.svg")
+                            .width(19)
+                            .height(19),
+                        text("Copy link")
+                    ]
+                    .align_y(Vertical::Center)
+                    .spacing(8)
+                )
+                .interaction(iced::mouse::Interaction::Pointer)
+                .on_press(Message::CopyText(utils::message_link(
+                    thread_id,
+                    &message.id.clone().unwrap_or("0".to_string())
+                ))),
+                click_area(
+                    row![
+                        utils::icon(theme, "forward.svg")
+                            .width(19)
+                            .height(19),
+                        text("Forward")
+                    ]
+                    .align_y(Vertical::Center)
+                    .spacing(8)
+                )
+                .interaction(iced::mouse::Interaction::Pointer)
+                .on_press(Message::ToggleForwardMessage(message.clone())),
             ]
             .spacing(12),
             if message.from.clone().unwrap_or("none".to_string()) == format!("8:orgid:{}", me.id) {
@@ -80,7 +108,7 @@ pub fn c_more_options<'a>(
                     space(),
                     c_horizontal_line(theme, 200.into()),
                     row![
-                        svg(utils::get_image_dir().join("pencil.svg"))
+                        utils::icon(theme, "pencil.svg")
                             .width(19)
                             .height(19),
                         text("Edit message")
@@ -92,7 +120,7 @@ pub fn c_more_options<'a>(
                     {
                         click_area(
                             row![
-                                svg(utils::get_image_dir().join("rotate-ccw.svg"))
+                                utils::icon(theme, "rotate-ccw.svg")
                                     .width(19)
                                     .height(19),
                                 text("Restore Message")
@@ -105,7 +133,7 @@ pub fn c_more_options<'a>(
                     } else {
                         click_area(
                             row![
-                                svg(utils::get_image_dir().join("trash.svg"))
+                                utils::icon(theme, "trash.svg")
                                     .width(19)
                                     .height(19),
                                 text("Delete Message")
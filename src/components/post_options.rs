@@ -0,0 +1,204 @@
+use crate::api::Team;
+use crate::components::cached_image::c_cached_image;
+use crate::types::{PostOptions, ReplyPolicy};
+use crate::utils::truncate_name;
+use crate::widgets::click_area::click_area;
+use crate::{Message, style::Theme};
+use iced::{
+    Border, Element, Length, Padding,
+    alignment::Vertical,
+    border,
+    widget::{Column, Id, checkbox, column, container, row, scrollable, text, text_input},
+};
+
+fn option_row<'a>(
+    theme: &'a Theme,
+    label: String,
+    is_checked: bool,
+    on_press: Message,
+) -> Element<'a, Message> {
+    click_area(
+        container(
+            row![
+                text(label),
+                container(checkbox(is_checked).style(|_, _| checkbox::Style {
+                    background: theme.colors.foreground_surface.into(),
+                    border: border::rounded(2),
+                    icon_color: theme.colors.text,
+                    text_color: None,
+                }))
+                .align_right(Length::Fill)
+            ]
+            .width(Length::Fill)
+            .align_y(Vertical::Center),
+        )
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            border: border::rounded(4),
+            ..Default::default()
+        })
+        .padding(Padding {
+            top: 9.0,
+            right: 6.0,
+            bottom: 9.0,
+            left: 6.0,
+        }),
+    )
+    .interaction(iced::mouse::Interaction::Pointer)
+    .on_press(on_press)
+    .into()
+}
+
+fn section_label<'a>(theme: &'a Theme, label: &'a str) -> Element<'a, Message> {
+    container(text(label).size(14).color(theme.colors.demo_text))
+        .padding(Padding {
+            top: 8.0,
+            bottom: 0.0,
+            left: 0.0,
+            right: 0.0,
+        })
+        .into()
+}
+
+pub fn c_post_options<'a>(
+    theme: &'a Theme,
+    post_options: &PostOptions,
+    teams: &'a Vec<Team>,
+    current_channel_id: &String,
+) -> Element<'a, Message> {
+    let mut options_column: Column<Message> = column![].spacing(4).padding(Padding {
+        left: 8.0,
+        right: 6.0,
+        top: 6.0,
+        bottom: 6.0,
+    });
+
+    // Announcement
+
+    options_column = options_column.push(option_row(
+        theme,
+        "Announcement".to_string(),
+        post_options.announcement,
+        Message::ToggleAnnouncement,
+    ));
+
+    if post_options.announcement {
+        options_column = options_column.push(
+            text_input("Headline", &post_options.headline)
+                .on_input(Message::HeadlineInputChanged)
+                .padding(8)
+                .id("headline_input")
+                .style(|_, _| theme.stylesheet.input),
+        );
+
+        let banner: Element<Message> = if let Some(banner) = &post_options.banner {
+            row![
+                c_cached_image(
+                    theme,
+                    banner.identifier.clone(),
+                    Message::DoNothing(()),
+                    120.0,
+                    40.0,
+                    4.0,
+                ),
+                click_area(text("Remove banner").color(theme.colors.demo_text))
+                    .on_press(Message::RemoveAnnouncementBanner)
+                    .interaction(iced::mouse::Interaction::Pointer)
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center)
+            .into()
+        } else {
+            click_area(text("Choose banner image..."))
+                .on_press(Message::PickAnnouncementBanner)
+                .interaction(iced::mouse::Interaction::Pointer)
+                .into()
+        };
+
+        options_column = options_column.push(container(banner).padding(Padding {
+            top: 4.0,
+            bottom: 4.0,
+            left: 6.0,
+            right: 6.0,
+        }));
+    }
+
+    // Who can reply
+
+    options_column = options_column.push(section_label(theme, "Who can reply"));
+    options_column = options_column.push(option_row(
+        theme,
+        "Everyone can reply".to_string(),
+        post_options.reply_policy == ReplyPolicy::Everyone,
+        Message::SetReplyPolicy(ReplyPolicy::Everyone),
+    ));
+    options_column = options_column.push(option_row(
+        theme,
+        "You and moderators can reply".to_string(),
+        post_options.reply_policy == ReplyPolicy::Moderators,
+        Message::SetReplyPolicy(ReplyPolicy::Moderators),
+    ));
+
+    // Cross posting
+
+    options_column = options_column.push(section_label(theme, "Also post in"));
+
+    for team in teams {
+        for channel in &team.channels {
+            if &channel.id == current_channel_id {
+                continue;
+            }
+
+            options_column = options_column.push(option_row(
+                theme,
+                format!(
+                    "{} > {}",
+                    truncate_name(team.display_name.clone(), 20),
+                    truncate_name(channel.display_name.clone(), 24)
+                ),
+                post_options.cross_post_channels.contains(&channel.id),
+                Message::ToggleCrossPostChannel(channel.id.clone()),
+            ));
+        }
+    }
+
+    click_area(
+        container(
+            column![
+                container(text("Post options").size(14).color(theme.colors.demo_text)).padding(
+                    Padding {
+                        top: 8.0,
+                        bottom: 0.0,
+                        left: 8.0,
+                        right: 8.0
+                    }
+                ),
+                scrollable(options_column)
+                    .direction(scrollable::Direction::Vertical(
+                        scrollable::Scrollbar::new()
+                            .width(4)
+                            .spacing(0)
+                            .scroller_width(4),
+                    ))
+                    .style(|_, _| theme.stylesheet.scrollable)
+                    .id(Id::new("post_options_column"))
+            ]
+            .spacing(6),
+        )
+        .width(450)
+        .height(500)
+        .padding(1) // Otherwise the border is bugging
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            border: Border {
+                color: theme.colors.line,
+                width: 1.0,
+                radius: 4.into(),
+            },
+            ..Default::default()
+        }),
+    )
+    .on_enter(Message::EnterCenteredOverlay)
+    .on_exit(Message::ExitCenteredOverlay)
+    .into()
+}
@@ -4,10 +4,24 @@ use indexmap::IndexMap;
 
 use crate::components::cached_image::c_cached_image;
 use crate::style;
-use crate::types::Emoji;
+use crate::types::{Draft, Emoji};
 use crate::utils::truncate_name;
 use crate::Message;
 
+// Marks a chat or channel with an unsent draft in the lists
+pub fn c_draft_preview<'a>(
+    theme: &'a style::Theme,
+    draft: &Draft,
+    max_len: usize,
+) -> Element<'a, Message> {
+    let draft_preview = draft.text.lines().next().unwrap_or("").to_string();
+
+    text!("Draft: {}", truncate_name(draft_preview, max_len))
+        .size(14)
+        .color(theme.colors.demo_text)
+        .into()
+}
+
 pub fn c_preview_message<'a>(
     theme: &'a style::Theme,
     activity: crate::api::Activity,
@@ -30,6 +44,7 @@ pub fn c_preview_message<'a>(
         let identifier = user_id.clone().replace(":", "");
 
         let user_picture = c_cached_image(
+            theme,
             identifier.clone(),
             Message::FetchUserImage(identifier, user_id, display_name.clone()),
             31.0,
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use iced::alignment::Vertical;
 use crate::widgets::click_area::click_area;
-use iced::widget::{column, container, row, svg, text};
+use iced::widget::{column, container, row, text};
 use iced::{Border, Element, Font, Padding, border, font};
 
 use crate::Message;
@@ -20,6 +20,7 @@ pub fn c_profile<'a>(
     let identifier = me.id.clone().replace(":", "");
 
     let user_picture_profile = c_cached_image(
+        theme,
         identifier.clone(),
         Message::FetchUserImage(
             identifier,
@@ -54,7 +55,7 @@ pub fn c_profile<'a>(
                     container(
                         row![
                             text("Offline"),
-                            svg(utils::get_image_dir().join("chevron-right.svg"))
+                            utils::icon(theme, "chevron-right.svg")
                                 .width(19)
                                 .height(19)
                         ]
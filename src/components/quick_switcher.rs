@@ -0,0 +1,125 @@
+use crate::types::{SwitcherItem, SwitcherTarget};
+use crate::utils::truncate_name;
+use crate::widgets::click_area::click_area;
+use crate::{Message, style::Theme};
+use iced::{
+    Border, Element, Length, Padding,
+    alignment::Vertical,
+    border,
+    widget::{Column, column, container, row, scrollable, text, text_input},
+};
+
+fn switcher_item<'a>(
+    theme: &'a Theme,
+    item: SwitcherItem,
+    is_selected: bool,
+) -> Element<'a, Message> {
+    let kind = match item.target {
+        SwitcherTarget::Chat(_) => "Chat",
+        SwitcherTarget::Channel(_, _) => "Channel",
+        SwitcherTarget::Person(_) => "Person",
+    };
+
+    click_area(
+        container(
+            row![
+                column![
+                    text(truncate_name(item.title, 48)),
+                    text(truncate_name(item.subtitle, 56))
+                        .size(13)
+                        .color(theme.colors.demo_text)
+                ]
+                .width(Length::Fill),
+                text(kind).size(13).color(theme.colors.demo_text)
+            ]
+            .align_y(Vertical::Center),
+        )
+        .width(Length::Fill)
+        .style(move |_| container::Style {
+            background: Some(if is_selected {
+                theme.colors.foreground_button_nobg_selected.into()
+            } else {
+                theme.colors.foreground.into()
+            }),
+            border: border::rounded(4),
+            ..Default::default()
+        })
+        .padding(Padding {
+            top: 6.0,
+            right: 8.0,
+            bottom: 6.0,
+            left: 8.0,
+        }),
+    )
+    .interaction(iced::mouse::Interaction::Pointer)
+    .on_press(Message::QuickSwitcherOpen(item.target))
+    .into()
+}
+
+pub fn c_quick_switcher<'a>(
+    theme: &'a Theme,
+    input_value: &str,
+    items: Vec<SwitcherItem>,
+    selected: usize,
+) -> Element<'a, Message> {
+    let mut items_column: Column<Message> = column![].spacing(2).padding(Padding {
+        left: 8.0,
+        right: 6.0,
+        top: 0.0,
+        bottom: 6.0,
+    });
+
+    if items.is_empty() {
+        items_column = items_column.push(
+            container(text("No matches").color(theme.colors.demo_text)).padding(8),
+        );
+    }
+
+    for (index, item) in items.into_iter().enumerate() {
+        items_column = items_column.push(switcher_item(theme, item, index == selected));
+    }
+
+    click_area(
+        container(
+            column![
+                container(
+                    text_input("Jump to a chat, channel or person...", input_value)
+                        .on_input(Message::QuickSwitcherInputChanged)
+                        .on_submit(Message::QuickSwitcherSubmit)
+                        .padding(10)
+                        .id("quick_switcher_input")
+                        .style(|_, _| theme.stylesheet.input)
+                )
+                .padding(Padding {
+                    top: 6.0,
+                    bottom: 0.0,
+                    left: 8.0,
+                    right: 8.0
+                }),
+                scrollable(items_column)
+                    .direction(scrollable::Direction::Vertical(
+                        scrollable::Scrollbar::new()
+                            .width(4)
+                            .spacing(0)
+                            .scroller_width(4),
+                    ))
+                    .style(|_, _| theme.stylesheet.scrollable)
+            ]
+            .spacing(6),
+        )
+        .width(500)
+        .padding(1) // Otherwise the border is bugging
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            border: Border {
+                color: theme.colors.line,
+                width: 1.0,
+                radius: 4.into(),
+            },
+            ..Default::default()
+        }),
+    )
+    .on_enter(Message::EnterCenteredOverlay)
+    .on_exit(Message::ExitCenteredOverlay)
+    .into()
+}
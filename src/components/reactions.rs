@@ -0,0 +1,255 @@
+use crate::api::{Emotion, Profile};
+use crate::components::cached_image::c_cached_image;
+use crate::components::emoji_picker::c_emoji;
+use crate::types::Emoji;
+use crate::widgets::click_area::click_area;
+use crate::widgets::viewport::ViewportHandler;
+use crate::{Message, style::Theme};
+use iced::{
+    Border, Element, Length, Padding,
+    alignment::Vertical,
+    border,
+    widget::{Column, Id, column, container, row, scrollable, text},
+};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+// Messages with at least this many reacting users get an "All reactions" button
+pub const MANY_REACTIONS: usize = 6;
+
+fn reaction_emoji<'a>(
+    theme: &Theme,
+    emoji_map: &IndexMap<String, Emoji>,
+    key: &String,
+) -> Element<'a, Message> {
+    match emoji_map.get(key) {
+        Some(emoji) => c_emoji(theme, emoji, 16.0),
+        None => text("(?)").into(),
+    }
+}
+
+fn reactor_row<'a>(
+    theme: &Theme,
+    mri: &str,
+    users: &HashMap<String, Profile>,
+    emoji: Option<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    let user_id = mri.replace("8:orgid:", "");
+
+    let display_name = users
+        .get(&user_id)
+        .map(|profile| {
+            profile
+                .display_name
+                .clone()
+                .unwrap_or("Unknown User".to_string())
+        })
+        .unwrap_or("Unknown User".to_string());
+
+    // Users outside of the directory listing are resolved once they are shown
+    let display_name_element: Element<Message> = if users.contains_key(&user_id) {
+        text(display_name.clone()).into()
+    } else {
+        ViewportHandler::new(text(display_name.clone()))
+            .on_enter_unique(
+                format!("short-profile-{}", user_id),
+                Message::FetchShortProfile(mri.to_string()),
+            )
+            .into()
+    };
+
+    let identifier = mri.replace(":", "");
+
+    let mut reactor = row![
+        c_cached_image(
+            theme,
+            identifier.clone(),
+            Message::FetchUserImage(identifier, mri.to_string(), display_name),
+            24.0,
+            24.0,
+            4.0,
+        ),
+        container(display_name_element).width(Length::Fill)
+    ]
+    .spacing(8)
+    .align_y(Vertical::Center);
+
+    if let Some(emoji) = emoji {
+        reactor = reactor.push(emoji);
+    }
+
+    reactor.into()
+}
+
+pub fn c_reaction_users<'a>(
+    theme: &'a Theme,
+    emotion: &Emotion,
+    emoji_map: &IndexMap<String, Emoji>,
+    users: &HashMap<String, Profile>,
+) -> Element<'a, Message> {
+    let mut users_column: Column<Message> = column![
+        row![
+            reaction_emoji(theme, emoji_map, &emotion.key),
+            text(emotion.key.clone()).size(14).color(theme.colors.demo_text)
+        ]
+        .spacing(6)
+        .align_y(Vertical::Center)
+    ]
+    .spacing(6);
+
+    for reactor in &emotion.users {
+        users_column = users_column.push(reactor_row(theme, &reactor.mri, users, None));
+    }
+
+    container(users_column)
+        .padding(10)
+        .width(220)
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground_surface.into()),
+            border: border::rounded(4),
+            ..Default::default()
+        })
+        .into()
+}
+
+fn filter_tab<'a>(
+    theme: &'a Theme,
+    label: Element<'a, Message>,
+    count: usize,
+    is_selected: bool,
+    filter: Option<String>,
+) -> Element<'a, Message> {
+    click_area(
+        container(
+            row![label, text(count).size(14)]
+                .spacing(4)
+                .align_y(Vertical::Center),
+        )
+        .padding(Padding {
+            top: 4.0,
+            bottom: 4.0,
+            left: 8.0,
+            right: 8.0,
+        })
+        .style(move |_| container::Style {
+            background: if is_selected {
+                Some(theme.colors.emotion_selected.into())
+            } else {
+                Some(theme.colors.foreground_surface.into())
+            },
+            border: border::rounded(4),
+            ..Default::default()
+        }),
+    )
+    .interaction(iced::mouse::Interaction::Pointer)
+    .on_press(Message::FilterReactions(filter))
+    .into()
+}
+
+pub fn c_reactions<'a>(
+    theme: &'a Theme,
+    emotions: &[Emotion],
+    filter: &Option<String>,
+    emoji_map: &IndexMap<String, Emoji>,
+    users: &HashMap<String, Profile>,
+) -> Element<'a, Message> {
+    let emotions: Vec<&Emotion> = emotions
+        .iter()
+        .filter(|emotion| !emotion.users.is_empty())
+        .collect();
+
+    // Tabs for all reactions and every emoji
+
+    let total: usize = emotions.iter().map(|emotion| emotion.users.len()).sum();
+
+    let mut tabs = row![filter_tab(
+        theme,
+        text("All").into(),
+        total,
+        filter.is_none(),
+        None
+    )]
+    .spacing(6);
+
+    for emotion in &emotions {
+        tabs = tabs.push(filter_tab(
+            theme,
+            reaction_emoji(theme, emoji_map, &emotion.key),
+            emotion.users.len(),
+            filter.as_ref() == Some(&emotion.key),
+            Some(emotion.key.clone()),
+        ));
+    }
+
+    // Reacting users, grouped by emoji
+
+    let mut users_column: Column<Message> = column![].spacing(8).padding(Padding {
+        left: 8.0,
+        right: 6.0,
+        top: 6.0,
+        bottom: 6.0,
+    });
+
+    for emotion in emotions {
+        if filter.as_ref().is_some_and(|filter| filter != &emotion.key) {
+            continue;
+        }
+
+        for reactor in &emotion.users {
+            users_column = users_column.push(reactor_row(
+                theme,
+                &reactor.mri,
+                users,
+                Some(reaction_emoji(theme, emoji_map, &emotion.key)),
+            ));
+        }
+    }
+
+    click_area(
+        container(
+            column![
+                container(
+                    scrollable(tabs)
+                        .direction(scrollable::Direction::Horizontal(
+                            scrollable::Scrollbar::new()
+                                .width(4)
+                                .spacing(0)
+                                .scroller_width(4),
+                        ))
+                        .style(|_, _| theme.stylesheet.scrollable)
+                )
+                .padding(Padding {
+                    top: 8.0,
+                    bottom: 0.0,
+                    left: 8.0,
+                    right: 8.0
+                }),
+                scrollable(users_column)
+                    .direction(scrollable::Direction::Vertical(
+                        scrollable::Scrollbar::new()
+                            .width(4)
+                            .spacing(0)
+                            .scroller_width(4),
+                    ))
+                    .style(|_, _| theme.stylesheet.scrollable)
+                    .id(Id::new("reactions_column"))
+            ]
+            .spacing(6),
+        )
+        .width(360)
+        .height(420)
+        .padding(1) // Otherwise the border is bugging
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            border: Border {
+                color: theme.colors.line,
+                width: 1.0,
+                radius: 4.into(),
+            },
+            ..Default::default()
+        }),
+    )
+    .on_enter(Message::EnterCenteredOverlay)
+    .on_exit(Message::ExitCenteredOverlay)
+    .into()
+}
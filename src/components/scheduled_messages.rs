@@ -0,0 +1,133 @@
+use crate::types::ScheduledMessage;
+use crate::utils::{format_local_time, truncate_name};
+use crate::widgets::click_area::click_area;
+use crate::{Message, style::Theme};
+use iced::{
+    Border, Element, Length, Padding,
+    alignment::Vertical,
+    border,
+    widget::{Column, Id, column, container, row, scrollable, text},
+};
+
+fn action_button<'a>(theme: &'a Theme, label: &'a str, on_press: Message) -> Element<'a, Message> {
+    click_area(
+        container(text(label).size(14))
+            .padding(Padding {
+                top: 4.0,
+                bottom: 4.0,
+                left: 8.0,
+                right: 8.0,
+            })
+            .style(|_| container::Style {
+                background: Some(theme.colors.foreground_surface.into()),
+                border: border::rounded(4),
+                ..Default::default()
+            }),
+    )
+    .interaction(iced::mouse::Interaction::Pointer)
+    .on_press(on_press)
+    .into()
+}
+
+pub fn c_scheduled_messages<'a>(
+    theme: &'a Theme,
+    scheduled_messages: Vec<&'a ScheduledMessage>,
+) -> Element<'a, Message> {
+    let mut scheduled_column: Column<Message> = column![].spacing(4).padding(Padding {
+        left: 8.0,
+        right: 6.0,
+        top: 6.0,
+        bottom: 6.0,
+    });
+
+    if scheduled_messages.is_empty() {
+        scheduled_column = scheduled_column
+            .push(text("No scheduled messages here.").color(theme.colors.demo_text));
+    }
+
+    for scheduled_message in scheduled_messages {
+        let preview = scheduled_message.text.lines().next().unwrap_or("").to_string();
+
+        scheduled_column = scheduled_column.push(
+            container(
+                row![
+                    column![
+                        text(format_local_time(scheduled_message.send_at))
+                            .size(14)
+                            .color(theme.colors.demo_text),
+                        text(truncate_name(preview, 36)),
+                    ]
+                    .spacing(2),
+                    container(
+                        row![
+                            action_button(
+                                theme,
+                                "Edit",
+                                Message::EditScheduledMessage(scheduled_message.id.clone())
+                            ),
+                            action_button(
+                                theme,
+                                "Cancel",
+                                Message::CancelScheduledMessage(scheduled_message.id.clone())
+                            ),
+                        ]
+                        .spacing(6)
+                    )
+                    .align_right(Length::Fill)
+                ]
+                .align_y(Vertical::Center),
+            )
+            .width(Length::Fill)
+            .padding(Padding {
+                top: 9.0,
+                right: 6.0,
+                bottom: 9.0,
+                left: 6.0,
+            })
+            .style(|_| container::Style {
+                background: Some(theme.colors.foreground.into()),
+                border: border::rounded(4),
+                ..Default::default()
+            }),
+        );
+    }
+
+    click_area(
+        container(
+            column![
+                container(text("Scheduled messages").size(14).color(theme.colors.demo_text))
+                    .padding(Padding {
+                        top: 8.0,
+                        bottom: 0.0,
+                        left: 8.0,
+                        right: 8.0
+                    }),
+                scrollable(scheduled_column)
+                    .direction(scrollable::Direction::Vertical(
+                        scrollable::Scrollbar::new()
+                            .width(4)
+                            .spacing(0)
+                            .scroller_width(4),
+                    ))
+                    .style(|_, _| theme.stylesheet.scrollable)
+                    .id(Id::new("scheduled_messages_column"))
+            ]
+            .spacing(6),
+        )
+        .width(450)
+        .height(400)
+        .padding(1) // Otherwise the border is bugging
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            border: Border {
+                color: theme.colors.line,
+                width: 1.0,
+                radius: 4.into(),
+            },
+            ..Default::default()
+        }),
+    )
+    .on_enter(Message::EnterCenteredOverlay)
+    .on_exit(Message::ExitCenteredOverlay)
+    .into()
+}
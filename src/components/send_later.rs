@@ -0,0 +1,126 @@
+use chrono::{Datelike, Days, Local, NaiveDate, TimeZone};
+
+use crate::utils::format_local_time;
+use crate::widgets::click_area::click_area;
+use crate::{Message, style::Theme};
+use iced::{
+    Border, Element, Length, Padding,
+    alignment::Vertical,
+    border,
+    widget::{column, container, row, text, text_input},
+};
+
+fn morning_of(date: NaiveDate) -> Option<i64> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(9, 0, 0)?)
+        .single()
+        .map(|time| time.timestamp())
+}
+
+fn preset_item<'a>(theme: &'a Theme, label: &'a str, send_at: Option<i64>) -> Element<'a, Message> {
+    let Some(send_at) = send_at else {
+        return column![].into();
+    };
+
+    click_area(
+        container(
+            row![
+                text(label),
+                container(
+                    text(format_local_time(send_at))
+                        .size(14)
+                        .color(theme.colors.demo_text)
+                )
+                .align_right(Length::Fill)
+            ]
+            .align_y(Vertical::Center),
+        )
+        .width(Length::Fill)
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            border: border::rounded(4),
+            ..Default::default()
+        })
+        .padding(Padding {
+            top: 9.0,
+            right: 6.0,
+            bottom: 9.0,
+            left: 6.0,
+        }),
+    )
+    .interaction(iced::mouse::Interaction::Pointer)
+    .on_press(Message::ScheduleMessage(send_at))
+    .into()
+}
+
+pub fn c_send_later<'a>(theme: &'a Theme, send_later_input_value: &str) -> Element<'a, Message> {
+    let now = Local::now();
+    let today = now.date_naive();
+
+    let days_until_monday = 7 - today.weekday().num_days_from_monday() as u64;
+
+    let in_an_hour = Some(now.timestamp() + 3600);
+    let tomorrow_morning = today
+        .checked_add_days(Days::new(1))
+        .and_then(morning_of);
+    let monday_morning = today
+        .checked_add_days(Days::new(days_until_monday))
+        .and_then(morning_of);
+
+    click_area(
+        container(
+            column![
+                text("Send later").size(14).color(theme.colors.demo_text),
+                preset_item(theme, "In 1 hour", in_an_hour),
+                preset_item(theme, "Tomorrow morning", tomorrow_morning),
+                preset_item(theme, "Next Monday", monday_morning),
+                row![
+                    text_input("YYYY-MM-DD HH:MM", send_later_input_value)
+                        .on_input(Message::SendLaterInputChanged)
+                        .on_submit(Message::ScheduleMessageFromInput)
+                        .padding(10)
+                        .id("send_later_input")
+                        .style(|_, _| theme.stylesheet.input),
+                    click_area(
+                        container(text("Schedule"))
+                            .padding(Padding {
+                                top: 8.0,
+                                bottom: 8.0,
+                                left: 13.0,
+                                right: 13.0
+                            })
+                            .style(|_| container::Style {
+                                background: Some(theme.colors.foreground_surface.into()),
+                                border: border::rounded(4),
+                                ..Default::default()
+                            })
+                    )
+                    .interaction(iced::mouse::Interaction::Pointer)
+                    .on_press(Message::ScheduleMessageFromInput)
+                ]
+                .align_y(Vertical::Center)
+                .spacing(10)
+            ]
+            .spacing(6)
+            .padding(Padding {
+                top: 8.0,
+                bottom: 8.0,
+                left: 8.0,
+                right: 8.0,
+            }),
+        )
+        .width(360)
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            border: Border {
+                color: theme.colors.line,
+                width: 1.0,
+                radius: 4.into(),
+            },
+            ..Default::default()
+        }),
+    )
+    .on_enter(Message::EnterCenteredOverlay)
+    .on_exit(Message::ExitCenteredOverlay)
+    .into()
+}
@@ -0,0 +1,77 @@
+use crate::types::{Settings, Shortcut};
+use crate::widgets::click_area::click_area;
+use crate::{Message, style::Theme};
+use iced::{
+    Border, Element, Length, Padding, border,
+    widget::{Column, column, container, row, text},
+};
+
+fn shortcut_row<'a>(
+    theme: &'a Theme,
+    binding: String,
+    description: &'a str,
+) -> Element<'a, Message> {
+    row![
+        container(
+            container(text(binding).size(13))
+                .padding(Padding::from([2, 6]))
+                .style(|_| container::Style {
+                    background: Some(theme.colors.foreground_surface.into()),
+                    border: border::rounded(4),
+                    ..Default::default()
+                })
+        )
+        .width(140),
+        text(description).size(14).width(Length::Fill)
+    ]
+    .spacing(12)
+    .into()
+}
+
+pub fn c_shortcuts<'a>(theme: &'a Theme, settings: &Settings) -> Element<'a, Message> {
+    let mut shortcuts_column: Column<Message> =
+        column![text("Keyboard shortcuts").size(18)].spacing(8).padding(16);
+
+    for shortcut in Shortcut::ALL {
+        shortcuts_column = shortcuts_column.push(shortcut_row(
+            theme,
+            settings.binding(&shortcut).to_string(),
+            shortcut.description(),
+        ));
+    }
+
+    // Not configurable
+
+    let send_binding = if settings.send_on_enter {
+        "Enter"
+    } else {
+        "Shift+Enter"
+    };
+
+    for (binding, description) in [
+        (send_binding, "Send the message"),
+        ("ArrowUp / ArrowDown", "Move through suggestions"),
+        ("Escape", "Close the current popup or cancel editing"),
+        ("Ctrl+C", "Copy the selected text"),
+    ] {
+        shortcuts_column =
+            shortcuts_column.push(shortcut_row(theme, binding.to_string(), description));
+    }
+
+    click_area(
+        container(shortcuts_column)
+            .width(520)
+            .style(|_| container::Style {
+                background: Some(theme.colors.foreground.into()),
+                border: Border {
+                    color: theme.colors.line,
+                    width: 1.0,
+                    radius: 4.into(),
+                },
+                ..Default::default()
+            }),
+    )
+    .on_enter(Message::EnterCenteredOverlay)
+    .on_exit(Message::ExitCenteredOverlay)
+    .into()
+}
@@ -14,6 +14,7 @@ use crate::components::horizontal_line::c_horizontal_line;
 use crate::components::picture_and_status::c_picture_and_status;
 use crate::components::profile::c_profile;
 use crate::components::vertical_line::c_vertical_line;
+use crate::types::SettingsSection;
 use crate::websockets::Presence;
 use crate::widgets::anchored_overlay::anchored_overlay;
 use crate::{Message, Page};
@@ -55,7 +56,7 @@ pub fn c_sidebar<'a>(
     });
     for team in teams {
         let team_picture = row![
-            if let Page::Team(current_team_id, _) = page {
+            if let Page::Team(current_team_id, _, _) = page {
                 if current_team_id.as_ref().map_or(false, |id| *id == team.id) {
                     container(svg_selected(theme))
                 } else {
@@ -66,6 +67,7 @@ pub fn c_sidebar<'a>(
             },
             tooltip(
                 ClickArea::new(c_cached_image(
+                    theme,
                     team.picture_e_tag
                         .clone()
                         .unwrap_or(team.display_name.clone()),
@@ -125,6 +127,7 @@ pub fn c_sidebar<'a>(
     let identifier = me.id.clone().replace(":", "");
 
     let user_picture = c_cached_image(
+        theme,
         identifier.clone(),
         Message::FetchUserImage(
             identifier.clone(),
@@ -151,7 +154,7 @@ pub fn c_sidebar<'a>(
                         tooltip(
                             container(
                                 click_area(
-                                    svg(utils::get_image_dir().join("message-square.svg"))
+                                    utils::icon(theme, "message-square.svg")
                                         .width(23)
                                         .height(23),
                                 )
@@ -190,7 +193,7 @@ pub fn c_sidebar<'a>(
                         tooltip(
                             container(
                                 click_area(
-                                    svg(utils::get_image_dir().join("bell.svg"))
+                                    utils::icon(theme, "bell.svg")
                                         .width(23)
                                         .height(23),
                                 )
@@ -223,36 +226,65 @@ pub fn c_sidebar<'a>(
                     container(c_horizontal_line(&theme, 38.into())).padding(padding::left(13))
                 ],
                 team_scrollbar,
-                column![c_horizontal_line(&theme, 38.into()), {
-                    let content = click_area(
-                        container(c_picture_and_status(
-                            theme,
-                            user_picture,
-                            presence,
-                            (28.0, 28.0),
-                        ))
-                        .padding(Padding {
-                            top: 4.0,
-                            bottom: 4.0,
-                            left: 0.0,
-                            right: 0.0,
-                        }),
-                    )
-                    .on_press(Message::ToggleShowProfile);
-
-                    if *show_profile {
-                        anchored_overlay(
-                            content,
-                            c_profile(theme, me, user_presences),
-                            crate::widgets::anchored_overlay::Position::Top,
-                            (-2.0, -1.0),
-                            false,
-                            *window_size,
+                column![
+                    c_horizontal_line(theme, 38.into()),
+                    tooltip(
+                        click_area(
+                            utils::icon(theme, "settings.svg")
+                                .width(20)
+                                .height(20),
+                        )
+                        .on_press(Message::OpenSettings(SettingsSection::Account))
+                        .interaction(iced::mouse::Interaction::Pointer),
+                        container(text("Settings"))
+                            .style(|_| container::Style {
+                                background: Some(theme.colors.tooltip.into()),
+                                border: Border {
+                                    color: theme.colors.line,
+                                    width: 1.0,
+                                    radius: 4.0.into(),
+                                },
+                                ..Default::default()
+                            })
+                            .padding(Padding {
+                                top: 8.0,
+                                bottom: 10.0,
+                                right: 10.0,
+                                left: 8.0,
+                            }),
+                        tooltip::Position::Right,
+                    ),
+                    {
+                        let content = click_area(
+                            container(c_picture_and_status(
+                                theme,
+                                user_picture,
+                                presence,
+                                (28.0, 28.0),
+                            ))
+                            .padding(Padding {
+                                top: 4.0,
+                                bottom: 4.0,
+                                left: 0.0,
+                                right: 0.0,
+                            }),
                         )
-                    } else {
-                        container(content).into()
+                        .on_press(Message::ToggleShowProfile);
+
+                        if *show_profile {
+                            anchored_overlay(
+                                content,
+                                c_profile(theme, me, user_presences),
+                                crate::widgets::anchored_overlay::Position::Top,
+                                (-2.0, -1.0),
+                                false,
+                                *window_size,
+                            )
+                        } else {
+                            container(content).into()
+                        }
                     }
-                }]
+                ]
                 .spacing(6)
                 .align_x(Alignment::Center)
                 .padding(padding::left(12)),
@@ -0,0 +1,282 @@
+use crate::api::DriveItem;
+use crate::components::horizontal_line::c_horizontal_line;
+use crate::components::toooltip::c_tooltip;
+use crate::types::FilesSortColumn;
+use crate::utils::{self, format_file_size, truncate_name};
+use crate::widgets::click_area::click_area;
+use crate::{Message, style};
+use iced::widget::{Column, Id, column, container, row, scrollable, space, text, tooltip};
+use iced::{Alignment, Element, Font, Length, Padding, border, font, padding};
+
+fn modified_by(item: &DriveItem) -> String {
+    item.last_modified_by
+        .as_ref()
+        .and_then(|identity_set| identity_set.user.as_ref())
+        .and_then(|user| user.display_name.clone())
+        .unwrap_or("".to_string())
+}
+
+fn column_header<'a>(
+    theme: &'a style::Theme,
+    label: &'a str,
+    column: FilesSortColumn,
+    sort: &(FilesSortColumn, bool),
+    width: Length,
+) -> Element<'a, Message> {
+    let label = if sort.0 == column {
+        format!("{} {}", label, if sort.1 { "↑" } else { "↓" })
+    } else {
+        label.to_string()
+    };
+
+    click_area(
+        container(text(label).size(14).color(theme.colors.demo_text)).width(width),
+    )
+    .on_press(Message::SortTeamFiles(column))
+    .interaction(iced::mouse::Interaction::Pointer)
+    .into()
+}
+
+// Items are stored sorted, so they are sorted when they arrive and when the sort changes
+pub fn sort_drive_items(items: &mut [DriveItem], sort: &(FilesSortColumn, bool)) {
+    items.sort_by(|a, b| {
+        let ordering = match sort.0 {
+            FilesSortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            FilesSortColumn::Size => a.size.cmp(&b.size),
+            FilesSortColumn::Modified => a.last_modified_date_time.cmp(&b.last_modified_date_time),
+            FilesSortColumn::ModifiedBy => modified_by(a).cmp(&modified_by(b)),
+        };
+        if sort.1 { ordering } else { ordering.reverse() }
+    });
+
+    // Folders are always listed first
+    items.sort_by_key(|item| item.folder.is_none());
+}
+
+pub fn c_team_files<'a>(
+    theme: &'a style::Theme,
+    location: Option<&Vec<DriveItem>>,
+    items: Option<&Vec<DriveItem>>,
+    sort: &(FilesSortColumn, bool),
+) -> Element<'a, Message> {
+    // Breadcrumbs
+
+    let mut breadcrumbs = row![].spacing(6).align_y(Alignment::Center);
+
+    if let Some(location) = location {
+        for (index, folder) in location.iter().enumerate() {
+            if index != 0 {
+                breadcrumbs = breadcrumbs.push(text(">").color(theme.colors.demo_text));
+            }
+
+            let name = if index == 0 {
+                "Files".to_string()
+            } else {
+                truncate_name(folder.name.clone(), 24)
+            };
+
+            breadcrumbs = breadcrumbs.push(
+                click_area(text(name).font(Font {
+                    weight: font::Weight::Bold,
+                    ..Default::default()
+                }))
+                .on_press(Message::NavigateDriveFolder(index))
+                .interaction(iced::mouse::Interaction::Pointer),
+            );
+        }
+    }
+
+    let toolbar = row![
+        breadcrumbs.wrap(),
+        container(
+            click_area(
+                container(
+                    row![
+                        utils::icon(theme, "upload.svg")
+                            .width(17)
+                            .height(17),
+                        text("Upload")
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(6)
+                )
+                .padding(Padding {
+                    top: 5.0,
+                    bottom: 5.0,
+                    left: 10.0,
+                    right: 10.0
+                })
+                .style(|_| container::Style {
+                    background: Some(theme.colors.foreground_button.into()),
+                    border: border::rounded(4),
+                    ..Default::default()
+                })
+            )
+            .on_press(Message::UploadTeamFile)
+            .interaction(iced::mouse::Interaction::Pointer)
+        )
+        .align_right(Length::Fill)
+    ]
+    .align_y(Alignment::Center)
+    .padding(Padding {
+        top: 10.0,
+        bottom: 10.0,
+        left: 14.0,
+        right: 20.0,
+    });
+
+    let header = row![
+        space().width(28),
+        column_header(theme, "Name", FilesSortColumn::Name, sort, Length::Fill),
+        column_header(
+            theme,
+            "Modified",
+            FilesSortColumn::Modified,
+            sort,
+            Length::Fixed(110.0)
+        ),
+        column_header(
+            theme,
+            "Modified By",
+            FilesSortColumn::ModifiedBy,
+            sort,
+            Length::Fixed(160.0)
+        ),
+        column_header(theme, "Size", FilesSortColumn::Size, sort, Length::Fixed(90.0)),
+        space().width(56),
+    ]
+    .spacing(8)
+    .padding(padding::horizontal(14));
+
+    let mut files_column: Column<Message> = column![].spacing(2).padding(Padding {
+        top: 4.0,
+        bottom: 8.0,
+        left: 8.0,
+        right: 8.0,
+    });
+
+    if let Some(items) = items {
+        if items.is_empty() {
+            files_column = files_column.push(
+                container(text("This folder is empty.").color(theme.colors.demo_text))
+                    .padding(12),
+            );
+        }
+
+        for item in items {
+            let is_folder = item.folder.is_some();
+
+            let modified = item
+                .last_modified_date_time
+                .as_ref()
+                .and_then(|time| time.split("T").next())
+                .map(|date| date.replace("-", "/"))
+                .unwrap_or("".to_string());
+
+            let size = if is_folder {
+                let child_count = item
+                    .folder
+                    .as_ref()
+                    .and_then(|folder| folder.child_count)
+                    .unwrap_or(0);
+                format!("{} items", child_count)
+            } else {
+                item.size.map(format_file_size).unwrap_or("".to_string())
+            };
+
+            let on_press = if is_folder {
+                Message::OpenDriveFolder(item.clone())
+            } else {
+                Message::DownloadDriveItem(item.clone())
+            };
+
+            let mut actions = row![].spacing(10);
+
+            if !is_folder {
+                actions = actions.push(tooltip(
+                    click_area(
+                        utils::icon(theme, "download.svg")
+                            .width(17)
+                            .height(17),
+                    )
+                    .on_press(on_press.clone())
+                    .interaction(iced::mouse::Interaction::Pointer),
+                    c_tooltip(theme, "Download"),
+                    tooltip::Position::Top,
+                ));
+            }
+
+            if let Some(web_url) = item.web_url.clone() {
+                actions = actions.push(tooltip(
+                    click_area(
+                        utils::icon(theme, "external-link.svg")
+                            .width(17)
+                            .height(17),
+                    )
+                    .on_press(Message::LinkClicked(web_url))
+                    .interaction(iced::mouse::Interaction::Pointer),
+                    c_tooltip(theme, "Open in Browser"),
+                    tooltip::Position::Top,
+                ));
+            }
+
+            files_column = files_column.push(
+                container(
+                    row![
+                        utils::icon(theme, if is_folder {
+                            "folder.svg"
+                        } else {
+                            "file.svg"
+                        })
+                        .width(20)
+                        .height(20),
+                        container(
+                            click_area(text(truncate_name(item.name.clone(), 60)))
+                                .on_press(on_press)
+                                .interaction(iced::mouse::Interaction::Pointer)
+                        )
+                        .width(Length::Fill),
+                        container(text(modified).size(14)).width(110),
+                        container(text(truncate_name(modified_by(item), 20)).size(14)).width(160),
+                        container(text(size).size(14)).width(90),
+                        container(actions).width(48),
+                    ]
+                    .spacing(8)
+                    .align_y(Alignment::Center),
+                )
+                .padding(Padding {
+                    top: 8.0,
+                    bottom: 8.0,
+                    left: 6.0,
+                    right: 6.0,
+                })
+                .style(|_| container::Style {
+                    background: Some(theme.colors.foreground.into()),
+                    border: border::rounded(4),
+                    ..Default::default()
+                }),
+            );
+        }
+    } else {
+        files_column = files_column.push(
+            container(text("Loading files...").color(theme.colors.demo_text)).padding(12),
+        );
+    }
+
+    column![
+        toolbar,
+        c_horizontal_line(theme, Length::Fill),
+        container(header).padding(padding::vertical(6)),
+        scrollable(files_column)
+            .direction(scrollable::Direction::Vertical(
+                scrollable::Scrollbar::new()
+                    .width(4)
+                    .spacing(0)
+                    .scroller_width(4),
+            ))
+            .style(|_, _| theme.stylesheet.scrollable)
+            .id(Id::new("files_column"))
+            .height(Length::Fill),
+    ]
+    .into()
+}
@@ -0,0 +1,344 @@
+use crate::rich_text::RichDocument;
+use iced::widget::text_editor::{Action, Content, Cursor, Edit, Motion, Position};
+use regex::Regex;
+use std::sync::LazyLock;
+
+// Nested list items are indented by four spaces, like in markdown
+pub const INDENT: &str = "    ";
+
+const HISTORY_LIMIT: usize = 100;
+
+static ORDERED_ITEM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d+)\. ").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditKind {
+    Typing,
+    Deleting,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    text: String,
+    cursor: Cursor,
+    rich_document: RichDocument,
+}
+
+impl Snapshot {
+    fn new(content: &Content, rich_document: &RichDocument) -> Self {
+        Snapshot {
+            text: content.text(),
+            cursor: content.cursor(),
+            rich_document: rich_document.clone(),
+        }
+    }
+
+    fn restore(self, content: &mut Content, rich_document: &mut RichDocument) {
+        *content = Content::with_text(&self.text);
+        content.move_to(self.cursor);
+        *rich_document = self.rich_document;
+    }
+}
+
+// Consecutive typing or deleting is undone as one step, formatting actions are a step each
+#[derive(Debug, Default)]
+pub struct MessageAreaHistory {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    group: Option<EditKind>,
+}
+
+impl MessageAreaHistory {
+    // Called before the edit, with the state it is about to change
+    pub fn record(&mut self, kind: EditKind, content: &Content, rich_document: &RichDocument) {
+        let continues_group = kind != EditKind::Other && self.group == Some(kind);
+        self.group = Some(kind);
+        self.redo.clear();
+
+        if continues_group {
+            return;
+        }
+
+        let snapshot = Snapshot::new(content, rich_document);
+        if self.undo.last().is_some_and(|last| {
+            last.text == snapshot.text && last.rich_document == snapshot.rich_document
+        }) {
+            return;
+        }
+
+        self.undo.push(snapshot);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    // The next edit starts a new step, after moving the cursor or finishing a word
+    pub fn break_group(&mut self) {
+        self.group = None;
+    }
+
+    pub fn undo(&mut self, content: &mut Content, rich_document: &mut RichDocument) -> bool {
+        let Some(snapshot) = self.undo.pop() else {
+            return false;
+        };
+
+        self.redo.push(Snapshot::new(content, rich_document));
+        snapshot.restore(content, rich_document);
+        self.group = None;
+        true
+    }
+
+    pub fn redo(&mut self, content: &mut Content, rich_document: &mut RichDocument) -> bool {
+        let Some(snapshot) = self.redo.pop() else {
+            return false;
+        };
+
+        self.undo.push(Snapshot::new(content, rich_document));
+        snapshot.restore(content, rich_document);
+        self.group = None;
+        true
+    }
+}
+
+// The length of the indentation and marker of a list item, with the prefix of the next item
+fn list_item(line: &str, bullet: &str) -> Option<(usize, String)> {
+    let content = line.trim_start_matches(' ');
+    let indentation = &line[..line.len() - content.len()];
+
+    if content.starts_with(bullet) {
+        return Some((
+            indentation.len() + bullet.chars().count(),
+            format!("{}{}", indentation, bullet),
+        ));
+    }
+
+    let captures = ORDERED_ITEM.captures(content)?;
+    let number: u64 = captures[1].parse().ok()?;
+    Some((
+        indentation.len() + captures[0].len(),
+        format!("{}{}. ", indentation, number + 1),
+    ))
+}
+
+pub enum ListContinuation {
+    // The prefix of the next item
+    Continue(String),
+    // Enter on an empty item ends the list
+    End,
+}
+
+pub fn list_continuation(line: &str, bullet: &str) -> Option<ListContinuation> {
+    let (prefix_length, next_prefix) = list_item(line, bullet)?;
+
+    if line.chars().count() == prefix_length {
+        Some(ListContinuation::End)
+    } else {
+        Some(ListContinuation::Continue(next_prefix))
+    }
+}
+
+// Indents the list items in the selection one level deeper, false when there are none
+pub fn indent_list_items(
+    content: &mut Content,
+    bullet: &str,
+    outdent: bool,
+    mut rich_document: Option<&mut RichDocument>,
+) -> bool {
+    let cursor = content.cursor();
+    let anchor = cursor.selection.unwrap_or(cursor.position);
+
+    let text = content.text();
+    let lines: Vec<&str> = text.split('\n').collect();
+    let first_line = cursor.position.line.min(anchor.line);
+    let last_line = cursor.position.line.max(anchor.line).min(lines.len() - 1);
+
+    let items: Vec<usize> = (first_line..=last_line)
+        .filter(|index| list_item(lines[*index], bullet).is_some())
+        .collect();
+    if items.is_empty() {
+        return false;
+    }
+
+    // How far the columns of every line moved
+    let mut shifts = vec![0isize; lines.len()];
+
+    for index in items {
+        if outdent {
+            let line = lines[index];
+            let removed = (line.len() - line.trim_start_matches(' ').len()).min(INDENT.len());
+            if removed == 0 {
+                continue;
+            }
+
+            content.move_to(Cursor {
+                position: Position {
+                    line: index,
+                    column: removed,
+                },
+                selection: Some(Position {
+                    line: index,
+                    column: 0,
+                }),
+            });
+            content.perform(Action::Edit(Edit::Backspace));
+            shifts[index] = -(removed as isize);
+        } else {
+            content.move_to(Cursor {
+                position: Position {
+                    line: index,
+                    column: 0,
+                },
+                selection: None,
+            });
+            for char in INDENT.chars() {
+                content.perform(Action::Edit(Edit::Insert(char)));
+            }
+            shifts[index] = INDENT.len() as isize;
+        }
+
+        if let Some(rich_document) = rich_document.as_deref_mut() {
+            rich_document.sync(&content.text());
+        }
+    }
+
+    let shift = |position: Position| Position {
+        line: position.line,
+        column: (position.column as isize + shifts[position.line]).max(0) as usize,
+    };
+    content.move_to(Cursor {
+        position: shift(cursor.position),
+        selection: cursor.selection.map(shift),
+    });
+
+    true
+}
+
+// Surrounds the selection with the markers and keeps it selected, or puts the cursor between them
+pub fn wrap_selection(content: &mut Content, open: &str, close: &str) {
+    let cursor = content.cursor();
+
+    let Some(selection) = content.selection() else {
+        for char in open.chars().chain(close.chars()) {
+            content.perform(Action::Edit(Edit::Insert(char)));
+        }
+        for _ in close.chars() {
+            content.perform(Action::Move(Motion::Left));
+        }
+        return;
+    };
+
+    let start = match cursor.selection {
+        Some(anchor)
+            if (anchor.line, anchor.column) < (cursor.position.line, cursor.position.column) =>
+        {
+            anchor
+        }
+        _ => cursor.position,
+    };
+
+    // Typing replaces the selection
+    for char in open.chars().chain(selection.chars()).chain(close.chars()) {
+        content.perform(Action::Edit(Edit::Insert(char)));
+    }
+
+    let inner_start = Position {
+        line: start.line,
+        column: start.column + open.chars().count(),
+    };
+    let inner_end = match selection.rsplit_once('\n') {
+        Some((before, last_line)) => Position {
+            line: start.line + before.matches('\n').count() + 1,
+            column: last_line.chars().count(),
+        },
+        None => Position {
+            line: start.line,
+            column: inner_start.column + selection.chars().count(),
+        },
+    };
+
+    content.move_to(Cursor {
+        position: inner_end,
+        selection: Some(inner_start),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rich_text::{BULLET_MARKER, CharFormat};
+
+    fn next_prefix(line: &str) -> Option<String> {
+        match list_continuation(line, BULLET_MARKER)? {
+            ListContinuation::Continue(prefix) => Some(prefix),
+            ListContinuation::End => Some("end".to_string()),
+        }
+    }
+
+    fn select_lines(content: &mut Content, first: usize, last: usize) {
+        content.move_to(Cursor {
+            position: Position {
+                line: last,
+                column: 0,
+            },
+            selection: Some(Position {
+                line: first,
+                column: 0,
+            }),
+        });
+    }
+
+    #[test]
+    fn lists_continue_on_the_next_line() {
+        assert_eq!(next_prefix("• one").as_deref(), Some("• "));
+        assert_eq!(next_prefix("    • nested").as_deref(), Some("    • "));
+        assert_eq!(next_prefix("3. three").as_deref(), Some("4. "));
+        assert_eq!(next_prefix("    9. nine").as_deref(), Some("    10. "));
+        assert_eq!(next_prefix("- one").as_deref(), None);
+        assert_eq!(next_prefix("plain").as_deref(), None);
+        assert_eq!(next_prefix("3.5 is not a list").as_deref(), None);
+    }
+
+    #[test]
+    fn enter_on_an_empty_item_ends_the_list() {
+        assert_eq!(next_prefix("• ").as_deref(), Some("end"));
+        assert_eq!(next_prefix("    2. ").as_deref(), Some("end"));
+    }
+
+    #[test]
+    fn only_list_items_are_indented() {
+        let mut content = Content::with_text("• one\nplain\n1. two");
+        select_lines(&mut content, 0, 2);
+
+        assert!(indent_list_items(&mut content, BULLET_MARKER, false, None));
+        assert_eq!(content.text(), "    • one\nplain\n    1. two");
+
+        assert!(indent_list_items(&mut content, BULLET_MARKER, true, None));
+        assert_eq!(content.text(), "• one\nplain\n1. two");
+
+        // Outdenting an item that isn't indented changes nothing
+        assert!(indent_list_items(&mut content, BULLET_MARKER, true, None));
+        assert_eq!(content.text(), "• one\nplain\n1. two");
+
+        select_lines(&mut content, 1, 1);
+        assert!(!indent_list_items(&mut content, BULLET_MARKER, false, None));
+    }
+
+    #[test]
+    fn indenting_keeps_the_formats() {
+        let bold = CharFormat {
+            bold: true,
+            ..Default::default()
+        };
+        let mut formats = vec![CharFormat::default(); 5];
+        formats[2..].fill(bold.clone());
+
+        let mut content = Content::with_text("• one");
+        let mut document = RichDocument::with_formats("• one", formats);
+        select_lines(&mut content, 0, 0);
+
+        assert!(indent_list_items(&mut content, BULLET_MARKER, false, Some(&mut document)));
+        assert_eq!(document.text(), "    • one");
+        assert!(document.formats()[..6].iter().all(|format| !format.bold));
+        assert!(document.formats()[6..].iter().all(|format| *format == bold));
+    }
+}
@@ -0,0 +1,118 @@
+use iced::Color;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+use xxhash_rust::xxh3::xxh3_64;
+
+type HighlightedCode = Vec<Vec<(String, Color)>>;
+type HighlightCache = HashMap<(u64, bool), Option<HighlightedCode>>;
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+// Code blocks are highlighted on every view, keyed by a hash of the language and the code
+static HIGHLIGHT_CACHE: LazyLock<Mutex<HighlightCache>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+const HIGHLIGHT_CACHE_LIMIT: usize = 500;
+
+// Label, the name Teams puts in the language- class and the syntect token
+pub const CODE_LANGUAGES: &[(&str, &str, &str)] = &[
+    ("Plain text", "plaintext", "txt"),
+    ("Bash", "bash", "sh"),
+    ("C", "c", "c"),
+    ("C++", "cpp", "cpp"),
+    ("C#", "csharp", "cs"),
+    ("CSS", "css", "css"),
+    ("Go", "go", "go"),
+    ("HTML", "html", "html"),
+    ("Java", "java", "java"),
+    ("JavaScript", "javascript", "js"),
+    ("JSON", "json", "json"),
+    ("Markdown", "markdown", "md"),
+    ("PHP", "php", "php"),
+    ("Python", "python", "py"),
+    ("Ruby", "ruby", "rb"),
+    ("Rust", "rust", "rs"),
+    ("SQL", "sql", "sql"),
+    ("TypeScript", "typescript", "js"),
+    ("XML", "xml", "xml"),
+    ("YAML", "yaml", "yaml"),
+];
+
+pub fn code_language_label(language: &str) -> String {
+    CODE_LANGUAGES
+        .iter()
+        .find(|(_, class, _)| *class == language)
+        .map(|(label, _, _)| label.to_string())
+        .unwrap_or(language.to_string())
+}
+
+// Lines of colored spans, None when there is no syntax for the language
+pub fn highlight_code(
+    language: &str,
+    code: &str,
+    dark_background: bool,
+) -> Option<HighlightedCode> {
+    let key = (
+        xxh3_64(format!("{}\0{}", language, code).as_bytes()),
+        dark_background,
+    );
+
+    let mut cache = HIGHLIGHT_CACHE.lock().unwrap();
+    if let Some(highlighted) = cache.get(&key) {
+        return highlighted.clone();
+    }
+
+    if cache.len() >= HIGHLIGHT_CACHE_LIMIT {
+        cache.clear();
+    }
+    let highlighted = highlight_uncached(language, code, dark_background);
+    cache.insert(key, highlighted.clone());
+    highlighted
+}
+
+fn highlight_uncached(
+    language: &str,
+    code: &str,
+    dark_background: bool,
+) -> Option<HighlightedCode> {
+    if language == "plaintext" {
+        return None;
+    }
+
+    let token = CODE_LANGUAGES
+        .iter()
+        .find(|(_, class, _)| *class == language)
+        .map(|(_, _, token)| *token)
+        .unwrap_or(language);
+
+    let syntax = SYNTAX_SET.find_syntax_by_token(token)?;
+    let theme: &Theme = &THEME_SET.themes[if dark_background {
+        "base16-ocean.dark"
+    } else {
+        "InspiredGitHub"
+    }];
+
+    let mut highlighter = HighlightLines::new(syntax, theme);
+
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let ranges = highlighter.highlight_line(line, &SYNTAX_SET).ok()?;
+            Some(
+                ranges
+                    .into_iter()
+                    .map(|(style, text)| {
+                        let color = style.foreground;
+                        (
+                            text.trim_end_matches(['\n', '\r']).to_string(),
+                            Color::from_rgb8(color.r, color.g, color.b),
+                        )
+                    })
+                    .collect(),
+            )
+        })
+        .collect()
+}
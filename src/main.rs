@@ -1,7 +1,13 @@
 mod api;
 mod api_types;
 mod components;
+mod editing;
+mod highlight;
+mod media;
 mod parsing;
+mod portal;
+mod rich_text;
+mod spelling;
 use base64::Engine;
 use base64::prelude::BASE64_URL_SAFE;
 use chrono::Utc;
@@ -18,42 +24,66 @@ mod utils;
 mod websockets;
 mod widgets;
 use api::{
-    AccessToken, Chat, Conversations, DeviceCodeInfo, File, Profile, Team, TeamConversations,
-    authorize_image, authorize_merged_profile_picture, authorize_profile_picture,
-    authorize_team_picture, consumption_horizon, conversations, gen_refresh_token_from_device_code,
-    me, send_message, sharepoint_download_file, site_info, team_conversations, teams_me, users,
+    AccessToken, Chat, Conversations, DeviceCodeInfo, File, PictureResponse, Profile, Team,
+    TeamConversations, authorize_image, authorize_merged_profile_picture,
+    authorize_profile_picture, authorize_team_picture, consumption_horizon, conversations,
+    gen_refresh_token_from_device_code, me, send_message, sharepoint_download_file, site_info,
+    team_conversations, teams_me, users,
 };
 use auth::{AuthError, authenticated_task, get_or_gen_skype_token};
-use components::cached_image::save_cached_image;
+use components::cached_image::{
+    cached_image_etag, cached_image_path, clear_image_cache, image_cache_size, mark_image_used,
+    save_cached_image, save_cached_image_etag, set_image_cache_limit,
+};
+use components::team_files::sort_drive_items;
+use components::message_area::MessageAreaState;
+use editing::{
+    EditKind, ListContinuation, MessageAreaHistory, indent_list_items, list_continuation,
+    wrap_selection,
+};
 use iced::clipboard;
 use iced::keyboard::Key;
 use iced::keyboard::key::Named;
 use iced::widget::scrollable::{RelativeOffset, Viewport};
-use iced::widget::text_editor::{self, Action, Content, Edit};
+use iced::widget::text_editor::{self, Action, Content, Cursor, Edit, Motion, Position};
 use iced::{
-    Color, Element, Event, Font, Size, Subscription, Task, Theme, event, keyboard, mouse, window,
+    Color, Element, Event, Font, Point, Size, Subscription, Task, Theme, event, keyboard, mouse,
+    window,
 };
 use pages::app;
-use pages::page_chat::chat;
+use pages::page_chat::{ChatView, chat};
 use pages::page_login::login;
-use pages::page_team::team;
+use pages::page_settings::{SettingsView, settings};
+use pages::page_team::{TeamView, team};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
-use regex::Regex;
 use reqwest::{Client, Method};
 use rfd::AsyncFileDialog;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use rich_text::{
+    BULLET_MARKER, CharFormat, InlineStyle, LineBlock, RichDocument, toggle_line_block,
+};
+use spelling::{SpellChecker, SpellingMenu, available_languages};
 use std::collections::HashSet;
 use std::env::home_dir;
+use std::ops::Range;
+use xxhash_rust::xxh3::xxh3_64;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use std::{collections::HashMap, fs};
-use style::global_theme;
+use style::{global_theme, theme_file_modified};
 use tokio::time::sleep;
 use types::*;
 use std::future::Future;
-use utils::{delete_cache, get_cache, get_epoch_ms, save_to_cache};
+use utils::{
+    apply_skin_tone, binding_matches, delete_cache, emoji_shortcode_query, first_link, fuzzy_score,
+    get_cache, get_config, get_epoch_ms, is_notes_tab, key_binding, save_to_cache, save_to_config,
+    search_emojis, tab_notes_section_id, tab_plan_id,
+};
 use webbrowser;
 use websockets::{
     ConnectionInfo, Presence, Presences, WebsocketMessage, WebsocketResponse,
@@ -61,21 +91,35 @@ use websockets::{
 };
 
 use crate::api::{
-    ChatMember, Conversation, Emotion, EmotionUser, add_member, delete_message, message_property,
-    start_thread,
+    Channel, ChatMember, Conversation, CustomEmojiMetadata, DriveItem, Emotion, EmotionUser,
+    OnenotePage, PlannerTask, ShortProfile, TeamMember, TeamsTab, add_member, channel_files_folder,
+    channel_tabs, create_ams_object, create_channel, custom_emojis, delete_channel, delete_message,
+    drive_item_children, edit_message, fetch_page_html, fetch_short_profile, section_onenote_pages,
+    message_property, plan_tasks, remove_team_member, start_thread, team_members, update_channel,
+    update_team_member, upload_ams_audio, upload_ams_image, upload_drive_item,
 };
 use crate::components::add_users::c_add_users;
+use crate::components::error_notice::c_error_notice;
 use crate::components::expanded_image::c_expanded_image;
+use crate::components::forward_message::c_forward_message;
+use crate::components::manage_team::c_manage_team;
+use crate::components::post_options::c_post_options;
+use crate::components::quick_switcher::c_quick_switcher;
+use crate::components::reactions::c_reactions;
+use crate::components::scheduled_messages::c_scheduled_messages;
+use crate::components::send_later::c_send_later;
+use crate::components::shortcuts::c_shortcuts;
 use crate::components::sidebar::c_sidebar;
 use crate::components::start_chat::c_start_chat;
+use crate::media::{VOICE_MESSAGE_NAME, media_path, record_voice_message, save_media};
 use crate::pages::page_activity::activity;
-use crate::parsing::get_html_preview;
+use crate::pages::page_chat::get_chat_title;
+use crate::parsing::{get_html_preview, get_reply_target, parse_open_graph};
 use crate::websockets::{WebsocketData, websocket_builder};
 use crate::widgets::centered_overlay::centered_overlay;
 use crate::widgets::selectable_text;
 
-const WINDOW_WIDTH: f32 = 1240.0;
-const WINDOW_HEIGHT: f32 = 780.0;
+const QUICK_SWITCHER_LIMIT: usize = 10;
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub enum ChatBody {
@@ -83,12 +127,20 @@ pub enum ChatBody {
     Members,
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub enum TeamBody {
+    Conversations,
+    Files,
+    Tab(String), // tab id
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 enum Page {
     Login,
-    Team(Option<String>, Option<String>), // current team id, current channel id
+    Team(Option<String>, Option<String>, TeamBody), // current team id, current channel id, team body
     Chat(Option<String>, ChatBody),       // current chat id, chat body
     Activity,
+    Settings(SettingsSection),
 }
 
 #[derive(Debug)]
@@ -97,6 +149,7 @@ struct Counter {
     access_tokens: Arc<RwLock<HashMap<String, AccessToken>>>,
     is_authorized: bool,
     session_expired: bool,
+    error_notice: Option<String>, // A failed request the user should know about
     device_code: String, // Only used when signing in for the first time
     device_user_code: Option<String>, // Only used when signing in for the first time
     tenant: String,
@@ -104,7 +157,17 @@ struct Counter {
     // App info
     page: Page,
     theme: style::Theme,
+    theme_modified: Option<SystemTime>, // Of the theme file, for hot reloading
+    system_prefers_dark: bool,
+    image_cache_size: u64, // In bytes, shown in the storage settings
+    spelling_languages: Vec<String>, // Dictionaries found, shown in the spelling settings
+    spell_checker: Option<SpellChecker>,
+    misspelled: Option<(u64, Vec<Vec<Range<usize>>>)>, // Hash of the composer text and its words
+    spelling_menu: Option<SpellingMenu>,
     emoji_map: IndexMap<String, Emoji>,
+    settings: Settings,
+    emoji_usage: HashMap<String, EmojiUsage>, // Where string is the emoji id
+    emoji_autocomplete: Option<String>,
     window_width: f32,
     window_height: f32,
     mouse_position: (f32, f32),
@@ -133,7 +196,13 @@ struct Counter {
     team_message_area_height: f32,
     chat_message_area_content: Content,
     chat_message_area_height: f32,
+    team_rich_document: RichDocument,
+    chat_rich_document: RichDocument,
+    team_message_area_history: MessageAreaHistory,
+    chat_message_area_history: MessageAreaHistory,
+    rich_link_input: Option<(Range<usize>, String)>, // Selection the link is for and the url
     subject_input_value: Option<String>,
+    message_area_preview: Option<String>, // The draft html, rendered instead of the editor
     expanded_image: Option<(String, String)>,
     add_users_checked: HashMap<String, bool>, // Where string is the user id
     show_message_area_emoji_picker: bool,
@@ -151,6 +220,46 @@ struct Counter {
     show_add_users: bool,
     show_start_chat: bool,
     start_chat_relevant_user: Option<String>,
+    show_forward_message: bool,
+    forward_message: Option<api::Message>,
+    show_reactions: bool,
+    reactions_message: Option<(String, String)>, // Thread id and message id
+    reactions_filter: Option<String>,
+    show_quick_switcher: bool,
+    quick_switcher_input_value: String,
+    quick_switcher_selected: usize,
+    show_shortcuts: bool,
+    editing_message: Option<(String, String)>, // Conversation id and message id
+    voice_recording: Option<Arc<AtomicBool>>,   // Stops the recording when set
+    modifiers: keyboard::Modifiers,
+    drafts: HashMap<String, Draft>, // Where string is the thread id
+    drafts_changed: bool,           // Written to disk every few seconds and on exit
+    reply_target: Option<ReplyTarget>,
+    show_send_later: bool,
+    send_later_input_value: String,
+    show_scheduled_messages: bool,
+    scheduled_messages: Vec<ScheduledMessage>,
+    outbox: IndexMap<String, OutboxMessage>, // Where string is the client message id
+    pending_images: Vec<PendingImage>,
+    link_preview_url: Option<String>, // The link of the message area the preview belongs to
+    link_preview: Option<api::Link>,
+    team_files_location: HashMap<String, Vec<DriveItem>>, // Where string is the channel id
+    drive_items: HashMap<String, Vec<DriveItem>>,         // Where string is the folder id
+    team_files_sort: (FilesSortColumn, bool),             // Column, ascending
+    channel_tabs: HashMap<String, Vec<TeamsTab>>,         // Where string is the channel id
+    channel_tab_selections: HashMap<String, TeamBody>,    // Where string is the channel id
+    planner_tasks: HashMap<String, Vec<PlannerTask>>,     // Where string is the plan id
+    channel_notes: HashMap<String, Vec<OnenotePage>>,     // Where string is the tab id
+    channel_settings: HashMap<String, ChannelSettings>,   // Where string is the channel id
+    show_hidden_channels: bool,
+    show_manage_team: bool,
+    team_members: HashMap<String, Vec<TeamMember>>, // Where string is the team id
+    channel_name_input_value: String,
+    renaming_channel: Option<String>, // Channel id
+    confirming_team_action: Option<String>, // Deleting, removing and leaving ask first
+    teams_refetch_scheduled: bool,          // Thread updates come in bursts
+    post_options: PostOptions,
+    show_post_options: bool,
 
     // Teams requested data
     me: Profile,
@@ -176,6 +285,9 @@ pub enum Message {
     // UI interactions
     MessageAreaEdit(text_editor::Action),
     MessageAreaAction(MessageAreaAction),
+    MessageAreaUndo,
+    MessageAreaRedo,
+    MessageAreaIndent(bool), // Tab, or Shift+Tab when outdenting
     LinkClicked(String),
     OpenTeam(String, String),
     OpenChat(String),
@@ -198,9 +310,25 @@ pub enum Message {
     Delete(Option<String>),
     Restore(Option<String>),
     EmojiPickerScrollTo(f32),
+    SetSkinTone(SkinTone),
+    OpenSettings(SettingsSection),
+    UpdateSettings(Settings),
+    ClearImageCache,
+    ClearPersonalDictionary,
+    SignOut,
+    CheckTheme,
+    GotSystemColorScheme(Option<bool>),
+    PickEmojiSuggestion(String, String),
+    GotCustomEmojis(Option<CustomEmojiMetadata>),
     CopySelected(Vec<(f32, String)>),
     AddSubject,
     RemoveSubject,
+    ToggleMessageAreaPreview,
+    RichLinkInputChanged(String),
+    SubmitRichLink,
+    OpenSpellingMenu(Point),
+    ReplaceMisspelledWord(String),
+    AddToDictionary,
     ToggleShowMoreOptions(String),
     ToggleShowProfile,
     SetStartChatRelevantUser(String),
@@ -218,7 +346,7 @@ pub enum Message {
     OnScroll(Viewport),
     PostMessage,
     StartChat(Vec<String>),
-    CreatedGroupChat(String, String, String), //callback
+    CreatedGroupChat(String, String, String), // Draft id, thread link, message html
     AddToGroupChat(String, Vec<String>),
     AddedToGroupChat(String, Vec<String>),
     FetchTeamImage(String, String, String, String),
@@ -227,10 +355,54 @@ pub enum Message {
     AuthorizeImage(String, String),
     DownloadImage(String, String),
     DownloadFile(File),
+    DownloadDriveItem(DriveItem),
     DownloadedFile(String),
+    PlayMedia(MediaAttachment),
+    ToggleVoiceRecording,
+    RecordedVoiceMessage(String, Result<(PathBuf, u64), String>), // Conversation id, file, duration
+    UploadedVoiceMessage(String, String, u64),            // Conversation id, object id, duration
     ToggleShowChatMembers,
     ToggleShowChatAdd,
     ToggleUserCheckbox(bool, String),
+    ToggleForwardMessage(api::Message),
+    ForwardMessage(String),
+    ForwardMessageToUser(String),
+    ToggleReactions(String, String),
+    FilterReactions(Option<String>),
+    FetchShortProfile(String),
+    GotShortProfiles(Vec<ShortProfile>),
+    Shortcut(Shortcut),
+    QuickSwitcherInputChanged(String),
+    QuickSwitcherSubmit,
+    QuickSwitcherOpen(SwitcherTarget),
+    ToggleSendLater,
+    SendLaterInputChanged(String),
+    ScheduleMessage(i64),
+    ScheduleMessageFromInput,
+    ToggleScheduledMessages,
+    EditScheduledMessage(String),
+    CancelScheduledMessage(String),
+    SendScheduledMessages,
+    SaveDrafts,
+    LoadedSpellChecker(Option<String>, Option<Arc<SpellChecker>>), // Language, checker
+    CancelReply,
+    OutboxMessageSent(String, bool),
+    RetryOutboxMessage(String),
+    DeleteOutboxMessage(String),
+    PasteImage,
+    GotPendingImage(Option<PendingImage>),
+    RemovePendingImage(String),
+    GotLinkPreview(String, Option<api::Link>),
+    RemoveLinkPreview,
+    UploadedImages(String, Result<Vec<(String, PendingImage)>, String>), // Client message id
+    TogglePostOptions,
+    ToggleAnnouncement,
+    HeadlineInputChanged(String),
+    PickAnnouncementBanner,
+    GotAnnouncementBanner(Option<PendingImage>),
+    RemoveAnnouncementBanner,
+    SetReplyPolicy(ReplyPolicy),
+    ToggleCrossPostChannel(String),
     ToggleMessageAreaEmojiPicker,
     ToggleMessageEmojiPicker(String),
     TogglePlusEmojiPicker(String),
@@ -246,6 +418,35 @@ pub enum Message {
     EmojiPickerReaction(String, String, String, String),
     EmotionClicked(String, Emotion),
     UploadFile,
+    OpenTeamFiles,
+    GotChannelFilesFolder(String, DriveItem), // Channel id, files folder
+    GotDriveItems(String, Vec<DriveItem>),    // Folder id, children
+    OpenDriveFolder(DriveItem),
+    NavigateDriveFolder(usize),
+    SortTeamFiles(FilesSortColumn),
+    UploadTeamFile,
+    PickedTeamFile(Option<(String, Vec<u8>)>),
+    OpenChannelTab(TeamBody),
+    GotChannelTabs(String, Vec<TeamsTab>),      // Channel id, tabs
+    GotPlannerTasks(String, Vec<PlannerTask>),  // Plan id, tasks
+    GotChannelNotes(String, Vec<OnenotePage>),  // Tab id, pages
+    ToggleFavouriteChannel(String),
+    ToggleHiddenChannel(String),
+    ToggleShowHiddenChannels,
+    ToggleManageTeam,
+    GotTeamMembers(String, Vec<TeamMember>), // Team id, members
+    ChannelNameInputChanged(String),
+    SubmitChannelName,
+    RenameChannel(Option<String>),     // Channel id, None cancels the rename
+    CreatedChannel(String, Channel),   // Team id, channel
+    DeleteChannel(String, String),     // Team id, channel id
+    SetTeamMemberRole(String, String, bool), // Team id, membership id, is owner
+    RemoveTeamMember(String, String),  // Team id, membership id
+    LeaveTeam(String),
+    ConfirmTeamAction(Option<String>), // Channel, membership or team id, None cancels
+    TeamChangeFailed(TeamRollback, String),
+    EditMessageFailed(String, String, Option<String>, String), // Conversation, message, old content
+    RefetchTeams,
 
     // Teams requests
     GotActivities(Vec<api::Message>),
@@ -256,10 +457,13 @@ pub enum Message {
     WSConnected(ConnectionInfo),
     GotWSMessage(WebsocketMessage),
     GotWSPresences(Presences),
+    GotWSThreadUpdate(String),
     TypingTimeoutFinished(String, String),
 
     // Other
     DoNothing(()),
+    ShowError(String),
+    DismissError,
     Join,          // For testing
     Hello(String), // For testing
 }
@@ -313,6 +517,18 @@ fn init_tasks(
             },
             |result| Message::GotUserDetails(result.0, result.1),
         ),
+        authed_task(
+            Arc::clone(&access_tokens),
+            "https://chatsvcagg.teams.microsoft.com/.default",
+            &tenant,
+            |token| async move {
+                custom_emojis(&token)
+                    .await
+                    .map_err(|err| eprintln!("Failed to fetch custom emojis: {}", err))
+                    .ok()
+            },
+            Message::GotCustomEmojis,
+        ),
         authed_task(
             Arc::clone(&access_tokens),
             "https://graph.microsoft.com/.default",
@@ -354,39 +570,22 @@ fn init_tasks(
     Task::batch(tasks)
 }
 
-fn post_message_task(
-    message_area_text: String,
-    acess_tokens_arc: Arc<RwLock<HashMap<String, AccessToken>>>,
-    tenant: String,
-    conversation_id: String,
-    me_id: String,
+fn outbox_preview_message(
+    outbox_message: &OutboxMessage,
+    me_id: &str,
     me_display_name: Option<String>,
-    subject: Option<String>,
-    present_messages: &mut HashSet<String>,
-    chat_conversations: &mut HashMap<String, Vec<api::Message>>,
-    team_conversations: &mut HashMap<String, TeamConversations>,
-    chats: &mut Vec<Chat>,
-) -> Task<Message> {
-    // Update conversations
-    let html = parse_message_markdown(message_area_text);
-
-    let mut rng = StdRng::from_os_rng();
-    let message_client_id: u64 = rng.random(); // generate the message_id randomly
-    let message_client_id = message_client_id.to_string();
-
-    let timestamp = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
-
-    let prefetched_message = api::Message {
+) -> api::Message {
+    api::Message {
         id: Some("-1".to_string()),
-        client_message_id: Some(message_client_id.clone()),
-        conversation_link: Some(format!("blah/{}", conversation_id)),
+        client_message_id: Some(outbox_message.client_message_id.clone()),
+        conversation_link: Some(format!("blah/{}", outbox_message.conversation_id)),
         from: Some(format!("8:orgid:{}", me_id)),
-        compose_time: Some(timestamp.clone()),
-        original_arrival_time: Some(timestamp.clone()),
-        content: Some(html.clone()),
-        message_type: Some("RichText/Html".to_string()),
+        compose_time: Some(outbox_message.timestamp.clone()),
+        original_arrival_time: Some(outbox_message.timestamp.clone()),
+        content: Some(outbox_message.html.clone()),
+        message_type: Some(outbox_message.message_type.clone()),
         container_id: None,
-        im_display_name: me_display_name.clone(),
+        im_display_name: me_display_name,
         properties: Some(api::MessageProperties {
             edittime: 0,
             deletetime: 0,
@@ -394,86 +593,409 @@ fn post_message_task(
             emotions: None,
             is_read: Some(true),
             activity: None,
-            subject: subject.clone(),
-            title: None,
+            subject: outbox_message.subject.clone(),
+            title: if outbox_message.post_options.announcement {
+                Some(outbox_message.post_options.headline.clone())
+            } else {
+                None
+            },
             cards: None,
+            mentions: None,
+            links: if outbox_message.links.is_empty() {
+                None
+            } else {
+                Some(outbox_message.links.clone())
+            },
             files: None,
         }),
-    };
-
-    if let Some(conversation) = chat_conversations.get_mut(&conversation_id) {
-        conversation.insert(0, prefetched_message.clone());
     }
+}
 
-    if let Some(conversation) = team_conversations.get_mut(&conversation_id) {
-        conversation.reply_chains.insert(
+// Messages that haven't reached the server are kept visible after a conversation is refetched
+fn insert_unsent_messages(
+    outbox: &IndexMap<String, OutboxMessage>,
+    conversation_id: &str,
+    messages: &mut Vec<api::Message>,
+    me_id: &str,
+    me_display_name: Option<String>,
+) {
+    for outbox_message in outbox.values() {
+        if outbox_message.conversation_id != conversation_id
+            || outbox_message.state == OutboxState::Sent
+        {
+            continue;
+        }
+
+        if messages.iter().any(|message| {
+            message.client_message_id.as_ref() == Some(&outbox_message.client_message_id)
+        }) {
+            continue;
+        }
+
+        messages.insert(
             0,
-            Conversation {
-                messages: vec![prefetched_message.clone()],
-                container_id: prefetched_message.id.clone().unwrap(),
-                id: prefetched_message.id.clone().unwrap(),
-                latest_delivery_time: prefetched_message
-                    .original_arrival_time
-                    .unwrap_or("n/a".to_string()),
-            },
+            outbox_preview_message(outbox_message, me_id, me_display_name.clone()),
         );
     }
+}
 
-    if let Some(pos) = chats.iter_mut().position(|chat| chat.id == conversation_id) {
-        let chat = chats.remove(pos);
-        chats.insert(0, chat);
-    }
-
-    present_messages.insert(message_client_id.clone());
+fn send_outbox_message_task(
+    acess_tokens_arc: Arc<RwLock<HashMap<String, AccessToken>>>,
+    tenant: String,
+    outbox_message: OutboxMessage,
+    me_id: String,
+    me_display_name: Option<String>,
+) -> Task<Message> {
+    let client_message_id = outbox_message.client_message_id.clone();
 
-    authed_task(
+    // A message that couldn't be authorized is failed rather than left sending
+    authenticated_task(
         acess_tokens_arc,
         "https://ic3.teams.office.com/.default",
         &tenant,
         move |token| async move {
+            let conversation_id = outbox_message.conversation_id;
+            let post_options = outbox_message.post_options;
+
+            // A link sent before its preview was fetched is fetched now
+            let mut links = vec![];
+            for link in outbox_message.links {
+                if link.preview.is_some() {
+                    links.push(link);
+                } else if let Some(link) = fetch_page_html(&link.url)
+                    .await
+                    .ok()
+                    .and_then(|html| parse_open_graph(&link.url, &html))
+                {
+                    links.push(link);
+                }
+            }
+            let links = serde_json::to_string(&links).unwrap();
+
+            // Cross posts list every channel, including the one that is posted to
+            let mut cross_post_channels = vec![];
+            if !post_options.cross_post_channels.is_empty() {
+                cross_post_channels.push(conversation_id.as_str());
+                for channel_id in &post_options.cross_post_channels {
+                    cross_post_channels.push(channel_id.as_str());
+                }
+            }
+
             let message = TeamsMessage {
                 id: "-1",
                 msg_type: "Message",
                 conversationid: &conversation_id,
                 conversation_link: &format!("blah/{}", conversation_id),
                 from: &format!("8:orgid:{}", me_id),
-                composetime: &timestamp,
-                originalarrivaltime: &timestamp,
-                content: &html,
-                messagetype: "RichText/Html",
+                composetime: &outbox_message.timestamp,
+                originalarrivaltime: &outbox_message.timestamp,
+                content: &outbox_message.html,
+                messagetype: &outbox_message.message_type,
                 contenttype: "Text",
                 imdisplayname: me_display_name.as_deref(),
-                clientmessageid: &message_client_id.to_string(),
+                clientmessageid: &outbox_message.client_message_id,
                 call_id: "",
                 state: 0,
                 version: "0",
-                amsreferences: vec![],
+                amsreferences: outbox_message
+                    .ams_references
+                    .iter()
+                    .map(|object_id| object_id.as_str())
+                    .collect(),
                 properties: Properties {
                     importance: "",
-                    subject: subject.as_deref(),
-                    title: "",
+                    subject: outbox_message.subject.as_deref(),
+                    title: if post_options.announcement {
+                        &post_options.headline
+                    } else {
+                        ""
+                    },
                     cards: "[]",
-                    links: "[]",
+                    links: &links,
                     mentions: "[]",
                     onbehalfof: None,
                     files: "[]",
                     policy_violation: None,
                     format_variant: "TEAMS",
+                    reply_restriction: match post_options.reply_policy {
+                        ReplyPolicy::Everyone => None,
+                        ReplyPolicy::Moderators => Some("ModeratorsOnly"),
+                    },
+                },
+                post_type: if post_options.announcement {
+                    "Announcement"
+                } else {
+                    "Standard"
                 },
-                post_type: "Standard",
-                cross_post_channels: vec![],
+                cross_post_channels,
             };
 
             let body = serde_json::to_string_pretty(&message).unwrap();
 
-            send_message(&token, &conversation_id, body)
+            send_message(&token, &conversation_id, body).await.is_ok()
+        },
+        move |result| match result {
+            Ok(is_sent) => Message::OutboxMessageSent(client_message_id, is_sent),
+            Err(AuthError::TokenExpired(_)) => Message::AuthExpired,
+            Err(AuthError::Other(_)) => Message::OutboxMessageSent(client_message_id, false),
+        },
+    )
+}
+
+fn drive_items_task(
+    acess_tokens_arc: Arc<RwLock<HashMap<String, AccessToken>>>,
+    tenant: String,
+    folder: &DriveItem,
+) -> Task<Message> {
+    let Some(drive_id) = folder
+        .parent_reference
+        .as_ref()
+        .and_then(|reference| reference.drive_id.clone())
+    else {
+        return Task::none();
+    };
+    let folder_id = folder.id.clone();
+
+    authed_task(
+        acess_tokens_arc,
+        "https://graph.microsoft.com/.default",
+        &tenant,
+        move |token| async move {
+            let children = drive_item_children(&token, &drive_id, &folder_id)
+                .await
+                .map(|collection| collection.value)
+                .unwrap_or_default();
+            (folder_id, children)
+        },
+        |(folder_id, children)| Message::GotDriveItems(folder_id, children),
+    )
+}
+
+fn upload_images_task(
+    acess_tokens_arc: Arc<RwLock<HashMap<String, AccessToken>>>,
+    tenant: String,
+    outbox_message: OutboxMessage,
+) -> Task<Message> {
+    let client_message_id = outbox_message.client_message_id.clone();
+
+    authenticated_task(
+        acess_tokens_arc.clone(),
+        "https://api.spaces.skype.com/Authorization.ReadWrite",
+        &tenant,
+        move |token| async move {
+            let conversation_id = outbox_message.conversation_id;
+            let mut uploaded_images = vec![];
+
+            let skype_token = get_or_gen_skype_token(acess_tokens_arc, token)
                 .await
-                .unwrap();
+                .map_err(|err| format!("{:?}", err))?;
+
+            for pending_image in outbox_message.pending_images {
+                let bytes = cached_image_path(&pending_image.identifier)
+                    .map(fs::read)
+                    .ok_or("The image is no longer in the cache")?
+                    .map_err(|err| err.to_string())?;
+
+                let body = json!({
+                    "type": "pish/image",
+                    "permissions": { conversation_id.clone(): ["read"] },
+                    "filename": format!("{}.png", pending_image.identifier),
+                    "sharingMode": "Inherit",
+                })
+                .to_string();
+
+                let object_id = create_ams_object(&skype_token, body)
+                    .await
+                    .map_err(|err| err.to_string())?
+                    .id;
+
+                upload_ams_image(&skype_token, &object_id, bytes.clone())
+                    .await
+                    .map_err(|err| err.to_string())?;
+
+                // Cache the image under the identifier of its url so the sent message shows it right away
+                let image_url = ams_image_url(&object_id);
+                let identifier = image_url
+                    .replace("https:", "")
+                    .replace("/", "")
+                    .replace(":", "");
+                save_cached_image(identifier, bytes.into());
+
+                uploaded_images.push((object_id, pending_image));
+            }
+
+            Ok::<_, String>(uploaded_images)
+        },
+        move |result| match result {
+            Ok(uploaded_images) => Message::UploadedImages(client_message_id, uploaded_images),
+            Err(AuthError::TokenExpired(_)) => Message::AuthExpired,
+            Err(err) => Message::UploadedImages(client_message_id, Err(format!("{:?}", err))),
+        },
+    )
+}
+
+// Post options only apply to channel posts, the banner only to announcements
+fn take_post_options(page: &Page, post_options: &mut PostOptions) -> PostOptions {
+    let Page::Team(_, _, _) = page else {
+        return PostOptions::default();
+    };
+
+    let mut post_options = std::mem::take(post_options);
+    if !post_options.announcement {
+        post_options.banner = None;
+    }
+    post_options
+}
+
+// Dictionaries take a while to parse, so they are loaded off the UI thread
+fn load_spell_checker_task(language: Option<String>) -> Task<Message> {
+    let Some(language) = language else {
+        return Task::none();
+    };
+
+    Task::perform(
+        {
+            let language = language.clone();
+            async move {
+                tokio::task::spawn_blocking(move || SpellChecker::load(&language).map(Arc::new))
+                    .await
+                    .ok()
+                    .flatten()
+            }
         },
-        Message::DoNothing,
+        move |spell_checker| Message::LoadedSpellChecker(Some(language.clone()), spell_checker),
+    )
+}
+
+fn ams_image_url(object_id: &str) -> String {
+    format!(
+        "https://eu-api.asm.skype.com/v1/objects/{}/views/imgo",
+        object_id
     )
 }
 
+fn new_outbox_message(conversation_id: String, html: String) -> OutboxMessage {
+    let mut rng = StdRng::from_os_rng();
+    let message_client_id: u64 = rng.random(); // generate the message_id randomly
+
+    let timestamp = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+
+    OutboxMessage {
+        client_message_id: message_client_id.to_string(),
+        conversation_id,
+        html,
+        subject: None,
+        ams_references: vec![],
+        timestamp,
+        state: OutboxState::Pending,
+        post_options: PostOptions::default(),
+        links: vec![],
+        pending_images: vec![],
+        message_type: "RichText/Html".to_string(),
+    }
+}
+
+// Drafts are keyed by the channel id for teams and by the thread id for chats
+fn draft_thread_id(page: &Page) -> Option<String> {
+    match page {
+        Page::Team(_, current_channel_id, _) => current_channel_id.clone(),
+        Page::Chat(current_chat_id, _) => current_chat_id.clone(),
+        _ => None,
+    }
+}
+
+// Settings used to live in the cache directory
+fn load_settings() -> Settings {
+    get_config::<Settings>("settings.json")
+        .or_else(|| get_cache::<Settings>("settings.json"))
+        .unwrap_or_default()
+}
+
+// Forwarded messages are posted as a reply block pointing to the original message, only its
+// text is quoted so no markup from the original is posted again
+fn forward_message_html(message: &api::Message, users: &HashMap<String, Profile>) -> String {
+    let display_name = message
+        .from
+        .as_ref()
+        .and_then(|user_id| users.get(&user_id.replace("8:orgid:", "")))
+        .and_then(|profile| profile.display_name.clone())
+        .or(message.im_display_name.clone())
+        .unwrap_or("Unknown User".to_string());
+
+    let preview = match &message.content {
+        Some(content) if message.message_type.as_deref() == Some("RichText/Html") => {
+            get_html_preview(content)
+        }
+        Some(content) => content.split_whitespace().collect::<Vec<_>>().join(" "),
+        None => String::new(),
+    };
+
+    // The brackets delimit the name and the id in the reply syntax
+    let display_name = display_name.replace(['[', ']'], "");
+    let message_id = message.id.clone().unwrap_or("0".to_string());
+
+    parse_message_markdown(format!(">[{}][{}] {}\n", display_name, message_id, preview))
+}
+
+// Custom emojis are typed as :shortcut: and sent as the image tag Teams expects
+fn expand_custom_emojis(text: &str, emoji_map: &IndexMap<String, Emoji>) -> String {
+    let mut text = text.to_string();
+
+    for (emoji_id, emoji) in emoji_map {
+        let Some(image_url) = &emoji.image_url else {
+            continue;
+        };
+
+        if text.contains(&emoji.unicode) {
+            let name = emoji.unicode.trim_matches(':');
+            text = text.replace(
+                &emoji.unicode,
+                &format!(
+                    "<img itemscope=\"\" itemtype=\"http://schema.skype.com/Emoji\" itemid=\"{}\" \
+                     src=\"{}\" alt=\"{}\" title=\"{}\" width=\"20\" height=\"20\">",
+                    emoji_id, image_url, name, name
+                ),
+            );
+        }
+    }
+
+    text
+}
+
+// Tenant custom emojis share the emoji map, under their own category
+fn insert_custom_emojis(emoji_map: &mut IndexMap<String, Emoji>, metadata: &CustomEmojiMetadata) {
+    for category in &metadata.categories {
+        for custom_emoji in &category.emoticons {
+            let name = custom_emoji
+                .shortcuts
+                .first()
+                .cloned()
+                .or(custom_emoji.description.clone())
+                .unwrap_or(custom_emoji.id.clone());
+
+            let mut keywords = custom_emoji.shortcuts.clone();
+            keywords.extend(custom_emoji.description.clone());
+
+            emoji_map.insert(
+                custom_emoji.id.clone(),
+                Emoji {
+                    unicode: format!(":{}:", name),
+                    category: "Custom".to_string(),
+                    keywords,
+                    image_url: Some(ams_image_url(&custom_emoji.document_id)),
+                },
+            );
+        }
+    }
+}
+
+fn record_emoji_usage(emoji_usage: &mut HashMap<String, EmojiUsage>, emoji_id: &str) {
+    let usage = emoji_usage.entry(emoji_id.to_string()).or_default();
+    usage.count += 1;
+    usage.last_used = utils::get_epoch_s();
+    save_to_cache("emoji_usage.json", emoji_usage);
+}
+
 fn content_send(content: &mut Content, message: &str) {
     for char in message.chars() {
         content.perform(Action::Edit(Edit::Insert(char)));
@@ -484,7 +1006,19 @@ impl Counter {
     fn new() -> (Self, Task<Message>) {
         let file_content =
             fs::read_to_string(utils::get_resource_dir().join("emojis.json")).unwrap();
-        let emojis: IndexMap<String, Emoji> = serde_json::from_str(&file_content).unwrap();
+        let mut emojis: IndexMap<String, Emoji> = serde_json::from_str(&file_content).unwrap();
+        if let Some(custom_emojis) = get_cache::<CustomEmojiMetadata>("custom_emojis.json") {
+            insert_custom_emojis(&mut emojis, &custom_emojis);
+        }
+        let settings = load_settings();
+        set_image_cache_limit(settings.image_cache_limit_mb);
+        let window_size = (settings.window_width, settings.window_height);
+        let theme_modified = match &settings.theme {
+            ThemePreference::File(file_name) => theme_file_modified(file_name),
+            _ => None,
+        };
+        let emoji_usage =
+            get_cache::<HashMap<String, EmojiUsage>>("emoji_usage.json").unwrap_or_default();
 
         let access_tokens = Arc::new(RwLock::new(HashMap::new()));
         if let Some(cached) = get_cache::<HashMap<String, AccessToken>>("access_tokens.json") {
@@ -495,6 +1029,22 @@ impl Counter {
         let user_profiles =
             get_cache::<HashMap<String, Profile>>("users.json").unwrap_or(HashMap::new());
         let profile = get_cache::<Profile>("me.json").unwrap_or(Profile::default());
+        let drafts = get_cache::<HashMap<String, Draft>>("drafts.json").unwrap_or_default();
+        let channel_tab_selections = get_cache::<HashMap<String, TeamBody>>("channel_tabs.json")
+            .unwrap_or_default();
+        let channel_settings =
+            get_cache::<HashMap<String, ChannelSettings>>("channel_settings.json")
+                .unwrap_or_default();
+        let scheduled_messages =
+            get_cache::<Vec<ScheduledMessage>>("scheduled_messages.json").unwrap_or_default();
+
+        // Messages that were still sending when the app was closed are retried once connected
+        let mut outbox = get_cache::<IndexMap<String, OutboxMessage>>("outbox.json")
+            .unwrap_or_default();
+        outbox.retain(|_, outbox_message| outbox_message.state != OutboxState::Sent);
+        for outbox_message in outbox.values_mut() {
+            outbox_message.state = OutboxState::Failed;
+        }
 
         // If the user doesn't have a refresh token, prompt them to the login page.
         let has_refresh_token = access_tokens.read().unwrap().get("refresh_token").is_some();
@@ -502,6 +1052,15 @@ impl Counter {
 
         let first_chat = chats.get(0).map(|chat| chat.id.clone());
 
+        let first_chat_draft = first_chat
+            .as_ref()
+            .and_then(|chat_id| drafts.get(chat_id))
+            .cloned()
+            .unwrap_or_default();
+        let first_chat_images = first_chat_draft.images.clone();
+        let first_chat_reply_target = first_chat_draft.reply_to.clone();
+        let spell_check_language = settings.spell_check_language.clone();
+
         let counter_self = Self {
             page: if has_refresh_token {
                 Page::Chat(first_chat.clone(), ChatBody::Messages)
@@ -509,16 +1068,32 @@ impl Counter {
                 Page::Login
             },
             present_messages: HashSet::new(),
-            theme: global_theme(),
+            theme: global_theme(&settings.theme, true),
+            theme_modified,
+            system_prefers_dark: true,
+            image_cache_size: 0,
+            spelling_languages: vec![],
+            spell_checker: None,
+            misspelled: None,
+            spelling_menu: None,
             device_user_code: None,
             device_code: "".to_string(),
             tenant: tenant.clone(),
             is_authorized: has_refresh_token,
             session_expired: false,
+            error_notice: None,
             team_message_area_content: Content::new(),
             team_message_area_height: 54.0,
-            chat_message_area_content: Content::new(),
+            chat_message_area_content: Content::with_text(&first_chat_draft.text),
             chat_message_area_height: 54.0,
+            team_rich_document: RichDocument::default(),
+            chat_rich_document: RichDocument::with_formats(
+                &first_chat_draft.text,
+                first_chat_draft.formats.clone(),
+            ),
+            team_message_area_history: MessageAreaHistory::default(),
+            chat_message_area_history: MessageAreaHistory::default(),
+            rich_link_input: None,
             reply_options: HashMap::new(),
             scrollbar_scroll: 0,
             scrollbar_percentage_scroll: 1.0,
@@ -532,13 +1107,17 @@ impl Counter {
             expanded_image: None,
             should_send_typing: true,
             emoji_map: emojis,
+            settings,
+            emoji_usage,
+            emoji_autocomplete: None,
             search_teams_input_value: "".to_string(),
             search_chats_input_value: "".to_string(),
             search_users_input_value: "".to_string(),
             search_emojis_input_value: "".to_string(),
             subject_input_value: None,
-            window_width: WINDOW_WIDTH,
-            window_height: WINDOW_HEIGHT,
+            message_area_preview: None,
+            window_width: window_size.0,
+            window_height: window_size.1,
             mouse_position: (0.0, 0.0),
             access_tokens: access_tokens.clone(),
             users: user_profiles,
@@ -566,18 +1145,61 @@ impl Counter {
             show_add_users: false,
             show_start_chat: false,
             start_chat_relevant_user: None,
+            show_forward_message: false,
+            forward_message: None,
+            show_reactions: false,
+            reactions_message: None,
+            reactions_filter: None,
+            show_quick_switcher: false,
+            quick_switcher_input_value: String::new(),
+            quick_switcher_selected: 0,
+            show_shortcuts: false,
+            editing_message: None,
+            voice_recording: None,
+            modifiers: keyboard::Modifiers::default(),
+            drafts,
+            drafts_changed: false,
+            reply_target: first_chat_reply_target,
+            show_send_later: false,
+            send_later_input_value: "".to_string(),
+            show_scheduled_messages: false,
+            scheduled_messages,
+            outbox,
+            pending_images: first_chat_images,
+            link_preview_url: None,
+            link_preview: None,
+            team_files_location: HashMap::new(),
+            drive_items: HashMap::new(),
+            team_files_sort: (FilesSortColumn::Name, true),
+            channel_tabs: HashMap::new(),
+            channel_tab_selections,
+            planner_tasks: HashMap::new(),
+            channel_notes: HashMap::new(),
+            channel_settings,
+            show_hidden_channels: false,
+            show_manage_team: false,
+            team_members: HashMap::new(),
+            channel_name_input_value: "".to_string(),
+            renaming_channel: None,
+            confirming_team_action: None,
+            teams_refetch_scheduled: false,
+            post_options: PostOptions::default(),
+            show_post_options: false,
             last_opened_chat: first_chat.clone(),
         };
         (
             counter_self,
-            if has_refresh_token {
-                init_tasks(access_tokens, tenant, first_chat)
-            } else {
-                Task::perform(
-                    async move { api::gen_device_code(&tenant).await.unwrap() },
-                    Message::GotDeviceCodeInfo,
-                )
-            },
+            Task::batch([
+                if has_refresh_token {
+                    init_tasks(access_tokens, tenant, first_chat)
+                } else {
+                    Task::perform(
+                        async move { api::gen_device_code(&tenant).await.unwrap() },
+                        Message::GotDeviceCodeInfo,
+                    )
+                },
+                load_spell_checker_task(spell_check_language),
+            ]),
         )
     }
 
@@ -598,10 +1220,10 @@ impl Counter {
                     &(self.window_width, self.window_height),
                 ),
                 match &self.page {
-                    Page::Team(current_team_id, current_channel_id) => {
+                    Page::Team(current_team_id, current_channel_id, team_body) => {
                         let current_team_id = current_team_id.as_ref().unwrap();
 
-                        let mut current_team = self
+                        let current_team = self
                             .teams
                             .iter()
                             .find(|team| &team.id == current_team_id)
@@ -617,29 +1239,56 @@ impl Counter {
                             .unwrap()
                             .clone();
 
-                        let reply_options = &self.reply_options;
+                        let conversations = self.team_conversations.get(current_channel_id);
+
+                        let files_location = self.team_files_location.get(current_channel_id);
+                        let files_items = files_location
+                            .and_then(|location| location.last())
+                            .and_then(|folder| self.drive_items.get(&folder.id));
 
-                        let conversation = self.team_conversations.get(current_channel_id);
+                        let channel_notes = match team_body {
+                            TeamBody::Tab(tab_id) => self.channel_notes.get(tab_id),
+                            _ => None,
+                        };
+
+                        let scheduled_message_count = self
+                            .scheduled_messages
+                            .iter()
+                            .filter(|scheduled_message| {
+                                &scheduled_message.conversation_id == current_channel_id
+                            })
+                            .count();
 
                         team(
                             &self.theme,
-                            &mut current_team,
-                            &current_channel,
-                            &conversation,
-                            &reply_options,
-                            &self.channel_list_options,
-                            &self.search_emojis_input_value,
-                            &self.emoji_map,
-                            &self.users,
-                            &self.me,
-                            &self.user_presences,
-                            &self.subject_input_value,
-                            &self.team_message_area_content,
-                            &self.team_message_area_height,
-                            &self.show_message_area_emoji_picker,
-                            &self.show_plus_emoji_picker,
-                            &self.emoji_picker_message_id,
-                            &(self.window_width, self.window_height),
+                            TeamView {
+                                team: current_team,
+                                page_channel: current_channel,
+                                conversations,
+                                reply_options: &self.reply_options,
+                                channel_list_options: &self.channel_list_options,
+                                settings: &self.settings,
+                                emoji_map: &self.emoji_map,
+                                users: &self.users,
+                                me: &self.me,
+                                user_presences: &self.user_presences,
+                                scheduled_message_count,
+                                outbox: &self.outbox,
+                                message_area: self.message_area_state(),
+                                show_plus_emoji_picker: &self.show_plus_emoji_picker,
+                                emoji_picker_message_id: &self.emoji_picker_message_id,
+                                window_size: (self.window_width, self.window_height),
+                                team_body,
+                                files_location,
+                                files_items,
+                                files_sort: &self.team_files_sort,
+                                channel_tabs: self.channel_tabs.get(current_channel_id),
+                                planner_tasks: &self.planner_tasks,
+                                channel_notes,
+                                channel_settings: &self.channel_settings,
+                                show_hidden_channels: self.show_hidden_channels,
+                                drafts: &self.drafts,
+                            },
                         )
                     }
                     Page::Chat(current_chat_id, chat_body) => {
@@ -660,30 +1309,43 @@ impl Counter {
                             None
                         };
 
+                        let scheduled_message_count = self
+                            .scheduled_messages
+                            .iter()
+                            .filter(|scheduled_message| {
+                                Some(&scheduled_message.conversation_id)
+                                    == current_chat_id.as_ref()
+                            })
+                            .count();
+
                         chat(
                             &self.theme,
-                            current_chat,
-                            &self.users_typing_timeouts,
-                            &self.chats,
-                            &conversation,
-                            &self.chat_message_options,
-                            &self.chat_list_options,
-                            &self.search_emojis_input_value,
-                            &self.emoji_map,
-                            &self.users,
-                            &self.user_presences,
-                            &self.me,
-                            self.search_chats_input_value.clone(),
-                            &self.chat_message_area_content,
-                            &self.chat_message_area_height,
-                            &chat_body,
-                            &self.show_more_options,
-                            &self.more_menu_message_id,
-                            &self.show_message_area_emoji_picker,
-                            &self.show_message_emoji_picker,
-                            &self.show_plus_emoji_picker,
-                            &self.emoji_picker_message_id,
-                            &(self.window_width, self.window_height),
+                            ChatView {
+                                current_chat,
+                                users_typing: &self.users_typing_timeouts,
+                                chats: &self.chats,
+                                conversation,
+                                chat_message_options: &self.chat_message_options,
+                                chat_list_options: &self.chat_list_options,
+                                settings: &self.settings,
+                                emoji_map: &self.emoji_map,
+                                users: &self.users,
+                                user_presences: &self.user_presences,
+                                me: &self.me,
+                                search_chats_input_value: &self.search_chats_input_value,
+                                editing_message: &self.editing_message,
+                                message_area: self.message_area_state(),
+                                drafts: &self.drafts,
+                                scheduled_message_count,
+                                outbox: &self.outbox,
+                                page_body: chat_body,
+                                show_more_options: &self.show_more_options,
+                                more_menu_message_id: &self.more_menu_message_id,
+                                show_message_emoji_picker: &self.show_message_emoji_picker,
+                                show_plus_emoji_picker: &self.show_plus_emoji_picker,
+                                emoji_picker_message_id: &self.emoji_picker_message_id,
+                                window_size: (self.window_width, self.window_height),
+                            },
                         )
                     }
                     Page::Activity => activity(
@@ -691,14 +1353,33 @@ impl Counter {
                         &self.activities,
                         &self.activity_expanded_conversations,
                         &self.search_emojis_input_value,
+                        &self.settings,
+                        &self.emoji_usage,
                         &self.emoji_map,
                         &self.users,
                         &self.user_presences,
+                        &self.outbox,
                         &self.me,
                         &self.show_plus_emoji_picker,
                         &self.emoji_picker_message_id,
                         &(self.window_width, self.window_height),
                     ),
+                    Page::Settings(section) => settings(
+                        &self.theme,
+                        SettingsView {
+                            section,
+                            settings: &self.settings,
+                            me: &self.me,
+                            tenant: &self.tenant,
+                            image_cache_size: self.image_cache_size,
+                            spelling_languages: &self.spelling_languages,
+                            personal_dictionary_size: self
+                                .spell_checker
+                                .as_ref()
+                                .map(|spell_checker| spell_checker.personal_dictionary_size()),
+                            window_size: (self.window_width, self.window_height),
+                        },
+                    ),
                     Page::Login => {
                         // Can't happen
                         panic!()
@@ -765,584 +1446,1770 @@ impl Counter {
                         }
                         _ => None,
                     }
+                } else if self.show_send_later {
+                    Some(centered_overlay(
+                        c_send_later(&self.theme, &self.send_later_input_value),
+                        (self.window_width, self.window_height),
+                        0.7,
+                    ))
+                } else if self.show_post_options {
+                    match &self.page {
+                        Page::Team(_, Some(channel_id), _) => Some(centered_overlay(
+                            c_post_options(
+                                &self.theme,
+                                &self.post_options,
+                                &self.teams,
+                                channel_id,
+                            ),
+                            (self.window_width, self.window_height),
+                            0.7,
+                        )),
+                        _ => None,
+                    }
+                } else if self.show_manage_team {
+                    match &self.page {
+                        Page::Team(Some(team_id), _, _) => self
+                            .teams
+                            .iter()
+                            .find(|team| &team.id == team_id)
+                            .map(|team| {
+                                centered_overlay(
+                                    c_manage_team(
+                                        &self.theme,
+                                        team,
+                                        self.team_members.get(team_id),
+                                        &self.channel_name_input_value,
+                                        &self.renaming_channel,
+                                        &self.confirming_team_action,
+                                        &self.me,
+                                    ),
+                                    (self.window_width, self.window_height),
+                                    0.7,
+                                )
+                            }),
+                        _ => None,
+                    }
+                } else if self.show_scheduled_messages {
+                    let current_chat_id = draft_thread_id(&self.page);
+
+                    Some(centered_overlay(
+                        c_scheduled_messages(
+                            &self.theme,
+                            self.scheduled_messages
+                                .iter()
+                                .filter(|scheduled_message| {
+                                    Some(&scheduled_message.conversation_id)
+                                        == current_chat_id.as_ref()
+                                })
+                                .collect(),
+                        ),
+                        (self.window_width, self.window_height),
+                        0.7,
+                    ))
+                } else if self.show_forward_message {
+                    Some(centered_overlay(
+                        c_forward_message(
+                            &self.theme,
+                            &self.chats,
+                            &self.teams,
+                            &self.users,
+                            &self.me,
+                            &self.search_users_input_value,
+                        ),
+                        (self.window_width, self.window_height),
+                        0.7,
+                    ))
+                } else if self.show_reactions {
+                    self.reactions_emotions().map(|emotions| {
+                        centered_overlay(
+                            c_reactions(
+                                &self.theme,
+                                emotions,
+                                &self.reactions_filter,
+                                &self.emoji_map,
+                                &self.users,
+                            ),
+                            (self.window_width, self.window_height),
+                            0.7,
+                        )
+                    })
+                } else if self.show_quick_switcher {
+                    Some(centered_overlay(
+                        c_quick_switcher(
+                            &self.theme,
+                            &self.quick_switcher_input_value,
+                            self.quick_switcher_items(),
+                            self.quick_switcher_selected,
+                        ),
+                        (self.window_width, self.window_height),
+                        0.7,
+                    ))
+                } else if self.show_shortcuts {
+                    Some(centered_overlay(
+                        c_shortcuts(&self.theme, &self.settings),
+                        (self.window_width, self.window_height),
+                        0.7,
+                    ))
                 } else {
                     None
                 },
+                self.error_notice
+                    .as_ref()
+                    .map(|error| c_error_notice(&self.theme, error)),
             ),
         }
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
-        match message {
-            // Authorization
-            Message::GotDeviceCodeInfo(device_code_info) => {
-                self.device_user_code = Some(device_code_info.user_code);
-                self.device_code = device_code_info.device_code.clone();
+        let previous_page = self.page.clone();
 
-                let tenant = self.tenant.clone();
-                Task::perform(
-                    async move {
-                        thread::sleep(Duration::new(1, 0));
+        let notification = match &message {
+            Message::GotWSMessage(message) => self.notification_task(&message.resource),
+            _ => Task::none(),
+        };
 
-                        let mut refresh_token: Option<AccessToken> = None;
-                        let result = gen_refresh_token_from_device_code(
-                            &device_code_info.device_code,
-                            &tenant,
-                        )
-                        .await;
-                        if let Ok(access_token) = result {
-                            refresh_token = Some(access_token);
-                            println!("Code polling succeeded.")
-                        }
+        // Sending also goes through the message area, so the cleared text is checked too
+        let edits_message_area = matches!(message, Message::MessageAreaEdit(_));
 
-                        refresh_token
-                    },
-                    |refresh_token| {
-                        if let Some(refresh_token) = refresh_token {
-                            Message::Authorized(refresh_token)
-                        } else {
-                            Message::PollDeviceCode
-                        }
-                    },
-                )
-            }
-            Message::PollDeviceCode => {
-                let device_code = self.device_code.clone();
-                let tenant = self.tenant.clone();
-                Task::perform(
-                    async move {
-                        thread::sleep(Duration::new(1, 0));
+        let task = self.handle_message(message);
+        if edits_message_area {
+            self.check_spelling();
+        }
 
-                        let mut refresh_token: Option<AccessToken> = None;
-                        let result =
-                            gen_refresh_token_from_device_code(&device_code, &tenant).await;
-                        // TODO: log non re-poll errors
-                        if let Ok(access_token) = result {
-                            refresh_token = Some(access_token);
-                            println!("Code polling succeeded.")
-                        }
+        // Edits within a thread are stored by the SaveDrafts tick
+        if draft_thread_id(&self.page) != draft_thread_id(&previous_page) {
+            // The composer still holds the previous thread's content at this point
+            self.store_draft(&previous_page);
+            self.save_drafts();
+            self.restore_draft();
+        }
 
-                        refresh_token
-                    },
-                    |refresh_token| {
-                        if let Some(refresh_token) = refresh_token {
-                            Message::Authorized(refresh_token)
-                        } else {
-                            Message::PollDeviceCode
-                        }
-                    },
-                )
-            }
-            Message::Authorized(refresh_token) => {
-                self.page = Page::Chat(None, ChatBody::Messages);
+        Task::batch([task, notification])
+    }
 
-                self.access_tokens
-                    .write()
-                    .unwrap()
-                    .insert("refresh_token".to_string(), refresh_token);
-                self.is_authorized = true;
-                self.session_expired = false;
-                init_tasks(self.access_tokens.clone(), self.tenant.clone(), None)
-            }
-            Message::AuthExpired => {
-                if self.page == Page::Login {
-                    return Task::none();
-                }
-                eprintln!("Authentication expired. Redirecting to login.");
-                delete_cache("access_tokens.json");
-                self.access_tokens.write().unwrap().clear();
-                self.is_authorized = false;
-                self.session_expired = true;
-                self.page = Page::Login;
-                self.device_user_code = None;
-                self.device_code = String::new();
-                let tenant = self.tenant.clone();
-                Task::perform(
-                    async move { api::gen_device_code(&tenant).await.unwrap() },
-                    Message::GotDeviceCodeInfo,
-                )
-            }
+    // Chat messages notify unless only mentions should, channel posts only notify mentions
+    fn notification_task(&self, message: &api::Message) -> Task<Message> {
+        if !self.settings.desktop_notifications
+            || !matches!(message.message_type.as_deref(), Some("RichText/Html" | "Text"))
+        {
+            return Task::none();
+        }
 
-            // App actions
-            Message::EventOccurred(event) => {
-                match event {
-                    Event::Window(window::Event::Resized(size)) => {
-                        self.window_width = size.width;
-                        self.window_height = size.height;
+        let me_mri = format!("8:orgid:{}", self.me.id);
+        let Some(properties) = &message.properties else {
+            return Task::none();
+        };
+        if properties.edittime != 0
+            || message.from.as_ref().is_none_or(|from| from.ends_with(&me_mri))
+        {
+            return Task::none();
+        }
 
-                        let max_area_height = 0.5 * self.window_height;
+        let Some(conversation_id) = message
+            .conversation_link
+            .as_ref()
+            .and_then(|link| link.rsplit('/').next())
+            .and_then(|link| link.split(';').next())
+        else {
+            return Task::none();
+        };
+        if draft_thread_id(&self.page).as_deref() == Some(conversation_id) {
+            return Task::none();
+        }
 
-                        if self.team_message_area_height > max_area_height {
-                            self.team_message_area_height = max_area_height;
-                        }
+        let is_mentioned = properties
+            .mentions
+            .as_ref()
+            .is_some_and(|mentions| mentions.iter().any(|mention| mention.mri == me_mri));
+        let is_chat = self.chats.iter().any(|chat| chat.id == conversation_id);
+        if !is_mentioned && (!is_chat || self.settings.notify_mentions_only) {
+            return Task::none();
+        }
 
-                        let (message_area_content, message_area_height) = match self.page {
-                            Page::Team(_, _) => (
-                                &mut self.team_message_area_content,
-                                &mut self.team_message_area_height,
-                            ),
-                            Page::Chat(_, _) => (
-                                &mut self.chat_message_area_content,
-                                &mut self.chat_message_area_height,
-                            ),
-                            _ => return Task::none(), // Should never happen
-                        };
+        let summary = message.im_display_name.clone().unwrap_or("New message".to_string());
+        let body = message.content.as_deref().map(get_html_preview).unwrap_or_default();
 
-                        // Handle sizing
+        Task::perform(
+            portal::show_notification(summary, body, self.settings.notification_sound),
+            |result| {
+                if let Err(err) = result {
+                    eprintln!("Failed to show a notification: {}", err);
+                }
+                Message::DoNothing(())
+            },
+        )
+    }
 
-                        let line_count = message_area_content.line_count();
-                        let new_height = 33.0 + line_count as f32 * 21.0;
+    fn current_draft(&self, page: &Page) -> Option<Draft> {
+        match page {
+            Page::Team(_, Some(_), _) => Some(Draft {
+                text: self.team_message_area_content.text(),
+                subject: self.subject_input_value.clone(),
+                images: self.pending_images.clone(),
+                formats: self.draft_formats(&self.team_rich_document),
+                reply_to: None,
+            }),
+            Page::Chat(Some(_), _) => Some(Draft {
+                text: self.chat_message_area_content.text(),
+                subject: None,
+                images: self.pending_images.clone(),
+                formats: self.draft_formats(&self.chat_rich_document),
+                reply_to: self.reply_target.clone(),
+            }),
+            _ => None,
+        }
+    }
 
-                        *message_area_height = if new_height > max_area_height {
-                            max_area_height
-                        } else {
-                            new_height
-                        };
-                    }
-                    Event::Mouse(mouse::Event::CursorMoved { position }) => {
-                        self.mouse_position = (position.x, position.y);
-                    }
-                    Event::Mouse(mouse::Event::ButtonReleased(_button)) => {
-                        if self.just_opened_overlay {
-                            self.just_opened_overlay = false;
-                        } else {
-                            if self.show_profile && !self.is_in_profile {
-                                self.show_profile = false;
-                            }
+    // Plain drafts don't need a format for every char
+    fn draft_formats(&self, rich_document: &RichDocument) -> Vec<CharFormat> {
+        let formats = rich_document.formats();
+        if !self.settings.rich_text_composer
+            || formats.iter().all(|format| *format == CharFormat::default())
+        {
+            return vec![];
+        }
+        formats.to_vec()
+    }
 
-                            if self.show_more_options && !self.is_in_more_options {
-                                self.show_more_options = false;
-                                self.more_menu_message_id = None;
-                            }
+    fn store_draft(&mut self, page: &Page) {
+        let (Some(thread_id), Some(draft)) = (draft_thread_id(page), self.current_draft(page)) else {
+            return;
+        };
 
-                            if self.show_message_area_emoji_picker && !self.is_in_emoji_picker {
-                                self.show_message_area_emoji_picker = false;
-                            }
+        if draft.text.trim().is_empty()
+            && draft.subject.as_ref().is_none_or(|s| s.is_empty())
+            && draft.images.is_empty()
+            && draft.reply_to.is_none()
+        {
+            if self.drafts.remove(&thread_id).is_none() {
+                return;
+            }
+        } else if self.drafts.get(&thread_id) != Some(&draft) {
+            self.drafts.insert(thread_id, draft);
+        } else {
+            return;
+        }
 
-                            if self.show_message_emoji_picker && !self.is_in_emoji_picker {
-                                self.show_message_emoji_picker = false;
-                                self.emoji_picker_message_id = None;
-                            }
+        self.drafts_changed = true;
+    }
 
-                            if self.show_plus_emoji_picker && !self.is_in_emoji_picker {
-                                self.show_plus_emoji_picker = false;
-                                self.emoji_picker_message_id = None;
-                            }
+    fn save_drafts(&mut self) {
+        if self.drafts_changed {
+            save_to_cache("drafts.json", &self.drafts);
+            self.drafts_changed = false;
+        }
+    }
 
-                            if let Some(_) = self.expanded_image
-                                && !self.is_in_centered_overlay
-                            {
-                                self.expanded_image = None;
-                            }
+    fn reactions_emotions(&self) -> Option<&Vec<Emotion>> {
+        let (thread_id, message_id) = self.reactions_message.as_ref()?;
+
+        let team_messages = self
+            .team_conversations
+            .values()
+            .flat_map(|conversations| &conversations.reply_chains)
+            .filter(|conversation| &conversation.container_id == thread_id)
+            .flat_map(|conversation| &conversation.messages);
+
+        self.chat_conversations
+            .get(thread_id)
+            .into_iter()
+            .flatten()
+            .chain(team_messages)
+            .chain(
+                self.activity_expanded_conversations
+                    .get(thread_id)
+                    .into_iter()
+                    .flat_map(|(_, messages)| messages),
+            )
+            .find(|message| message.id.as_ref() == Some(message_id))?
+            .properties
+            .as_ref()?
+            .emotions
+            .as_ref()
+    }
 
-                            if self.show_add_users && !self.is_in_centered_overlay {
-                                self.show_add_users = false;
-                            }
+    fn shortcut_for(&self, key: &Key, modifiers: keyboard::Modifiers) -> Option<Shortcut> {
+        let pressed = key_binding(key, modifiers)?;
 
-                            if self.show_start_chat && !self.is_in_centered_overlay {
-                                self.show_start_chat = false;
-                            }
-                        }
-                    }
-                    Event::Keyboard(keyboard::Event::KeyPressed {
-                        key,
-                        modified_key: _,
-                        physical_key: _,
-                        location: _,
-                        modifiers: _,
-                        text: _,
-                        repeat: _,
-                    }) => match key {
-                        Key::Named(Named::Enter) => {
-                            if self.show_start_chat {
-                                self.show_start_chat = false;
-                                self.is_in_centered_overlay = false;
+        Shortcut::ALL
+            .into_iter()
+            .find(|shortcut| binding_matches(self.settings.binding(shortcut), &pressed))
+    }
 
-                                let relevant_user = if let Some(start_chat_relevant_user) =
-                                    self.start_chat_relevant_user.clone()
-                                {
-                                    Some(start_chat_relevant_user)
-                                } else {
-                                    let search = self.search_users_input_value.to_lowercase();
+    fn quick_switcher_items(&self) -> Vec<SwitcherItem> {
+        let mut items = vec![];
 
-                                    // Exactly like the logic in c_start_chat, the vec matches the filtering from the search users input
+        for chat in &self.chats {
+            items.push(SwitcherItem {
+                title: get_chat_title(chat, &self.me.id, &self.users),
+                subtitle: String::new(),
+                target: SwitcherTarget::Chat(chat.id.clone()),
+            });
+        }
 
-                                    let filtered_user_ids: Vec<String> = self
-                                        .users
-                                        .iter()
-                                        .filter(|(_, profile)| {
-                                            profile.surname.is_some()
-                                                && profile.display_name.is_some()
-                                        })
-                                        .filter(|(_, profile)| {
-                                            profile
-                                                .display_name
-                                                .as_ref()
-                                                .map(|name| {
-                                                    name.to_lowercase().starts_with(&search)
-                                                })
-                                                .unwrap_or(false)
-                                        })
-                                        .filter(|(id, _)| **id != self.me.id)
-                                        .map(|(id, _)| id.clone())
-                                        .collect();
+        for team in &self.teams {
+            for channel in &team.channels {
+                items.push(SwitcherItem {
+                    title: channel.display_name.clone(),
+                    subtitle: team.display_name.clone(),
+                    target: SwitcherTarget::Channel(team.id.clone(), channel.id.clone()),
+                });
+            }
+        }
 
-                                    // If empty return none
+        for (user_id, profile) in &self.users {
+            // Same filtering as in c_start_chat, which removes all non "human" users
+            if profile.surname.is_none() || *user_id == self.me.id {
+                continue;
+            }
+            let Some(display_name) = &profile.display_name else {
+                continue;
+            };
 
-                                    if filtered_user_ids.len() == 0 {
-                                        None
-                                    } else {
-                                        Some(filtered_user_ids[0].clone())
-                                    }
-                                };
+            items.push(SwitcherItem {
+                title: display_name.clone(),
+                subtitle: profile.job_title.clone().unwrap_or_default(),
+                target: SwitcherTarget::Person(user_id.clone()),
+            });
+        }
 
-                                // Continue if none
-                                if relevant_user.is_none() {
-                                    return Task::none();
-                                }
+        if self.quick_switcher_input_value.trim().is_empty() {
+            // Recent chats first
+            items.truncate(QUICK_SWITCHER_LIMIT);
+            return items;
+        }
 
-                                // Check if the chat already exists
+        let mut scored_items: Vec<(i64, SwitcherItem)> = items
+            .into_iter()
+            .filter_map(|item| {
+                let score = fuzzy_score(&self.quick_switcher_input_value, &item.title)?;
+                Some((score, item))
+            })
+            .collect();
+
+        scored_items.sort_by_key(|(score, _)| -score);
+        scored_items
+            .into_iter()
+            .take(QUICK_SWITCHER_LIMIT)
+            .map(|(_, item)| item)
+            .collect()
+    }
 
-                                for chat in &self.chats {
-                                    if chat.members.len() == 2 {
-                                        if chat.members.iter().any(|member| {
-                                            member.mri.replace("8:orgid:", "")
-                                                == relevant_user.clone().unwrap()
-                                        }) {
-                                            let chat_id = chat.id.clone();
+    // The chat or channel `offset` places away from the current one, in sidebar order
+    fn open_adjacent_conversation(&mut self, offset: isize) -> Task<Message> {
+        let (conversation_ids, current_id): (Vec<String>, Option<String>) = match &self.page {
+            Page::Chat(current_chat_id, _) => (
+                self.chats.iter().map(|chat| chat.id.clone()).collect(),
+                current_chat_id.clone(),
+            ),
+            Page::Team(Some(team_id), current_channel_id, _) => {
+                let Some(team) = self.teams.iter().find(|team| &team.id == team_id) else {
+                    return Task::none();
+                };
 
-                                            self.page = Page::Chat(
-                                                Some(chat_id.clone()),
-                                                ChatBody::Messages,
-                                            );
+                let mut channels: Vec<&Channel> = team
+                    .channels
+                    .iter()
+                    .filter(|channel| {
+                        !self
+                            .channel_settings
+                            .get(&channel.id)
+                            .is_some_and(|settings| settings.hidden)
+                    })
+                    .collect();
 
-                                            let chat_id_clone = chat_id.clone();
-                                            let access_tokens_arc = self.access_tokens.clone();
-                                            let tenant = self.tenant.clone();
+                // Same order as the channel list, general first, then favourites
+                channels.sort_by_key(|channel| {
+                    (
+                        channel.id != team.id,
+                        !self
+                            .channel_settings
+                            .get(&channel.id)
+                            .is_some_and(|settings| settings.favourite),
+                    )
+                });
 
-                                            // Prefetch the chat in case it wasn't already fetched
-                                            return Task::batch(vec![
-                                                authed_task(
-                                                    access_tokens_arc,
-                                                    "https://ic3.teams.office.com/.default",
-                                                    &tenant,
-                                                    move |token| async move {
-                                                        conversations(&token, &chat_id, &None)
-                                                            .await
-                                                            .unwrap()
-                                                    },
-                                                    move |result| {
-                                                        Message::GotChatConversations(
-                                                            chat_id_clone.clone(),
-                                                            result,
-                                                        )
-                                                    },
-                                                ),
-                                                snap_to(
-                                                    Id::new("conversation_column"),
-                                                    RelativeOffset::END,
-                                                ),
-                                            ]);
-                                        }
-                                    }
-                                }
+                (
+                    channels.iter().map(|channel| channel.id.clone()).collect(),
+                    current_channel_id.clone(),
+                )
+            }
+            _ => return Task::none(),
+        };
 
-                                // Push yourself back to create the chat properly
+        let Some(index) = current_id
+            .and_then(|current_id| conversation_ids.iter().position(|id| *id == current_id))
+        else {
+            return Task::none();
+        };
 
-                                let user_ids = vec![relevant_user.unwrap(), self.me.id.clone()];
+        let Some(target_id) = index
+            .checked_add_signed(offset)
+            .and_then(|target| conversation_ids.get(target))
+        else {
+            return Task::none();
+        };
 
-                                let members: Vec<ChatMember> = user_ids
-                                    .iter()
-                                    .map(|user_id| ChatMember {
-                                        mri: format!("8:orgid:{}", user_id), // Users should all be of 8:orgid:
-                                        is_muted: None,
-                                        object_id: None,
-                                        is_identity_masked: None,
-                                        role: None,
-                                    })
-                                    .collect();
-
-                                let mut rng = StdRng::from_os_rng();
+        let message = match &self.page {
+            Page::Team(Some(team_id), _, _) => {
+                Message::OpenTeam(team_id.clone(), target_id.clone())
+            }
+            _ => Message::OpenChat(target_id.clone()),
+        };
 
-                                let draft_id = format!("draft:{}", rng.random::<u64>()); // Generate the temproary draft chat_id randomly
+        self.handle_message(message)
+    }
 
-                                self.chats.insert(
-                                    0,
-                                    Chat {
-                                        id: draft_id.clone(), // Signal that it isn't a real chat
-                                        members: members,
-                                        is_read: None,
-                                        is_high_importance: None,
-                                        is_one_on_one: if user_ids.len() > 2 {
-                                            Some(false)
-                                        } else {
-                                            Some(true)
-                                        },
-                                        is_conversation_deleted: None,
-                                        is_external: None,
-                                        is_messaging_disabled: None,
-                                        is_disabled: None,
-                                        title: None,
-                                        last_message: None,
-                                        is_last_message_from_me: None,
-                                        chat_sub_type: None,
-                                        last_join_at: None,
-                                        created_at: None,
-                                        creator: None,
-                                        hidden: None,
-                                        added_by: None,
-                                        chat_type: Some("draft".to_string()),
-                                        picture: None,
-                                    },
-                                );
+    // Loads the last own message of the current chat into the message area
+    fn edit_last_message(&mut self) -> bool {
+        let Page::Chat(Some(chat_id), _) = &self.page else {
+            return false;
+        };
 
-                                self.page = Page::Chat(Some(draft_id), ChatBody::Messages);
-                            }
-                        }
-                        Key::Named(Named::Escape) => {
-                            if self.show_message_area_emoji_picker {
-                                self.show_message_area_emoji_picker = false
-                            } else if self.show_more_options {
-                                self.show_more_options = false;
-                                self.is_in_more_options = false;
-                                self.more_menu_message_id = None;
-                            } else if self.show_message_emoji_picker {
-                                self.show_message_emoji_picker = false;
-                                self.is_in_emoji_picker = false;
-                                self.emoji_picker_message_id = None;
-                            } else if self.show_plus_emoji_picker {
-                                self.show_plus_emoji_picker = false;
-                                self.is_in_emoji_picker = false;
-                                self.emoji_picker_message_id = None;
-                            } else if let Some(_) = self.expanded_image {
-                                self.expanded_image = None;
-                            } else if self.show_add_users {
-                                self.show_add_users = false;
-                            } else if self.show_start_chat {
-                                self.show_start_chat = false;
-                            }
-                        }
-                        Key::Named(Named::ArrowDown) => {
-                            let search = self.search_users_input_value.to_lowercase();
+        if self.chat_message_area_content.text().trim() != "" {
+            return false;
+        }
 
-                            let filtered_user_ids: Vec<String> = self
-                                .users
-                                .iter()
-                                .filter(|(_, profile)| {
-                                    profile.surname.is_some() && profile.display_name.is_some()
-                                })
-                                .filter(|(_, profile)| {
-                                    profile
-                                        .display_name
-                                        .as_ref()
-                                        .map(|name| name.to_lowercase().starts_with(&search))
-                                        .unwrap_or(false)
-                                })
-                                .filter(|(id, _)| **id != self.me.id)
-                                .map(|(id, _)| id.clone())
-                                .collect();
+        let my_mri = format!("8:orgid:{}", self.me.id);
 
-                            if self.start_chat_relevant_user.is_none() {
-                                self.start_chat_relevant_user = Some(filtered_user_ids[1].clone());
-                            } else if let Some(index) = filtered_user_ids.iter().position(|id| {
-                                id == self.start_chat_relevant_user.as_ref().unwrap()
-                            }) {
-                                let next_index = (index + 1) % filtered_user_ids.len(); // Wrap around
-                                self.start_chat_relevant_user =
-                                    Some(filtered_user_ids[next_index].clone())
-                            }
-                        }
-                        Key::Named(Named::ArrowUp) => {
-                            let search = self.search_users_input_value.to_lowercase();
+        let Some(message) = self.chat_conversations.get(chat_id).and_then(|conversation| {
+            conversation.iter().find(|message| {
+                message.from.as_ref() == Some(&my_mri)
+                    && message.id.as_deref() != Some("-1") // Not yet sent
+                    && matches!(
+                        message.message_type.as_deref(),
+                        Some("RichText/Html") | Some("Text")
+                    )
+            })
+        }) else {
+            return false;
+        };
 
-                            let filtered_user_ids: Vec<String> = self
-                                .users
-                                .iter()
-                                .filter(|(_, profile)| {
-                                    profile.surname.is_some() && profile.display_name.is_some()
-                                })
-                                .filter(|(_, profile)| {
-                                    profile
-                                        .display_name
-                                        .as_ref()
-                                        .map(|name| name.to_lowercase().starts_with(&search))
-                                        .unwrap_or(false)
-                                })
-                                .filter(|(id, _)| **id != self.me.id)
-                                .map(|(id, _)| id.clone())
-                                .collect();
+        let (Some(message_id), Some(content)) = (&message.id, &message.content) else {
+            return false;
+        };
 
-                            if self.start_chat_relevant_user.is_none() {
-                                self.start_chat_relevant_user =
-                                    Some(filtered_user_ids[filtered_user_ids.len() - 1].clone());
-                            } else if let Some(index) = filtered_user_ids.iter().position(|id| {
-                                id == self.start_chat_relevant_user.as_ref().unwrap()
-                            }) {
-                                let prev_index = if index == 0 {
-                                    filtered_user_ids.len() - 1
-                                } else {
-                                    index - 1
-                                };
-                                self.start_chat_relevant_user =
-                                    Some(filtered_user_ids[prev_index].clone())
-                            }
-                        }
+        self.editing_message = Some((chat_id.clone(), message_id.clone()));
 
-                        Key::Named(Named::Shift) => self.shift_held_down = true,
-                        Key::Named(Named::Control) => self.control_held_down = true,
+        // Edited in the composer's own format so the formatting is kept
+        let document = RichDocument::from_html(content);
+        let text = if self.settings.rich_text_composer {
+            document.text().to_string()
+        } else {
+            document.to_markdown()
+        };
+        self.reply_target = get_reply_target(content);
+        self.message_area_preview = None;
+        self.chat_message_area_content = Content::with_text(&text);
+        self.chat_message_area_history = MessageAreaHistory::default();
+        self.chat_rich_document = document;
+
+        let line_count = self.chat_message_area_content.line_count();
+        let new_height = 33.0 + line_count as f32 * 21.0;
+        self.chat_message_area_height = new_height.min(0.5 * self.window_height);
+        self.check_spelling();
+
+        true
+    }
 
-                        Key::Character(char) => {
-                            if (char == "c" || char == "x") && self.control_held_down {
-                                return selectable_text::selected(|selected_text| {
-                                    Message::CopySelected(selected_text)
-                                });
-                            }
-                        }
-                        _ => {}
-                    },
-                    Event::Keyboard(keyboard::Event::KeyReleased {
-                        key,
-                        modified_key: _,
-                        physical_key: _,
-                        location: _,
-                        modifiers: _,
-                    }) => match key {
-                        // For some reason when pressing escape while focusing an input doesn't cause a KeyPressed event.
-                        Key::Named(Named::Escape) => {
-                            if self.show_add_users {
-                                self.show_add_users = false;
-                            } else if self.show_start_chat {
-                                self.show_start_chat = false;
-                            }
-                        }
-                        Key::Named(Named::Shift) => self.shift_held_down = false,
-                        Key::Named(Named::Control) => self.control_held_down = false,
+    // Previews are fetched once the link is complete rather than for every typed character
+    fn link_preview_task(&mut self, message_area_text: &str, is_paste: bool) -> Task<Message> {
+        match first_link(message_area_text, is_paste) {
+            Some(url) if self.link_preview_url.as_ref() != Some(&url) => {
+                self.link_preview_url = Some(url.clone());
+                self.link_preview = None;
 
-                        _ => {}
+                let page_url = url.clone();
+                Task::perform(
+                    async move {
+                        let html = fetch_page_html(&page_url).await.ok()?;
+                        parse_open_graph(&page_url, &html)
                     },
-                    _ => {}
+                    move |link_preview| Message::GotLinkPreview(url.clone(), link_preview),
+                )
+            }
+            Some(_) => Task::none(),
+            None => {
+                if self
+                    .link_preview_url
+                    .as_ref()
+                    .is_some_and(|url| !message_area_text.contains(url.as_str()))
+                {
+                    self.link_preview_url = None;
+                    self.link_preview = None;
                 }
-
                 Task::none()
             }
+        }
+    }
 
-            // UI interactions
-            Message::MessageAreaEdit(action) => {
-                let max_area_height = 0.5 * self.window_height;
+    // Shows the message in its conversation right away and sends it through the outbox
+    fn post_message_task(&mut self, outbox_message: OutboxMessage) -> Task<Message> {
+        let conversation_id = outbox_message.conversation_id.clone();
+        let prefetched_message = outbox_preview_message(
+            &outbox_message,
+            &self.me.id,
+            self.me.display_name.clone(),
+        );
 
-                // Determine the current message area and height
-                let (message_area_content, message_area_height) = match self.page {
-                    Page::Team(_, _) => (
-                        &mut self.team_message_area_content,
-                        &mut self.team_message_area_height,
-                    ),
-                    Page::Chat(_, _) => (
-                        &mut self.chat_message_area_content,
-                        &mut self.chat_message_area_height,
-                    ),
-                    _ => return Task::none(), // Should never happen
-                };
+        if let Some(conversation) = self.chat_conversations.get_mut(&conversation_id) {
+            conversation.insert(0, prefetched_message.clone());
+        }
 
-                let conversation_id = match &self.page {
-                    Page::Team(_, current_channel_id) => current_channel_id.clone().unwrap(),
-                    Page::Chat(current_chat_id, _) => current_chat_id.clone().unwrap(),
-                    _ => "".to_string(),
-                };
+        if let Some(conversation) = self.team_conversations.get_mut(&conversation_id) {
+            conversation.reply_chains.insert(
+                0,
+                Conversation {
+                    messages: vec![prefetched_message.clone()],
+                    container_id: prefetched_message.id.clone().unwrap(),
+                    id: prefetched_message.id.clone().unwrap(),
+                    latest_delivery_time: prefetched_message
+                        .original_arrival_time
+                        .unwrap_or("n/a".to_string()),
+                },
+            );
+        }
 
-                let message_area_text = message_area_content.text();
-                let cursor_line_index = message_area_content.cursor().position.line;
-                let current_line = message_area_text.lines().nth(cursor_line_index);
-                match action {
-                    Action::Edit(Edit::Enter) => {
-                        if self.shift_held_down {
-                            if let Some(current_line) = current_line {
-                                if current_line.starts_with("- ") && current_line != "- " {
-                                    message_area_content.perform(action);
-                                    content_send(message_area_content, "- ");
-                                } else if current_line == "- " {
-                                    // Low-key a hotfix but it works
-                                    message_area_content.perform(Action::SelectLine);
-                                    message_area_content.perform(Action::Edit(Edit::Backspace));
-                                } else if Regex::new(r"^\d+\. .+").unwrap().is_match(current_line) {
-                                    message_area_content.perform(action);
-                                    if let Some((number, _)) = current_line.split_once('.') {
-                                        if let Ok(number) = number.parse::<u64>() {
-                                            content_send(
-                                                message_area_content,
-                                                &format!("{}. ", number + 1),
-                                            );
-                                        }
-                                    }
-                                } else if Regex::new(r"^\d+\. $").unwrap().is_match(current_line) {
-                                    message_area_content.perform(Action::SelectLine);
-                                    message_area_content.perform(Action::Edit(Edit::Backspace));
-                                } else {
-                                    message_area_content.perform(action);
-                                }
-                            } else {
-                                message_area_content.perform(action);
-                            }
-                        } else if message_area_content.text() != "\n".to_string() {
-                            // Post a message instead if the content is not empty
+        if let Some(pos) = self.chats.iter().position(|chat| chat.id == conversation_id) {
+            let chat = self.chats.remove(pos);
+            self.chats.insert(0, chat);
+        }
 
-                            let subject_text = if let Page::Team(_, _) = self.page {
-                                self.subject_input_value.clone()
-                            } else {
-                                None
-                            };
+        self.present_messages.insert(outbox_message.client_message_id.clone());
 
-                            match self.page {
-                                Page::Team(_, _) => self.team_message_area_content = Content::new(),
-                                Page::Chat(_, _) => self.chat_message_area_content = Content::new(),
-                                _ => {}
-                            }
+        self.outbox.insert(outbox_message.client_message_id.clone(), outbox_message.clone());
+        save_to_cache("outbox.json", &self.outbox);
 
-                            *message_area_height = 54.0;
+        if !outbox_message.pending_images.is_empty() {
+            return upload_images_task(
+                self.access_tokens.clone(),
+                self.tenant.clone(),
+                outbox_message,
+            );
+        }
 
-                            self.subject_input_value = None;
+        send_outbox_message_task(
+            self.access_tokens.clone(),
+            self.tenant.clone(),
+            outbox_message,
+            self.me.id.clone(),
+            self.me.display_name.clone(),
+        )
+    }
 
-                            let me_id = self.me.id.clone();
+    // Empties the composer once its message is sent or scheduled
+    fn clear_message_area(&mut self) {
+        let (content, history, height, rich_document) = match self.page {
+            Page::Team(_, _, _) => (
+                &mut self.team_message_area_content,
+                &mut self.team_message_area_history,
+                &mut self.team_message_area_height,
+                &mut self.team_rich_document,
+            ),
+            Page::Chat(_, _) => (
+                &mut self.chat_message_area_content,
+                &mut self.chat_message_area_history,
+                &mut self.chat_message_area_height,
+                &mut self.chat_rich_document,
+            ),
+            _ => return,
+        };
 
-                            let me_display_name = self.me.display_name.clone();
+        *content = Content::new();
+        *history = MessageAreaHistory::default();
+        *height = 54.0;
+        *rich_document = RichDocument::default();
 
-                            let acess_tokens_arc = self.access_tokens.clone();
-                            let tenant = self.tenant.clone();
+        self.subject_input_value = None;
+        self.reply_target = None;
+        self.message_area_preview = None;
+    }
 
-                            if !conversation_id.starts_with("draft:") {
-                                return post_message_task(
-                                    message_area_text,
-                                    acess_tokens_arc,
-                                    tenant,
-                                    conversation_id,
-                                    me_id,
-                                    me_display_name,
-                                    subject_text,
-                                    &mut self.present_messages,
-                                    &mut self.chat_conversations,
-                                    &mut self.team_conversations,
-                                    &mut self.chats,
-                                );
-                            } else {
-                                let current_chat = self
-                                    .chats
-                                    .iter()
-                                    .find(|chat| chat.id == conversation_id)
-                                    .unwrap();
+    // Team posts have a subject, chat messages can be replies and voice messages
+    fn message_area_state(&self) -> MessageAreaState<'_> {
+        let is_team = matches!(self.page, Page::Team(_, _, _));
+        let (content, height, rich_document) = if is_team {
+            (
+                &self.team_message_area_content,
+                &self.team_message_area_height,
+                &self.team_rich_document,
+            )
+        } else {
+            (
+                &self.chat_message_area_content,
+                &self.chat_message_area_height,
+                &self.chat_rich_document,
+            )
+        };
 
-                                let members: Vec<ThreadMember> = current_chat
-                                    .members
-                                    .iter()
-                                    .map(|memeber| ThreadMember {
-                                        id: memeber.mri.clone(),
-                                        role: "Admin".to_string(),
-                                        share_history_time: None,
-                                    })
-                                    .collect();
+        MessageAreaState {
+            content,
+            subject: if is_team { &self.subject_input_value } else { &None },
+            height,
+            pending_images: &self.pending_images,
+            reply_target: if is_team { &None } else { &self.reply_target },
+            link_preview: &self.link_preview,
+            preview: &self.message_area_preview,
+            rich_document: self.settings.rich_text_composer.then_some(rich_document),
+            rich_link_input: self.rich_link_input.as_ref().map(|(_, url)| url),
+            misspelled: self.misspelled.as_ref().map(|(_, misspelled)| misspelled),
+            spelling_menu: &self.spelling_menu,
+            is_recording_voice: !is_team && self.voice_recording.is_some(),
+            show_emoji_picker: &self.show_message_area_emoji_picker,
+            search_emojis_input_value: &self.search_emojis_input_value,
+            emoji_usage: &self.emoji_usage,
+            emoji_autocomplete: &self.emoji_autocomplete,
+        }
+    }
 
-                                let new_thread = Thread {
-                                    members: members,
-                                    properties: if current_chat.members.len() == 2 {
-                                        // You and the other person (one on one chat)
+    // The preview is dropped if its link was removed from the message, a link that was never
+    // fetched is sent without a preview so the outbox fetches it
+    fn take_link_preview(&mut self, message_area_text: &str) -> Vec<api::Link> {
+        let link_preview_url = self.link_preview_url.take();
+        if let Some(link_preview) = self.link_preview.take() {
+            return Some(link_preview)
+                .filter(|link| message_area_text.contains(link.url.as_str()))
+                .into_iter()
+                .collect();
+        }
+
+        first_link(message_area_text, true)
+            .filter(|url| link_preview_url.as_ref() != Some(url))
+            .map(|url| api::Link {
+                link_type: "http://schema.skype.com/HyperLink".to_string(),
+                itemid: 0,
+                url,
+                previewenabled: true,
+                preview: None,
+            })
+            .into_iter()
+            .collect()
+    }
+
+    // Spelling is checked once for every change of the composer text rather than every frame
+    fn check_spelling(&mut self) {
+        let Some(spell_checker) = &self.spell_checker else {
+            self.misspelled = None;
+            return;
+        };
+
+        let text = match self.page {
+            Page::Team(_, _, _) => self.team_message_area_content.text(),
+            Page::Chat(_, _) => self.chat_message_area_content.text(),
+            _ => return,
+        };
+
+        let hash = xxh3_64(text.as_bytes());
+        if self.misspelled.as_ref().is_none_or(|(misspelled_hash, _)| *misspelled_hash != hash) {
+            self.misspelled = Some((hash, spell_checker.misspelled_lines(&text)));
+        }
+    }
+
+    fn sync_rich_document(&mut self) {
+        if !self.settings.rich_text_composer {
+            return;
+        }
+
+        match self.page {
+            Page::Team(_, _, _) => {
+                self.team_rich_document.sync(&self.team_message_area_content.text())
+            }
+            Page::Chat(_, _) => {
+                self.chat_rich_document.sync(&self.chat_message_area_content.text())
+            }
+            _ => {}
+        }
+    }
+
+    // The rich text composer applies formatting to the selection instead of typing markdown
+    fn rich_text_action(&mut self, action: MessageAreaAction) -> Task<Message> {
+        let (content, rich_document) = match self.page {
+            Page::Team(_, _, _) => (
+                &mut self.team_message_area_content,
+                &mut self.team_rich_document,
+            ),
+            Page::Chat(_, _) => (
+                &mut self.chat_message_area_content,
+                &mut self.chat_rich_document,
+            ),
+            _ => return Task::none(),
+        };
+
+        rich_document.sync(&content.text());
+        let selection = rich_document.selection(&content.cursor());
+
+        match action {
+            MessageAreaAction::Bold => rich_document.toggle(selection, InlineStyle::Bold),
+            MessageAreaAction::Italic => rich_document.toggle(selection, InlineStyle::Italic),
+            MessageAreaAction::Underline => {
+                rich_document.toggle(selection, InlineStyle::Underline)
+            }
+            MessageAreaAction::Striketrough => {
+                rich_document.toggle(selection, InlineStyle::Strikethrough)
+            }
+            MessageAreaAction::Code => rich_document.toggle(selection, InlineStyle::Code),
+            MessageAreaAction::Blockquote => {
+                toggle_line_block(content, rich_document, LineBlock::Quote)
+            }
+            MessageAreaAction::List => {
+                toggle_line_block(content, rich_document, LineBlock::Bullet)
+            }
+            MessageAreaAction::OrderedList => {
+                toggle_line_block(content, rich_document, LineBlock::Ordered)
+            }
+            MessageAreaAction::Link => {
+                self.rich_link_input = Some((selection, String::new()));
+                return focus(Id::new("rich_link_input"));
+            }
+            MessageAreaAction::Image => {
+                return Task::perform(
+                    async {
+                        let file = AsyncFileDialog::new()
+                            .set_directory(home_dir().unwrap_or("/".into()))
+                            .add_filter("Images", &["png", "jpg", "jpeg", "gif", "webp", "bmp"])
+                            .pick_file()
+                            .await?;
+
+                        utils::load_pending_image(file.path())
+                    },
+                    Message::GotPendingImage,
+                );
+            }
+            // Fences are kept as text, like in markdown
+            MessageAreaAction::CodeBlock(_) => {}
+        }
+
+        focus(Id::new("message_area"))
+    }
+
+    // Formatting actions and links are undone as a single step
+    fn record_message_area_step(&mut self) {
+        match self.page {
+            Page::Team(_, _, _) => self.team_message_area_history.record(
+                EditKind::Other,
+                &self.team_message_area_content,
+                &self.team_rich_document,
+            ),
+            Page::Chat(_, _) => self.chat_message_area_history.record(
+                EditKind::Other,
+                &self.chat_message_area_content,
+                &self.chat_rich_document,
+            ),
+            _ => {}
+        }
+    }
+
+    // Tab indents the list items in the selection and Shift+Tab outdents them
+    fn indent_message_area(&mut self, outdent: bool) -> Task<Message> {
+        let bullet = if self.settings.rich_text_composer {
+            BULLET_MARKER
+        } else {
+            "- "
+        };
+
+        let (content, history, rich_document) = match self.page {
+            Page::Team(_, _, _) => (
+                &mut self.team_message_area_content,
+                &mut self.team_message_area_history,
+                &mut self.team_rich_document,
+            ),
+            Page::Chat(_, _) => (
+                &mut self.chat_message_area_content,
+                &mut self.chat_message_area_history,
+                &mut self.chat_rich_document,
+            ),
+            _ => return Task::none(),
+        };
+
+        history.record(EditKind::Other, content, rich_document);
+        let indented = indent_list_items(
+            content,
+            bullet,
+            outdent,
+            self.settings.rich_text_composer.then_some(rich_document),
+        );
+
+        // Outside of lists Tab is typed like any other character
+        if !indented && !outdent {
+            return self.handle_message(Message::MessageAreaEdit(Action::Edit(Edit::Insert(
+                '\t',
+            ))));
+        }
+        Task::none()
+    }
+
+    fn message_area_history_step(&mut self, redo: bool) -> Task<Message> {
+        let (content, message_area_height, history, rich_document) = match self.page {
+            Page::Team(_, _, _) => (
+                &mut self.team_message_area_content,
+                &mut self.team_message_area_height,
+                &mut self.team_message_area_history,
+                &mut self.team_rich_document,
+            ),
+            Page::Chat(_, _) => (
+                &mut self.chat_message_area_content,
+                &mut self.chat_message_area_height,
+                &mut self.chat_message_area_history,
+                &mut self.chat_rich_document,
+            ),
+            _ => return Task::none(),
+        };
+
+        let changed = if redo {
+            history.redo(content, rich_document)
+        } else {
+            history.undo(content, rich_document)
+        };
+        if !changed {
+            return Task::none();
+        }
+
+        let line_count = content.line_count();
+        let new_height = 33.0 + line_count as f32 * 21.0;
+        *message_area_height = new_height.min(0.5 * self.window_height);
+
+        self.emoji_autocomplete = None;
+
+        focus(Id::new("message_area"))
+    }
+
+    // The html body the message area is sent as, the rich text composer skips markdown
+    fn draft_html(&self) -> String {
+        let (content, rich_document) = match self.page {
+            Page::Team(_, _, _) => (&self.team_message_area_content, &self.team_rich_document),
+            Page::Chat(_, _) => (&self.chat_message_area_content, &self.chat_rich_document),
+            _ => return String::new(),
+        };
+
+        let text = content.text();
+        if text.trim().is_empty() {
+            return String::new();
+        }
+
+        let html = if self.settings.rich_text_composer {
+            // The editor text may have been replaced since the document was last synced
+            let mut rich_document = rich_document.clone();
+            rich_document.sync(&text);
+            rich_document.to_html()
+        } else {
+            parse_message_markdown(text.trim_end().to_string())
+        };
+
+        // The quoted message goes above the text
+        let reply_html = self
+            .reply_target
+            .as_ref()
+            .map(|reply| {
+                parse_message_markdown(format!(
+                    ">[{}][{}] {}\n",
+                    reply.name, reply.message_id, reply.preview
+                ))
+            })
+            .unwrap_or_default();
+
+        expand_custom_emojis(&format!("{}{}", reply_html, html), &self.emoji_map)
+    }
+
+    fn team_group_id(&self, team_id: &String) -> Option<String> {
+        self.teams
+            .iter()
+            .find(|team| &team.id == team_id)
+            .map(|team| team.team_site_information.group_id.clone())
+    }
+
+    fn restore_draft(&mut self) {
+        let Some(thread_id) = draft_thread_id(&self.page) else {
+            return;
+        };
+
+        let draft = self.drafts.get(&thread_id).cloned().unwrap_or_default();
+        let max_area_height = 0.5 * self.window_height;
+
+        self.pending_images = draft.images;
+        self.reply_target = draft.reply_to;
+        self.link_preview_url = None;
+        self.link_preview = None;
+        self.message_area_preview = None;
+
+        let (message_area_content, message_area_height, history, rich_document) = match self.page
+        {
+            Page::Team(_, _, _) => {
+                self.subject_input_value = draft.subject;
+                (
+                    &mut self.team_message_area_content,
+                    &mut self.team_message_area_height,
+                    &mut self.team_message_area_history,
+                    &mut self.team_rich_document,
+                )
+            }
+            Page::Chat(_, _) => (
+                &mut self.chat_message_area_content,
+                &mut self.chat_message_area_height,
+                &mut self.chat_message_area_history,
+                &mut self.chat_rich_document,
+            ),
+            _ => return,
+        };
+
+        *message_area_content = Content::with_text(&draft.text);
+        *history = MessageAreaHistory::default();
+        // The formats of the previous conversation would otherwise be carried over
+        *rich_document = RichDocument::with_formats(&draft.text, draft.formats);
+
+        let line_count = message_area_content.line_count();
+        let new_height = 33.0 + line_count as f32 * 21.0;
+
+        *message_area_height = if new_height > max_area_height {
+            max_area_height
+        } else {
+            new_height
+        };
+
+        self.check_spelling();
+    }
+
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
+        match message {
+            // Authorization
+            Message::GotDeviceCodeInfo(device_code_info) => {
+                self.device_user_code = Some(device_code_info.user_code);
+                self.device_code = device_code_info.device_code.clone();
+
+                let tenant = self.tenant.clone();
+                Task::perform(
+                    async move {
+                        thread::sleep(Duration::new(1, 0));
+
+                        let mut refresh_token: Option<AccessToken> = None;
+                        let result = gen_refresh_token_from_device_code(
+                            &device_code_info.device_code,
+                            &tenant,
+                        )
+                        .await;
+                        if let Ok(access_token) = result {
+                            refresh_token = Some(access_token);
+                            println!("Code polling succeeded.")
+                        }
+
+                        refresh_token
+                    },
+                    |refresh_token| {
+                        if let Some(refresh_token) = refresh_token {
+                            Message::Authorized(refresh_token)
+                        } else {
+                            Message::PollDeviceCode
+                        }
+                    },
+                )
+            }
+            Message::PollDeviceCode => {
+                let device_code = self.device_code.clone();
+                let tenant = self.tenant.clone();
+                Task::perform(
+                    async move {
+                        thread::sleep(Duration::new(1, 0));
+
+                        let mut refresh_token: Option<AccessToken> = None;
+                        let result =
+                            gen_refresh_token_from_device_code(&device_code, &tenant).await;
+                        // TODO: log non re-poll errors
+                        if let Ok(access_token) = result {
+                            refresh_token = Some(access_token);
+                            println!("Code polling succeeded.")
+                        }
+
+                        refresh_token
+                    },
+                    |refresh_token| {
+                        if let Some(refresh_token) = refresh_token {
+                            Message::Authorized(refresh_token)
+                        } else {
+                            Message::PollDeviceCode
+                        }
+                    },
+                )
+            }
+            Message::Authorized(refresh_token) => {
+                self.page = Page::Chat(None, ChatBody::Messages);
+
+                self.access_tokens
+                    .write()
+                    .unwrap()
+                    .insert("refresh_token".to_string(), refresh_token);
+                self.is_authorized = true;
+                self.session_expired = false;
+                init_tasks(self.access_tokens.clone(), self.tenant.clone(), None)
+            }
+            Message::AuthExpired => {
+                if self.page == Page::Login {
+                    return Task::none();
+                }
+                eprintln!("Authentication expired. Redirecting to login.");
+
+                // Whatever was still sending can be retried after logging in again
+                for outbox_message in self.outbox.values_mut() {
+                    if outbox_message.state == OutboxState::Pending {
+                        outbox_message.state = OutboxState::Failed;
+                    }
+                }
+                save_to_cache("outbox.json", &self.outbox);
+
+                delete_cache("access_tokens.json");
+                self.access_tokens.write().unwrap().clear();
+                self.is_authorized = false;
+                self.session_expired = true;
+                self.page = Page::Login;
+                self.device_user_code = None;
+                self.device_code = String::new();
+                let tenant = self.tenant.clone();
+                Task::perform(
+                    async move { api::gen_device_code(&tenant).await.unwrap() },
+                    Message::GotDeviceCodeInfo,
+                )
+            }
+
+            // App actions
+            Message::EventOccurred(event) => {
+                match event {
+                    Event::Window(window::Event::FileDropped(path)) => {
+                        if let Page::Team(_, _, _) | Page::Chat(_, _) = self.page {
+                            return Task::perform(
+                                async move { utils::load_pending_image(&path) },
+                                Message::GotPendingImage,
+                            );
+                        }
+                    }
+                    Event::Window(window::Event::CloseRequested) => {
+                        self.store_draft(&self.page.clone());
+                        self.save_drafts();
+                        return iced::exit();
+                    }
+                    Event::Window(window::Event::Resized(size)) => {
+                        self.window_width = size.width;
+                        self.window_height = size.height;
+
+                        let max_area_height = 0.5 * self.window_height;
+
+                        if self.team_message_area_height > max_area_height {
+                            self.team_message_area_height = max_area_height;
+                        }
+
+                        let (message_area_content, message_area_height) = match self.page {
+                            Page::Team(_, _, _) => (
+                                &mut self.team_message_area_content,
+                                &mut self.team_message_area_height,
+                            ),
+                            Page::Chat(_, _) => (
+                                &mut self.chat_message_area_content,
+                                &mut self.chat_message_area_height,
+                            ),
+                            _ => return Task::none(), // Should never happen
+                        };
+
+                        // Handle sizing
+
+                        let line_count = message_area_content.line_count();
+                        let new_height = 33.0 + line_count as f32 * 21.0;
+
+                        *message_area_height = if new_height > max_area_height {
+                            max_area_height
+                        } else {
+                            new_height
+                        };
+                    }
+                    Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                        self.mouse_position = (position.x, position.y);
+                    }
+                    Event::Mouse(mouse::Event::ButtonReleased(_button)) => {
+                        if self.just_opened_overlay {
+                            self.just_opened_overlay = false;
+                        } else {
+                            if self.show_profile && !self.is_in_profile {
+                                self.show_profile = false;
+                            }
+
+                            if self.show_more_options && !self.is_in_more_options {
+                                self.show_more_options = false;
+                                self.more_menu_message_id = None;
+                            }
+
+                            if self.show_message_area_emoji_picker && !self.is_in_emoji_picker {
+                                self.show_message_area_emoji_picker = false;
+                            }
+
+                            if self.show_message_emoji_picker && !self.is_in_emoji_picker {
+                                self.show_message_emoji_picker = false;
+                                self.emoji_picker_message_id = None;
+                            }
+
+                            if self.show_plus_emoji_picker && !self.is_in_emoji_picker {
+                                self.show_plus_emoji_picker = false;
+                                self.emoji_picker_message_id = None;
+                            }
+
+                            if let Some(_) = self.expanded_image
+                                && !self.is_in_centered_overlay
+                            {
+                                self.expanded_image = None;
+                            }
+
+                            if self.show_add_users && !self.is_in_centered_overlay {
+                                self.show_add_users = false;
+                            }
+
+                            if self.show_start_chat && !self.is_in_centered_overlay {
+                                self.show_start_chat = false;
+                            }
+
+                            if self.show_forward_message && !self.is_in_centered_overlay {
+                                self.show_forward_message = false;
+                                self.forward_message = None;
+                            }
+
+                            if self.show_reactions && !self.is_in_centered_overlay {
+                                self.show_reactions = false;
+                                self.reactions_message = None;
+                            }
+
+                            if self.show_quick_switcher && !self.is_in_centered_overlay {
+                                self.show_quick_switcher = false;
+                            }
+
+                            if self.show_shortcuts && !self.is_in_centered_overlay {
+                                self.show_shortcuts = false;
+                            }
+
+                            if self.show_send_later && !self.is_in_centered_overlay {
+                                self.show_send_later = false;
+                            }
+
+                            if self.show_scheduled_messages && !self.is_in_centered_overlay {
+                                self.show_scheduled_messages = false;
+                            }
+
+                            if self.show_manage_team && !self.is_in_centered_overlay {
+                                self.show_manage_team = false;
+                                self.renaming_channel = None;
+                                self.confirming_team_action = None;
+                            }
+
+                            if self.show_post_options && !self.is_in_centered_overlay {
+                                self.show_post_options = false;
+                            }
+                        }
+                    }
+                    // EditLastMessage only applies to the message area, see MessageAreaEdit
+                    Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. })
+                        if self
+                            .shortcut_for(&key, modifiers)
+                            .is_some_and(|shortcut| shortcut != Shortcut::EditLastMessage) =>
+                    {
+                        let shortcut = self.shortcut_for(&key, modifiers).unwrap();
+                        return self.handle_message(Message::Shortcut(shortcut));
+                    }
+                    Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                        self.modifiers = modifiers;
+                    }
+                    Event::Keyboard(keyboard::Event::KeyPressed {
+                        key,
+                        modified_key: _,
+                        physical_key: _,
+                        location: _,
+                        modifiers: _,
+                        text: _,
+                        repeat: _,
+                    }) => match key {
+                        Key::Named(Named::Enter) => {
+                            if self.show_start_chat {
+                                self.show_start_chat = false;
+                                self.is_in_centered_overlay = false;
+
+                                let relevant_user = if let Some(start_chat_relevant_user) =
+                                    self.start_chat_relevant_user.clone()
+                                {
+                                    Some(start_chat_relevant_user)
+                                } else {
+                                    let search = self.search_users_input_value.to_lowercase();
+
+                                    // Exactly like the logic in c_start_chat, the vec matches the filtering from the search users input
+
+                                    let filtered_user_ids: Vec<String> = self
+                                        .users
+                                        .iter()
+                                        .filter(|(_, profile)| {
+                                            profile.surname.is_some()
+                                                && profile.display_name.is_some()
+                                        })
+                                        .filter(|(_, profile)| {
+                                            profile
+                                                .display_name
+                                                .as_ref()
+                                                .map(|name| {
+                                                    name.to_lowercase().starts_with(&search)
+                                                })
+                                                .unwrap_or(false)
+                                        })
+                                        .filter(|(id, _)| **id != self.me.id)
+                                        .map(|(id, _)| id.clone())
+                                        .collect();
+
+                                    // If empty return none
+
+                                    if filtered_user_ids.len() == 0 {
+                                        None
+                                    } else {
+                                        Some(filtered_user_ids[0].clone())
+                                    }
+                                };
+
+                                // Continue if none
+                                if relevant_user.is_none() {
+                                    return Task::none();
+                                }
+
+                                // Check if the chat already exists
+
+                                for chat in &self.chats {
+                                    if chat.members.len() == 2 {
+                                        if chat.members.iter().any(|member| {
+                                            member.mri.replace("8:orgid:", "")
+                                                == relevant_user.clone().unwrap()
+                                        }) {
+                                            let chat_id = chat.id.clone();
+
+                                            self.page = Page::Chat(
+                                                Some(chat_id.clone()),
+                                                ChatBody::Messages,
+                                            );
+
+                                            let chat_id_clone = chat_id.clone();
+                                            let access_tokens_arc = self.access_tokens.clone();
+                                            let tenant = self.tenant.clone();
+
+                                            // Prefetch the chat in case it wasn't already fetched
+                                            return Task::batch(vec![
+                                                authed_task(
+                                                    access_tokens_arc,
+                                                    "https://ic3.teams.office.com/.default",
+                                                    &tenant,
+                                                    move |token| async move {
+                                                        conversations(&token, &chat_id, &None)
+                                                            .await
+                                                            .unwrap()
+                                                    },
+                                                    move |result| {
+                                                        Message::GotChatConversations(
+                                                            chat_id_clone.clone(),
+                                                            result,
+                                                        )
+                                                    },
+                                                ),
+                                                snap_to(
+                                                    Id::new("conversation_column"),
+                                                    RelativeOffset::END,
+                                                ),
+                                            ]);
+                                        }
+                                    }
+                                }
+
+                                // Push yourself back to create the chat properly
+
+                                let user_ids = vec![relevant_user.unwrap(), self.me.id.clone()];
+
+                                let members: Vec<ChatMember> = user_ids
+                                    .iter()
+                                    .map(|user_id| ChatMember {
+                                        mri: format!("8:orgid:{}", user_id), // Users should all be of 8:orgid:
+                                        is_muted: None,
+                                        object_id: None,
+                                        is_identity_masked: None,
+                                        role: None,
+                                    })
+                                    .collect();
+
+                                let mut rng = StdRng::from_os_rng();
+
+                                let draft_id = format!("draft:{}", rng.random::<u64>()); // Generate the temproary draft chat_id randomly
+
+                                self.chats.insert(
+                                    0,
+                                    Chat {
+                                        id: draft_id.clone(), // Signal that it isn't a real chat
+                                        members: members,
+                                        is_read: None,
+                                        is_high_importance: None,
+                                        is_one_on_one: if user_ids.len() > 2 {
+                                            Some(false)
+                                        } else {
+                                            Some(true)
+                                        },
+                                        is_conversation_deleted: None,
+                                        is_external: None,
+                                        is_messaging_disabled: None,
+                                        is_disabled: None,
+                                        title: None,
+                                        last_message: None,
+                                        is_last_message_from_me: None,
+                                        chat_sub_type: None,
+                                        last_join_at: None,
+                                        created_at: None,
+                                        creator: None,
+                                        hidden: None,
+                                        added_by: None,
+                                        chat_type: Some("draft".to_string()),
+                                        picture: None,
+                                    },
+                                );
+
+                                self.page = Page::Chat(Some(draft_id), ChatBody::Messages);
+                            }
+                        }
+                        Key::Named(Named::Escape) => {
+                            if self.show_message_area_emoji_picker {
+                                self.show_message_area_emoji_picker = false
+                            } else if self.show_more_options {
+                                self.show_more_options = false;
+                                self.is_in_more_options = false;
+                                self.more_menu_message_id = None;
+                            } else if self.show_message_emoji_picker {
+                                self.show_message_emoji_picker = false;
+                                self.is_in_emoji_picker = false;
+                                self.emoji_picker_message_id = None;
+                            } else if self.show_plus_emoji_picker {
+                                self.show_plus_emoji_picker = false;
+                                self.is_in_emoji_picker = false;
+                                self.emoji_picker_message_id = None;
+                            } else if let Some(_) = self.expanded_image {
+                                self.expanded_image = None;
+                            } else if self.show_add_users {
+                                self.show_add_users = false;
+                            } else if self.show_start_chat {
+                                self.show_start_chat = false;
+                            } else if self.show_forward_message {
+                                self.show_forward_message = false;
+                                self.forward_message = None;
+                            } else if self.show_reactions {
+                                self.show_reactions = false;
+                                self.reactions_message = None;
+                            } else if self.show_quick_switcher {
+                                self.show_quick_switcher = false;
+                            } else if self.show_shortcuts {
+                                self.show_shortcuts = false;
+                            } else if self.show_send_later {
+                                self.show_send_later = false;
+                            } else if self.show_scheduled_messages {
+                                self.show_scheduled_messages = false;
+                            } else if self.show_manage_team {
+                                self.show_manage_team = false;
+                                self.renaming_channel = None;
+                                self.confirming_team_action = None;
+                            } else if self.show_post_options {
+                                self.show_post_options = false;
+                            }
+                        }
+                        Key::Named(Named::ArrowDown) => {
+                            if self.show_quick_switcher {
+                                let item_count = self.quick_switcher_items().len();
+                                self.quick_switcher_selected = (self.quick_switcher_selected + 1)
+                                    .min(item_count.saturating_sub(1));
+                                return Task::none();
+                            }
+
+                            let search = self.search_users_input_value.to_lowercase();
+
+                            let filtered_user_ids: Vec<String> = self
+                                .users
+                                .iter()
+                                .filter(|(_, profile)| {
+                                    profile.surname.is_some() && profile.display_name.is_some()
+                                })
+                                .filter(|(_, profile)| {
+                                    profile
+                                        .display_name
+                                        .as_ref()
+                                        .map(|name| name.to_lowercase().starts_with(&search))
+                                        .unwrap_or(false)
+                                })
+                                .filter(|(id, _)| **id != self.me.id)
+                                .map(|(id, _)| id.clone())
+                                .collect();
+
+                            if self.start_chat_relevant_user.is_none() {
+                                self.start_chat_relevant_user = Some(filtered_user_ids[1].clone());
+                            } else if let Some(index) = filtered_user_ids.iter().position(|id| {
+                                id == self.start_chat_relevant_user.as_ref().unwrap()
+                            }) {
+                                let next_index = (index + 1) % filtered_user_ids.len(); // Wrap around
+                                self.start_chat_relevant_user =
+                                    Some(filtered_user_ids[next_index].clone())
+                            }
+                        }
+                        Key::Named(Named::ArrowUp) => {
+                            if self.show_quick_switcher {
+                                self.quick_switcher_selected =
+                                    self.quick_switcher_selected.saturating_sub(1);
+                                return Task::none();
+                            }
+
+                            let search = self.search_users_input_value.to_lowercase();
+
+                            let filtered_user_ids: Vec<String> = self
+                                .users
+                                .iter()
+                                .filter(|(_, profile)| {
+                                    profile.surname.is_some() && profile.display_name.is_some()
+                                })
+                                .filter(|(_, profile)| {
+                                    profile
+                                        .display_name
+                                        .as_ref()
+                                        .map(|name| name.to_lowercase().starts_with(&search))
+                                        .unwrap_or(false)
+                                })
+                                .filter(|(id, _)| **id != self.me.id)
+                                .map(|(id, _)| id.clone())
+                                .collect();
+
+                            if self.start_chat_relevant_user.is_none() {
+                                self.start_chat_relevant_user =
+                                    Some(filtered_user_ids[filtered_user_ids.len() - 1].clone());
+                            } else if let Some(index) = filtered_user_ids.iter().position(|id| {
+                                id == self.start_chat_relevant_user.as_ref().unwrap()
+                            }) {
+                                let prev_index = if index == 0 {
+                                    filtered_user_ids.len() - 1
+                                } else {
+                                    index - 1
+                                };
+                                self.start_chat_relevant_user =
+                                    Some(filtered_user_ids[prev_index].clone())
+                            }
+                        }
+
+                        Key::Named(Named::Shift) => self.shift_held_down = true,
+                        Key::Named(Named::Control) => self.control_held_down = true,
+
+                        Key::Character(char) => {
+                            if (char == "c" || char == "x") && self.control_held_down {
+                                return selectable_text::selected(|selected_text| {
+                                    Message::CopySelected(selected_text)
+                                });
+                            }
+                        }
+                        _ => {}
+                    },
+                    Event::Keyboard(keyboard::Event::KeyReleased {
+                        key,
+                        modified_key: _,
+                        physical_key: _,
+                        location: _,
+                        modifiers: _,
+                    }) => match key {
+                        // For some reason when pressing escape while focusing an input doesn't cause a KeyPressed event.
+                        Key::Named(Named::Escape) => {
+                            if self.show_add_users {
+                                self.show_add_users = false;
+                            } else if self.show_start_chat {
+                                self.show_start_chat = false;
+                            } else if self.show_forward_message {
+                                self.show_forward_message = false;
+                                self.forward_message = None;
+                            } else if self.show_reactions {
+                                self.show_reactions = false;
+                                self.reactions_message = None;
+                            } else if self.show_quick_switcher {
+                                self.show_quick_switcher = false;
+                            } else if self.show_shortcuts {
+                                self.show_shortcuts = false;
+                            } else if self.show_send_later {
+                                self.show_send_later = false;
+                            } else if self.show_scheduled_messages {
+                                self.show_scheduled_messages = false;
+                            } else if self.show_manage_team {
+                                self.show_manage_team = false;
+                                self.renaming_channel = None;
+                                self.confirming_team_action = None;
+                            } else if self.show_post_options {
+                                self.show_post_options = false;
+                            } else if self.emoji_autocomplete.is_some() {
+                                self.emoji_autocomplete = None;
+                            } else if self.rich_link_input.is_some() {
+                                self.rich_link_input = None;
+                            } else if self.spelling_menu.is_some() {
+                                self.spelling_menu = None;
+                            } else if self.editing_message.take().is_some() {
+                                self.reply_target = None;
+                                self.chat_message_area_content = Content::new();
+                                self.chat_message_area_history = MessageAreaHistory::default();
+                                self.chat_message_area_height = 54.0;
+                            }
+                        }
+                        Key::Named(Named::Shift) => self.shift_held_down = false,
+                        Key::Named(Named::Control) => self.control_held_down = false,
+
+                        // The message area captures the key press, but not the release
+                        Key::Character(char) if char == "v" && self.control_held_down => {
+                            return self.handle_message(Message::PasteImage);
+                        }
+
+                        _ => {}
+                    },
+                    _ => {}
+                }
+
+                Task::none()
+            }
+
+            // UI interactions
+            Message::MessageAreaEdit(action) => {
+                self.spelling_menu = None;
+
+                // The message area captures the arrow keys, so their shortcuts are handled here
+                if let Action::Move(motion @ (Motion::Up | Motion::Down)) = &action {
+                    let key = Key::Named(if matches!(motion, Motion::Up) {
+                        Named::ArrowUp
+                    } else {
+                        Named::ArrowDown
+                    });
+
+                    match self.shortcut_for(&key, self.modifiers) {
+                        Some(Shortcut::EditLastMessage) if self.edit_last_message() => {
+                            return Task::none();
+                        }
+                        // The arrow key moves the cursor when there is no message to edit
+                        Some(Shortcut::EditLastMessage) => {}
+                        Some(shortcut) => return self.handle_message(Message::Shortcut(shortcut)),
+                        None => {}
+                    }
+                }
+
+                // Typing a marker with a selection wraps it instead of replacing it
+                if let Action::Edit(Edit::Insert(char @ ('*' | '`' | '~'))) = &action
+                    && match self.page {
+                        Page::Team(_, _, _) => self.team_message_area_content.selection(),
+                        Page::Chat(_, _) => self.chat_message_area_content.selection(),
+                        _ => None,
+                    }
+                    .is_some()
+                {
+                    return self.handle_message(Message::MessageAreaAction(match char {
+                        '*' => MessageAreaAction::Italic,
+                        '`' => MessageAreaAction::Code,
+                        _ => MessageAreaAction::Striketrough,
+                    }));
+                }
+
+                let max_area_height = 0.5 * self.window_height;
+
+                // Determine the current message area and height
+                let (message_area_content, message_area_height, history, rich_document) =
+                    match self.page {
+                        Page::Team(_, _, _) => (
+                            &mut self.team_message_area_content,
+                            &mut self.team_message_area_height,
+                            &mut self.team_message_area_history,
+                            &mut self.team_rich_document,
+                        ),
+                        Page::Chat(_, _) => (
+                            &mut self.chat_message_area_content,
+                            &mut self.chat_message_area_height,
+                            &mut self.chat_message_area_history,
+                            &mut self.chat_rich_document,
+                        ),
+                        _ => return Task::none(), // Should never happen
+                    };
+
+                // Typing and deleting are grouped into words, other edits are a step each
+                match &action {
+                    Action::Edit(Edit::Insert(char)) if *char != '\t' => {
+                        history.record(EditKind::Typing, message_area_content, rich_document);
+                        if char.is_whitespace() {
+                            history.break_group();
+                        }
+                    }
+                    Action::Edit(Edit::Enter) => {
+                        history.record(EditKind::Typing, message_area_content, rich_document);
+                        history.break_group();
+                    }
+                    Action::Edit(Edit::Backspace | Edit::Delete) => {
+                        history.record(EditKind::Deleting, message_area_content, rich_document);
+                    }
+                    Action::Edit(_) => {
+                        history.record(EditKind::Other, message_area_content, rich_document);
+                    }
+                    _ => history.break_group(),
+                }
+
+                let bullet = if self.settings.rich_text_composer {
+                    BULLET_MARKER
+                } else {
+                    "- "
+                };
+
+                let conversation_id = match &self.page {
+                    Page::Team(_, current_channel_id, _) => current_channel_id.clone().unwrap(),
+                    Page::Chat(current_chat_id, _) => current_chat_id.clone().unwrap(),
+                    _ => "".to_string(),
+                };
+
+                let message_area_text = message_area_content.text();
+                let cursor_line_index = message_area_content.cursor().position.line;
+                let current_line = message_area_text.lines().nth(cursor_line_index);
+                let is_paste = matches!(action, Action::Edit(Edit::Paste(_)));
+                match action {
+                    Action::Edit(Edit::Enter) => {
+                        // Shift + Enter does the opposite of Enter
+                        if self.shift_held_down == self.settings.send_on_enter {
+                            match current_line.and_then(|line| list_continuation(line, bullet)) {
+                                Some(ListContinuation::Continue(prefix)) => {
+                                    message_area_content.perform(action);
+                                    content_send(message_area_content, &prefix);
+                                }
+                                Some(ListContinuation::End) => {
+                                    // Low-key a hotfix but it works
+                                    message_area_content.perform(Action::SelectLine);
+                                    message_area_content.perform(Action::Edit(Edit::Backspace));
+                                }
+                                None => message_area_content.perform(action),
+                            }
+                        } else if let Some(query) = &self.emoji_autocomplete {
+                            // Enter picks the top suggestion while autocompleting a shortcode
+                            let top_suggestion =
+                                search_emojis(&self.emoji_map, &self.emoji_usage, query, Some(1))
+                                    .first()
+                                    .map(|(emoji_id, emoji)| {
+                                        let emoji_unicode = if emoji.image_url.is_some() {
+                                            emoji.unicode.clone()
+                                        } else {
+                                            apply_skin_tone(
+                                                &emoji.unicode,
+                                                &self.settings.skin_tone,
+                                            )
+                                        };
+                                        (emoji_id.to_string(), emoji_unicode)
+                                    });
+
+                            if let Some((emoji_id, emoji_unicode)) = top_suggestion {
+                                return self.handle_message(Message::PickEmojiSuggestion(
+                                    emoji_id,
+                                    emoji_unicode,
+                                ));
+                            }
+                            message_area_content.perform(action);
+                        } else if message_area_content.text() != "\n"
+                            || !self.pending_images.is_empty()
+                        {
+                            // Post a message instead if the content is not empty
+
+                            let subject_text = if let Page::Team(_, _, _) = self.page {
+                                self.subject_input_value.clone()
+                            } else {
+                                None
+                            };
+
+                            let html = self.draft_html();
+                            self.clear_message_area();
+
+                            let links = self.take_link_preview(&message_area_text);
+
+                            if let Some((editing_conversation_id, message_id)) =
+                                self.editing_message.take()
+                                && editing_conversation_id == conversation_id
+                            {
+                                let mut previous_content = None;
+                                if let Some(message) = self
+                                    .chat_conversations
+                                    .get_mut(&conversation_id)
+                                    .and_then(|conversation| {
+                                        conversation.iter_mut().find(|message| {
+                                            message.id.as_ref() == Some(&message_id)
+                                        })
+                                    })
+                                {
+                                    previous_content = message.content.replace(html.clone());
+                                    if let Some(properties) = &mut message.properties {
+                                        properties.edittime = get_epoch_ms() as i64;
+                                    }
+                                }
+
+                                let body = json!({
+                                    "content": html,
+                                    "messagetype": "RichText/Html",
+                                    "contenttype": "text",
+                                })
+                                .to_string();
+
+                                return authed_task(
+                                    self.access_tokens.clone(),
+                                    "https://ic3.teams.office.com/.default",
+                                    &self.tenant,
+                                    {
+                                        let conversation_id = conversation_id.clone();
+                                        let message_id = message_id.clone();
+                                        move |token| async move {
+                                            edit_message(
+                                                &token,
+                                                &conversation_id,
+                                                &message_id,
+                                                body,
+                                            )
+                                            .await
+                                            .map_err(|error| error.to_string())
+                                        }
+                                    },
+                                    move |result| match result {
+                                        Ok(_) => Message::DoNothing(()),
+                                        Err(error) => Message::EditMessageFailed(
+                                            conversation_id,
+                                            message_id,
+                                            previous_content,
+                                            format!("Couldn't edit the message: {}", error),
+                                        ),
+                                    },
+                                );
+                            }
+
+                            let acess_tokens_arc = self.access_tokens.clone();
+                            let tenant = self.tenant.clone();
+
+                            if !conversation_id.starts_with("draft:") {
+                                let post_options =
+                                    take_post_options(&self.page, &mut self.post_options);
+
+                                let mut images = std::mem::take(&mut self.pending_images);
+                                if let Some(banner) = post_options.banner.clone() {
+                                    images.insert(0, banner);
+                                }
+
+                                return self.post_message_task(OutboxMessage {
+                                    subject: subject_text,
+                                    pending_images: images,
+                                    post_options,
+                                    links,
+                                    ..new_outbox_message(conversation_id, html)
+                                });
+                            } else {
+                                let current_chat = self
+                                    .chats
+                                    .iter()
+                                    .find(|chat| chat.id == conversation_id)
+                                    .unwrap();
+
+                                let members: Vec<ThreadMember> = current_chat
+                                    .members
+                                    .iter()
+                                    .map(|memeber| ThreadMember {
+                                        id: memeber.mri.clone(),
+                                        role: "Admin".to_string(),
+                                        share_history_time: None,
+                                    })
+                                    .collect();
+
+                                let new_thread = Thread {
+                                    members: members,
+                                    properties: if current_chat.members.len() == 2 {
+                                        // You and the other person (one on one chat)
                                         Some(ThreadProperties {
                                             thread_type: "chat".to_string(),
                                             fixed_roster: Some(true),
@@ -1357,236 +3224,845 @@ impl Counter {
                                     },
                                 };
 
-                                let body = serde_json::to_string(&new_thread).unwrap();
+                                let body = serde_json::to_string(&new_thread).unwrap();
+
+                                return authed_task(
+                                    acess_tokens_arc,
+                                    "https://ic3.teams.office.com/.default",
+                                    &tenant,
+                                    move |token| async move {
+                                        start_thread(&token, body)
+                                            .await
+                                            .map(|thread_link| (conversation_id, thread_link))
+                                            .map_err(|error| error.to_string())
+                                    },
+                                    move |result| match result {
+                                        Ok((conversation_id, thread_link)) => {
+                                            Message::CreatedGroupChat(
+                                                conversation_id,
+                                                thread_link,
+                                                html.clone(),
+                                            )
+                                        }
+                                        Err(error) => Message::ShowError(format!(
+                                            "Couldn't start the chat: {}",
+                                            error
+                                        )),
+                                    },
+                                );
+                            }
+                        }
+                    }
+                    _ => message_area_content.perform(action),
+                }
+
+                let message_area_text = message_area_content.text();
+                let cursor = message_area_content.cursor().position;
+                self.emoji_autocomplete = message_area_text
+                    .lines()
+                    .nth(cursor.line)
+                    .and_then(|line| emoji_shortcode_query(line, cursor.column));
+
+                // Handle sizing
+
+                let line_count = message_area_content.line_count();
+                let new_height = 33.0 + line_count as f32 * 21.0;
+
+                *message_area_height = if new_height > max_area_height {
+                    max_area_height
+                } else {
+                    new_height
+                };
+
+                self.sync_rich_document();
+
+                let link_preview_task = self.link_preview_task(&message_area_text, is_paste);
+
+                if let Page::Chat(current_chat_id, _) = &self.page {
+                    if self.should_send_typing
+                        && self.settings.send_typing_indicator
+                        && !conversation_id.starts_with("draft:")
+                    {
+                        self.should_send_typing = false;
+
+                        let acess_tokens_arc = self.access_tokens.clone();
+                        let tenant = self.tenant.clone();
+                        let conversation_id = current_chat_id.clone().unwrap();
+
+                        return Task::batch(vec![
+                            authed_task(
+                                acess_tokens_arc,
+                                "https://ic3.teams.office.com/.default",
+                                &tenant,
+                                move |token| async move {
+                                    let body = "{\"content\":\"\",\"contenttype\":\"Application/Message\",\"messagetype\":\"Control/Typing\"}";
+                                    send_message(&token, &conversation_id, body.to_string())
+                                        .await
+                                        .unwrap();
+                                },
+                                Message::DoNothing,
+                            ),
+                            Task::perform(
+                                async {
+                                    sleep(Duration::from_secs(4)).await;
+                                },
+                                Message::AllowPostIsTyping,
+                            ),
+                            link_preview_task,
+                        ]);
+                    }
+                }
+                link_preview_task
+            }
+            Message::MessageAreaAction(action) => {
+                self.record_message_area_step();
+
+                if self.settings.rich_text_composer
+                    && !matches!(action, MessageAreaAction::CodeBlock(_))
+                {
+                    return self.rich_text_action(action);
+                }
+
+                let (content, message_area_height) = match self.page {
+                    Page::Team(_, _, _) => (
+                        &mut self.team_message_area_content,
+                        &mut self.team_message_area_height,
+                    ),
+                    Page::Chat(_, _) => (
+                        &mut self.chat_message_area_content,
+                        &mut self.chat_message_area_height,
+                    ),
+                    _ => return Task::none(), // Should never happen
+                };
+
+                let selection = content.selection();
+
+                match action {
+                    MessageAreaAction::Bold => wrap_selection(content, "**", "**"),
+                    MessageAreaAction::Italic => wrap_selection(content, "*", "*"),
+                    MessageAreaAction::Underline => wrap_selection(content, "<u>", "</u>"),
+                    MessageAreaAction::Striketrough => wrap_selection(content, "~~", "~~"),
+                    MessageAreaAction::Blockquote => {
+                        if content.text() != "" {
+                            content_send(content, "\n\n");
+                        }
 
-                                return authed_task(
-                                    acess_tokens_arc,
-                                    "https://ic3.teams.office.com/.default",
-                                    &tenant,
-                                    move |token| async move {
-                                        let thread_link =
-                                            start_thread(&token, body).await.unwrap();
-                                        (conversation_id, thread_link)
-                                    },
-                                    move |(conversation_id, thread_link)| {
-                                        Message::CreatedGroupChat(
-                                            conversation_id,
-                                            thread_link,
-                                            message_area_text.clone(),
-                                        )
-                                    },
-                                );
+                        content_send(content, "> ");
+                    }
+                    MessageAreaAction::Link => {
+                        content_send(content, "[");
+
+                        if let Some(selection) = selection {
+                            for char in selection.chars() {
+                                content.perform(Action::Edit(Edit::Insert(char)));
                             }
                         }
+                        content_send(content, "](url)");
+                    }
+                    MessageAreaAction::Image => {
+                        content_send(content, "![");
+
+                        if let Some(selection) = selection {
+                            for char in selection.chars() {
+                                content.perform(Action::Edit(Edit::Insert(char)));
+                            }
+                        }
+                        content_send(content, "](url)");
+                    }
+                    MessageAreaAction::Code => wrap_selection(content, "`", "`"),
+                    MessageAreaAction::CodeBlock(language) => {
+                        // Fences have to be on their own lines
+                        if content.cursor().position.column != 0 {
+                            content.perform(Action::Edit(Edit::Enter));
+                        }
+                        content_send(content, &format!("```{}", language));
+                        content.perform(Action::Edit(Edit::Enter));
+
+                        if let Some(selection) = &selection {
+                            for char in selection.chars() {
+                                content.perform(Action::Edit(Edit::Insert(char)));
+                            }
+                        }
+
+                        content.perform(Action::Edit(Edit::Enter));
+                        content_send(content, "```");
+
+                        // Leave the cursor inside the empty block
+                        if selection.is_none() {
+                            content.perform(Action::Move(Motion::Up));
+                        }
+                    }
+                    MessageAreaAction::List => {
+                        if let Some(selection) = selection {
+                            let mut lines = selection.lines().peekable();
+
+                            while let Some(line) = lines.next() {
+                                content_send(content, &format!("- {}", line));
+                                if lines.peek().is_some() {
+                                    content_send(content, "\n");
+                                }
+                            }
+                        } else {
+                            content_send(content, "- ");
+                        }
+                    }
+                    MessageAreaAction::OrderedList => {
+                        if let Some(selection) = selection {
+                            let mut lines = selection.lines().enumerate().peekable();
+
+                            while let Some((index, line)) = lines.next() {
+                                content_send(content, &format!("{}. {}", index + 1, line));
+
+                                if lines.peek().is_some() {
+                                    content_send(content, "\n");
+                                }
+                            }
+                        } else {
+                            content_send(content, "1.  ");
+                        }
                     }
-                    _ => message_area_content.perform(action),
                 }
 
                 // Handle sizing
 
-                let line_count = message_area_content.line_count();
-                let new_height = 33.0 + line_count as f32 * 21.0;
+                let max_area_height = 0.5 * self.window_height;
+
+                let line_count = content.line_count();
+                let new_height = 33.0 + line_count as f32 * 21.0;
+
+                *message_area_height = if new_height > max_area_height {
+                    max_area_height
+                } else {
+                    new_height
+                };
+
+                self.sync_rich_document();
+
+                Task::none()
+            }
+            Message::MessageAreaUndo => self.message_area_history_step(false),
+            Message::MessageAreaRedo => self.message_area_history_step(true),
+            Message::MessageAreaIndent(outdent) => self.indent_message_area(outdent),
+            Message::AllowPostIsTyping(()) => {
+                self.should_send_typing = true;
+                Task::none()
+            }
+            Message::LinkClicked(url) => {
+                if !webbrowser::open(url.as_str()).is_ok() {
+                    eprintln!("Failed to open link : {}", url);
+                }
+
+                Task::none()
+            }
+            Message::OpenTeam(team_id, channel_id) => {
+                self.page = Page::Team(
+                    Some(team_id.clone()),
+                    Some(channel_id.clone()),
+                    TeamBody::Conversations,
+                );
+
+                let mut tasks = vec![];
+
+                if !self.channel_tabs.contains_key(&channel_id)
+                    && let Some(team) = self.teams.iter().find(|team| team.id == team_id)
+                {
+                    let group_id = team.team_site_information.group_id.clone();
+
+                    tasks.push(authed_task(
+                        self.access_tokens.clone(),
+                        "https://graph.microsoft.com/.default",
+                        &self.tenant,
+                        move |token| async move {
+                            let tabs = channel_tabs(&token, &group_id, &channel_id)
+                                .await
+                                .map(|collection| collection.value)
+                                .unwrap_or_default();
+                            (channel_id, tabs)
+                        },
+                        |(channel_id, tabs)| Message::GotChannelTabs(channel_id, tabs),
+                    ));
+                }
+
+                // Reopen the tab that was last selected in this channel
+                let Page::Team(_, Some(channel_id), _) = &self.page else {
+                    return Task::none();
+                };
+                let team_body = self
+                    .channel_tab_selections
+                    .get(channel_id)
+                    .cloned()
+                    .unwrap_or(TeamBody::Conversations);
+
+                tasks.push(self.handle_message(Message::OpenChannelTab(team_body)));
+
+                Task::batch(tasks)
+            }
+            Message::OpenChannelTab(team_body) => {
+                let Page::Team(Some(team_id), Some(channel_id), _) = self.page.clone() else {
+                    return Task::none();
+                };
+
+                self.channel_tab_selections
+                    .insert(channel_id.clone(), team_body.clone());
+                save_to_cache("channel_tabs.json", &self.channel_tab_selections);
+
+                match team_body {
+                    TeamBody::Conversations => {
+                        self.page = Page::Team(
+                            Some(team_id),
+                            Some(channel_id),
+                            TeamBody::Conversations,
+                        );
+                        snap_to(Id::new("conversation_column"), RelativeOffset::END)
+                    }
+                    TeamBody::Files => self.handle_message(Message::OpenTeamFiles),
+                    TeamBody::Tab(tab_id) => {
+                        self.page = Page::Team(
+                            Some(team_id.clone()),
+                            Some(channel_id.clone()),
+                            TeamBody::Tab(tab_id.clone()),
+                        );
+
+                        let Some(tab) = self
+                            .channel_tabs
+                            .get(&channel_id)
+                            .and_then(|tabs| tabs.iter().find(|tab| tab.id == tab_id))
+                        else {
+                            return Task::none();
+                        };
+
+                        if let Some(plan_id) = tab_plan_id(tab) {
+                            return authed_task(
+                                self.access_tokens.clone(),
+                                "https://graph.microsoft.com/.default",
+                                &self.tenant,
+                                move |token| async move {
+                                    plan_tasks(&token, &plan_id)
+                                        .await
+                                        .map(|collection| (plan_id, collection.value))
+                                        .map_err(|error| error.to_string())
+                                },
+                                |result| match result {
+                                    Ok((plan_id, tasks)) => {
+                                        Message::GotPlannerTasks(plan_id, tasks)
+                                    }
+                                    Err(error) => Message::ShowError(format!(
+                                        "Couldn't load the tasks: {}",
+                                        error
+                                    )),
+                                },
+                            );
+                        }
+
+                        if !is_notes_tab(tab) {
+                            return Task::none();
+                        }
+
+                        let tab_id = tab.id.clone();
+                        let Some(section_id) = tab_notes_section_id(tab) else {
+                            self.error_notice =
+                                Some("Couldn't find the notebook section of this tab".to_string());
+                            return Task::none();
+                        };
+
+                        let Some(group_id) = self.team_group_id(&team_id) else {
+                            return Task::none();
+                        };
+
+                        authed_task(
+                            self.access_tokens.clone(),
+                            "https://graph.microsoft.com/.default",
+                            &self.tenant,
+                            move |token| async move {
+                                section_onenote_pages(&token, &group_id, &section_id)
+                                    .await
+                                    .map(|collection| (tab_id, collection.value))
+                                    .map_err(|error| error.to_string())
+                            },
+                            |result| match result {
+                                Ok((tab_id, pages)) => Message::GotChannelNotes(tab_id, pages),
+                                Err(error) => Message::ShowError(format!(
+                                    "Couldn't load the notes: {}",
+                                    error
+                                )),
+                            },
+                        )
+                    }
+                }
+            }
+            Message::GotChannelTabs(channel_id, tabs) => {
+                self.channel_tabs.insert(channel_id.clone(), tabs);
+
+                // The selected tab could only be loaded once the tabs were known
+                if let Page::Team(_, Some(current_channel_id), team_body) = self.page.clone()
+                    && current_channel_id == channel_id && matches!(team_body, TeamBody::Tab(_))
+                {
+                    return self.handle_message(Message::OpenChannelTab(team_body));
+                }
+                Task::none()
+            }
+            Message::GotPlannerTasks(plan_id, tasks) => {
+                self.planner_tasks.insert(plan_id, tasks);
+                Task::none()
+            }
+            Message::GotChannelNotes(tab_id, pages) => {
+                self.channel_notes.insert(tab_id, pages);
+                Task::none()
+            }
+            Message::TogglePostOptions => {
+                self.just_opened_overlay = true;
+                self.show_post_options = !self.show_post_options;
+                Task::none()
+            }
+            Message::ToggleAnnouncement => {
+                self.post_options.announcement = !self.post_options.announcement;
+                if self.post_options.announcement {
+                    return focus(Id::new("headline_input"));
+                }
+                Task::none()
+            }
+            Message::HeadlineInputChanged(value) => {
+                self.post_options.headline = value;
+                Task::none()
+            }
+            Message::PickAnnouncementBanner => Task::perform(
+                async {
+                    let file = AsyncFileDialog::new()
+                        .set_directory(home_dir().unwrap_or("/".into()))
+                        .add_filter("Images", &["png", "jpg", "jpeg", "gif", "webp", "bmp"])
+                        .pick_file()
+                        .await?;
+
+                    utils::load_pending_image(file.path())
+                },
+                Message::GotAnnouncementBanner,
+            ),
+            Message::GotAnnouncementBanner(banner) => {
+                if banner.is_some() {
+                    self.post_options.banner = banner;
+                }
+                Task::none()
+            }
+            Message::RemoveAnnouncementBanner => {
+                self.post_options.banner = None;
+                Task::none()
+            }
+            Message::SetReplyPolicy(reply_policy) => {
+                self.post_options.reply_policy = reply_policy;
+                Task::none()
+            }
+            Message::ToggleCrossPostChannel(channel_id) => {
+                let cross_post_channels = &mut self.post_options.cross_post_channels;
+
+                if let Some(pos) = cross_post_channels.iter().position(|id| *id == channel_id) {
+                    cross_post_channels.remove(pos);
+                } else {
+                    cross_post_channels.push(channel_id);
+                }
+                Task::none()
+            }
+            Message::ToggleFavouriteChannel(channel_id) => {
+                let settings = self.channel_settings.entry(channel_id).or_default();
+                settings.favourite = !settings.favourite;
+                save_to_cache("channel_settings.json", &self.channel_settings);
+                Task::none()
+            }
+            Message::ToggleHiddenChannel(channel_id) => {
+                let settings = self.channel_settings.entry(channel_id).or_default();
+                settings.hidden = !settings.hidden;
+                save_to_cache("channel_settings.json", &self.channel_settings);
+                Task::none()
+            }
+            Message::ToggleShowHiddenChannels => {
+                self.show_hidden_channels = !self.show_hidden_channels;
+                Task::none()
+            }
+            Message::ToggleManageTeam => {
+                self.just_opened_overlay = true;
+                self.show_manage_team = !self.show_manage_team;
+                self.renaming_channel = None;
+                self.confirming_team_action = None;
+                self.channel_name_input_value = "".to_string();
+
+                let Page::Team(Some(team_id), _, _) = &self.page else {
+                    return Task::none();
+                };
+                let team_id = team_id.clone();
+                let Some(group_id) = self.team_group_id(&team_id) else {
+                    return Task::none();
+                };
+
+                authed_task(
+                    self.access_tokens.clone(),
+                    "https://graph.microsoft.com/.default",
+                    &self.tenant,
+                    move |token| async move {
+                        let members = team_members(&token, &group_id)
+                            .await
+                            .map(|collection| collection.value)
+                            .unwrap_or_default();
+                        (team_id, members)
+                    },
+                    |(team_id, members)| Message::GotTeamMembers(team_id, members),
+                )
+            }
+            Message::GotTeamMembers(team_id, members) => {
+                self.team_members.insert(team_id, members);
+                Task::none()
+            }
+            Message::ChannelNameInputChanged(value) => {
+                self.channel_name_input_value = value;
+                Task::none()
+            }
+            Message::RenameChannel(channel_id) => {
+                self.channel_name_input_value = channel_id
+                    .as_ref()
+                    .and_then(|channel_id| {
+                        self.teams
+                            .iter()
+                            .flat_map(|team| &team.channels)
+                            .find(|channel| &channel.id == channel_id)
+                    })
+                    .map(|channel| channel.display_name.clone())
+                    .unwrap_or("".to_string());
+                self.renaming_channel = channel_id;
+                Task::none()
+            }
+            Message::SubmitChannelName => {
+                let display_name = self.channel_name_input_value.trim().to_string();
+                if display_name.is_empty() {
+                    return Task::none();
+                }
+
+                let Page::Team(Some(team_id), _, _) = &self.page else {
+                    return Task::none();
+                };
+                let team_id = team_id.clone();
+                let Some(group_id) = self.team_group_id(&team_id) else {
+                    return Task::none();
+                };
+
+                self.channel_name_input_value = "".to_string();
+
+                if let Some(channel_id) = self.renaming_channel.take() {
+                    // Renamed right away, the thread update confirms it
+                    let Some(channel) = self
+                        .teams
+                        .iter_mut()
+                        .flat_map(|team| team.channels.iter_mut())
+                        .find(|channel| channel.id == channel_id)
+                    else {
+                        return Task::none();
+                    };
+                    let rollback =
+                        TeamRollback::ChannelName(channel_id.clone(), channel.display_name.clone());
+                    channel.display_name = display_name.clone();
+                    save_to_cache("teams.json", &self.teams);
+
+                    let body = json!({ "displayName": display_name }).to_string();
+
+                    authed_task(
+                        self.access_tokens.clone(),
+                        "https://graph.microsoft.com/.default",
+                        &self.tenant,
+                        move |token| async move {
+                            update_channel(&token, &group_id, &channel_id, body)
+                                .await
+                                .map_err(|error| error.to_string())
+                        },
+                        move |result| match result {
+                            Ok(_) => Message::DoNothing(()),
+                            Err(error) => Message::TeamChangeFailed(
+                                rollback,
+                                format!("Couldn't rename the channel: {}", error),
+                            ),
+                        },
+                    )
+                } else {
+                    let body = json!({
+                        "displayName": display_name,
+                        "membershipType": "standard",
+                    })
+                    .to_string();
+
+                    authed_task(
+                        self.access_tokens.clone(),
+                        "https://graph.microsoft.com/.default",
+                        &self.tenant,
+                        move |token| async move {
+                            create_channel(&token, &group_id, body)
+                                .await
+                                .map(|channel| (team_id, channel))
+                                .ok()
+                        },
+                        |result| match result {
+                            Some((team_id, channel)) => Message::CreatedChannel(team_id, channel),
+                            None => Message::DoNothing(()),
+                        },
+                    )
+                }
+            }
+            Message::CreatedChannel(team_id, channel) => {
+                if let Some(team) = self.teams.iter_mut().find(|team| team.id == team_id)
+                    && !team.channels.iter().any(|existing| existing.id == channel.id)
+                {
+                    team.channels.push(channel);
+                }
+                save_to_cache("teams.json", &self.teams);
+                Task::none()
+            }
+            Message::DeleteChannel(team_id, channel_id) => {
+                let Some(group_id) = self.team_group_id(&team_id) else {
+                    return Task::none();
+                };
+
+                self.confirming_team_action = None;
 
-                *message_area_height = if new_height > max_area_height {
-                    max_area_height
-                } else {
-                    new_height
+                let Some(team) = self.teams.iter_mut().find(|team| team.id == team_id) else {
+                    return Task::none();
                 };
+                let Some(position) =
+                    team.channels.iter().position(|channel| channel.id == channel_id)
+                else {
+                    return Task::none();
+                };
+                let channel = team.channels.remove(position);
+                let rollback = TeamRollback::Channel(team_id.clone(), position, channel);
+                save_to_cache("teams.json", &self.teams);
 
-                if let Page::Chat(current_chat_id, _) = &self.page {
-                    if self.should_send_typing && !conversation_id.starts_with("draft:") {
-                        self.should_send_typing = false;
-
-                        let acess_tokens_arc = self.access_tokens.clone();
-                        let tenant = self.tenant.clone();
-                        let conversation_id = current_chat_id.clone().unwrap();
+                let mut tasks = vec![];
 
-                        return Task::batch(vec![
-                            authed_task(
-                                acess_tokens_arc,
-                                "https://ic3.teams.office.com/.default",
-                                &tenant,
-                                move |token| async move {
-                                    let body = "{\"content\":\"\",\"contenttype\":\"Application/Message\",\"messagetype\":\"Control/Typing\"}";
-                                    send_message(&token, &conversation_id, body.to_string())
-                                        .await
-                                        .unwrap();
-                                },
-                                Message::DoNothing,
-                            ),
-                            Task::perform(
-                                async {
-                                    sleep(Duration::from_secs(4)).await;
-                                },
-                                Message::AllowPostIsTyping,
-                            ),
-                        ]);
-                    }
+                // Fall back to the general channel
+                if let Page::Team(_, Some(current_channel_id), _) = &self.page
+                    && current_channel_id == &channel_id
+                {
+                    tasks.push(
+                        self.handle_message(Message::OpenTeam(team_id.clone(), team_id.clone())),
+                    );
                 }
-                Task::none()
+
+                tasks.push(authed_task(
+                    self.access_tokens.clone(),
+                    "https://graph.microsoft.com/.default",
+                    &self.tenant,
+                    move |token| async move {
+                        delete_channel(&token, &group_id, &channel_id)
+                            .await
+                            .map_err(|error| error.to_string())
+                    },
+                    move |result| match result {
+                        Ok(_) => Message::DoNothing(()),
+                        Err(error) => Message::TeamChangeFailed(
+                            rollback,
+                            format!("Couldn't delete the channel: {}", error),
+                        ),
+                    },
+                ));
+
+                Task::batch(tasks)
             }
-            Message::MessageAreaAction(action) => {
-                let (content, message_area_height) = match self.page {
-                    Page::Team(_, _) => (
-                        &mut self.team_message_area_content,
-                        &mut self.team_message_area_height,
-                    ),
-                    Page::Chat(_, _) => (
-                        &mut self.chat_message_area_content,
-                        &mut self.chat_message_area_height,
-                    ),
-                    _ => return Task::none(), // Should never happen
+            Message::SetTeamMemberRole(team_id, membership_id, is_owner) => {
+                let Some(group_id) = self.team_group_id(&team_id) else {
+                    return Task::none();
                 };
 
-                let selection = content.selection();
-
-                match action {
-                    MessageAreaAction::Bold => {
-                        content_send(content, "**");
+                let roles: Vec<String> = if is_owner {
+                    vec!["owner".to_string()]
+                } else {
+                    vec![]
+                };
 
-                        if let Some(selection) = selection {
-                            for char in selection.chars() {
-                                content.perform(Action::Edit(Edit::Insert(char)));
-                            }
-                        }
+                let Some(member) = self.team_members.get_mut(&team_id).and_then(|members| {
+                    members
+                        .iter_mut()
+                        .find(|member| member.id == membership_id)
+                }) else {
+                    return Task::none();
+                };
+                let rollback = TeamRollback::MemberRoles(
+                    team_id.clone(),
+                    membership_id.clone(),
+                    std::mem::replace(&mut member.roles, roles.clone()),
+                );
 
-                        content_send(content, "**");
-                    }
-                    MessageAreaAction::Italic => {
-                        content_send(content, "*");
+                let body = json!({
+                    "@odata.type": "#microsoft.graph.aadUserConversationMember",
+                    "roles": roles,
+                })
+                .to_string();
 
-                        if let Some(selection) = selection {
-                            for char in selection.chars() {
-                                content.perform(Action::Edit(Edit::Insert(char)));
-                            }
-                        }
+                authed_task(
+                    self.access_tokens.clone(),
+                    "https://graph.microsoft.com/.default",
+                    &self.tenant,
+                    move |token| async move {
+                        update_team_member(&token, &group_id, &membership_id, body)
+                            .await
+                            .map_err(|error| error.to_string())
+                    },
+                    move |result| match result {
+                        Ok(_) => Message::DoNothing(()),
+                        Err(error) => Message::TeamChangeFailed(
+                            rollback,
+                            format!("Couldn't change the member's role: {}", error),
+                        ),
+                    },
+                )
+            }
+            Message::RemoveTeamMember(team_id, membership_id) => {
+                let Some(group_id) = self.team_group_id(&team_id) else {
+                    return Task::none();
+                };
 
-                        content_send(content, "*");
-                    }
-                    MessageAreaAction::Underline => {
-                        content_send(content, "<u>");
+                self.confirming_team_action = None;
 
-                        if let Some(selection) = selection {
-                            for char in selection.chars() {
-                                content.perform(Action::Edit(Edit::Insert(char)));
-                            }
-                        }
+                let Some(members) = self.team_members.get_mut(&team_id) else {
+                    return Task::none();
+                };
+                let Some(position) = members.iter().position(|member| member.id == membership_id)
+                else {
+                    return Task::none();
+                };
+                let rollback =
+                    TeamRollback::Member(team_id.clone(), position, members.remove(position));
 
-                        content_send(content, "</u>");
-                    }
-                    MessageAreaAction::Striketrough => {
-                        content_send(content, "~~");
+                authed_task(
+                    self.access_tokens.clone(),
+                    "https://graph.microsoft.com/.default",
+                    &self.tenant,
+                    move |token| async move {
+                        remove_team_member(&token, &group_id, &membership_id)
+                            .await
+                            .map_err(|error| error.to_string())
+                    },
+                    move |result| match result {
+                        Ok(_) => Message::DoNothing(()),
+                        Err(error) => Message::TeamChangeFailed(
+                            rollback,
+                            format!("Couldn't remove the member: {}", error),
+                        ),
+                    },
+                )
+            }
+            Message::LeaveTeam(team_id) => {
+                let Some(group_id) = self.team_group_id(&team_id) else {
+                    return Task::none();
+                };
+                let me_id = self.me.id.clone();
+                self.confirming_team_action = None;
 
-                        if let Some(selection) = selection {
-                            for char in selection.chars() {
-                                content.perform(Action::Edit(Edit::Insert(char)));
-                            }
-                        }
+                let Some(position) = self.teams.iter().position(|team| team.id == team_id) else {
+                    return Task::none();
+                };
+                let rollback = TeamRollback::Team(
+                    position,
+                    self.teams.remove(position),
+                    self.team_members.remove(&team_id),
+                );
+                save_to_cache("teams.json", &self.teams);
 
-                        content_send(content, "~~");
-                    }
-                    MessageAreaAction::Blockquote => {
-                        if content.text() != "" {
-                            content_send(content, "\n\n");
-                        }
+                self.show_manage_team = false;
+                self.is_in_centered_overlay = false;
 
-                        content_send(content, "> ");
-                    }
-                    MessageAreaAction::Link => {
-                        content_send(content, "[");
+                Task::batch(vec![
+                    self.handle_message(Message::OpenCurrentChat),
+                    authed_task(
+                        self.access_tokens.clone(),
+                        "https://graph.microsoft.com/.default",
+                        &self.tenant,
+                        move |token| async move {
+                            let membership_id = team_members(&token, &group_id)
+                                .await
+                                .map_err(|error| error.to_string())?
+                                .value
+                                .into_iter()
+                                .find(|member| member.user_id.as_ref() == Some(&me_id))
+                                .map(|member| member.id)
+                                .ok_or("membership not found")?;
+
+                            remove_team_member(&token, &group_id, &membership_id)
+                                .await
+                                .map_err(|error| error.to_string())
+                        },
+                        move |result| match result {
+                            Ok(_) => Message::DoNothing(()),
+                            Err(error) => Message::TeamChangeFailed(
+                                rollback,
+                                format!("Couldn't leave the team: {}", error),
+                            ),
+                        },
+                    ),
+                ])
+            }
+            Message::ConfirmTeamAction(id) => {
+                self.confirming_team_action = id;
+                Task::none()
+            }
+            Message::EditMessageFailed(conversation_id, message_id, previous_content, error) => {
+                if let Some(message) = self
+                    .chat_conversations
+                    .get_mut(&conversation_id)
+                    .and_then(|conversation| {
+                        conversation
+                            .iter_mut()
+                            .find(|message| message.id.as_ref() == Some(&message_id))
+                    })
+                {
+                    message.content = previous_content;
+                }
 
-                        if let Some(selection) = selection {
-                            for char in selection.chars() {
-                                content.perform(Action::Edit(Edit::Insert(char)));
-                            }
+                self.error_notice = Some(error);
+                Task::none()
+            }
+            Message::TeamChangeFailed(rollback, error) => {
+                match rollback {
+                    TeamRollback::ChannelName(channel_id, display_name) => {
+                        if let Some(channel) = self
+                            .teams
+                            .iter_mut()
+                            .flat_map(|team| team.channels.iter_mut())
+                            .find(|channel| channel.id == channel_id)
+                        {
+                            channel.display_name = display_name;
                         }
-                        content_send(content, "](url)");
                     }
-                    MessageAreaAction::Image => {
-                        content_send(content, "![");
-
-                        if let Some(selection) = selection {
-                            for char in selection.chars() {
-                                content.perform(Action::Edit(Edit::Insert(char)));
-                            }
+                    TeamRollback::Channel(team_id, position, channel) => {
+                        if let Some(team) = self.teams.iter_mut().find(|team| team.id == team_id) {
+                            team.channels.insert(position.min(team.channels.len()), channel);
                         }
-                        content_send(content, "](url)");
                     }
-                    MessageAreaAction::Code => {
-                        content_send(content, "`");
-
-                        if let Some(selection) = selection {
-                            for char in selection.chars() {
-                                content.perform(Action::Edit(Edit::Insert(char)));
-                            }
+                    TeamRollback::Member(team_id, position, member) => {
+                        if let Some(members) = self.team_members.get_mut(&team_id) {
+                            members.insert(position.min(members.len()), member);
                         }
-                        content_send(content, "`");
                     }
-                    MessageAreaAction::List => {
-                        if let Some(selection) = selection {
-                            let mut lines = selection.lines().peekable();
-
-                            while let Some(line) = lines.next() {
-                                content_send(content, &format!("- {}", line));
-                                if lines.peek().is_some() {
-                                    content_send(content, "\n");
-                                }
-                            }
-                        } else {
-                            content_send(content, "- ");
+                    TeamRollback::MemberRoles(team_id, membership_id, roles) => {
+                        if let Some(member) = self
+                            .team_members
+                            .get_mut(&team_id)
+                            .and_then(|members| {
+                                members.iter_mut().find(|member| member.id == membership_id)
+                            })
+                        {
+                            member.roles = roles;
                         }
                     }
-                    MessageAreaAction::OrderedList => {
-                        if let Some(selection) = selection {
-                            let mut lines = selection.lines().enumerate().peekable();
-
-                            while let Some((index, line)) = lines.next() {
-                                content_send(content, &format!("{}. {}", index + 1, line));
-
-                                if lines.peek().is_some() {
-                                    content_send(content, "\n");
-                                }
-                            }
-                        } else {
-                            content_send(content, "1.  ");
+                    TeamRollback::Team(position, team, members) => {
+                        if let Some(members) = members {
+                            self.team_members.insert(team.id.clone(), members);
                         }
+                        self.teams.insert(position.min(self.teams.len()), team);
                     }
                 }
+                save_to_cache("teams.json", &self.teams);
 
-                // Handle sizing
-
-                let max_area_height = 0.5 * self.window_height;
-
-                let line_count = content.line_count();
-                let new_height = 33.0 + line_count as f32 * 21.0;
-
-                *message_area_height = if new_height > max_area_height {
-                    max_area_height
-                } else {
-                    new_height
-                };
-
-                Task::none()
-            }
-            Message::AllowPostIsTyping(()) => {
-                self.should_send_typing = true;
-                Task::none()
-            }
-            Message::LinkClicked(url) => {
-                if !webbrowser::open(url.as_str()).is_ok() {
-                    eprintln!("Failed to open link : {}", url);
-                }
-
+                self.error_notice = Some(error);
                 Task::none()
             }
-            Message::OpenTeam(team_id, channel_id) => {
-                self.page = Page::Team(Some(team_id), Some(channel_id));
-
-                snap_to(Id::new("conversation_column"), RelativeOffset::END)
-            }
             Message::OpenChat(thread_id) => {
                 let access_tokens_arc = self.access_tokens.clone();
                 let tenant = self.tenant.clone();
+                let send_read_receipts = self.settings.send_read_receipts;
 
                 self.page = Page::Chat(Some(thread_id.clone()), ChatBody::Messages);
                 self.last_opened_chat = Some(thread_id.clone());
@@ -1607,9 +4083,11 @@ impl Counter {
                                     "{{\"consumptionhorizon\":\"{};{};{}\"}}",
                                     time, time, time
                                 );
-                                consumption_horizon(&token, &thread_id, body)
-                                    .await
-                                    .unwrap();
+                                if send_read_receipts {
+                                    consumption_horizon(&token, &thread_id, body)
+                                        .await
+                                        .unwrap();
+                                }
                                 thread_id
                             },
                             Message::ReadChat,
@@ -1622,6 +4100,7 @@ impl Counter {
             Message::OpenCurrentChat => {
                 let access_tokens_arc = self.access_tokens.clone();
                 let tenant = self.tenant.clone();
+                let send_read_receipts = self.settings.send_read_receipts;
 
                 self.page = Page::Chat(self.last_opened_chat.clone(), ChatBody::Messages);
 
@@ -1641,9 +4120,11 @@ impl Counter {
                                         "{{\"consumptionhorizon\":\"{};{};{}\"}}",
                                         time, time, time
                                     );
-                                    consumption_horizon(&token, &chat_id, body)
-                                        .await
-                                        .unwrap();
+                                    if send_read_receipts {
+                                        consumption_horizon(&token, &chat_id, body)
+                                            .await
+                                            .unwrap();
+                                    }
                                     chat_id
                                 },
                                 Message::ReadChat,
@@ -1720,39 +4201,24 @@ impl Counter {
                     self.more_menu_message_id = None;
                 }
 
-                let area_content = &mut self.chat_message_area_content;
-
-                if area_content.text() != "" {
-                    content_send(area_content, "\n\n");
-                }
-
-                content_send(
-                    area_content,
-                    &format!(
-                        ">[{}][{}] {}\n",
-                        display_name.unwrap_or("Unknown User".to_string()),
-                        message_id.unwrap_or("0".to_string()),
-                        if let Some(message_content) = message_content {
-                            get_html_preview(&message_content)
-                        } else {
-                            "".to_string()
-                        },
-                    ),
-                );
-
-                let max_area_height = 0.5 * self.window_height;
-
-                let line_count = area_content.line_count();
-                let new_height = 33.0 + line_count as f32 * 21.0;
-
-                self.chat_message_area_height = if new_height > max_area_height {
-                    max_area_height
-                } else {
-                    new_height
-                };
+                // Shown above the message area and saved with the draft until it is sent
+                self.reply_target = Some(ReplyTarget {
+                    message_id: message_id.unwrap_or("0".to_string()),
+                    name: display_name.unwrap_or("Unknown User".to_string()),
+                    preview: message_content
+                        .map(|message_content| get_html_preview(&message_content))
+                        .unwrap_or_default(),
+                });
 
                 focus(Id::new("message_area"))
             }
+            Message::CancelReply => {
+                self.reply_target = None;
+                if self.message_area_preview.is_some() {
+                    self.message_area_preview = Some(self.draft_html());
+                }
+                Task::none()
+            }
             Message::CopyText(text) => {
                 if self.show_more_options && self.is_in_more_options {
                     self.show_more_options = false;
@@ -1836,6 +4302,106 @@ impl Counter {
                 Id::new("emoji_column"),
                 RelativeOffset { x: 0.0, y: height },
             ),
+            Message::SetSkinTone(skin_tone) => {
+                self.settings.skin_tone = skin_tone;
+                save_to_config("settings.json", &self.settings);
+                Task::none()
+            }
+            Message::OpenSettings(section) => {
+                self.page = Page::Settings(section);
+                self.image_cache_size = image_cache_size();
+                self.spelling_languages = available_languages();
+                Task::none()
+            }
+            Message::UpdateSettings(settings) => {
+                if settings.theme != self.settings.theme {
+                    self.theme = global_theme(&settings.theme, self.system_prefers_dark);
+                    self.theme_modified = match &settings.theme {
+                        ThemePreference::File(file_name) => theme_file_modified(file_name),
+                        _ => None,
+                    };
+                }
+                if settings.image_cache_limit_mb != self.settings.image_cache_limit_mb {
+                    set_image_cache_limit(settings.image_cache_limit_mb);
+                    self.image_cache_size = image_cache_size();
+                }
+                let mut task = Task::none();
+                if settings.spell_check_language != self.settings.spell_check_language {
+                    self.spell_checker = None;
+                    self.misspelled = None;
+                    self.spelling_menu = None;
+                    task = load_spell_checker_task(settings.spell_check_language.clone());
+                }
+                self.settings = settings;
+                save_to_config("settings.json", &self.settings);
+                task
+            }
+            Message::LoadedSpellChecker(language, spell_checker) => {
+                // The language may have changed again while the dictionary was loading
+                if language == self.settings.spell_check_language {
+                    self.spell_checker = spell_checker.and_then(Arc::into_inner);
+                    self.misspelled = None;
+                    self.check_spelling();
+                }
+                Task::none()
+            }
+            Message::ClearImageCache => {
+                clear_image_cache();
+                self.image_cache_size = image_cache_size();
+                Task::none()
+            }
+            Message::ClearPersonalDictionary => {
+                if let Some(spell_checker) = &mut self.spell_checker {
+                    spell_checker.clear_personal_dictionary();
+                }
+                self.misspelled = None;
+                self.check_spelling();
+                Task::none()
+            }
+            Message::SignOut => {
+                for filename in ["access_tokens.json", "me.json", "teams.json", "chats.json"] {
+                    delete_cache(filename);
+                }
+                self.access_tokens.write().unwrap().clear();
+                self.is_authorized = false;
+                self.page = Page::Login;
+                self.device_user_code = None;
+                self.device_code = String::new();
+                let tenant = self.tenant.clone();
+                Task::perform(
+                    async move { api::gen_device_code(&tenant).await.unwrap() },
+                    Message::GotDeviceCodeInfo,
+                )
+            }
+            Message::CheckTheme => {
+                if let ThemePreference::File(file_name) = &self.settings.theme {
+                    let modified = theme_file_modified(file_name);
+                    if modified != self.theme_modified {
+                        self.theme_modified = modified;
+                        self.theme = global_theme(&self.settings.theme, self.system_prefers_dark);
+                    }
+                }
+                Task::none()
+            }
+            Message::GotSystemColorScheme(prefers_dark) => {
+                if let Some(prefers_dark) = prefers_dark
+                    && prefers_dark != self.system_prefers_dark
+                {
+                    self.system_prefers_dark = prefers_dark;
+                    if self.settings.theme == ThemePreference::System {
+                        self.theme = global_theme(&self.settings.theme, prefers_dark);
+                    }
+                }
+                Task::none()
+            }
+            Message::GotCustomEmojis(metadata) => {
+                if let Some(metadata) = metadata {
+                    self.emoji_map.retain(|_, emoji| emoji.image_url.is_none());
+                    insert_custom_emojis(&mut self.emoji_map, &metadata);
+                    save_to_cache("custom_emojis.json", &metadata);
+                }
+                Task::none()
+            }
             Message::CopySelected(selection) => {
                 let line_height_min = 18.0;
                 let mut last_y = 0.0;
@@ -1866,6 +4432,137 @@ impl Counter {
                 self.subject_input_value = None;
                 Task::none()
             }
+            Message::RichLinkInputChanged(url) => {
+                if let Some((_, link_input)) = &mut self.rich_link_input {
+                    *link_input = url;
+                }
+                Task::none()
+            }
+            Message::SubmitRichLink => {
+                let Some((selection, url)) = self.rich_link_input.take() else {
+                    return Task::none();
+                };
+
+                self.record_message_area_step();
+
+                let url = url.trim().to_string();
+                let (content, rich_document) = match self.page {
+                    Page::Team(_, _, _) => (
+                        &mut self.team_message_area_content,
+                        &mut self.team_rich_document,
+                    ),
+                    Page::Chat(_, _) => (
+                        &mut self.chat_message_area_content,
+                        &mut self.chat_rich_document,
+                    ),
+                    _ => return Task::none(),
+                };
+
+                if !url.is_empty() {
+                    rich_document.sync(&content.text());
+
+                    // Without a selection the url itself becomes the text of the link
+                    let selection = if selection.is_empty() {
+                        content_send(content, &url);
+                        rich_document.sync(&content.text());
+                        selection.start..selection.start + url.chars().count()
+                    } else {
+                        selection
+                    };
+
+                    rich_document.set_link(selection, &url);
+                }
+
+                focus(Id::new("message_area"))
+            }
+            Message::OpenSpellingMenu(position) => {
+                let Some(spell_checker) = &self.spell_checker else {
+                    return Task::none();
+                };
+
+                let content = match self.page {
+                    Page::Team(_, _, _) => &mut self.team_message_area_content,
+                    Page::Chat(_, _) => &mut self.chat_message_area_content,
+                    _ => return Task::none(),
+                };
+
+                // Right clicks don't move the cursor, the position is offset by the editor padding
+                content.perform(Action::Click(Point::new(position.x - 5.0, position.y - 5.0)));
+
+                let cursor = content.cursor().position;
+                self.spelling_menu =
+                    spell_checker.menu_at(&content.text(), cursor.line, cursor.column);
+                self.emoji_autocomplete = None;
+
+                Task::none()
+            }
+            Message::ReplaceMisspelledWord(replacement) => {
+                let Some(menu) = self.spelling_menu.take() else {
+                    return Task::none();
+                };
+
+                self.record_message_area_step();
+
+                let content = match self.page {
+                    Page::Team(_, _, _) => &mut self.team_message_area_content,
+                    Page::Chat(_, _) => &mut self.chat_message_area_content,
+                    _ => return Task::none(),
+                };
+
+                // The word may have been edited since the menu was opened
+                let text = content.text();
+                let word = text.split('\n').nth(menu.line).map(|line| {
+                    line.chars()
+                        .skip(menu.columns.start)
+                        .take(menu.columns.len())
+                        .collect::<String>()
+                });
+
+                if word.as_ref() == Some(&menu.word) {
+                    content.move_to(Cursor {
+                        position: Position {
+                            line: menu.line,
+                            column: menu.columns.end,
+                        },
+                        selection: Some(Position {
+                            line: menu.line,
+                            column: menu.columns.start,
+                        }),
+                    });
+                    content_send(content, &replacement);
+                }
+
+                self.sync_rich_document();
+                self.check_spelling();
+
+                focus(Id::new("message_area"))
+            }
+            Message::AddToDictionary => {
+                if let Some(menu) = self.spelling_menu.take()
+                    && let Some(spell_checker) = &mut self.spell_checker
+                {
+                    spell_checker.add_word(&menu.word);
+                    self.misspelled = None;
+                    self.check_spelling();
+                }
+
+                focus(Id::new("message_area"))
+            }
+            Message::ToggleMessageAreaPreview => {
+                // The text can't be edited while previewing, so it is only rendered here
+                self.message_area_preview = match self.message_area_preview {
+                    Some(_) => None,
+                    None => Some(self.draft_html()),
+                };
+
+                if self.message_area_preview.is_some() {
+                    self.show_message_area_emoji_picker = false;
+                    self.emoji_autocomplete = None;
+                    Task::none()
+                } else {
+                    focus(Id::new("message_area"))
+                }
+            }
             Message::ToggleShowMoreOptions(message_id) => {
                 // We need to hide the other overlays.
                 if self.show_profile {
@@ -2035,6 +4732,17 @@ impl Counter {
                         );
                     }
                 }
+
+                // The open team or channel may have been removed elsewhere
+                if let Page::Team(Some(team_id), Some(channel_id), _) = self.page.clone() {
+                    let Some(team) = self.teams.iter().find(|team| team.id == team_id) else {
+                        return self.handle_message(Message::OpenCurrentChat);
+                    };
+
+                    if !team.channels.iter().any(|channel| channel.id == channel_id) {
+                        return self.handle_message(Message::OpenTeam(team_id.clone(), team_id));
+                    }
+                }
                 Task::none()
             }
             // UI initiated
@@ -2124,27 +4832,27 @@ impl Counter {
                 Task::none()
             }
             Message::PrefetchCurrentChat => {
-                if let Page::Team(chat_id, _) = &self.page {
-                    if let Some(chat_id) = chat_id {
-                        let chat_id_clone = chat_id.clone();
-                        let chat_id_clone2 = chat_id.clone();
+                if let Page::Team(chat_id, _, _) = &self.page
+                    && let Some(chat_id) = chat_id
+                {
+                    let chat_id_clone = chat_id.clone();
+                    let chat_id_clone2 = chat_id.clone();
 
-                        let access_tokens_arc = self.access_tokens.clone();
-                        let tenant = self.tenant.clone();
+                    let access_tokens_arc = self.access_tokens.clone();
+                    let tenant = self.tenant.clone();
 
-                        if !chat_id.starts_with("draft:") {
-                            return authed_task(
-                                access_tokens_arc,
-                                "https://ic3.teams.office.com/.default",
-                                &tenant,
-                                move |token| async move {
-                                    conversations(&token, &chat_id_clone, &None)
-                                        .await
-                                        .unwrap()
-                                },
-                                move |result| Message::GotChatConversations(chat_id_clone2, result), // This calls a message
-                            );
-                        }
+                    if !chat_id.starts_with("draft:") {
+                        return authed_task(
+                            access_tokens_arc,
+                            "https://ic3.teams.office.com/.default",
+                            &tenant,
+                            move |token| async move {
+                                conversations(&token, &chat_id_clone, &None)
+                                    .await
+                                    .unwrap()
+                            },
+                            move |result| Message::GotChatConversations(chat_id_clone2, result), // This calls a message
+                        );
                     }
                 }
                 Task::none()
@@ -2177,13 +4885,29 @@ impl Counter {
                 Task::none()
             }
             Message::GotChatConversations(thread_id, conversations) => {
-                self.chat_conversations
-                    .insert(thread_id, conversations.messages);
+                let mut messages = conversations.messages;
+                insert_unsent_messages(
+                    &self.outbox,
+                    &thread_id,
+                    &mut messages,
+                    &self.me.id,
+                    self.me.display_name.clone(),
+                );
+
+                self.chat_conversations.insert(thread_id, messages);
                 Task::none()
             }
             Message::GotChatConversationsFirst(thread_id, conversations) => {
-                self.chat_conversations
-                    .insert(thread_id, conversations.messages);
+                let mut messages = conversations.messages;
+                insert_unsent_messages(
+                    &self.outbox,
+                    &thread_id,
+                    &mut messages,
+                    &self.me.id,
+                    self.me.display_name.clone(),
+                );
+
+                self.chat_conversations.insert(thread_id, messages);
                 snap_to(Id::new("conversation_column"), RelativeOffset::END)
             }
             Message::OnScroll(viewport) => {
@@ -2195,19 +4919,13 @@ impl Counter {
                 Task::none()
             }
             Message::PostMessage => {
-                let (message_area_content, message_area_height) = match self.page {
-                    Page::Team(_, _) => (
-                        &mut self.team_message_area_content,
-                        &mut self.team_message_area_height,
-                    ),
-                    Page::Chat(_, _) => (
-                        &mut self.chat_message_area_content,
-                        &mut self.chat_message_area_height,
-                    ),
+                let message_area_content = match self.page {
+                    Page::Team(_, _, _) => &self.team_message_area_content,
+                    Page::Chat(_, _) => &self.chat_message_area_content,
                     _ => return Task::none(), // Should never happen
                 };
 
-                let subject_text = if let Page::Team(_, _) = self.page {
+                let subject_text = if let Page::Team(_, _, _) = self.page {
                     self.subject_input_value.clone()
                 } else {
                     None
@@ -2215,41 +4933,35 @@ impl Counter {
 
                 let message_area_text = message_area_content.text();
 
-                match self.page {
-                    Page::Team(_, _) => self.team_message_area_content = Content::new(),
-                    Page::Chat(_, _) => self.chat_message_area_content = Content::new(),
-                    _ => {}
-                }
-
-                *message_area_height = 54.0;
+                let html = self.draft_html();
+                self.clear_message_area();
 
-                self.subject_input_value = None;
+                let links = self.take_link_preview(&message_area_text);
 
                 let conversation_id = match &self.page {
-                    Page::Team(_, current_channel_id) => current_channel_id.clone().unwrap(),
+                    Page::Team(_, current_channel_id, _) => current_channel_id.clone().unwrap(),
                     Page::Chat(current_chat_id, _) => current_chat_id.clone().unwrap(),
                     _ => "".to_string(),
                 };
 
                 let acess_tokens_arc = self.access_tokens.clone();
                 let tenant = self.tenant.clone();
-                let me_id = self.me.id.clone();
-                let me_display_name = self.me.display_name.clone();
 
                 if !conversation_id.starts_with("draft:") {
-                    return post_message_task(
-                        message_area_text,
-                        acess_tokens_arc,
-                        tenant,
-                        conversation_id,
-                        me_id,
-                        me_display_name,
-                        subject_text,
-                        &mut self.present_messages,
-                        &mut self.chat_conversations,
-                        &mut self.team_conversations,
-                        &mut self.chats,
-                    );
+                    let post_options = take_post_options(&self.page, &mut self.post_options);
+
+                    let mut images = std::mem::take(&mut self.pending_images);
+                    if let Some(banner) = post_options.banner.clone() {
+                        images.insert(0, banner);
+                    }
+
+                    self.post_message_task(OutboxMessage {
+                        subject: subject_text,
+                        pending_images: images,
+                        post_options,
+                        links,
+                        ..new_outbox_message(conversation_id, html)
+                    })
                 } else {
                     let current_chat = self
                         .chats
@@ -2283,16 +4995,20 @@ impl Counter {
                         "https://ic3.teams.office.com/.default",
                         &tenant,
                         move |token| async move {
-                            let thread_link = start_thread(&token, body).await.unwrap();
-
-                            (conversation_id, thread_link)
+                            start_thread(&token, body)
+                                .await
+                                .map(|thread_link| (conversation_id, thread_link))
+                                .map_err(|error| error.to_string())
                         },
-                        move |(conversation_id, thread_link)| {
-                            Message::CreatedGroupChat(
+                        move |result| match result {
+                            Ok((conversation_id, thread_link)) => Message::CreatedGroupChat(
                                 conversation_id,
                                 thread_link,
-                                message_area_text.clone(),
-                            )
+                                html.clone(),
+                            ),
+                            Err(error) => {
+                                Message::ShowError(format!("Couldn't start the chat: {}", error))
+                            }
                         },
                     );
                 }
@@ -2402,10 +5118,18 @@ impl Counter {
 
                 Task::none()
             }
-            Message::CreatedGroupChat(draft_id, thread_link, message) => {
+            Message::CreatedGroupChat(draft_id, thread_link, html) => {
                 let chat_id =
                     thread_link.replace("https://emea.ng.msg.teams.microsoft.com/v1/threads/", "");
 
+                // Images can only be uploaded once the thread exists
+                let pending_images =
+                    if self.page == Page::Chat(Some(draft_id.clone()), ChatBody::Messages) {
+                        std::mem::take(&mut self.pending_images)
+                    } else {
+                        vec![]
+                    };
+
                 self.chats
                     .iter_mut()
                     .find(|chat| chat.id == draft_id)
@@ -2422,19 +5146,10 @@ impl Counter {
 
                 self.page = Page::Chat(Some(chat_id.clone()), ChatBody::Messages);
 
-                post_message_task(
-                    message,
-                    self.access_tokens.clone(),
-                    self.tenant.clone(),
-                    chat_id,
-                    self.me.id.clone(),
-                    self.me.display_name.clone(),
-                    None,
-                    &mut self.present_messages,
-                    &mut self.chat_conversations,
-                    &mut self.team_conversations,
-                    &mut self.chats,
-                )
+                self.post_message_task(OutboxMessage {
+                    pending_images,
+                    ..new_outbox_message(chat_id, html)
+                })
                 .chain(snap_to(Id::new("conversation_column"), RelativeOffset::END))
             }
             Message::AddToGroupChat(chat_id, user_ids) => {
@@ -2516,6 +5231,11 @@ impl Counter {
                 Task::none()
             }
             Message::FetchTeamImage(identifier, picture_e_tag, group_id, display_name) => {
+                // The identifier is the picture etag, so a cached picture is never outdated
+                if mark_image_used(&identifier) {
+                    return Task::none();
+                }
+
                 let acess_tokens_arc = self.access_tokens.clone();
                 let tenant = self.tenant.clone();
                 authed_task(
@@ -2534,12 +5254,14 @@ impl Counter {
                         .await
                         .unwrap();
 
-                        save_cached_image(identifier, "jpeg", bytes);
+                        save_cached_image(identifier, bytes);
                     },
                     Message::DoNothing,
                 )
             }
             Message::FetchUserImage(identifier, user_id, display_name) => {
+                mark_image_used(&identifier);
+
                 let acess_tokens_arc = self.access_tokens.clone();
                 let tenant = self.tenant.clone();
                 authed_task(
@@ -2548,64 +5270,223 @@ impl Counter {
                     &tenant,
                     move |token| async move {
                         let user_id = user_id;
+                        let etag = cached_image_etag(&identifier);
+
+                        // Revalidated once per session
+                        let picture =
+                            authorize_profile_picture(&token, &user_id, &display_name, etag)
+                                .await
+                                .unwrap();
+
+                        if let PictureResponse::Modified(bytes, etag) = picture {
+                            save_cached_image(identifier.clone(), bytes);
+                            if let Some(etag) = etag {
+                                save_cached_image_etag(&identifier, &etag);
+                            }
+                        }
+                    },
+                    Message::DoNothing,
+                )
+            }
+            Message::FetchMergedProfilePicture(identifier, users) => {
+                if mark_image_used(&identifier) {
+                    return Task::none();
+                }
 
+                let acess_tokens_arc = self.access_tokens.clone();
+                let tenant = self.tenant.clone();
+                let user_id = self.me.id.clone();
+                authed_task(
+                    acess_tokens_arc,
+                    "https://api.spaces.skype.com/Authorization.ReadWrite",
+                    &tenant,
+                    move |token| async move {
                         let bytes =
-                            authorize_profile_picture(&token, &user_id, &display_name)
+                            authorize_merged_profile_picture(&token, &users, &user_id)
                                 .await
                                 .unwrap();
 
-                        save_cached_image(identifier, "jpeg", bytes);
-                    },
-                    Message::DoNothing,
-                )
-            }
-            Message::FetchMergedProfilePicture(identifier, users) => {
-                let acess_tokens_arc = self.access_tokens.clone();
-                let tenant = self.tenant.clone();
-                let user_id = self.me.id.clone();
-                authed_task(
-                    acess_tokens_arc,
-                    "https://api.spaces.skype.com/Authorization.ReadWrite",
-                    &tenant,
-                    move |token| async move {
-                        let bytes =
-                            authorize_merged_profile_picture(&token, &users, &user_id)
-                                .await
-                                .unwrap();
+                        save_cached_image(identifier, bytes);
+                    },
+                    Message::DoNothing,
+                )
+            }
+            Message::AuthorizeImage(url, identifier) => {
+                if mark_image_used(&identifier) {
+                    return Task::none();
+                }
+
+                let acess_tokens_arc = self.access_tokens.clone();
+                let tenant = self.tenant.clone();
+                authed_task(
+                    acess_tokens_arc.clone(),
+                    "https://api.spaces.skype.com/Authorization.ReadWrite",
+                    &tenant,
+                    move |token| async move {
+                        if let Ok(skype_token) =
+                            get_or_gen_skype_token(acess_tokens_arc, token).await
+                        {
+                            let bytes = authorize_image(&skype_token, &url).await.unwrap();
+                            save_cached_image(identifier, bytes);
+                        }
+                    },
+                    Message::DoNothing,
+                )
+            }
+            Message::DownloadImage(url, identifier) => {
+                if mark_image_used(&identifier) {
+                    return Task::none();
+                }
+
+                Task::perform(
+                    async move {
+                        let client = Client::new();
+                        let response = client.get(url).send().await.unwrap();
+                        let bytes = response.bytes().await.unwrap();
+
+                        save_cached_image(identifier, bytes);
+                    },
+                    Message::DoNothing,
+                )
+            }
+            Message::PlayMedia(media) => {
+                let path = media_path(&media);
+                if path.exists() {
+                    return self.handle_message(Message::DownloadedFile(
+                        path.to_string_lossy().to_string(),
+                    ));
+                }
+
+                let acess_tokens_arc = self.access_tokens.clone();
+                let tenant = self.tenant.clone();
+                authed_task(
+                    acess_tokens_arc.clone(),
+                    "https://api.spaces.skype.com/Authorization.ReadWrite",
+                    &tenant,
+                    move |token| async move {
+                        let skype_token = get_or_gen_skype_token(acess_tokens_arc, token).await;
+                        let Ok(skype_token) = skype_token else {
+                            return None;
+                        };
+
+                        // AMS media uses the same authorization as AMS images
+                        match authorize_image(&skype_token, &media.view_url()).await {
+                            Ok(bytes) => save_media(&path, &bytes)
+                                .map(|_| path.to_string_lossy().to_string()),
+                            Err(err) => {
+                                eprintln!("Failed to download media: {}", err);
+                                None
+                            }
+                        }
+                    },
+                    |path| match path {
+                        Some(path) => Message::DownloadedFile(path),
+                        None => Message::DoNothing(()),
+                    },
+                )
+            }
+            Message::ToggleVoiceRecording => {
+                if let Some(stop) = self.voice_recording.take() {
+                    stop.store(true, Ordering::Relaxed);
+                    return Task::none();
+                }
+
+                let Page::Chat(Some(conversation_id), _) = &self.page else {
+                    return Task::none();
+                };
+                if conversation_id.starts_with("draft:") {
+                    return Task::none();
+                }
+                let conversation_id = conversation_id.clone();
+
+                let stop = Arc::new(AtomicBool::new(false));
+                self.voice_recording = Some(stop.clone());
 
-                        save_cached_image(identifier, "jpeg", bytes);
+                Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || record_voice_message(stop))
+                            .await
+                            .unwrap_or_else(|err| Err(err.to_string()))
+                    },
+                    move |recording| {
+                        Message::RecordedVoiceMessage(conversation_id.clone(), recording)
                     },
-                    Message::DoNothing,
                 )
             }
-            Message::AuthorizeImage(url, identifier) => {
+            Message::RecordedVoiceMessage(conversation_id, recording) => {
+                // Recording ended by itself, for example at the length limit
+                self.voice_recording = None;
+
+                let (path, duration_ms) = match recording {
+                    Ok(recording) => recording,
+                    Err(err) => {
+                        self.error_notice =
+                            Some(format!("Couldn't record the voice message: {}", err));
+                        return Task::none();
+                    }
+                };
+
                 let acess_tokens_arc = self.access_tokens.clone();
                 let tenant = self.tenant.clone();
+                let conversation_id_clone = conversation_id.clone();
+
                 authed_task(
                     acess_tokens_arc.clone(),
                     "https://api.spaces.skype.com/Authorization.ReadWrite",
                     &tenant,
                     move |token| async move {
-                        if let Ok(skype_token) =
-                            get_or_gen_skype_token(acess_tokens_arc, token).await
-                        {
-                            let bytes = authorize_image(&skype_token, &url).await.unwrap();
-                            save_cached_image(identifier, "jpeg", bytes);
-                        }
+                        let skype_token = get_or_gen_skype_token(acess_tokens_arc, token)
+                            .await
+                            .map_err(|err| format!("{:?}", err))?;
+                        let bytes = fs::read(&path).map_err(|err| err.to_string())?;
+                        let _ = fs::remove_file(&path);
+
+                        let body = json!({
+                            "type": "sharing/audio",
+                            "permissions": { conversation_id.clone(): ["read"] },
+                            "filename": VOICE_MESSAGE_NAME,
+                        })
+                        .to_string();
+
+                        let object_id = create_ams_object(&skype_token, body)
+                            .await
+                            .map_err(|err| err.to_string())?
+                            .id;
+
+                        upload_ams_audio(&skype_token, &object_id, bytes)
+                            .await
+                            .map_err(|err| err.to_string())?;
+
+                        Ok::<_, String>(object_id)
+                    },
+                    move |object_id| match object_id {
+                        Ok(object_id) => Message::UploadedVoiceMessage(
+                            conversation_id_clone.clone(),
+                            object_id,
+                            duration_ms,
+                        ),
+                        Err(err) => Message::ShowError(format!(
+                            "Couldn't upload the voice message: {}",
+                            err
+                        )),
                     },
-                    Message::DoNothing,
                 )
             }
-            Message::DownloadImage(url, identifier) => Task::perform(
-                async move {
-                    let client = Client::new();
-                    let response = client.get(url).send().await.unwrap();
-                    let bytes = response.bytes().await.unwrap();
+            Message::UploadedVoiceMessage(conversation_id, object_id, duration_ms) => {
+                let object_url = format!("https://eu-api.asm.skype.com/v1/objects/{}", object_id);
+                let content = format!(
+                    "<URIObject type=\"Audio.1/Message.1\" uri=\"{url}\" url_thumbnail=\"{url}/views/audio_thumbnail\"><OriginalName v=\"{name}\"></OriginalName><duration_ms>{duration}</duration_ms></URIObject>",
+                    url = object_url,
+                    name = VOICE_MESSAGE_NAME,
+                    duration = duration_ms,
+                );
 
-                    save_cached_image(identifier, "gif", bytes);
-                },
-                Message::DoNothing,
-            ),
+                self.post_message_task(OutboxMessage {
+                    ams_references: vec![object_id],
+                    message_type: "RichText/Media_AudioMsg".to_string(),
+                    ..new_outbox_message(conversation_id, content)
+                })
+            }
             Message::DownloadFile(file) => {
                 let acess_tokens_arc = self.access_tokens.clone();
                 let tenant = self.tenant.clone();
@@ -2633,64 +5514,649 @@ impl Counter {
                         site_url.replace(format!("https://{}/sites/", web_url).as_str(), "");
                     let item_id = file.item_id.unwrap().to_string();
 
-                    return authed_task(
-                        acess_tokens_arc.clone(),
-                        "https://graph.microsoft.com/.default",
-                        &tenant,
-                        move |token| async move {
-                            let site_id = site_info(&token, &web_url, &site_path)
-                                .await
-                                .unwrap()
-                                .id;
+                    return authed_task(
+                        acess_tokens_arc.clone(),
+                        "https://graph.microsoft.com/.default",
+                        &tenant,
+                        move |token| async move {
+                            let site_id = site_info(&token, &web_url, &site_path)
+                                .await
+                                .unwrap()
+                                .id;
+
+                            let url = format!(
+                                "https://graph.microsoft.com/v1.0/sites/{}/drive/items/{}/content",
+                                site_id, item_id
+                            );
+
+                            sharepoint_download_file(&token, &url).await.unwrap()
+                        },
+                        Message::DownloadedFile,
+                    );
+                }
+                Task::none()
+            }
+            Message::DownloadDriveItem(item) => {
+                let Some(drive_id) = item
+                    .parent_reference
+                    .and_then(|reference| reference.drive_id)
+                else {
+                    return Task::none();
+                };
+
+                authed_task(
+                    self.access_tokens.clone(),
+                    "https://graph.microsoft.com/.default",
+                    &self.tenant,
+                    move |token| async move {
+                        let url = format!(
+                            "https://graph.microsoft.com/v1.0/drives/{}/items/{}/content",
+                            drive_id, item.id
+                        );
+
+                        sharepoint_download_file(&token, &url)
+                            .await
+                            .map_err(|error| error.to_string())
+                    },
+                    |result| match result {
+                        Ok(url) => Message::DownloadedFile(url),
+                        Err(error) => {
+                            Message::ShowError(format!("Couldn't download the file: {}", error))
+                        }
+                    },
+                )
+            }
+            Message::DownloadedFile(url) => {
+                if !webbrowser::open(url.as_str()).is_ok() {
+                    eprintln!("Failed to open link : {}", url);
+                }
+                Task::none()
+            }
+            Message::ToggleShowChatMembers => {
+                if let Page::Chat(chat_id, chat_body) = &self.page {
+                    if *chat_body != ChatBody::Members {
+                        self.page = Page::Chat(chat_id.clone(), ChatBody::Members);
+                        snap_to(Id::new("members_column"), RelativeOffset { x: 0.0, y: 0.0 })
+                    } else {
+                        self.page = Page::Chat(chat_id.clone(), ChatBody::Messages);
+
+                        snap_to(
+                            Id::new("conversation_column"),
+                            RelativeOffset {
+                                x: 0.0,
+                                y: self.scrollbar_percentage_scroll,
+                            },
+                        )
+                    }
+                } else {
+                    Task::none()
+                }
+            }
+            Message::ToggleShowChatAdd => {
+                self.search_users_input_value = String::new();
+                self.just_opened_overlay = true;
+                self.show_add_users = !self.show_add_users;
+                focus(Id::new("search_users_input"))
+            }
+            Message::ToggleUserCheckbox(checked, id) => {
+                self.add_users_checked.insert(id, !checked);
+                Task::none()
+            }
+            Message::ToggleForwardMessage(message) => {
+                if self.show_more_options && self.is_in_more_options {
+                    self.show_more_options = false;
+                    self.is_in_more_options = false;
+                    self.more_menu_message_id = None;
+                }
+
+                self.just_opened_overlay = true;
+                self.show_forward_message = !self.show_forward_message;
+                self.forward_message = if self.show_forward_message {
+                    Some(message)
+                } else {
+                    None
+                };
+                self.search_users_input_value = String::new();
+                focus(Id::new("search_users_input"))
+            }
+            Message::ToggleReactions(thread_id, message_id) => {
+                self.just_opened_overlay = true;
+                self.show_reactions = !self.show_reactions;
+                self.reactions_message = if self.show_reactions {
+                    Some((thread_id, message_id))
+                } else {
+                    None
+                };
+                self.reactions_filter = None;
+                Task::none()
+            }
+            Message::FilterReactions(filter) => {
+                self.reactions_filter = filter;
+                Task::none()
+            }
+            Message::Shortcut(shortcut) => match shortcut {
+                Shortcut::QuickSwitcher => {
+                    self.show_quick_switcher = !self.show_quick_switcher;
+                    self.quick_switcher_input_value = String::new();
+                    self.quick_switcher_selected = 0;
+                    focus(Id::new("quick_switcher_input"))
+                }
+                Shortcut::PreviousConversation => self.open_adjacent_conversation(-1),
+                Shortcut::NextConversation => self.open_adjacent_conversation(1),
+                Shortcut::OpenActivity => self.handle_message(Message::OpenActivity),
+                Shortcut::EditLastMessage => {
+                    self.edit_last_message();
+                    Task::none()
+                }
+                Shortcut::Search => {
+                    let open_task = if matches!(self.page, Page::Chat(_, _)) {
+                        Task::none()
+                    } else {
+                        self.handle_message(Message::OpenCurrentChat)
+                    };
+                    Task::batch(vec![open_task, focus(Id::new("search_chats_input"))])
+                }
+                Shortcut::ShowShortcuts => {
+                    self.show_shortcuts = !self.show_shortcuts;
+                    Task::none()
+                }
+                Shortcut::Undo => self.handle_message(Message::MessageAreaUndo),
+                Shortcut::Redo => self.handle_message(Message::MessageAreaRedo),
+            },
+            Message::QuickSwitcherInputChanged(value) => {
+                self.quick_switcher_input_value = value;
+                self.quick_switcher_selected = 0;
+                Task::none()
+            }
+            Message::QuickSwitcherSubmit => {
+                if let Some(item) = self
+                    .quick_switcher_items()
+                    .into_iter()
+                    .nth(self.quick_switcher_selected)
+                {
+                    return self.handle_message(Message::QuickSwitcherOpen(item.target));
+                }
+                Task::none()
+            }
+            Message::QuickSwitcherOpen(target) => {
+                self.show_quick_switcher = false;
+                self.is_in_centered_overlay = false;
+
+                match target {
+                    SwitcherTarget::Chat(chat_id) => Task::batch(vec![
+                        self.handle_message(Message::OpenChat(chat_id)),
+                        focus(Id::new("message_area")),
+                    ]),
+                    SwitcherTarget::Channel(team_id, channel_id) => {
+                        self.handle_message(Message::OpenTeam(team_id, channel_id))
+                    }
+                    SwitcherTarget::Person(user_id) => {
+                        self.handle_message(Message::StartChat(vec![user_id]))
+                    }
+                }
+            }
+            Message::FetchShortProfile(mri) => authed_task(
+                self.access_tokens.clone(),
+                "https://chatsvcagg.teams.microsoft.com/.default",
+                &self.tenant,
+                move |token| async move {
+                    fetch_short_profile(&token, &vec![mri])
+                        .await
+                        .map(|profiles| profiles.value)
+                        .unwrap_or_default()
+                },
+                Message::GotShortProfiles,
+            ),
+            Message::GotShortProfiles(profiles) => {
+                for profile in profiles {
+                    let id = profile
+                        .object_id
+                        .clone()
+                        .unwrap_or(profile.mri.replace("8:orgid:", ""));
+
+                    self.users.insert(
+                        id.clone(),
+                        Profile {
+                            id,
+                            display_name: profile.display_name,
+                            given_name: profile.given_name,
+                            surname: profile.surname,
+                            job_title: profile.job_title,
+                            mail: profile.email,
+                            company_name: profile.company_name,
+                            user_principal_name: profile.user_principal_name,
+                            is_short_profile: Some(true),
+                            ..Default::default()
+                        },
+                    );
+                }
+                Task::none()
+            }
+            Message::ForwardMessage(conversation_id) => {
+                self.show_forward_message = false;
+                self.is_in_centered_overlay = false;
+
+                let Some(message) = self.forward_message.take() else {
+                    return Task::none();
+                };
+
+                let html = forward_message_html(&message, &self.users);
+                self.post_message_task(new_outbox_message(conversation_id, html))
+            }
+            Message::ToggleSendLater => {
+                self.just_opened_overlay = true;
+                self.show_send_later = !self.show_send_later;
+                self.send_later_input_value = String::new();
+                focus(Id::new("send_later_input"))
+            }
+            Message::SendLaterInputChanged(content) => {
+                self.send_later_input_value = content;
+                Task::none()
+            }
+            Message::ScheduleMessageFromInput => {
+                let Some(send_at) = utils::parse_local_time(&self.send_later_input_value) else {
+                    return Task::none();
+                };
+
+                self.handle_message(Message::ScheduleMessage(send_at))
+            }
+            Message::ScheduleMessage(send_at) => {
+                let Some(conversation_id) = draft_thread_id(&self.page) else {
+                    return Task::none();
+                };
+
+                // Draft chats are only created on the server when the first message is sent
+                if conversation_id.starts_with("draft:") {
+                    return Task::none();
+                }
+
+                if send_at <= Utc::now().timestamp() {
+                    return self.handle_message(Message::ShowError(
+                        "Messages can't be scheduled in the past".to_string(),
+                    ));
+                }
+
+                let message_area_content = match self.page {
+                    Page::Team(_, _, _) => &self.team_message_area_content,
+                    _ => &self.chat_message_area_content,
+                };
+                let message_area_text =
+                    expand_custom_emojis(&message_area_content.text(), &self.emoji_map);
+                if message_area_text.trim().is_empty() {
+                    return Task::none();
+                }
+
+                // Replies are only kept in the html
+                let html = (self.settings.rich_text_composer || self.reply_target.is_some())
+                    .then(|| self.draft_html());
+
+                let subject = match self.page {
+                    Page::Team(_, _, _) => self
+                        .subject_input_value
+                        .clone()
+                        .filter(|subject| !subject.trim().is_empty()),
+                    _ => None,
+                };
+
+                // The images and the link preview are sent with the message when it is due
+                let links = self.take_link_preview(&message_area_text);
+                let pending_images = std::mem::take(&mut self.pending_images);
+                self.clear_message_area();
+
+                let mut rng = StdRng::from_os_rng();
+
+                self.scheduled_messages.push(ScheduledMessage {
+                    id: rng.random::<u64>().to_string(),
+                    conversation_id,
+                    text: message_area_text,
+                    html,
+                    subject,
+                    send_at,
+                    pending_images,
+                    links,
+                });
+                save_to_cache("scheduled_messages.json", &self.scheduled_messages);
+
+                self.show_send_later = false;
+                self.is_in_centered_overlay = false;
+
+                Task::none()
+            }
+            Message::ToggleScheduledMessages => {
+                self.just_opened_overlay = true;
+                self.show_scheduled_messages = !self.show_scheduled_messages;
+                Task::none()
+            }
+            Message::EditScheduledMessage(scheduled_message_id) => {
+                let Some(pos) = self
+                    .scheduled_messages
+                    .iter()
+                    .position(|scheduled_message| scheduled_message.id == scheduled_message_id)
+                else {
+                    return Task::none();
+                };
+
+                let scheduled_message = self.scheduled_messages.remove(pos);
+                save_to_cache("scheduled_messages.json", &self.scheduled_messages);
+
+                self.show_scheduled_messages = false;
+                self.is_in_centered_overlay = false;
+
+                self.pending_images.extend(scheduled_message.pending_images.clone());
+
+                // Move the message back into the composer so it can be changed and rescheduled
+                let (area_content, area_height) = match self.page {
+                    Page::Team(_, _, _) => {
+                        if scheduled_message.subject.is_some() {
+                            self.subject_input_value = scheduled_message.subject.clone();
+                        }
+                        (
+                            &mut self.team_message_area_content,
+                            &mut self.team_message_area_height,
+                        )
+                    }
+                    _ => (
+                        &mut self.chat_message_area_content,
+                        &mut self.chat_message_area_height,
+                    ),
+                };
+
+                if area_content.text().trim() != "" {
+                    content_send(area_content, "\n\n");
+                }
+                content_send(area_content, &scheduled_message.text);
+
+                let max_area_height = 0.5 * self.window_height;
+
+                let line_count = area_content.line_count();
+                let new_height = 33.0 + line_count as f32 * 21.0;
+
+                *area_height = if new_height > max_area_height {
+                    max_area_height
+                } else {
+                    new_height
+                };
+
+                focus(Id::new("message_area"))
+            }
+            Message::CancelScheduledMessage(scheduled_message_id) => {
+                self.scheduled_messages
+                    .retain(|scheduled_message| scheduled_message.id != scheduled_message_id);
+                save_to_cache("scheduled_messages.json", &self.scheduled_messages);
+
+                let current_chat_id = draft_thread_id(&self.page);
+                if !self.scheduled_messages.iter().any(|scheduled_message| {
+                    Some(&scheduled_message.conversation_id) == current_chat_id.as_ref()
+                }) {
+                    self.show_scheduled_messages = false;
+                    self.is_in_centered_overlay = false;
+                }
+
+                Task::none()
+            }
+            Message::SaveDrafts => {
+                self.store_draft(&self.page.clone());
+                self.save_drafts();
+                Task::none()
+            }
+            Message::SendScheduledMessages => {
+                // The profile is needed to post messages
+                if self.me.id.is_empty() {
+                    return Task::none();
+                }
+
+                let now = Utc::now().timestamp();
+
+                let (due, pending): (Vec<ScheduledMessage>, Vec<ScheduledMessage>) = self
+                    .scheduled_messages
+                    .drain(..)
+                    .partition(|scheduled_message| scheduled_message.send_at <= now);
+
+                self.scheduled_messages = pending;
+
+                if due.is_empty() {
+                    return Task::none();
+                }
+
+                save_to_cache("scheduled_messages.json", &self.scheduled_messages);
 
-                            let url = format!(
-                                "https://graph.microsoft.com/v1.0/sites/{}/drive/items/{}/content",
-                                site_id, item_id
-                            );
+                let mut tasks = vec![];
+                for scheduled_message in due {
+                    let html = scheduled_message
+                        .html
+                        .unwrap_or_else(|| parse_message_markdown(scheduled_message.text));
+
+                    tasks.push(self.post_message_task(OutboxMessage {
+                        subject: scheduled_message.subject,
+                        pending_images: scheduled_message.pending_images,
+                        links: scheduled_message.links,
+                        ..new_outbox_message(scheduled_message.conversation_id, html)
+                    }));
+                }
 
-                            sharepoint_download_file(&token, &url).await.unwrap()
-                        },
-                        Message::DownloadedFile,
-                    );
+                Task::batch(tasks)
+            }
+            Message::OutboxMessageSent(client_message_id, is_sent) => {
+                if let Some(outbox_message) = self.outbox.get_mut(&client_message_id) {
+                    // The entry is removed once the server echo arrives over the websocket
+                    outbox_message.state = if is_sent {
+                        OutboxState::Sent
+                    } else {
+                        OutboxState::Failed
+                    };
+                    save_to_cache("outbox.json", &self.outbox);
                 }
                 Task::none()
             }
-            Message::DownloadedFile(url) => {
-                if !webbrowser::open(url.as_str()).is_ok() {
-                    eprintln!("Failed to open link : {}", url);
+            Message::RetryOutboxMessage(client_message_id) => {
+                let Some(outbox_message) = self.outbox.get_mut(&client_message_id) else {
+                    return Task::none();
+                };
+
+                outbox_message.state = OutboxState::Pending;
+                let outbox_message = outbox_message.clone();
+
+                self.present_messages.insert(client_message_id);
+                save_to_cache("outbox.json", &self.outbox);
+
+                if !outbox_message.pending_images.is_empty() {
+                    return upload_images_task(
+                        self.access_tokens.clone(),
+                        self.tenant.clone(),
+                        outbox_message,
+                    );
                 }
-                Task::none()
+
+                send_outbox_message_task(
+                    self.access_tokens.clone(),
+                    self.tenant.clone(),
+                    outbox_message,
+                    self.me.id.clone(),
+                    self.me.display_name.clone(),
+                )
             }
-            Message::ToggleShowChatMembers => {
-                if let Page::Chat(chat_id, chat_body) = &self.page {
-                    if *chat_body != ChatBody::Members {
-                        self.page = Page::Chat(chat_id.clone(), ChatBody::Members);
-                        snap_to(Id::new("members_column"), RelativeOffset { x: 0.0, y: 0.0 })
-                    } else {
-                        self.page = Page::Chat(chat_id.clone(), ChatBody::Messages);
+            Message::DeleteOutboxMessage(client_message_id) => {
+                let Some(outbox_message) = self.outbox.shift_remove(&client_message_id) else {
+                    return Task::none();
+                };
+                save_to_cache("outbox.json", &self.outbox);
 
-                        snap_to(
-                            Id::new("conversation_column"),
-                            RelativeOffset {
-                                x: 0.0,
-                                y: self.scrollbar_percentage_scroll,
-                            },
-                        )
-                    }
+                self.present_messages.remove(&client_message_id);
+
+                let is_unsent_message = |message: &api::Message| {
+                    message.client_message_id.as_ref() == Some(&client_message_id)
+                        && message.id.as_deref() == Some("-1")
+                };
+
+                if let Some(conversation) = self
+                    .chat_conversations
+                    .get_mut(&outbox_message.conversation_id)
+                {
+                    conversation.retain(|message| !is_unsent_message(message));
+                }
+
+                if let Some(conversation) = self
+                    .team_conversations
+                    .get_mut(&outbox_message.conversation_id)
+                {
+                    conversation.reply_chains.retain(|reply_chain| {
+                        !reply_chain.messages.iter().any(&is_unsent_message)
+                    });
+                }
+
+                Task::none()
+            }
+            Message::PasteImage => {
+                if let Page::Team(_, _, _) | Page::Chat(_, _) = self.page {
+                    Task::perform(
+                        async { utils::read_clipboard_image() },
+                        Message::GotPendingImage,
+                    )
                 } else {
                     Task::none()
                 }
             }
-            Message::ToggleShowChatAdd => {
-                self.search_users_input_value = String::new();
-                self.just_opened_overlay = true;
-                self.show_add_users = !self.show_add_users;
-                focus(Id::new("search_users_input"))
+            Message::GotPendingImage(pending_image) => {
+                if let Some(pending_image) = pending_image {
+                    self.pending_images.push(pending_image);
+                }
+                Task::none()
             }
-            Message::ToggleUserCheckbox(checked, id) => {
-                self.add_users_checked.insert(id, !checked);
+            Message::RemovePendingImage(identifier) => {
+                self.pending_images
+                    .retain(|pending_image| pending_image.identifier != identifier);
+                Task::none()
+            }
+            Message::GotLinkPreview(url, link_preview) => {
+                // The message area may have moved on to another link in the meantime
+                if self.link_preview_url.as_ref() == Some(&url) {
+                    self.link_preview = link_preview;
+                }
+                Task::none()
+            }
+            Message::RemoveLinkPreview => {
+                // The url is kept so the preview isn't fetched again for this message
+                self.link_preview = None;
                 Task::none()
             }
+            Message::UploadedImages(client_message_id, uploaded_images) => {
+                let Some(outbox_message) = self.outbox.get_mut(&client_message_id) else {
+                    return Task::none();
+                };
+
+                let uploaded_images = match uploaded_images {
+                    Ok(uploaded_images) => uploaded_images,
+                    Err(err) => {
+                        // The images stay with the message so retrying uploads them again
+                        outbox_message.state = OutboxState::Failed;
+                        save_to_cache("outbox.json", &self.outbox);
+                        self.error_notice = Some(format!("Couldn't upload the images: {}", err));
+                        return Task::none();
+                    }
+                };
+
+                for (object_id, pending_image) in &uploaded_images {
+                    let image_url = ams_image_url(object_id);
+                    let image_html = format!(
+                        "<img itemtype=\"http://schema.skype.com/AMSImage\" src=\"{url}\" width=\"{width}\" height=\"{height}\" id=\"{id}\" itemscope=\"\" itemid=\"{id}\" href=\"{url}\" target-src=\"{url}\">",
+                        url = image_url,
+                        width = pending_image.width,
+                        height = pending_image.height,
+                        id = object_id,
+                    );
+
+                    // The announcement banner is a regular image above the text of the post
+                    if outbox_message.post_options.banner.as_ref() == Some(pending_image) {
+                        outbox_message.html.insert_str(0, &format!("<p>{}</p>", image_html));
+                    } else {
+                        outbox_message.html.push_str(&format!("<p>{}</p>", image_html));
+                    }
+                }
+                outbox_message.post_options.banner = None;
+                outbox_message.pending_images.clear();
+                outbox_message.ams_references =
+                    uploaded_images.into_iter().map(|(object_id, _)| object_id).collect();
+
+                let outbox_message = outbox_message.clone();
+                save_to_cache("outbox.json", &self.outbox);
+
+                send_outbox_message_task(
+                    self.access_tokens.clone(),
+                    self.tenant.clone(),
+                    outbox_message,
+                    self.me.id.clone(),
+                    self.me.display_name.clone(),
+                )
+            }
+            Message::ForwardMessageToUser(user_id) => {
+                // Forward to the one on one chat if it already exists
+                if let Some(chat) = self.chats.iter().find(|chat| {
+                    chat.members.len() == 2
+                        && !chat.id.starts_with("draft:")
+                        && chat
+                            .members
+                            .iter()
+                            .any(|member| member.mri.replace("8:orgid:", "") == user_id)
+                }) {
+                    let chat_id = chat.id.clone();
+                    return self.handle_message(Message::ForwardMessage(chat_id));
+                }
+
+                self.show_forward_message = false;
+                self.is_in_centered_overlay = false;
+
+                let Some(message) = self.forward_message.take() else {
+                    return Task::none();
+                };
+
+                let html = forward_message_html(&message, &self.users);
+
+                // Creates the draft chat and opens it
+                let _ = self.handle_message(Message::StartChat(vec![user_id]));
+
+                let draft_id = self.chats[0].id.clone();
+
+                let members: Vec<ThreadMember> = self.chats[0]
+                    .members
+                    .iter()
+                    .map(|member| ThreadMember {
+                        id: member.mri.clone(),
+                        role: "Admin".to_string(),
+                        share_history_time: None,
+                    })
+                    .collect();
+
+                let new_thread = Thread {
+                    members,
+                    properties: Some(ThreadProperties {
+                        thread_type: "chat".to_string(),
+                        fixed_roster: Some(false),
+                        unique_roster_thread: Some(false),
+                    }),
+                };
+
+                let body = serde_json::to_string(&new_thread).unwrap();
+
+                authed_task(
+                    self.access_tokens.clone(),
+                    "https://ic3.teams.office.com/.default",
+                    &self.tenant,
+                    move |token| async move {
+                        start_thread(&token, body)
+                            .await
+                            .map(|thread_link| (draft_id, thread_link))
+                            .map_err(|error| error.to_string())
+                    },
+                    move |result| match result {
+                        Ok((draft_id, thread_link)) => {
+                            Message::CreatedGroupChat(draft_id, thread_link, html.clone())
+                        }
+                        Err(error) => {
+                            Message::ShowError(format!("Couldn't forward the message: {}", error))
+                        }
+                    },
+                )
+            }
             Message::ToggleMessageAreaEmojiPicker => {
                 if self.show_profile {
                     self.show_profile = false;
@@ -2802,22 +6268,43 @@ impl Counter {
                 self.is_in_centered_overlay = false;
                 Task::none()
             }
-            Message::EmojiPickerSend(_emoji_id, emoji_unicode) => {
+            Message::EmojiPickerSend(emoji_id, emoji_unicode) => {
                 if self.show_message_area_emoji_picker && self.is_in_emoji_picker {
                     self.show_message_area_emoji_picker = false;
                     self.is_in_emoji_picker = false;
                 }
 
                 let content = match self.page {
-                    Page::Team(_, _) => &mut self.team_message_area_content,
+                    Page::Team(_, _, _) => &mut self.team_message_area_content,
                     Page::Chat(_, _) => &mut self.chat_message_area_content,
                     _ => return Task::none(),
                 };
 
                 content_send(content, &emoji_unicode);
+                record_emoji_usage(&mut self.emoji_usage, &emoji_id);
 
                 Task::none()
             }
+            Message::PickEmojiSuggestion(emoji_id, emoji_unicode) => {
+                let Some(query) = self.emoji_autocomplete.take() else {
+                    return Task::none();
+                };
+
+                let content = match self.page {
+                    Page::Team(_, _, _) => &mut self.team_message_area_content,
+                    Page::Chat(_, _) => &mut self.chat_message_area_content,
+                    _ => return Task::none(),
+                };
+
+                // Replace the typed :shortcode with the emoji
+                for _ in 0..query.chars().count() + 1 {
+                    content.perform(Action::Edit(Edit::Backspace));
+                }
+                content_send(content, &emoji_unicode);
+                record_emoji_usage(&mut self.emoji_usage, &emoji_id);
+
+                focus(Id::new("message_area"))
+            }
             Message::EmojiPickerReaction(emoji_id, _emoji_unicode, message_id, thread_id) => {
                 if self.show_message_emoji_picker && self.is_in_emoji_picker {
                     self.show_message_emoji_picker = false;
@@ -2831,6 +6318,8 @@ impl Counter {
                     self.emoji_picker_message_id = None;
                 }
 
+                record_emoji_usage(&mut self.emoji_usage, &emoji_id);
+
                 let access_tokens_arc = self.access_tokens.clone();
                 let tenant = self.tenant.clone();
 
@@ -2917,7 +6406,7 @@ impl Counter {
                 );
 
                 let thread_id = match &self.page {
-                    Page::Team(current_team_id, _) => current_team_id.clone().unwrap(),
+                    Page::Team(current_team_id, _, _) => current_team_id.clone().unwrap(),
                     Page::Chat(current_chat_id, _) => current_chat_id.clone().unwrap(),
                     _ => return Task::none(),
                 };
@@ -3046,6 +6535,161 @@ impl Counter {
                 },
                 Message::DoNothing,
             ),
+            Message::OpenTeamFiles => {
+                let Page::Team(Some(team_id), Some(channel_id), _) = self.page.clone() else {
+                    return Task::none();
+                };
+
+                self.page = Page::Team(
+                    Some(team_id.clone()),
+                    Some(channel_id.clone()),
+                    TeamBody::Files,
+                );
+
+                if let Some(location) = self.team_files_location.get(&channel_id) {
+                    // Refresh the folder that was left open
+                    return drive_items_task(
+                        self.access_tokens.clone(),
+                        self.tenant.clone(),
+                        location.last().unwrap(),
+                    );
+                }
+
+                let Some(group_id) = self
+                    .teams
+                    .iter()
+                    .find(|team| team.id == team_id)
+                    .map(|team| team.team_site_information.group_id.clone())
+                else {
+                    return Task::none();
+                };
+
+                authed_task(
+                    self.access_tokens.clone(),
+                    "https://graph.microsoft.com/.default",
+                    &self.tenant,
+                    move |token| async move {
+                        channel_files_folder(&token, &group_id, &channel_id)
+                            .await
+                            .map(|folder| (channel_id, folder))
+                            .ok()
+                    },
+                    |result| match result {
+                        Some((channel_id, folder)) => {
+                            Message::GotChannelFilesFolder(channel_id, folder)
+                        }
+                        None => Message::DoNothing(()),
+                    },
+                )
+            }
+            Message::GotChannelFilesFolder(channel_id, folder) => {
+                let task =
+                    drive_items_task(self.access_tokens.clone(), self.tenant.clone(), &folder);
+                self.team_files_location.insert(channel_id, vec![folder]);
+                task
+            }
+            Message::GotDriveItems(folder_id, mut children) => {
+                sort_drive_items(&mut children, &self.team_files_sort);
+                self.drive_items.insert(folder_id, children);
+                Task::none()
+            }
+            Message::OpenDriveFolder(folder) => {
+                let Page::Team(_, Some(channel_id), _) = &self.page else {
+                    return Task::none();
+                };
+
+                let task =
+                    drive_items_task(self.access_tokens.clone(), self.tenant.clone(), &folder);
+                if let Some(location) = self.team_files_location.get_mut(channel_id) {
+                    location.push(folder);
+                }
+                task
+            }
+            Message::NavigateDriveFolder(index) => {
+                let Page::Team(_, Some(channel_id), _) = &self.page else {
+                    return Task::none();
+                };
+
+                if let Some(location) = self.team_files_location.get_mut(channel_id) {
+                    location.truncate(index + 1);
+                    return drive_items_task(
+                        self.access_tokens.clone(),
+                        self.tenant.clone(),
+                        location.last().unwrap(),
+                    );
+                }
+                Task::none()
+            }
+            Message::SortTeamFiles(column) => {
+                self.team_files_sort = if self.team_files_sort.0 == column {
+                    (column, !self.team_files_sort.1)
+                } else {
+                    (column, true)
+                };
+                for items in self.drive_items.values_mut() {
+                    sort_drive_items(items, &self.team_files_sort);
+                }
+                Task::none()
+            }
+            Message::UploadTeamFile => Task::perform(
+                async {
+                    let file = AsyncFileDialog::new()
+                        .set_directory(home_dir().unwrap_or("/".into()))
+                        .pick_file()
+                        .await?;
+
+                    Some((file.file_name(), file.read().await))
+                },
+                Message::PickedTeamFile,
+            ),
+            Message::PickedTeamFile(file) => {
+                let Some((file_name, bytes)) = file else {
+                    return Task::none();
+                };
+
+                let Page::Team(_, Some(channel_id), _) = &self.page else {
+                    return Task::none();
+                };
+
+                let Some(folder) = self
+                    .team_files_location
+                    .get(channel_id)
+                    .and_then(|location| location.last())
+                    .cloned()
+                else {
+                    return Task::none();
+                };
+
+                let Some(drive_id) = folder
+                    .parent_reference
+                    .as_ref()
+                    .and_then(|reference| reference.drive_id.clone())
+                else {
+                    return Task::none();
+                };
+
+                authed_task(
+                    self.access_tokens.clone(),
+                    "https://graph.microsoft.com/.default",
+                    &self.tenant,
+                    move |token| async move {
+                        upload_drive_item(&token, &drive_id, &folder.id, &file_name, bytes)
+                            .await
+                            .map_err(|error| format!("Couldn't upload {}: {}", file_name, error))?;
+
+                        // Refresh the folder so the new file shows up
+                        let children = drive_item_children(&token, &drive_id, &folder.id)
+                            .await
+                            .map(|collection| collection.value)
+                            .unwrap_or_default();
+                        Ok::<_, String>((folder.id, children))
+                    },
+                    |result| match result {
+                        Ok((folder_id, children)) => Message::GotDriveItems(folder_id, children),
+                        Err(error) => Message::ShowError(error),
+                    },
+                )
+            }
 
             // Websockets
             Message::WSConnected(info) => {
@@ -3055,6 +6699,19 @@ impl Counter {
                 // This will be more useful in the future...
                 self.websockets_connection_info = Some(info.clone());
 
+                // Connectivity is back, so retry the messages that failed to send
+                let failed_messages: Vec<String> = self
+                    .outbox
+                    .values()
+                    .filter(|outbox_message| outbox_message.state == OutboxState::Failed)
+                    .map(|outbox_message| outbox_message.client_message_id.clone())
+                    .collect();
+
+                let mut tasks = vec![];
+                for client_message_id in failed_messages {
+                    tasks.push(self.handle_message(Message::RetryOutboxMessage(client_message_id)));
+                }
+
                 let surl = info.surl.clone();
                 let endpoint = info.endpoint.clone();
 
@@ -3082,7 +6739,7 @@ impl Counter {
                     surl, subscriptions
                 );
 
-                authed_task(
+                tasks.push(authed_task(
                     access_tokens_arc,
                     "https://presence.teams.microsoft.com/.default",
                     &tenant,
@@ -3092,13 +6749,22 @@ impl Counter {
                             .unwrap();
                     },
                     Message::DoNothing,
-                )
+                ));
+
+                Task::batch(tasks)
             }
             Message::GotWSMessage(message) => {
                 let access_tokens_arc = self.access_tokens.clone();
                 let tenant = self.tenant.clone();
 
                 let message = message.resource;
+
+                if let Some(client_message_id) = &message.client_message_id
+                    && self.outbox.shift_remove(client_message_id).is_some()
+                {
+                    save_to_cache("outbox.json", &self.outbox);
+                }
+
                 if let Some(message_type) = &message.message_type {
                     if message_type == "Control/Typing" {
                         let chat_id = message.conversation_link.unwrap().replace(
@@ -3212,6 +6878,10 @@ impl Counter {
                                                 RelativeOffset::END,
                                             ))
                                         }
+                                    }
+                                    if &chat_id == current_chat_id
+                                        && self.settings.send_read_receipts
+                                    {
                                         tasks.push(authed_task(
                                             access_tokens_arc,
                                             "https://ic3.teams.office.com/.default",
@@ -3233,7 +6903,7 @@ impl Counter {
 
                                 return Task::batch(tasks);
                             }
-                            Page::Team(_, _) => {
+                            Page::Team(_, _, _) => {
                                 let message_link_data = message
                                     .conversation_link
                                     .clone()
@@ -3347,6 +7017,42 @@ impl Counter {
 
                 Task::none()
             }
+            Message::GotWSThreadUpdate(thread_id) => {
+                // Team and channel changes are confirmed by refetching the teams, an unknown
+                // thread can be a team that was just joined
+                let is_chat_thread = self.chats.iter().any(|chat| chat.id == thread_id);
+                if is_chat_thread || self.teams_refetch_scheduled {
+                    return Task::none();
+                }
+
+                // A rename or membership change sends several updates, they share one refetch
+                self.teams_refetch_scheduled = true;
+                Task::perform(
+                    async {
+                        sleep(Duration::from_secs(2)).await;
+                    },
+                    |_| Message::RefetchTeams,
+                )
+            }
+            Message::RefetchTeams => {
+                self.teams_refetch_scheduled = false;
+
+                authed_task(
+                    self.access_tokens.clone(),
+                    "https://chatsvcagg.teams.microsoft.com/.default",
+                    &self.tenant,
+                    |token| async move {
+                        let user_details = teams_me(&token).await.ok()?;
+                        save_to_cache("teams.json", &user_details.teams);
+                        save_to_cache("chats.json", &user_details.chats);
+                        Some((user_details.teams, user_details.chats))
+                    },
+                    |result| match result {
+                        Some((teams, chats)) => Message::GotUserDetails(teams, chats),
+                        None => Message::DoNothing(()),
+                    },
+                )
+            }
             Message::GotWSPresences(presences) => {
                 for presence in presences.presence {
                     self.user_presences.insert(presence.mri.clone(), presence);
@@ -3363,6 +7069,14 @@ impl Counter {
 
             // Other
             Message::DoNothing(_) => Task::none(),
+            Message::ShowError(error) => {
+                self.error_notice = Some(error);
+                Task::none()
+            }
+            Message::DismissError => {
+                self.error_notice = None;
+                Task::none()
+            }
             Message::Join => {
                 println!("Join message called!");
                 Task::none()
@@ -3382,12 +7096,32 @@ impl Counter {
             tenant: self.tenant.clone(),
         };
 
+        match self.settings.theme {
+            ThemePreference::System => subscriptions.push(
+                Subscription::run(portal::color_scheme_changes)
+                    .map(Message::GotSystemColorScheme),
+            ),
+            ThemePreference::File(_) => subscriptions.push(
+                iced::time::every(Duration::from_secs(2)).map(|_| Message::CheckTheme),
+            ),
+            _ => {}
+        }
+
         if self.is_authorized {
+            // Also sends the messages that became due while the app was closed
+            subscriptions.push(
+                iced::time::every(Duration::from_secs(15)).map(|_| Message::SendScheduledMessages),
+            );
+            subscriptions.push(
+                iced::time::every(Duration::from_secs(3)).map(|_| Message::SaveDrafts),
+            );
+
             subscriptions.push(Subscription::run_with(data, websocket_builder).map(
                 |response_type| match response_type {
                     WebsocketResponse::Connected(info) => Message::WSConnected(info),
                     WebsocketResponse::Message(value) => Message::GotWSMessage(value),
                     WebsocketResponse::Presences(value) => Message::GotWSPresences(value),
+                    WebsocketResponse::ThreadUpdate(value) => Message::GotWSThreadUpdate(value),
                     WebsocketResponse::AuthExpired => Message::AuthExpired,
                     WebsocketResponse::Other(_value) => Message::DoNothing(()),
                 },
@@ -3433,17 +7167,20 @@ impl Counter {
             },
         };
 
-        Theme::custom("Squads Dark".to_string(), custom_palette)
+        Theme::custom(self.theme.name.clone(), custom_palette)
     }
 }
 
 pub fn main() -> iced::Result {
+    let settings = load_settings();
+
     iced::application(Counter::new, Counter::update, Counter::view)
         .window_size(Size {
-            width: WINDOW_WIDTH,
-            height: WINDOW_HEIGHT,
+            width: settings.window_width,
+            height: settings.window_height,
         })
         .subscription(Counter::subscription)
+        .exit_on_close_request(false)
         .theme(Counter::theme)
         .font(include_bytes!("../resources/OpenSans-Regular-COLR.ttf").as_slice()) // Increases startup time with about 100 ms...
         .default_font(Font::with_name("Open Sans Twemoji"))
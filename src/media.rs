@@ -0,0 +1,169 @@
+use crate::types::{MediaAttachment, MediaKind};
+use crate::utils::ams_image_identifier;
+#[cfg(feature = "voice-recording")]
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+#[cfg(feature = "voice-recording")]
+use cpal::{SampleFormat, SizedSample, Stream, StreamConfig};
+use directories::ProjectDirs;
+#[cfg(feature = "voice-recording")]
+use hound::{WavSpec, WavWriter};
+#[cfg(feature = "voice-recording")]
+use std::fs::File;
+use std::fs::{create_dir_all, rename, write};
+#[cfg(feature = "voice-recording")]
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+#[cfg(feature = "voice-recording")]
+use std::sync::{Mutex, atomic::Ordering};
+#[cfg(feature = "voice-recording")]
+use std::thread;
+#[cfg(feature = "voice-recording")]
+use std::time::{Duration, Instant};
+
+pub const VOICE_MESSAGE_NAME: &str = "VoiceMessage.wav";
+
+#[cfg(feature = "voice-recording")]
+const MAX_VOICE_MESSAGE: Duration = Duration::from_secs(5 * 60);
+
+// Speech doesn't need more, this keeps a five minute message around 10 MB
+#[cfg(feature = "voice-recording")]
+const VOICE_SAMPLE_RATE: u32 = 16000;
+
+#[cfg(feature = "voice-recording")]
+type SharedWriter = Arc<Mutex<Option<WavWriter<BufWriter<File>>>>>;
+
+pub fn media_cache_dir() -> PathBuf {
+    let project_dirs = ProjectDirs::from("", "ianterzo", "squads");
+    project_dirs.unwrap().cache_dir().join("media-cache")
+}
+
+// Where a downloaded attachment is kept for the system player
+pub fn media_path(media: &MediaAttachment) -> PathBuf {
+    // Voice messages sent from here are wav, the name has the real extension
+    let extension = media
+        .name
+        .as_deref()
+        .and_then(|name| Path::new(name).extension())
+        .and_then(|extension| extension.to_str())
+        .unwrap_or(match media.kind {
+            MediaKind::Audio => "m4a",
+            MediaKind::Video => "mp4",
+        });
+    media_cache_dir().join(format!("{}.{}", ams_image_identifier(&media.url), extension))
+}
+
+pub fn save_media(path: &PathBuf, bytes: &[u8]) -> Option<()> {
+    create_dir_all(media_cache_dir()).ok()?;
+
+    let temp_path = path.with_extension("tmp");
+    write(&temp_path, bytes).ok()?;
+    rename(temp_path, path).ok()
+}
+
+pub fn format_duration(duration_ms: u64) -> String {
+    let seconds = duration_ms / 1000;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(feature = "voice-recording")]
+fn build_input_stream<T: SizedSample + Send + 'static>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    writer: SharedWriter,
+    factor: usize,
+    to_i16: fn(T) -> i16,
+) -> Result<Stream, cpal::BuildStreamError> {
+    let channels = config.channels as usize;
+    // Every frame is mixed down to mono and `factor` frames are averaged into one sample
+    let mut sum = 0i64;
+    let mut count = 0usize;
+
+    device.build_input_stream(
+        config,
+        move |samples: &[T], _| {
+            let mut writer = writer.lock().unwrap();
+            let Some(writer) = writer.as_mut() else {
+                return;
+            };
+
+            for frame in samples.chunks(channels) {
+                let mono: i64 = frame.iter().map(|sample| to_i16(*sample) as i64).sum::<i64>()
+                    / frame.len() as i64;
+                sum += mono;
+                count += 1;
+
+                if count == factor {
+                    let _ = writer.write_sample((sum / factor as i64) as i16);
+                    sum = 0;
+                    count = 0;
+                }
+            }
+        },
+        |err| eprintln!("Recording error: {}", err),
+        None,
+    )
+}
+
+// Records the default microphone to a mono wav file until `stop` is set, with the duration
+#[cfg(feature = "voice-recording")]
+pub fn record_voice_message(stop: Arc<AtomicBool>) -> Result<(PathBuf, u64), String> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or("No microphone found")?;
+    let supported_config = device.default_input_config().map_err(|err| err.to_string())?;
+    let config: StreamConfig = supported_config.clone().into();
+
+    create_dir_all(media_cache_dir()).map_err(|err| err.to_string())?;
+    let path = media_cache_dir().join(format!("recording{}.wav", rand::random::<u64>()));
+
+    let factor = (config.sample_rate.0 / VOICE_SAMPLE_RATE).max(1);
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: config.sample_rate.0 / factor,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let writer = WavWriter::create(&path, spec).map_err(|err| err.to_string())?;
+    let writer: SharedWriter = Arc::new(Mutex::new(Some(writer)));
+    let factor = factor as usize;
+
+    let stream = match supported_config.sample_format() {
+        SampleFormat::F32 => {
+            build_input_stream(&device, &config, writer.clone(), factor, |sample: f32| {
+                (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+            })
+        }
+        SampleFormat::I16 => {
+            build_input_stream(&device, &config, writer.clone(), factor, |sample| sample)
+        }
+        SampleFormat::U16 => {
+            build_input_stream(&device, &config, writer.clone(), factor, |sample: u16| {
+                (sample as i32 - 32768) as i16
+            })
+        }
+        sample_format => return Err(format!("Unsupported sample format {}", sample_format)),
+    }
+    .map_err(|err| err.to_string())?;
+
+    stream.play().map_err(|err| err.to_string())?;
+
+    let started = Instant::now();
+    while !stop.load(Ordering::Relaxed) && started.elapsed() < MAX_VOICE_MESSAGE {
+        thread::sleep(Duration::from_millis(50));
+    }
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    drop(stream);
+    if let Some(writer) = writer.lock().unwrap().take() {
+        writer.finalize().map_err(|err| err.to_string())?;
+    }
+
+    Ok((path, duration_ms))
+}
+
+#[cfg(not(feature = "voice-recording"))]
+pub fn record_voice_message(_stop: Arc<AtomicBool>) -> Result<(PathBuf, u64), String> {
+    Err("Squads was built without voice recording".to_string())
+}
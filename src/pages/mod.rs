@@ -1,6 +1,7 @@
 pub mod page_activity;
 pub mod page_chat;
 pub mod page_login;
+pub mod page_settings;
 pub mod page_team;
 use crate::Message;
 use iced::Element;
@@ -10,10 +11,14 @@ pub fn app<'a>(
     sidebar: Element<'a, Message>,
     content: Element<'a, Message>,
     overlay: Option<Element<'a, Message>>,
+    error_notice: Option<Element<'a, Message>>,
 ) -> Element<'a, Message> {
+    let mut layers = stack![row![sidebar, content]];
     if let Some(overlay) = overlay {
-        stack![row![sidebar, content], overlay].into()
-    } else {
-        stack![row![sidebar, content]].into()
+        layers = layers.push(overlay);
     }
+    if let Some(error_notice) = error_notice {
+        layers = layers.push(error_notice);
+    }
+    layers.into()
 }
@@ -6,7 +6,7 @@ use crate::api::Profile;
 use crate::components::conversation::c_conversation;
 use crate::components::preview_message::c_preview_message;
 use crate::style;
-use crate::types::Emoji;
+use crate::types::{Emoji, EmojiUsage, OutboxMessage, Settings};
 use crate::websockets::Presence;
 use iced::Element;
 use iced::Length;
@@ -21,9 +21,12 @@ pub fn activity<'a>(
     activities: &Vec<crate::api::Message>,
     expanded_conversations: &HashMap<String, (bool, Vec<api::Message>)>,
     search_emojis_input_value: &String,
+    settings: &Settings,
+    emoji_usage: &HashMap<String, EmojiUsage>,
     emoji_map: &'a IndexMap<String, Emoji>,
     users: &'a HashMap<String, Profile>,
     user_presences: &'a HashMap<String, Presence>,
+    outbox: &'a IndexMap<String, OutboxMessage>,
     me: &'a Profile,
     show_plus_emoji_picker: &'a bool,
     emoji_picker_message_id: &'a Option<String>,
@@ -59,9 +62,12 @@ pub fn activity<'a>(
                             false,
                             emoji_map,
                             search_emojis_input_value,
+                            settings,
+                            emoji_usage,
                             users,
                             me,
                             user_presences,
+                            outbox,
                             show_plus_emoji_picker,
                             emoji_picker_message_id,
                             window_size,
@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::str::FromStr;
 
 use crate::Message;
 use crate::api::{self, Chat, Profile};
@@ -7,9 +6,11 @@ use crate::components::horizontal_line::c_horizontal_line;
 use crate::components::picture_and_status::c_picture_and_status;
 use crate::components::toooltip::c_tooltip;
 use crate::components::{
-    cached_image::c_cached_image, chat_message::c_chat_message, message_area::c_message_area,
+    cached_image::c_cached_image, chat_message::c_chat_message,
+    message_area::{MessageAreaState, c_message_area},
+    preview_message::c_draft_preview,
 };
-use crate::types::Emoji;
+use crate::types::{Draft, Emoji, OutboxMessage, Settings};
 use crate::utils::{self, truncate_name};
 use crate::websockets::Presence;
 use crate::widgets::circle::circle;
@@ -17,14 +18,13 @@ use crate::{ChatBody, style};
 
 use iced::alignment::Vertical;
 use iced::task::Handle;
-use iced::widget::text_editor::Content;
 use crate::widgets::click_area::click_area;
-use iced::widget::{Id, column, container, row, space, svg, tooltip};
+use iced::widget::{Id, column, container, row, space, text_input, tooltip};
 use iced::widget::{scrollable, text};
-use iced::{Alignment, Color, Element, Length, Padding, border, padding};
+use iced::{Alignment, Element, Length, Padding, border, padding};
 use indexmap::IndexMap;
 
-fn get_chat_title(chat: &Chat, user_id: &String, users: &HashMap<String, Profile>) -> String {
+pub fn get_chat_title(chat: &Chat, user_id: &String, users: &HashMap<String, Profile>) -> String {
     if let Some(chat_title) = &chat.title {
         chat_title.clone()
     } else {
@@ -67,6 +67,7 @@ fn get_chat_title(chat: &Chat, user_id: &String, users: &HashMap<String, Profile
 }
 
 fn get_chat_picture<'a>(
+    theme: &style::Theme,
     chat: &'a Chat,
     user_id: &'a String,
     users: &'a HashMap<String, Profile>,
@@ -76,6 +77,7 @@ fn get_chat_picture<'a>(
         let identifier = url.replace("https:", "").replace("/", "").replace(":", "");
 
         c_cached_image(
+            theme,
             identifier.clone(),
             Message::AuthorizeImage(url.to_string(), identifier),
             28.0,
@@ -110,6 +112,7 @@ fn get_chat_picture<'a>(
 
         if identifier != "" {
             c_cached_image(
+                theme,
                 identifier.clone(),
                 Message::FetchMergedProfilePicture(identifier, member_profiles),
                 28.0,
@@ -117,14 +120,10 @@ fn get_chat_picture<'a>(
                 4.0,
             )
         } else {
+            let placeholder = theme.colors.image_placeholder;
             container(container(space()))
-                .style(|_| container::Style {
-                    background: Some(
-                        Color::from_str("#b8b4b4")
-                            .expect("Background color is invalid.")
-                            .into(),
-                    ),
-
+                .style(move |_| container::Style {
+                    background: Some(placeholder.into()),
                     ..Default::default()
                 })
                 .width(28.0)
@@ -134,31 +133,62 @@ fn get_chat_picture<'a>(
     }
 }
 
-pub fn chat<'a>(
-    theme: &'a style::Theme,
-    current_chat: Option<&'a Chat>,
-    users_typing: &HashMap<String, HashMap<String, Handle>>,
-    chats: &'a Vec<Chat>,
-    conversation: &Option<&Vec<api::Message>>,
-    chat_message_options: &'a HashMap<String, bool>,
-    chat_list_options: &'a HashMap<String, bool>,
-    search_emojis_input_value: &String,
-    emoji_map: &'a IndexMap<String, Emoji>,
-    users: &'a HashMap<String, Profile>,
-    user_presences: &'a HashMap<String, Presence>,
-    me: &'a Profile,
-    search_chats_input_value: String,
-    message_area_content: &'a Content,
-    message_area_height: &f32,
-    page_body: &'a ChatBody,
-    show_more_options: &'a bool,
-    more_menu_message_id: &'a Option<String>,
-    show_message_area_emoji_picker: &bool,
-    show_message_emoji_picker: &'a bool,
-    show_plus_emoji_picker: &'a bool,
-    emoji_picker_message_id: &'a Option<String>,
-    window_size: &(f32, f32),
-) -> Element<'a, Message> {
+pub struct ChatView<'a> {
+    pub current_chat: Option<&'a Chat>,
+    pub users_typing: &'a HashMap<String, HashMap<String, Handle>>,
+    pub chats: &'a Vec<Chat>,
+    pub conversation: Option<&'a Vec<api::Message>>,
+    pub chat_message_options: &'a HashMap<String, bool>,
+    pub chat_list_options: &'a HashMap<String, bool>,
+    pub settings: &'a Settings,
+    pub emoji_map: &'a IndexMap<String, Emoji>,
+    pub users: &'a HashMap<String, Profile>,
+    pub user_presences: &'a HashMap<String, Presence>,
+    pub me: &'a Profile,
+    pub search_chats_input_value: &'a String,
+    pub editing_message: &'a Option<(String, String)>,
+    pub message_area: MessageAreaState<'a>,
+    pub drafts: &'a HashMap<String, Draft>,
+    pub scheduled_message_count: usize,
+    pub outbox: &'a IndexMap<String, OutboxMessage>,
+    pub page_body: &'a ChatBody,
+    pub show_more_options: &'a bool,
+    pub more_menu_message_id: &'a Option<String>,
+    pub show_message_emoji_picker: &'a bool,
+    pub show_plus_emoji_picker: &'a bool,
+    pub emoji_picker_message_id: &'a Option<String>,
+    pub window_size: (f32, f32),
+}
+
+pub fn chat<'a>(theme: &'a style::Theme, view: ChatView<'a>) -> Element<'a, Message> {
+    let ChatView {
+        current_chat,
+        users_typing,
+        chats,
+        conversation,
+        chat_message_options,
+        chat_list_options,
+        settings,
+        emoji_map,
+        users,
+        user_presences,
+        me,
+        search_chats_input_value,
+        editing_message,
+        message_area,
+        drafts,
+        scheduled_message_count,
+        outbox,
+        page_body,
+        show_more_options,
+        more_menu_message_id,
+        show_message_emoji_picker,
+        show_plus_emoji_picker,
+        emoji_picker_message_id,
+        window_size,
+    } = view;
+    let window_size = &window_size;
+
     let mut page = row![].spacing(0);
 
     // Side panel
@@ -181,13 +211,13 @@ pub fn chat<'a>(
 
         let mut chat_items = row![].align_y(Alignment::Center);
 
-        if !chat.is_read.unwrap_or(true) {
+        if settings.unread_badges && !chat.is_read.unwrap_or(true) {
             chat_items = chat_items.push(circle(2.5, theme.colors.notification))
         } else {
             chat_items = chat_items.push(space().width(5))
         }
 
-        let picture = get_chat_picture(&chat, &me.id, &users);
+        let picture = get_chat_picture(theme, chat, &me.id, users);
 
         if chat.is_one_on_one.unwrap_or(false) {
             let my_mri = format!("8:orgid:{}", me.id);
@@ -222,6 +252,10 @@ pub fn chat<'a>(
         } else if chat.chat_type.clone().unwrap_or("any".to_string()) == "draft" {
             chat_info_column =
                 chat_info_column.push(text("Draft").size(14).color(theme.colors.demo_text));
+        } else if let Some(draft) = drafts.get(&chat.id)
+            && current_chat.is_none_or(|current_chat| current_chat.id != chat.id)
+        {
+            chat_info_column = chat_info_column.push(c_draft_preview(theme, draft, 18));
         } else if !chat.is_one_on_one.unwrap_or(true) {
             chat_info_column = chat_info_column.push(
                 text!("{} members", chat.members.len())
@@ -310,6 +344,19 @@ pub fn chat<'a>(
         .width(Length::Fill)
         .align_x(Alignment::Center)
         .align_y(Vertical::Center),
+        container(
+            text_input("Search chats...", search_chats_input_value)
+                .on_input(Message::SearchChatsContentChanged)
+                .padding(6)
+                .id("search_chats_input")
+                .style(|_, _| theme.stylesheet.input)
+        )
+        .padding(Padding {
+            top: 0.0,
+            bottom: 8.0,
+            left: 8.0,
+            right: 8.0
+        }),
         c_horizontal_line(theme, Length::Fill),
         container(space().width(Length::Fill).height(2)).style(|_| container::Style {
             background: Some(theme.colors.foreground.into()),
@@ -330,7 +377,7 @@ pub fn chat<'a>(
 
     if let Some(current_chat) = current_chat {
         let title = truncate_name(get_chat_title(&current_chat, &me.id, &users), 52);
-        let picture = get_chat_picture(&current_chat, &me.id, &users);
+        let picture = get_chat_picture(theme, current_chat, &me.id, users);
 
         // Chat page title
 
@@ -343,7 +390,7 @@ pub fn chat<'a>(
                 if !is_one_on_one {
                     row![
                         space().width(8),
-                        svg(utils::get_image_dir().join("pencil.svg"))
+                        utils::icon(theme, "pencil.svg")
                             .width(17)
                             .height(17),
                     ]
@@ -357,7 +404,7 @@ pub fn chat<'a>(
                 row![
                     tooltip(
                         click_area(
-                            svg(utils::get_image_dir().join("users-round.svg"))
+                            utils::icon(theme, "users-round.svg")
                                 .width(19)
                                 .height(19)
                         )
@@ -368,7 +415,7 @@ pub fn chat<'a>(
                     ),
                     tooltip(
                         click_area(
-                            svg(utils::get_image_dir().join("user-round-plus.svg"))
+                            utils::icon(theme, "user-round-plus.svg")
                                 .width(19)
                                 .height(19)
                         )
@@ -377,6 +424,26 @@ pub fn chat<'a>(
                         c_tooltip(theme, "Add Users to DM"),
                         tooltip::Position::Bottom
                     ),
+                    if scheduled_message_count > 0 {
+                        row![tooltip(
+                            click_area(
+                                row![
+                                    utils::icon(theme, "clock.svg")
+                                        .width(19)
+                                        .height(19),
+                                    text(scheduled_message_count).size(14)
+                                ]
+                                .align_y(Alignment::Center)
+                                .spacing(4)
+                            )
+                            .on_press(Message::ToggleScheduledMessages)
+                            .interaction(iced::mouse::Interaction::Pointer),
+                            c_tooltip(theme, "Scheduled Messages"),
+                            tooltip::Position::Bottom
+                        )]
+                    } else {
+                        row![]
+                    },
                 ]
                 .spacing(14)
             )
@@ -402,7 +469,8 @@ pub fn chat<'a>(
 
         let body = match page_body {
             ChatBody::Messages => {
-                let mut message_column = column![].spacing(10).padding(Padding {
+                let density = settings.message_density.scale();
+                let mut message_column = column![].spacing(10.0 * density).padding(Padding {
                     left: 8.0,
                     right: 8.0,
                     top: 0.0,
@@ -422,12 +490,15 @@ pub fn chat<'a>(
                             users,
                             me,
                             user_presences,
+                            outbox,
                             show_more_options,
                             more_menu_message_id,
                             show_message_emoji_picker,
                             show_plus_emoji_picker,
                             emoji_picker_message_id,
-                            search_emojis_input_value,
+                            message_area.search_emojis_input_value,
+                            settings,
+                            message_area.emoji_usage,
                             window_size,
                         ) {
                             message_column = message_column.push(message_element);
@@ -497,6 +568,7 @@ pub fn chat<'a>(
 
                     // The Teams api *might still work when the username is wrong
                     let user_picture = c_cached_image(
+                        theme,
                         identifier.clone(),
                         Message::FetchUserImage(
                             identifier,
@@ -558,7 +630,26 @@ pub fn chat<'a>(
 
         content_page = content_page.push(body);
 
-        if let Some(chat_typing) = users_typing.get(&current_chat.id) {
+        let is_editing = editing_message
+            .as_ref()
+            .is_some_and(|(conversation_id, _)| *conversation_id == current_chat.id);
+
+        if is_editing {
+            content_page = content_page.push(
+                container(
+                    text("Editing message, press Escape to cancel")
+                        .size(14)
+                        .color(theme.colors.demo_text),
+                )
+                .padding(Padding {
+                    top: 4.0,
+                    right: 10.0,
+                    bottom: 3.0,
+                    left: 10.0,
+                })
+                .height(25),
+            );
+        } else if let Some(chat_typing) = users_typing.get(&current_chat.id) {
             if !chat_typing.is_empty() {
                 let typers = chat_typing
                     .keys()
@@ -598,12 +689,9 @@ pub fn chat<'a>(
 
         let message_area = container(c_message_area(
             theme,
-            message_area_content,
-            &None,
+            message_area,
             crate::Page::Chat(None, page_body.clone()),
-            message_area_height,
-            show_message_area_emoji_picker,
-            search_emojis_input_value,
+            settings,
             emoji_map,
             window_size,
         ))
@@ -1,6 +1,6 @@
 use crate::Message;
 use iced::widget::{column, container, rich_text, span, text, text_input};
-use iced::{Alignment, Color, Element, Font, Length};
+use iced::{Alignment, Element, Font, Length};
 
 pub fn login<'a>(theme: &'a crate::style::Theme, code: &'a Option<String>, session_expired: bool) -> Element<'a, Message> {
     let code = code.as_deref().unwrap_or("...");
@@ -10,7 +10,7 @@ pub fn login<'a>(theme: &'a crate::style::Theme, code: &'a Option<String>, sessi
         content = content.push(
             container(
                 text("Your session has expired. Please sign in again.")
-                    .color(Color::from_rgb(1.0, 0.6, 0.2))
+                    .color(theme.colors.warning)
             )
             .align_x(Alignment::Center)
             .width(Length::Fill)
@@ -0,0 +1,596 @@
+use crate::Message;
+use crate::api::Profile;
+use crate::components::horizontal_line::c_horizontal_line;
+use crate::style;
+use crate::types::{MessageDensity, Settings, SettingsSection, Shortcut, ThemePreference};
+use crate::widgets::click_area::click_area;
+use iced::alignment::Vertical;
+use iced::widget::{Column, checkbox, column, container, row, scrollable, text, text_input};
+use iced::{Border, Element, Length, Padding, border};
+
+fn section_item<'a>(
+    theme: &'a style::Theme,
+    section: SettingsSection,
+    is_selected: bool,
+) -> Element<'a, Message> {
+    click_area(
+        container(text(section.title()))
+            .width(190)
+            .padding(Padding {
+                top: 4.0,
+                bottom: 4.0,
+                left: 8.0,
+                right: 8.0,
+            })
+            .style(move |_| container::Style {
+                background: Some(if is_selected {
+                    theme.colors.foreground_button_nobg_selected.into()
+                } else {
+                    theme.colors.foreground.into()
+                }),
+                border: border::rounded(6),
+                ..Default::default()
+            }),
+    )
+    .on_press(Message::OpenSettings(section))
+    .interaction(iced::mouse::Interaction::Pointer)
+    .into()
+}
+
+fn setting_label<'a>(
+    theme: &'a style::Theme,
+    label: &'a str,
+    description: &'a str,
+) -> Element<'a, Message> {
+    column![
+        text(label),
+        text(description).size(14).color(theme.colors.demo_text)
+    ]
+    .spacing(2)
+    .width(Length::Fill)
+    .into()
+}
+
+fn toggle_setting<'a>(
+    theme: &'a style::Theme,
+    label: &'a str,
+    description: &'a str,
+    is_checked: bool,
+    updated: Settings,
+) -> Element<'a, Message> {
+    row![
+        setting_label(theme, label, description),
+        checkbox(is_checked)
+            .on_toggle(move |_| Message::UpdateSettings(updated.clone()))
+            .style(|_, _| checkbox::Style {
+                background: theme.colors.foreground_surface.into(),
+                border: border::rounded(2),
+                icon_color: theme.colors.text,
+                text_color: None,
+            })
+    ]
+    .spacing(12)
+    .align_y(Vertical::Center)
+    .into()
+}
+
+fn option_chip<'a>(
+    theme: &'a style::Theme,
+    label: &'a str,
+    is_selected: bool,
+    updated: Settings,
+) -> Element<'a, Message> {
+    click_area(
+        container(text(label))
+            .padding(Padding {
+                top: 4.0,
+                bottom: 4.0,
+                left: 10.0,
+                right: 10.0,
+            })
+            .style(move |_| container::Style {
+                background: Some(if is_selected {
+                    theme.colors.emotion_selected.into()
+                } else {
+                    theme.colors.foreground_surface.into()
+                }),
+                border: Border {
+                    color: if is_selected {
+                        theme.colors.emotion_selected_line
+                    } else {
+                        theme.colors.foreground_surface
+                    },
+                    width: 1.0,
+                    radius: 4.into(),
+                },
+                ..Default::default()
+            }),
+    )
+    .on_press(Message::UpdateSettings(updated))
+    .interaction(iced::mouse::Interaction::Pointer)
+    .into()
+}
+
+fn settings_button<'a>(
+    theme: &'a style::Theme,
+    label: &'a str,
+    message: Message,
+) -> Element<'a, Message> {
+    click_area(
+        container(text(label))
+            .padding(Padding::from([6, 12]))
+            .style(|_| container::Style {
+                background: Some(theme.colors.background_button.into()),
+                border: border::rounded(4),
+                ..Default::default()
+            }),
+    )
+    .on_press(message)
+    .interaction(iced::mouse::Interaction::Pointer)
+    .into()
+}
+
+fn account_section<'a>(
+    theme: &'a style::Theme,
+    me: &'a Profile,
+    tenant: &'a String,
+) -> Column<'a, Message> {
+    column![
+        setting_label(
+            theme,
+            me.display_name.as_deref().unwrap_or("Unknown User"),
+            me.mail.as_deref().unwrap_or("")
+        ),
+        text!("Tenant: {}", tenant)
+            .size(14)
+            .color(theme.colors.demo_text),
+        settings_button(theme, "Sign out", Message::SignOut)
+    ]
+}
+
+fn appearance_section<'a>(
+    theme: &'a style::Theme,
+    settings: &'a Settings,
+    window_size: &(f32, f32),
+) -> Column<'a, Message> {
+    let mut theme_options = row![].spacing(6);
+
+    for (label, preference) in [
+        ("Dark", ThemePreference::Dark),
+        ("Light", ThemePreference::Light),
+        ("System", ThemePreference::System),
+    ] {
+        theme_options = theme_options.push(option_chip(
+            theme,
+            label,
+            settings.theme == preference,
+            Settings {
+                theme: preference,
+                ..settings.clone()
+            },
+        ));
+    }
+
+    let theme_file = match &settings.theme {
+        ThemePreference::File(file_name) => Some(file_name.clone()),
+        _ => None,
+    };
+
+    theme_options = theme_options.push(option_chip(
+        theme,
+        "Theme file",
+        theme_file.is_some(),
+        Settings {
+            theme: ThemePreference::File("theme.json".to_string()),
+            ..settings.clone()
+        },
+    ));
+
+    let mut theme_column = column![
+        setting_label(
+            theme,
+            "Theme",
+            "System follows the light or dark preference of your desktop"
+        ),
+        theme_options
+    ]
+    .spacing(8);
+
+    if let Some(file_name) = theme_file {
+        let settings_clone = settings.clone();
+        theme_column = theme_column.push(
+            text_input("theme.json", &file_name)
+                .padding(8)
+                .width(300)
+                .on_input(move |value| {
+                    Message::UpdateSettings(Settings {
+                        theme: ThemePreference::File(value),
+                        ..settings_clone.clone()
+                    })
+                })
+                .style(|_, _| theme.stylesheet.input),
+        );
+    }
+
+    let mut density_options = row![].spacing(6);
+
+    for (label, density) in [
+        ("Comfortable", MessageDensity::Comfortable),
+        ("Compact", MessageDensity::Compact),
+    ] {
+        density_options = density_options.push(option_chip(
+            theme,
+            label,
+            settings.message_density == density,
+            Settings {
+                message_density: density,
+                ..settings.clone()
+            },
+        ));
+    }
+
+    column![
+        theme_column,
+        column![
+            setting_label(theme, "Message density", "Spacing between messages"),
+            density_options
+        ]
+        .spacing(8),
+        row![
+            setting_label(
+                theme,
+                "Window size",
+                "The size of the window when Squads is started"
+            ),
+            text!(
+                "{} x {}",
+                settings.window_width as u32,
+                settings.window_height as u32
+            ),
+            settings_button(
+                theme,
+                "Use current size",
+                Message::UpdateSettings(Settings {
+                    window_width: window_size.0,
+                    window_height: window_size.1,
+                    ..settings.clone()
+                })
+            )
+        ]
+        .spacing(12)
+        .align_y(Vertical::Center)
+    ]
+}
+
+fn storage_section<'a>(
+    theme: &'a style::Theme,
+    settings: &'a Settings,
+    image_cache_size: u64,
+) -> Column<'a, Message> {
+    let settings_clone = settings.clone();
+
+    column![
+        row![
+            setting_label(
+                theme,
+                "Image cache limit (MB)",
+                "Least recently used images are removed first, 0 disables the limit"
+            ),
+            text_input("500", &settings.image_cache_limit_mb.to_string())
+                .padding(8)
+                .width(100)
+                .on_input(move |value| {
+                    Message::UpdateSettings(Settings {
+                        image_cache_limit_mb: value.parse().unwrap_or(0),
+                        ..settings_clone.clone()
+                    })
+                })
+                .style(|_, _| theme.stylesheet.input)
+        ]
+        .spacing(12)
+        .align_y(Vertical::Center),
+        row![
+            setting_label(
+                theme,
+                "Image cache",
+                "Profile pictures, team pictures and images in messages"
+            ),
+            text!("{:.1} MB", image_cache_size as f64 / 1_000_000.0),
+            settings_button(theme, "Clear", Message::ClearImageCache)
+        ]
+        .spacing(12)
+        .align_y(Vertical::Center)
+    ]
+}
+
+fn keyboard_section<'a>(theme: &'a style::Theme, settings: &'a Settings) -> Column<'a, Message> {
+    let mut shortcuts = column![].spacing(4);
+
+    for shortcut in Shortcut::ALL {
+        let settings_clone = settings.clone();
+
+        shortcuts = shortcuts.push(
+            row![
+                text(shortcut.description())
+                    .size(14)
+                    .color(theme.colors.demo_text)
+                    .width(Length::Fill),
+                text_input(
+                    shortcut.default_binding(),
+                    settings
+                        .keybindings
+                        .get(&shortcut)
+                        .map_or("", String::as_str)
+                )
+                    .size(14)
+                    .padding(6)
+                    .width(160)
+                    .on_input(move |value| {
+                        let mut keybindings = settings_clone.keybindings.clone();
+                        // An empty binding falls back to the default
+                        if value.trim().is_empty() {
+                            keybindings.remove(&shortcut);
+                        } else {
+                            keybindings.insert(shortcut, value);
+                        }
+                        Message::UpdateSettings(Settings {
+                            keybindings,
+                            ..settings_clone.clone()
+                        })
+                    })
+                    .style(|_, _| theme.stylesheet.input)
+            ]
+            .spacing(12)
+            .align_y(Vertical::Center),
+        );
+    }
+
+    column![
+        toggle_setting(
+            theme,
+            "Send with Enter",
+            "When disabled, Enter adds a new line and Shift + Enter sends",
+            settings.send_on_enter,
+            Settings {
+                send_on_enter: !settings.send_on_enter,
+                ..settings.clone()
+            }
+        ),
+        toggle_setting(
+            theme,
+            "Rich text composer",
+            "Formatting is applied to the selection and shown while writing, instead of markdown",
+            settings.rich_text_composer,
+            Settings {
+                rich_text_composer: !settings.rich_text_composer,
+                ..settings.clone()
+            }
+        ),
+        column![
+            setting_label(
+                theme,
+                "Shortcuts",
+                "For example Ctrl+Shift+A, press ? to see all shortcuts"
+            ),
+            shortcuts
+        ]
+        .spacing(8)
+    ]
+}
+
+fn spelling_section<'a>(
+    theme: &'a style::Theme,
+    settings: &'a Settings,
+    spelling_languages: &'a Vec<String>,
+    personal_dictionary_size: Option<usize>,
+) -> Column<'a, Message> {
+    let mut language_options = row![option_chip(
+        theme,
+        "Off",
+        settings.spell_check_language.is_none(),
+        Settings {
+            spell_check_language: None,
+            ..settings.clone()
+        },
+    )]
+    .spacing(6);
+
+    for language in spelling_languages {
+        language_options = language_options.push(option_chip(
+            theme,
+            language,
+            settings.spell_check_language.as_ref() == Some(language),
+            Settings {
+                spell_check_language: Some(language.clone()),
+                ..settings.clone()
+            },
+        ));
+    }
+
+    let mut spelling_column = column![
+        column![
+            setting_label(
+                theme,
+                "Spell checking",
+                "Uses the Hunspell dictionaries in /usr/share/hunspell and the dictionaries \
+                 folder of the config directory, right click a word for suggestions"
+            ),
+            language_options.wrap()
+        ]
+        .spacing(8)
+    ];
+
+    if let Some(size) = personal_dictionary_size {
+        spelling_column = spelling_column.push(
+            row![
+                setting_label(
+                    theme,
+                    "Personal dictionary",
+                    "Words added from the message area, for every language"
+                ),
+                text!("{} words", size),
+                settings_button(theme, "Clear", Message::ClearPersonalDictionary)
+            ]
+            .spacing(12)
+            .align_y(Vertical::Center),
+        );
+    }
+
+    spelling_column
+}
+
+pub struct SettingsView<'a> {
+    pub section: &'a SettingsSection,
+    pub settings: &'a Settings,
+    pub me: &'a Profile,
+    pub tenant: &'a String,
+    pub image_cache_size: u64,
+    pub spelling_languages: &'a Vec<String>,
+    pub personal_dictionary_size: Option<usize>, // Set when a spell checker is loaded
+    pub window_size: (f32, f32),
+}
+
+pub fn settings<'a>(theme: &'a style::Theme, view: SettingsView<'a>) -> Element<'a, Message> {
+    let SettingsView {
+        section,
+        settings,
+        me,
+        tenant,
+        image_cache_size,
+        spelling_languages,
+        personal_dictionary_size,
+        window_size,
+    } = view;
+    let window_size = &window_size;
+
+    let mut sections_column = column![
+        container(text("Settings").size(20)).padding(Padding {
+            top: 10.0,
+            bottom: 8.0,
+            left: 8.0,
+            right: 0.0,
+        })
+    ]
+    .spacing(4)
+    .padding(Padding {
+        right: 4.0,
+        left: 6.0,
+        top: 6.0,
+        bottom: 6.0,
+    });
+
+    for item in SettingsSection::ALL {
+        sections_column = sections_column.push(section_item(theme, item, item == *section));
+    }
+
+    let sections_column = container(sections_column)
+        .width(200)
+        .height(Length::Fill)
+        .style(|_| container::Style {
+            background: Some(theme.colors.foreground.into()),
+            ..Default::default()
+        });
+
+    let section_content = match section {
+        SettingsSection::Account => account_section(theme, me, tenant),
+        SettingsSection::Appearance => appearance_section(theme, settings, window_size),
+        SettingsSection::Notifications => column![
+            toggle_setting(
+                theme,
+                "Unread badges",
+                "Highlight chats with unread messages",
+                settings.unread_badges,
+                Settings {
+                    unread_badges: !settings.unread_badges,
+                    ..settings.clone()
+                }
+            ),
+            toggle_setting(
+                theme,
+                "Desktop notifications",
+                "Show a notification for new messages in chats you don't have open",
+                settings.desktop_notifications,
+                Settings {
+                    desktop_notifications: !settings.desktop_notifications,
+                    ..settings.clone()
+                }
+            ),
+            toggle_setting(
+                theme,
+                "Notification sound",
+                "Play a sound with desktop notifications",
+                settings.notification_sound,
+                Settings {
+                    notification_sound: !settings.notification_sound,
+                    ..settings.clone()
+                }
+            ),
+            toggle_setting(
+                theme,
+                "Only mentions",
+                "Only notify when you are mentioned",
+                settings.notify_mentions_only,
+                Settings {
+                    notify_mentions_only: !settings.notify_mentions_only,
+                    ..settings.clone()
+                }
+            )
+        ],
+        SettingsSection::Privacy => column![
+            toggle_setting(
+                theme,
+                "Read receipts",
+                "Let others know when you have read their messages",
+                settings.send_read_receipts,
+                Settings {
+                    send_read_receipts: !settings.send_read_receipts,
+                    ..settings.clone()
+                }
+            ),
+            toggle_setting(
+                theme,
+                "Typing indicator",
+                "Let others know when you are typing",
+                settings.send_typing_indicator,
+                Settings {
+                    send_typing_indicator: !settings.send_typing_indicator,
+                    ..settings.clone()
+                }
+            )
+        ],
+        SettingsSection::Storage => storage_section(theme, settings, image_cache_size),
+        SettingsSection::Keyboard => keyboard_section(theme, settings),
+        SettingsSection::Spelling => {
+            spelling_section(theme, settings, spelling_languages, personal_dictionary_size)
+        }
+    };
+
+    let content = container(
+        scrollable(
+            column![
+                text(section.title()).size(20),
+                c_horizontal_line(theme, Length::Fill),
+                section_content.spacing(20)
+            ]
+            .spacing(12)
+            .max_width(700)
+            .padding(Padding {
+                top: 16.0,
+                bottom: 16.0,
+                left: 24.0,
+                right: 24.0,
+            }),
+        )
+        .direction(scrollable::Direction::Vertical(
+            scrollable::Scrollbar::new()
+                .width(4)
+                .spacing(0)
+                .scroller_width(4),
+        ))
+        .style(|_, _| theme.stylesheet.scrollable),
+    )
+    .width(Length::Fill)
+    .height(Length::Fill);
+
+    row![sections_column, content].spacing(0).into()
+}
@@ -1,40 +1,180 @@
-use crate::Message;
-use crate::api::{Channel, Profile, Team, TeamConversations};
+use crate::api::{
+    Channel, DriveItem, OnenotePage, PlannerTask, Profile, Team, TeamConversations, TeamsTab,
+};
+use crate::components::channel_tab::c_channel_tab;
 use crate::components::horizontal_line::c_horizontal_line;
-use crate::components::{conversation::c_conversation, message_area::c_message_area};
+use crate::components::preview_message::c_draft_preview;
+use crate::components::team_files::c_team_files;
+use crate::components::toooltip::c_tooltip;
+use crate::components::conversation::c_conversation;
+use crate::components::message_area::{MessageAreaState, c_message_area};
 use crate::style;
-use crate::types::Emoji;
-use crate::utils::truncate_name;
+use crate::types::{ChannelSettings, Draft, Emoji, FilesSortColumn, OutboxMessage, Settings};
+use crate::{Message, TeamBody};
+use crate::utils::{self, tab_plan_id, truncate_name};
 use crate::websockets::Presence;
-use directories::ProjectDirs;
 use iced::alignment::{Horizontal, Vertical};
-use iced::widget::text_editor::Content;
 use crate::widgets::click_area::click_area;
-use iced::widget::{Column, Id, column, container, row, scrollable, space, text};
-use iced::{Element, Length, Padding, border, font, padding};
+use iced::widget::{Column, Id, column, container, row, scrollable, space, text, tooltip};
+use iced::{Alignment, Element, Length, Padding, border, font, padding};
 use indexmap::IndexMap;
 use std::collections::HashMap;
 
-pub fn team<'a>(
+fn c_channel_item<'a>(
     theme: &'a style::Theme,
-    team: &mut Team,
+    team_id: &str,
+    channel: Channel,
     page_channel: &Channel,
-    conversations: &Option<&TeamConversations>,
-    reply_options: &HashMap<String, bool>,
     channel_list_options: &'a HashMap<String, bool>,
-    search_emojis_input_value: &String,
-    emoji_map: &'a IndexMap<String, Emoji>,
-    users: &HashMap<String, Profile>,
-    me: &Profile,
-    user_presences: &'a HashMap<String, Presence>,
-    subject_input_content: &Option<String>,
-    message_area_content: &'a Content,
-    message_area_height: &f32,
-    show_message_area_emoji_picker: &bool,
-    show_plus_emoji_picker: &bool,
-    emoji_picker_message_id: &Option<String>,
-    window_size: &(f32, f32),
+    channel_settings: &HashMap<String, ChannelSettings>,
+    drafts: &HashMap<String, Draft>,
 ) -> Element<'a, Message> {
+    let settings = channel_settings.get(&channel.id).cloned().unwrap_or_default();
+
+    let is_selected = channel.id == page_channel.id;
+    let is_hovered = channel_list_options
+        .get(&channel.id)
+        .unwrap_or(&false)
+        .to_owned();
+
+    let mut channel_info_column = column![text(truncate_name(channel.display_name.clone(), 16))];
+    if let Some(draft) = drafts.get(&channel.id)
+        && !is_selected
+    {
+        channel_info_column = channel_info_column.push(c_draft_preview(theme, draft, 14));
+    }
+
+    let mut channel_row = row![container(channel_info_column).width(Length::Fill)]
+    .spacing(8)
+    .align_y(Vertical::Center);
+
+    if is_hovered || settings.favourite {
+        channel_row = channel_row.push(
+            click_area(
+                utils::icon(theme, if settings.favourite {
+                    "star-filled.svg"
+                } else {
+                    "star.svg"
+                })
+                .width(16)
+                .height(16),
+            )
+            .on_press(Message::ToggleFavouriteChannel(channel.id.clone()))
+            .interaction(iced::mouse::Interaction::Pointer),
+        );
+    }
+
+    if is_hovered {
+        channel_row = channel_row.push(
+            click_area(
+                utils::icon(theme, if settings.hidden {
+                    "eye.svg"
+                } else {
+                    "eye-off.svg"
+                })
+                .width(16)
+                .height(16),
+            )
+            .on_press(Message::ToggleHiddenChannel(channel.id.clone()))
+            .interaction(iced::mouse::Interaction::Pointer),
+        );
+    }
+
+    click_area(
+        container(channel_row)
+            .style(move |_| {
+                if is_selected {
+                    container::Style {
+                        background: Some(theme.colors.foreground_button_nobg_selected.into()),
+                        border: border::rounded(6),
+                        ..Default::default()
+                    }
+                } else if is_hovered {
+                    container::Style {
+                        background: Some(theme.colors.foreground_button_nobg_hovered.into()),
+                        border: border::rounded(6),
+                        ..Default::default()
+                    }
+                } else {
+                    container::Style {
+                        background: Some(theme.colors.foreground.into()),
+                        border: border::rounded(6),
+                        ..Default::default()
+                    }
+                }
+            })
+            .padding(Padding::from([0, 8]))
+            .align_y(Vertical::Center)
+            .height(40)
+            .width(216),
+    )
+    .on_enter(Message::PrefetchTeam(team_id.to_string(), channel.id.clone()))
+    .on_exit(Message::StopShowChannelListOptions(channel.id.clone()))
+    .on_press(Message::OpenTeam(team_id.to_string(), channel.id))
+    .interaction(iced::mouse::Interaction::Pointer)
+    .into()
+}
+
+pub struct TeamView<'a> {
+    pub team: Team,
+    pub page_channel: Channel,
+    pub conversations: Option<&'a TeamConversations>,
+    pub reply_options: &'a HashMap<String, bool>,
+    pub channel_list_options: &'a HashMap<String, bool>,
+    pub settings: &'a Settings,
+    pub emoji_map: &'a IndexMap<String, Emoji>,
+    pub users: &'a HashMap<String, Profile>,
+    pub me: &'a Profile,
+    pub user_presences: &'a HashMap<String, Presence>,
+    pub scheduled_message_count: usize,
+    pub outbox: &'a IndexMap<String, OutboxMessage>,
+    pub message_area: MessageAreaState<'a>,
+    pub show_plus_emoji_picker: &'a bool,
+    pub emoji_picker_message_id: &'a Option<String>,
+    pub window_size: (f32, f32),
+    pub team_body: &'a TeamBody,
+    pub files_location: Option<&'a Vec<DriveItem>>,
+    pub files_items: Option<&'a Vec<DriveItem>>,
+    pub files_sort: &'a (FilesSortColumn, bool),
+    pub channel_tabs: Option<&'a Vec<TeamsTab>>,
+    pub planner_tasks: &'a HashMap<String, Vec<PlannerTask>>,
+    pub channel_notes: Option<&'a Vec<OnenotePage>>,
+    pub channel_settings: &'a HashMap<String, ChannelSettings>,
+    pub show_hidden_channels: bool,
+    pub drafts: &'a HashMap<String, Draft>,
+}
+
+pub fn team<'a>(theme: &'a style::Theme, view: TeamView<'a>) -> Element<'a, Message> {
+    let TeamView {
+        mut team,
+        page_channel,
+        conversations,
+        reply_options,
+        channel_list_options,
+        settings,
+        emoji_map,
+        users,
+        me,
+        user_presences,
+        scheduled_message_count,
+        outbox,
+        message_area,
+        show_plus_emoji_picker,
+        emoji_picker_message_id,
+        window_size,
+        team_body,
+        files_location,
+        files_items,
+        files_sort,
+        channel_tabs,
+        planner_tasks,
+        channel_notes,
+        channel_settings,
+        show_hidden_channels,
+        drafts,
+    } = view;
+    let (team, page_channel, window_size) = (&mut team, &page_channel, &window_size);
+
     let mut conversation_column = column![].spacing(12).padding(Padding {
         left: 8.0,
         right: 8.0,
@@ -61,10 +201,13 @@ pub fn team<'a>(
                 conversation.id,
                 show_replies,
                 emoji_map,
-                search_emojis_input_value,
+                message_area.search_emojis_input_value,
+                settings,
+                message_area.emoji_usage,
                 users,
                 me,
                 user_presences,
+                outbox,
                 show_plus_emoji_picker,
                 emoji_picker_message_id,
                 window_size,
@@ -98,12 +241,9 @@ pub fn team<'a>(
 
     let message_area = container(c_message_area(
         theme,
-        message_area_content,
-        subject_input_content,
-        crate::Page::Team(None, None),
-        message_area_height,
-        show_message_area_emoji_picker,
-        search_emojis_input_value,
+        message_area,
+        crate::Page::Team(None, None, TeamBody::Conversations),
+        settings,
         emoji_map,
         window_size,
     ))
@@ -114,7 +254,22 @@ pub fn team<'a>(
         bottom: 6.0,
     });
 
-    let content_page = column![
+    let content_body: Element<'a, Message> = match team_body {
+        TeamBody::Files => c_team_files(theme, files_location, files_items, files_sort),
+        TeamBody::Tab(tab_id) => {
+            let tab = channel_tabs.and_then(|tabs| tabs.iter().find(|tab| &tab.id == tab_id));
+
+            if let Some(tab) = tab {
+                let tasks = tab_plan_id(tab).and_then(|plan_id| planner_tasks.get(&plan_id));
+                c_channel_tab(theme, tab, tasks, channel_notes)
+            } else {
+                container(text("Loading tab...").color(theme.colors.demo_text))
+                    .padding(28)
+                    .height(Length::Fill)
+                    .into()
+            }
+        }
+        TeamBody::Conversations => column![
         if !is_empty {
             conversation_scrollbar
         } else {
@@ -125,49 +280,165 @@ pub fn team<'a>(
         space().height(7),
         message_area
     ]
-    .spacing(7);
+    .spacing(7)
+    .into(),
+    };
 
-    let project_dirs = ProjectDirs::from("", "ianterzo", "squads");
+    // Channel tabs
 
-    let mut image_path = project_dirs.unwrap().cache_dir().to_path_buf();
-    image_path.push("image-cache");
-    image_path.push(format!(
-        "{}.jpeg",
-        team.picture_e_tag
-            .clone()
-            .unwrap_or(team.display_name.clone())
-    ));
+    let mut tab_items = vec![
+        ("Posts".to_string(), TeamBody::Conversations),
+        ("Files".to_string(), TeamBody::Files),
+    ];
+
+    if let Some(channel_tabs) = channel_tabs {
+        for tab in channel_tabs {
+            tab_items.push((
+                truncate_name(tab.display_name.clone(), 20),
+                TeamBody::Tab(tab.id.clone()),
+            ));
+        }
+    }
+
+    let mut tab_bar = row![].spacing(4).align_y(Vertical::Center);
+
+    for (label, body) in tab_items {
+        let is_selected = &body == team_body;
+
+        tab_bar = tab_bar.push(
+            click_area(
+                container(text(label))
+                    .padding(Padding {
+                        top: 4.0,
+                        bottom: 4.0,
+                        left: 10.0,
+                        right: 10.0,
+                    })
+                    .style(move |_| container::Style {
+                        background: Some(if is_selected {
+                            theme.colors.foreground_button_nobg_selected.into()
+                        } else {
+                            theme.colors.foreground.into()
+                        }),
+                        border: border::rounded(6),
+                        ..Default::default()
+                    }),
+            )
+            .on_press(Message::OpenChannelTab(body))
+            .interaction(iced::mouse::Interaction::Pointer),
+        );
+    }
+
+    let channel_header = column![
+        container(
+            column![
+                row![
+                    text(truncate_name(page_channel.display_name.clone(), 40)).font(font::Font {
+                        weight: font::Weight::Bold,
+                        ..Default::default()
+                    }),
+                    if scheduled_message_count > 0 {
+                        container(tooltip(
+                            click_area(
+                                row![
+                                    utils::icon(theme, "clock.svg")
+                                        .width(19)
+                                        .height(19),
+                                    text(scheduled_message_count).size(14)
+                                ]
+                                .align_y(Alignment::Center)
+                                .spacing(4),
+                            )
+                            .on_press(Message::ToggleScheduledMessages)
+                            .interaction(iced::mouse::Interaction::Pointer),
+                            c_tooltip(theme, "Scheduled Messages"),
+                            tooltip::Position::Bottom,
+                        ))
+                        .align_right(Length::Fill)
+                    } else {
+                        container(space())
+                    },
+                ]
+                .align_y(Alignment::Center),
+                scrollable(tab_bar).direction(scrollable::Direction::Horizontal(
+                    scrollable::Scrollbar::new()
+                        .width(0)
+                        .spacing(0)
+                        .scroller_width(0),
+                )),
+            ]
+            .spacing(4)
+        )
+        .padding(Padding {
+            top: 6.0,
+            bottom: 6.0,
+            left: 10.0,
+            right: 10.0,
+        }),
+        c_horizontal_line(theme, Length::Fill)
+    ];
+
+    let content_page = column![channel_header, content_body];
 
     let team_info = column![
         container(
-            text!("{}", truncate_name(team.display_name.clone(), 24)).font(font::Font {
-                weight: font::Weight::Bold,
-                ..Default::default()
-            })
+            row![
+                container(
+                    text!("{}", truncate_name(team.display_name.clone(), 20)).font(font::Font {
+                        weight: font::Weight::Bold,
+                        ..Default::default()
+                    })
+                )
+                .width(Length::Fill),
+                click_area(
+                    utils::icon(theme, "settings.svg")
+                        .width(18)
+                        .height(18)
+                )
+                .on_press(Message::ToggleManageTeam)
+                .interaction(iced::mouse::Interaction::Pointer)
+            ]
+            .align_y(Vertical::Center)
         )
-        .padding(padding::left(10))
+        .padding(Padding {
+            top: 0.0,
+            bottom: 0.0,
+            left: 10.0,
+            right: 14.0,
+        })
+        .width(230)
         .height(43)
         .align_y(Vertical::Center),
         c_horizontal_line(theme, 230.into())
     ]
     .spacing(5);
+    let files_selected = *team_body == TeamBody::Files;
+
     let additionals = column![
         container(column![
-            container(text!("Files"))
-                .width(190)
-                .padding(Padding {
-                    top: 4.0,
-                    bottom: 4.0,
-                    left: 8.0,
-                    right: 8.0,
-                })
-                .style(move |_| {
-                    container::Style {
-                        background: Some(theme.colors.foreground.into()),
-                        border: border::rounded(6),
-                        ..Default::default()
-                    }
-                })
+            click_area(
+                container(text!("Files"))
+                    .width(190)
+                    .padding(Padding {
+                        top: 4.0,
+                        bottom: 4.0,
+                        left: 8.0,
+                        right: 8.0,
+                    })
+                    .style(move |_| {
+                        container::Style {
+                            background: Some(if files_selected {
+                                theme.colors.foreground_button_nobg_selected.into()
+                            } else {
+                                theme.colors.foreground.into()
+                            }),
+                            border: border::rounded(6),
+                            ..Default::default()
+                        }
+                    })
+            )
+            .on_press(Message::OpenChannelTab(TeamBody::Files))
+            .interaction(iced::mouse::Interaction::Pointer)
         ])
         .padding(Padding {
             top: 8.0,
@@ -194,52 +465,66 @@ pub fn team<'a>(
         bottom: 6.0,
     });
 
-    team.channels.sort_by_key(|item| item.id != team.id);
-    for channel in team.channels.clone() {
-        let page_channel_cloned = page_channel.clone();
-        let channel_cloned = channel.clone();
+    // General first, then favourites
+    team.channels.sort_by_key(|item| {
+        (
+            item.id != team.id,
+            !channel_settings
+                .get(&item.id)
+                .is_some_and(|settings| settings.favourite),
+        )
+    });
+
+    let (hidden_channels, channels): (Vec<Channel>, Vec<Channel>) =
+        team.channels.clone().into_iter().partition(|channel| {
+            channel_settings
+                .get(&channel.id)
+                .is_some_and(|settings| settings.hidden)
+        });
+
+    for channel in channels {
+        channels_coloumn = channels_coloumn.push(c_channel_item(
+            theme,
+            &team.id,
+            channel,
+            page_channel,
+            channel_list_options,
+            channel_settings,
+            drafts,
+        ));
+    }
+
+    if !hidden_channels.is_empty() {
         channels_coloumn = channels_coloumn.push(
             click_area(
-                container(text(truncate_name(channel.display_name, 16)))
-                    .style(move |_| {
-                        if channel_cloned.id == page_channel_cloned.id {
-                            container::Style {
-                                background: Some(
-                                    theme.colors.foreground_button_nobg_selected.into(),
-                                ),
-                                border: border::rounded(6),
-                                ..Default::default()
-                            }
-                        } else if channel_list_options
-                            .get(&channel_cloned.id)
-                            .unwrap_or(&false)
-                            .to_owned()
-                        {
-                            container::Style {
-                                background: Some(
-                                    theme.colors.foreground_button_nobg_hovered.into(),
-                                ),
-                                border: border::rounded(6),
-                                ..Default::default()
-                            }
-                        } else {
-                            container::Style {
-                                background: Some(theme.colors.foreground.into()),
-                                border: border::rounded(6),
-                                ..Default::default()
-                            }
-                        }
+                container(
+                    text(if show_hidden_channels {
+                        format!("Hide hidden channels ({})", hidden_channels.len())
+                    } else {
+                        format!("Show hidden channels ({})", hidden_channels.len())
                     })
-                    .padding(Padding::from([0, 8]))
-                    .align_y(Vertical::Center)
-                    .height(40)
-                    .width(216),
+                    .size(14)
+                    .color(theme.colors.demo_text),
+                )
+                .padding(Padding::from([6, 8])),
             )
-            .on_enter(Message::PrefetchTeam(team.id.clone(), channel.id.clone()))
-            .on_exit(Message::StopShowChannelListOptions(channel.id.clone()))
-            .on_press(Message::OpenTeam(team.id.clone(), channel.id))
+            .on_press(Message::ToggleShowHiddenChannels)
             .interaction(iced::mouse::Interaction::Pointer),
         );
+
+        if show_hidden_channels {
+            for channel in hidden_channels {
+                channels_coloumn = channels_coloumn.push(c_channel_item(
+                    theme,
+                    &team.id,
+                    channel,
+                    page_channel,
+                    channel_list_options,
+                    channel_settings,
+                    drafts,
+                ));
+            }
+        }
     }
 
     let team_scrollbar = scrollable(channels_coloumn)
@@ -1,14 +1,19 @@
 use crate::Message;
-use crate::components::cached_image::c_cached_gif;
+use crate::components::cached_image::{c_cached_gif, cached_image_path};
 use crate::components::cached_image::c_cached_image;
+use crate::components::code_block::c_code_block;
+use crate::components::media_attachment::c_media_attachment;
+use crate::editing::{ListContinuation, list_continuation};
 use crate::style;
+use crate::api::{Link, UrlPreview};
+use crate::types::{MediaAttachment, MediaKind, ReplyTarget};
+use crate::utils::ams_image_identifier;
 use crate::widgets::circle::circle;
 use crate::widgets::selectable_rich_text::selectable_rich_text;
 use crate::widgets::selectable_text;
 use crate::widgets::selectable_text::selectable_text;
 use base64::Engine as _;
 use base64::engine::general_purpose::STANDARD;
-use directories::ProjectDirs;
 use iced::Alignment;
 use iced::Color;
 use iced::border;
@@ -17,10 +22,11 @@ use iced::padding;
 use crate::widgets::click_area::click_area;
 use iced::widget::space;
 use iced::widget::span;
-use iced::widget::{Column, Container, Row, column, container, row, text};
-use iced::{Element, Font, font};
+use iced::widget::{column, container, row, text};
+use iced::{Border, Element, Font, Length, Padding, font};
 use image::image_dimensions;
 use markdown_it::parser::block::{BlockRule, BlockState};
+use markdown_it::plugins::cmark::block::fence::CodeFence;
 use markdown_it::parser::inline::InlineRule;
 use markdown_it::parser::inline::InlineState;
 use markdown_it::plugins::extra::linkify;
@@ -31,6 +37,7 @@ use scraper::{Html, Selector};
 use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::str::FromStr;
 use xxhash_rust::xxh3::xxh3_64;
 
 #[derive(Debug)]
@@ -131,6 +138,20 @@ impl BlockRule for BlockQuoteScanner {
     }
 }
 
+#[derive(Debug)]
+pub struct CodeSnippet(String, String); // Language, code
+
+// Teams code snippets put the language class on the pre element, markdown puts it on code
+impl NodeValue for CodeSnippet {
+    fn render(&self, _node: &Node, fmt: &mut dyn Renderer) {
+        fmt.open("pre", &[("class", format!("language-{}", self.0))]);
+        fmt.open("code", &[]);
+        fmt.text(&self.1);
+        fmt.close("code");
+        fmt.close("pre");
+    }
+}
+
 #[derive(Debug)]
 // This is a structure that represents your custom Node in AST.
 pub struct NewlineParser;
@@ -169,11 +190,12 @@ pub fn parse_message_markdown(text: String) -> String {
     for line in text.lines() {
         if line.is_empty() && !in_code_block {
             processed.push_str("<p>&nbsp;</p>\n\n");
-        } else if line.starts_with("- ") && line != "- " {
+        } else if matches!(list_continuation(line, "- "), Some(ListContinuation::Continue(_))) {
+            // Indented items stay in the list so they nest
             processed.push_str(&format!("{}\n", line));
             in_list_block = true;
         } else if line.starts_with("```") && !in_code_block {
-            processed.push_str("```\n");
+            processed.push_str(&format!("{}\n", line.trim_end()));
             in_code_block = true;
         } else if line == "```" && in_code_block {
             processed.push_str("```\n");
@@ -199,451 +221,845 @@ pub fn parse_message_markdown(text: String) -> String {
     strikethrough::add(&mut md);
     tables::add(&mut md);
 
-    let html = md.parse(&processed).render();
+    let mut ast = md.parse(&processed);
+    ast.walk_mut(|node, _| {
+        if let Some(fence) = node.cast::<CodeFence>() {
+            let language = fence.info.split_whitespace().next().unwrap_or("plaintext");
+            let snippet = CodeSnippet(language.to_lowercase(), fence.content.clone());
+            node.replace(snippet);
+        }
+    });
+
+    let html = ast.render();
     html
 }
 
-enum DynamicContainer {
-    Row(Row<'static, Message>),
-    RowWrapping(Container<'static, Message>), // I must put it in a container since iced_widget:row:Wrapping is not public
-    Column(Column<'static, Message>),
+pub fn is_media_message(message_type: &str) -> bool {
+    matches!(message_type, "RichText/Media_AudioMsg" | "RichText/Media_Video")
 }
 
-impl DynamicContainer {
-    fn push(self, element: Element<'static, Message>) -> Self {
-        match self {
-            DynamicContainer::Row(mut row) => {
-                row = row.push(element);
-                DynamicContainer::Row(row)
-            }
-            DynamicContainer::RowWrapping(row_container) => {
-                DynamicContainer::RowWrapping(row_container)
-            }
-            DynamicContainer::Column(mut column) => {
-                column = column.push(element);
-                DynamicContainer::Column(column)
-            }
+// Builds the preview of our own links from the Open Graph tags of the page, like Teams does
+pub fn parse_open_graph(page_url: &str, html: &str) -> Option<Link> {
+    let document = Html::parse_document(html);
+
+    let mut tags = HashMap::new();
+    for element in document.select(&Selector::parse("meta").unwrap()) {
+        let key = element.attr("property").or(element.attr("name"));
+        if let (Some(key), Some(content)) = (key, element.attr("content")) {
+            tags.entry(key.to_lowercase()).or_insert(content.trim().to_string());
         }
     }
 
-    fn into_element(self) -> Element<'static, Message> {
-        match self {
-            DynamicContainer::Row(row) => row.into(),
-            DynamicContainer::RowWrapping(row_container) => row_container.into(),
-            DynamicContainer::Column(column) => column.into(),
-        }
+    let title = tags.get("og:title").cloned().or_else(|| {
+        document
+            .select(&Selector::parse("title").unwrap())
+            .next()
+            .map(|title| title.text().collect::<String>().trim().to_string())
+    })?;
+    if title.is_empty() {
+        return None;
     }
 
-    fn wrap(self) -> Self {
-        match self {
-            DynamicContainer::Row(row) => {
-                let wrapped = row.wrap(); // `row.wrap()` moves `row`, so capture the result
-                DynamicContainer::RowWrapping(container(wrapped))
-            }
-            DynamicContainer::RowWrapping(row_container) => {
-                DynamicContainer::RowWrapping(row_container)
-            }
+    let image_url = tags.get("og:image").and_then(|image| {
+        url::Url::parse(page_url)
+            .and_then(|base| base.join(image))
+            .ok()
+            .map(|image_url| image_url.to_string())
+    });
+
+    Some(Link {
+        link_type: "http://schema.skype.com/HyperLink".to_string(),
+        itemid: 0,
+        url: page_url.to_string(),
+        previewenabled: true,
+        preview: Some(UrlPreview {
+            previewfilename: image_url
+                .as_ref()
+                .and_then(|image_url| image_url.rsplit('/').next())
+                .map(|filename| filename.to_string()),
+            previewurl: image_url,
+            preview_type: Some("image".to_string()),
+            title: Some(title),
+            description: tags
+                .get("og:description")
+                .or(tags.get("description"))
+                .cloned(),
+            is_link_unsafe: false,
+        }),
+    })
+}
+
+// Voice messages and videos are URIObjects, AMS videos in html messages are video elements
+fn media_attachment(element: scraper::ElementRef) -> Option<MediaAttachment> {
+    let child = |name: &str| {
+        element
+            .descendants()
+            .filter_map(scraper::ElementRef::wrap)
+            .find(|child| child.value().name() == name)
+    };
+
+    if element.value().name() == "video" {
+        return Some(MediaAttachment {
+            kind: MediaKind::Video,
+            url: element.attr("src")?.to_string(),
+            thumbnail_url: element.attr("poster").map(|poster| poster.to_string()),
+            duration_ms: None,
+            name: None,
+        });
+    }
+
+    let kind = match element.attr("type")? {
+        uri_type if uri_type.starts_with("Audio") => MediaKind::Audio,
+        uri_type if uri_type.starts_with("Video") => MediaKind::Video,
+        _ => return None,
+    };
 
-            DynamicContainer::Column(column) => DynamicContainer::Column(column),
+    Some(MediaAttachment {
+        kind,
+        url: element.attr("uri")?.to_string(),
+        thumbnail_url: element.attr("url_thumbnail").map(|url| url.to_string()),
+        duration_ms: child("duration_ms")
+            .and_then(|duration| duration.text().collect::<String>().trim().parse().ok()),
+        name: child("originalname")
+            .and_then(|name| name.attr("v"))
+            .map(|name| name.to_string()),
+    })
+}
+
+// Line breaks in code blocks are either newlines or br elements
+fn code_block_text(element: scraper::ElementRef) -> String {
+    let mut code = String::new();
+    for node in element.descendants() {
+        if let Some(text) = node.value().as_text() {
+            code.push_str(text);
+        } else if node.value().as_element().is_some_and(|element| element.name() == "br") {
+            code.push('\n');
         }
     }
+    code.trim_end_matches('\n').to_string()
 }
 
-fn transform_html<'a>(
-    theme: &'a style::Theme,
-    element: scraper::ElementRef<'a>,
-    mut cascading_properties: HashMap<&'a str, String>,
-) -> DynamicContainer {
-    let selection_color = theme.colors.text_selection;
+// Block elements start on their own line, everything else flows like text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "div", "ul", "ol", "li", "table", "blockquote", "pre", "codeblock", "hr", "h1", "h2",
+    "h3", "h4", "h5", "h6",
+];
+
+const HEADING_SIZES: [f32; 6] = [24.0, 21.0, 18.0, 16.0, 16.0, 16.0];
+
+// Formatting inherited from the enclosing elements
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub mention: bool,
+    pub link: Option<String>,
+    pub color: Option<Color>,
+    pub highlight: Option<Color>,
+    pub size: Option<f32>,
+}
 
-    let element_tagname = element.value().name();
+// The message html as a layout tree, kept apart from the widgets so it can be tested
+#[derive(Debug, Clone, PartialEq)]
+pub enum HtmlNode {
+    Column(Vec<HtmlNode>),
+    Flow(Vec<HtmlNode>), // Inline content that wraps like text
+    Text(String, TextStyle),
+    LineBreak,
+    Emoji(String),                           // Alt text
+    CustomEmoji(String),                     // AMS url
+    Image(String, Option<f32>, Option<f32>), // AMS url, html width and height
+    Gif(String, Option<f32>, Option<f32>),   // Url, html width and height
+    Media(MediaAttachment),
+    Reply(String, String), // Name, preview
+    Quote(Box<HtmlNode>),
+    InlineCode(String),
+    CodeBlock(Option<String>, String),  // Language, code
+    List(Option<usize>, Vec<HtmlNode>), // First number of ordered lists, items
+    Table(Vec<(bool, Vec<HtmlNode>)>),  // Whether it is a header row, cells
+    Heading(u8, Box<HtmlNode>),
+    Rule,
+}
+
+fn heading_level(element_name: &str) -> Option<u8> {
+    element_name
+        .strip_prefix('h')?
+        .parse()
+        .ok()
+        .filter(|level| (1..=6).contains(level))
+}
 
-    // Initialize container as either row or column based on the tag name
-    let mut dynamic_container = if element_tagname == "body" {
-        DynamicContainer::Column(column![])
-    } else if element_tagname == "p" {
-        DynamicContainer::Row(row![])
+// Word and Outlook write colors by name, these are the ones they use
+const NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("black", [0, 0, 0]),
+    ("white", [255, 255, 255]),
+    ("gray", [128, 128, 128]),
+    ("grey", [128, 128, 128]),
+    ("silver", [192, 192, 192]),
+    ("red", [255, 0, 0]),
+    ("maroon", [128, 0, 0]),
+    ("darkred", [139, 0, 0]),
+    ("yellow", [255, 255, 0]),
+    ("olive", [128, 128, 0]),
+    ("orange", [255, 165, 0]),
+    ("lime", [0, 255, 0]),
+    ("green", [0, 128, 0]),
+    ("darkgreen", [0, 100, 0]),
+    ("aqua", [0, 255, 255]),
+    ("cyan", [0, 255, 255]),
+    ("teal", [0, 128, 128]),
+    ("darkcyan", [0, 139, 139]),
+    ("blue", [0, 0, 255]),
+    ("navy", [0, 0, 128]),
+    ("darkblue", [0, 0, 139]),
+    ("fuchsia", [255, 0, 255]),
+    ("magenta", [255, 0, 255]),
+    ("purple", [128, 0, 128]),
+    ("darkmagenta", [139, 0, 139]),
+];
+
+// The system colors Office pastes for unformatted text and the default text colors of Teams
+const OFFICE_DEFAULT_NAMES: &[&str] = &["windowtext", "window", "auto", "transparent"];
+const OFFICE_DEFAULT_COLORS: &[[u8; 3]] = &[[36, 36, 36], [37, 36, 35]];
+
+// Office defaults are left to the theme so dark themes stay readable
+fn parse_css_color(value: &str) -> Option<Color> {
+    let value = value.trim_end_matches("!important").trim().to_lowercase();
+
+    let channels = if let Some(channels) = value
+        .strip_prefix("rgb(")
+        .or(value.strip_prefix("rgba("))
+    {
+        let channels: Vec<f32> = channels
+            .trim_end_matches(')')
+            .split(',')
+            .filter_map(|channel| channel.trim().parse().ok())
+            .collect();
+        // Fully transparent backgrounds are how Teams writes none
+        if channels.len() < 3 || channels.get(3) == Some(&0.0) {
+            return None;
+        }
+        [channels[0] as u8, channels[1] as u8, channels[2] as u8]
     } else {
-        // Default to a row if no specific tag matches
-        DynamicContainer::Row(row![])
+        // The background shorthand can be followed by an image and repeat
+        let value = value.split_whitespace().next()?;
+        if OFFICE_DEFAULT_NAMES.contains(&value) {
+            return None;
+        }
+
+        match NAMED_COLORS.iter().find(|(name, _)| *name == value) {
+            Some((_, channels)) => *channels,
+            None => {
+                let [r, g, b, _] = Color::from_str(value).ok()?.into_rgba8();
+                [r, g, b]
+            }
+        }
     };
 
-    if matches!(element_tagname, "strong" | "u" | "s" | "i" | "em") {
-        cascading_properties.insert(element_tagname, element_tagname.to_string());
-    } else if element_tagname == "span" {
-        if let Some(attr) = element.attr("itemtype") {
-            if attr == "http://schema.skype.com/Mention" {
-                cascading_properties.insert("span", attr.to_string());
+    if OFFICE_DEFAULT_COLORS.contains(&channels) {
+        return None;
+    }
+
+    Some(Color::from_rgb8(channels[0], channels[1], channels[2]))
+}
+
+fn inline_style(element: scraper::ElementRef, mut style: TextStyle) -> TextStyle {
+    let element_name = element.value().name();
+
+    match element_name {
+        "strong" | "b" | "th" => style.bold = true,
+        "i" | "em" => style.italic = true,
+        "u" | "ins" => style.underline = true,
+        "s" | "strike" | "del" => style.strikethrough = true,
+        "a" => {
+            if let Some(href) = element.attr("href") {
+                style.link = Some(href.to_string());
             }
         }
-    } else if element_tagname == "a" {
-        if let Some(attr) = element.attr("href") {
-            cascading_properties.insert("a", attr.to_string());
+        "span" => {
+            if element.attr("itemtype") == Some("http://schema.skype.com/Mention") {
+                style.mention = true;
+            }
+        }
+        _ => {
+            if let Some(level) = heading_level(element_name) {
+                style.bold = true;
+                style.size = Some(HEADING_SIZES[level as usize - 1]);
+            }
         }
     }
 
-    for child in element.children() {
-        if let Some(child_element) = scraper::ElementRef::wrap(child) {
-            let element_name = child_element.value().name();
-            if child.has_children()
-                && element_name != "blockquote"
-                && element_name != "code"
-                && element_name != "ul"
-                && element_name != "ol"
-            {
-                dynamic_container = dynamic_container.push(
-                    transform_html(theme, child_element, cascading_properties.clone())
-                        .into_element(),
-                );
-            }
-            // Special cases
-            else if element_name == "br" {
-                dynamic_container = dynamic_container.push(space().width(10000).into());
-            } else if element_name == "img" {
-                if let Some(itemtype) = child_element.attr("itemtype") {
-                    if itemtype == "http://schema.skype.com/Emoji" {
-                        if let Some(alt) = child_element.attr("alt") {
-                            dynamic_container = dynamic_container.push(
-                                selectable_rich_text(vec![span::<(), Font>(alt.to_string())])
-                                    .style(move |_| selectable_text::Style {
-                                        color: None,
-                                        selection_color: selection_color,
-                                    })
-                                    .into(),
-                            );
-                        }
-                    } else if itemtype == "http://schema.skype.com/AMSImage" {
-                        // most consistent way to get the image id
-                        let image_url = child_element.attr("src").unwrap().to_string();
-                        let identifier = image_url
-                            .replace("https:", "")
-                            .replace("/", "")
-                            .replace(":", ""); // Windows
-
-                        let mut image_width = 400.0;
-                        let mut image_height = 400.0;
-
-                        let mut image_path = ProjectDirs::from("", "ianterzo", "squads")
-                            .unwrap()
-                            .cache_dir()
-                            .to_path_buf();
-                        image_path.push("image-cache");
-                        image_path.push(format!("{}.jpeg", &identifier));
-
-                        // Use the html sizes if not able to fetch the image dimensions or if the image has not been downloaded yet
-                        match image_dimensions(&image_path) {
-                            Ok((width, height)) => {
-                                image_width = width as f32;
-                                image_height = height as f32;
-                            }
-                            Err(_e) => {
-                                if let Some(width) = child_element.attr("width") {
-                                    let width = width.parse().unwrap();
-                                    image_width = width;
-                                }
-
-                                if let Some(height) = child_element.attr("height") {
-                                    let height = height.parse().unwrap();
-                                    image_height = height;
-                                }
-                            }
-                        }
-
-                        // Limit image sizes
-
-                        if image_width == image_height && image_width > 280.0 {
-                            image_width = 250.0;
-                            image_height = 250.0;
-                        }
-
-                        if image_width >= 420.0 {
-                            let factor = 400.0 / image_width;
-                            image_width = 400.0;
-                            image_height = image_height * factor;
-                        }
-
-                        if image_height >= 380.0 {
-                            let factor = 400.0 / image_height;
-                            image_height = 400.0;
-                            image_width = image_width * factor;
-                        }
-
-                        let team_picture = click_area(c_cached_image(
-                            identifier.clone(),
-                            Message::AuthorizeImage(image_url, identifier.clone()),
-                            image_width,
-                            image_height,
-                            8.0,
-                        ))
-                        .on_press(Message::ExpandImage(identifier, "jpeg".to_string()))
-                        .interaction(mouse::Interaction::Pointer);
-
-                        dynamic_container = dynamic_container.push(team_picture.into());
-                    } else if itemtype == "http://schema.skype.com/Giphy" {
-                        if let Some(image_url) = child_element.attr("src") {
-                            let identifier = xxh3_64(image_url.to_string().as_bytes()).to_string();
-
-                            let mut image_width = 250.0;
-                            let mut image_height = 250.0;
-
-                            if let Some(width) = child_element.attr("width") {
-                                let width = width.parse().unwrap();
-                                image_width = width;
-                            }
-
-                            if let Some(height) = child_element.attr("height") {
-                                let height = height.parse().unwrap();
-                                image_height = height;
-                            }
-                            let team_picture = click_area(c_cached_gif(
-                                identifier.clone(),
-                                Message::DownloadImage(image_url.to_string(), identifier.clone()),
-                                image_width,
-                                image_height,
-                            ))
-                            .on_press(Message::ExpandImage(identifier, "gif".to_string()))
-                            .interaction(mouse::Interaction::Pointer);
-                            dynamic_container = dynamic_container.push(team_picture.into());
-                        } else {
-                            dynamic_container =
-                                dynamic_container.push(text!("Failed to load gif").into());
-                        }
-                    }
+    if let Some(css) = element.attr("style") {
+        for declaration in css.split(';') {
+            let Some((property, value)) = declaration.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match property.trim().to_lowercase().as_str() {
+                "color" => style.color = parse_css_color(value).or(style.color),
+                "background-color" | "background" => {
+                    style.highlight = parse_css_color(value).or(style.highlight)
                 }
-            } else if element_name == "blockquote" {
-                let color = theme.colors.foreground_surface;
-
-                if let Some(itemtype) = child_element.attr("itemtype") {
-                    if itemtype == "http://schema.skype.com/Reply" {
-                        let mut name = "Unknown User".to_string();
-
-                        if let Some(element) = child_element
-                            .select(&Selector::parse("strong").unwrap())
-                            .next()
-                        {
-                            name = element.text().collect::<String>();
-                        }
-
-                        if let Some(element) =
-                            child_element.select(&Selector::parse("p").unwrap()).next()
-                        {
-                            let text = element.text().collect::<String>();
-
-                            dynamic_container = dynamic_container.push(
-                                container(
-                                    container(column![
-                                        text!("{}", name).color(theme.colors.demo_text).size(14),
-                                        text!("{}", text)
-                                    ])
-                                    .padding(5)
-                                    .style(move |_| {
-                                        container::Style {
-                                            background: Some(color.into()),
-                                            border: border::rounded(5),
-                                            ..Default::default()
-                                        }
-                                    }),
-                                )
-                                .padding(padding::bottom(8))
-                                .into(),
-                            );
-                        }
-                    }
-                } else {
-                    dynamic_container = dynamic_container.push(
-                        container(row![
-                            text("| ").color(theme.colors.demo_text),
-                            transform_html(theme, child_element, cascading_properties.clone())
-                                .into_element()
-                        ])
-                        .padding(5)
-                        .style(move |_| container::Style {
-                            background: Some(color.into()),
-                            border: border::rounded(4),
-                            ..Default::default()
-                        })
-                        .into(),
-                    );
-                }
-            } else if element_name == "code" {
-                let color = theme.colors.foreground_surface;
-                let mut raw_code = child_element.inner_html();
-
-                raw_code = raw_code.replace("<br>", "\n");
-
-                let lines_count = raw_code.lines().count();
-
-                let lines: Vec<Element<Message>> = raw_code
-                    .lines()
-                    .map(|line| {
-                        selectable_text(format!("{}", line.to_string()))
-                            .style(move |_| selectable_text::Style {
-                                color: None,
-                                selection_color: selection_color,
-                            })
-                            .into()
-                    })
-                    .collect();
-
-                if lines_count > 1 {
-                    dynamic_container = dynamic_container.push(
-                        container(container(column(lines)).padding(10).style(move |_| {
-                            container::Style {
-                                background: Some(color.into()),
-                                border: border::rounded(5),
-                                ..Default::default()
-                            }
-                        }))
-                        .padding(padding::vertical(6))
-                        .into(),
-                    );
-                } else {
-                    dynamic_container = dynamic_container.push(
-                        container(container(column(lines)).padding(3).style(move |_| {
-                            container::Style {
-                                background: Some(color.into()),
-                                border: border::rounded(3),
-                                ..Default::default()
-                            }
-                        }))
-                        .into(),
-                    );
+                "font-weight"
+                    if value == "bold"
+                        || value.parse::<u32>().is_ok_and(|weight| weight >= 600) =>
+                {
+                    style.bold = true;
                 }
-            } else if element_name == "ul" {
-                let mut ul_list = column![].spacing(2);
-                for li in child_element.select(&Selector::parse("li").unwrap()) {
-                    ul_list = ul_list.push(
-                        row![
-                            space().width(1).height(1),
-                            circle(3.0, Color::from_rgba(1.0, 1.0, 1.0, 1.0)),
-                            transform_html(theme, li, cascading_properties.clone()).into_element()
-                        ]
-                        .spacing(10)
-                        .align_y(Alignment::Center),
-                    );
+                "font-style" if value == "italic" => style.italic = true,
+                "text-decoration" | "text-decoration-line" => {
+                    if value.contains("underline") {
+                        style.underline = true;
+                    }
+                    if value.contains("line-through") {
+                        style.strikethrough = true;
+                    }
                 }
+                _ => {}
+            }
+        }
+    }
 
-                dynamic_container = dynamic_container.push(ul_list.into())
-            } else if element_name == "ol" {
-                let mut ol_list = column![].spacing(2);
-                for (i, li) in child_element
-                    .select(&Selector::parse("li").unwrap())
-                    .enumerate()
-                {
-                    ol_list = ol_list.push(
-                        row![
-                            space().width(10).height(1),
-                            text!("{}. ", i + 1),
-                            transform_html(theme, li, cascading_properties.clone()).into_element()
-                        ]
-                        .align_y(Alignment::Center),
-                    );
-                }
+    style
+}
+
+fn parse_image(element: scraper::ElementRef) -> Option<HtmlNode> {
+    let size = |attribute: &str| element.attr(attribute).and_then(|size| size.parse().ok());
 
-                dynamic_container = dynamic_container.push(ol_list.into())
+    match element.attr("itemtype")? {
+        "http://schema.skype.com/Emoji" => {
+            // Standard emojis point to the static cdn, custom emojis to an AMS object
+            let custom_emoji_url = element
+                .attr("src")
+                .filter(|src| !src.contains("statics.teams.cdn.office.net"));
+
+            match custom_emoji_url {
+                Some(image_url) => Some(HtmlNode::CustomEmoji(image_url.to_string())),
+                None => Some(HtmlNode::Emoji(element.attr("alt")?.to_string())),
             }
-        } else if child.value().is_text() {
-            let mut font_text = Font::with_name("Open Sans Twemoji");
-            let mut color = theme.colors.text;
-            let mut underline = false;
-            let mut strikethrough = false;
-            let mut link_href: Option<String> = None;
+        }
+        "http://schema.skype.com/AMSImage" => Some(HtmlNode::Image(
+            element.attr("src")?.to_string(),
+            size("width"),
+            size("height"),
+        )),
+        "http://schema.skype.com/Giphy" => Some(match element.attr("src") {
+            Some(image_url) => HtmlNode::Gif(image_url.to_string(), size("width"), size("height")),
+            None => HtmlNode::Text("Failed to load gif".to_string(), TextStyle::default()),
+        }),
+        _ => None,
+    }
+}
 
-            if let Some(property) = cascading_properties.get("strong") {
-                // check for consistency
-                if property == "strong" {
-                    font_text.weight = font::Weight::Bold;
+fn parse_list(element: scraper::ElementRef, style: &TextStyle) -> HtmlNode {
+    let mut items: Vec<HtmlNode> = vec![];
+
+    for child in element.children().filter_map(scraper::ElementRef::wrap) {
+        match child.value().name() {
+            "li" => items.push(parse_html_node(child, style)),
+            // Office nests lists directly in the list instead of in an item
+            "ul" | "ol" => {
+                let nested_list = parse_list(child, style);
+                match items.pop() {
+                    Some(HtmlNode::Column(mut blocks)) => {
+                        blocks.push(nested_list);
+                        items.push(HtmlNode::Column(blocks));
+                    }
+                    Some(item) => items.push(HtmlNode::Column(vec![item, nested_list])),
+                    None => items.push(nested_list),
                 }
             }
-            if let Some(property) = cascading_properties.get("u") {
-                if property == "u" {
-                    underline = true;
-                }
+            _ => {}
+        }
+    }
+
+    let start = (element.value().name() == "ol").then(|| {
+        element
+            .attr("start")
+            .and_then(|start| start.parse().ok())
+            .unwrap_or(1)
+    });
+
+    HtmlNode::List(start, items)
+}
+
+fn parse_table(element: scraper::ElementRef, style: &TextStyle) -> HtmlNode {
+    let mut rows = vec![];
+
+    // Rows of nested tables belong to their own table
+    let table_rows = element
+        .descendants()
+        .filter_map(scraper::ElementRef::wrap)
+        .filter(|row| row.value().name() == "tr")
+        .filter(|row| {
+            row.ancestors()
+                .filter_map(scraper::ElementRef::wrap)
+                .find(|ancestor| ancestor.value().name() == "table")
+                .is_some_and(|table| table.id() == element.id())
+        });
+
+    for table_row in table_rows {
+        let cells: Vec<scraper::ElementRef> = table_row
+            .children()
+            .filter_map(scraper::ElementRef::wrap)
+            .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+            .collect();
+
+        let in_head = table_row
+            .parent()
+            .and_then(scraper::ElementRef::wrap)
+            .is_some_and(|parent| parent.value().name() == "thead");
+        let is_header =
+            in_head || (!cells.is_empty() && cells.iter().all(|cell| cell.value().name() == "th"));
+
+        rows.push((
+            is_header,
+            cells
+                .into_iter()
+                .map(|cell| parse_html_node(cell, style))
+                .collect(),
+        ));
+    }
+
+    HtmlNode::Table(rows)
+}
+
+fn parse_child_element(element: scraper::ElementRef, style: &TextStyle) -> Option<HtmlNode> {
+    let element_name = element.value().name();
+
+    if matches!(element_name, "uriobject" | "video")
+        && let Some(media) = media_attachment(element)
+    {
+        return Some(HtmlNode::Media(media));
+    }
+
+    if let Some(level) = heading_level(element_name) {
+        return Some(HtmlNode::Heading(level, Box::new(parse_html_node(element, style))));
+    }
+
+    match element_name {
+        "br" => Some(HtmlNode::LineBreak),
+        "hr" => Some(HtmlNode::Rule),
+        "img" => parse_image(element),
+        "blockquote" => match element.attr("itemtype") {
+            Some("http://schema.skype.com/Reply") => {
+                let name = element
+                    .select(&Selector::parse("strong").unwrap())
+                    .next()
+                    .map(|name| name.text().collect::<String>())
+                    .unwrap_or("Unknown User".to_string());
+
+                let preview = element.select(&Selector::parse("p").unwrap()).next()?;
+                Some(HtmlNode::Reply(name, preview.text().collect::<String>()))
             }
-            if let Some(property) = cascading_properties.get("s") {
-                if property == "s" {
-                    strikethrough = true;
-                }
+            Some(_) => None,
+            None => Some(HtmlNode::Quote(Box::new(parse_html_node(element, style)))),
+        },
+        "pre" | "codeblock" => {
+            // Codeblocks name the language directly in their class
+            let language = element
+                .descendants()
+                .filter_map(scraper::ElementRef::wrap)
+                .filter_map(|element| element.attr("class"))
+                .flat_map(|class| class.split_whitespace())
+                .find_map(|class| {
+                    if element_name == "codeblock" {
+                        Some(class.to_lowercase())
+                    } else {
+                        class.strip_prefix("language-").map(|language| language.to_string())
+                    }
+                });
+
+            Some(HtmlNode::CodeBlock(language, code_block_text(element)))
+        }
+        "code" => Some(HtmlNode::InlineCode(code_block_text(element))),
+        "ul" | "ol" => Some(parse_list(element, style)),
+        "table" => Some(parse_table(element, style)),
+        _ if element.has_children() => Some(parse_html_node(element, style)),
+        _ => None,
+    }
+}
+
+// Elements with block children become columns, inline runs between them become flows
+fn parse_html_node(element: scraper::ElementRef, parent_style: &TextStyle) -> HtmlNode {
+    let style = inline_style(element, parent_style.clone());
+
+    let mut blocks = vec![];
+    let mut inline = vec![];
+
+    for child in element.children() {
+        if let Some(text) = child.value().as_text() {
+            // Indentation between the tags of formatted html
+            if text.contains('\n') && text.trim().is_empty() {
+                continue;
             }
-            if let Some(property) = cascading_properties.get("i") {
-                if property == "i" {
-                    font_text.style = font::Style::Italic;
+            // Office wraps long lines, a newline inside text is a space like in a browser
+            let text_content = text.replace("\r\n", " ").replace(['\n', '\r'], " ");
+            inline.push(HtmlNode::Text(text_content, style.clone()));
+            continue;
+        }
+
+        let Some(child_element) = scraper::ElementRef::wrap(child) else {
+            continue;
+        };
+        let Some(node) = parse_child_element(child_element, &style) else {
+            continue;
+        };
+
+        if BLOCK_ELEMENTS.contains(&child_element.value().name()) {
+            if !inline.is_empty() {
+                blocks.push(HtmlNode::Flow(std::mem::take(&mut inline)));
+            }
+            blocks.push(node);
+        } else if let HtmlNode::Flow(children) = node {
+            inline.extend(children);
+        } else {
+            inline.push(node);
+        }
+    }
+
+    if blocks.is_empty() && element.value().name() != "body" {
+        return HtmlNode::Flow(inline);
+    }
+
+    if !inline.is_empty() {
+        blocks.push(HtmlNode::Flow(inline));
+    }
+    HtmlNode::Column(blocks)
+}
+
+pub fn parse_html_tree(content: &str) -> Option<HtmlNode> {
+    let content = content.trim_end().trim_end_matches("<p>&nbsp;</p>"); //Remove tailing space
+    let document = Html::parse_document(content);
+
+    let selector = Selector::parse("body").unwrap();
+    let root_element = document.select(&selector).next()?;
+    Some(parse_html_node(root_element, &TextStyle::default()))
+}
+
+// Every word is its own rich text, not ideal but necessary to mantain correct wrapping
+fn render_text(
+    theme: &style::Theme,
+    text_content: String,
+    style: TextStyle,
+) -> Vec<Element<'static, Message>> {
+    let selection_color = theme.colors.text_selection;
+
+    let mut font_text = Font::with_name("Open Sans Twemoji");
+    if style.bold {
+        font_text.weight = font::Weight::Bold;
+    }
+    if style.italic {
+        font_text.style = font::Style::Italic;
+    }
+
+    let color = if style.link.is_some() || style.mention {
+        theme.colors.text_link
+    } else {
+        style.color.unwrap_or(theme.colors.text)
+    };
+
+    text_content
+        .split_inclusive(" ")
+        .map(|word| {
+            let mut word_span = span::<(), Font>(word.to_string())
+                .font(font_text)
+                .color(color)
+                .underline(style.underline)
+                .strikethrough(style.strikethrough);
+            if let Some(size) = style.size {
+                word_span = word_span.size(size);
+            }
+            if let Some(highlight) = style.highlight {
+                word_span = word_span.background(highlight);
+            }
+            if style.link.is_some() {
+                word_span = word_span.link(());
+            }
+
+            let word_text = selectable_rich_text(vec![word_span]).style(move |_| {
+                selectable_text::Style {
+                    color: None,
+                    selection_color,
                 }
+            });
+
+            if let Some(href) = style.link.clone() {
+                word_text
+                    .on_link(move |_| Message::LinkClicked(href.clone()))
+                    .into()
+            } else {
+                word_text.into()
             }
-            if let Some(property) = cascading_properties.get("em") {
-                if property == "em" {
-                    font_text.style = font::Style::Italic;
+        })
+        .collect()
+}
+
+fn render_html_node(theme: &style::Theme, node: HtmlNode) -> Element<'static, Message> {
+    let selection_color = theme.colors.text_selection;
+
+    match node {
+        HtmlNode::Column(children) => column(
+            children
+                .into_iter()
+                .map(|child| render_html_node(theme, child)),
+        )
+        .into(),
+        HtmlNode::Flow(children) => {
+            let mut elements = vec![];
+            for child in children {
+                if let HtmlNode::Text(text_content, style) = child {
+                    elements.extend(render_text(theme, text_content, style));
+                } else {
+                    elements.push(render_html_node(theme, child));
                 }
             }
-            if let Some(value) = cascading_properties.get("a") {
-                link_href = Some(value.clone());
-                color = theme.colors.text_link;
+            container(row(elements).wrap()).into()
+        }
+        HtmlNode::Text(text_content, style) => {
+            container(row(render_text(theme, text_content, style)).wrap()).into()
+        }
+        HtmlNode::LineBreak => space().width(10000).into(),
+        HtmlNode::Emoji(alt) => selectable_rich_text(vec![span::<(), Font>(alt)])
+            .style(move |_| selectable_text::Style {
+                color: None,
+                selection_color,
+            })
+            .into(),
+        HtmlNode::CustomEmoji(image_url) => {
+            let identifier = ams_image_identifier(&image_url);
+            c_cached_image(
+                theme,
+                identifier.clone(),
+                Message::AuthorizeImage(image_url, identifier),
+                20.0,
+                20.0,
+                2.0,
+            )
+        }
+        HtmlNode::Image(image_url, width, height) => {
+            // most consistent way to get the image id
+            let identifier = ams_image_identifier(&image_url);
+
+            // Use the html sizes if not able to fetch the image dimensions or if the image has not been downloaded yet
+            let (mut image_width, mut image_height) =
+                match cached_image_path(&identifier).map(image_dimensions) {
+                    Some(Ok((width, height))) => (width as f32, height as f32),
+                    _ => (width.unwrap_or(400.0), height.unwrap_or(400.0)),
+                };
+
+            // Limit image sizes
+
+            if image_width == image_height && image_width > 280.0 {
+                image_width = 250.0;
+                image_height = 250.0;
             }
-            if let Some(property) = cascading_properties.get("span") {
-                if property == "http://schema.skype.com/Mention" {
-                    color = theme.colors.text_link;
-                }
+
+            if image_width >= 420.0 {
+                let factor = 400.0 / image_width;
+                image_width = 400.0;
+                image_height *= factor;
             }
 
-            // Turn every word into its own rich text (not ideal but necessary to mantain correct wrapping)
+            if image_height >= 380.0 {
+                let factor = 400.0 / image_height;
+                image_height = 400.0;
+                image_width *= factor;
+            }
 
-            let text_content = child.value().as_text().unwrap().text.to_string();
-            // Remove things like newlines since it is handled separately
-            let text_content = text_content.replace("\n", "").replace("\r", "");
+            click_area(c_cached_image(
+                theme,
+                identifier.clone(),
+                Message::AuthorizeImage(image_url, identifier.clone()),
+                image_width,
+                image_height,
+                8.0,
+            ))
+            .on_press(Message::ExpandImage(identifier, "jpeg".to_string()))
+            .interaction(mouse::Interaction::Pointer)
+            .into()
+        }
+        HtmlNode::Gif(image_url, width, height) => {
+            let identifier = xxh3_64(image_url.as_bytes()).to_string();
+
+            click_area(c_cached_gif(
+                theme,
+                identifier.clone(),
+                Message::DownloadImage(image_url, identifier.clone()),
+                width.unwrap_or(250.0),
+                height.unwrap_or(250.0),
+            ))
+            .on_press(Message::ExpandImage(identifier, "gif".to_string()))
+            .interaction(mouse::Interaction::Pointer)
+            .into()
+        }
+        HtmlNode::Media(media) => c_media_attachment(theme, media),
+        HtmlNode::Reply(name, preview) => {
+            let color = theme.colors.foreground_surface;
+
+            container(
+                container(column![
+                    text!("{}", name).color(theme.colors.demo_text).size(14),
+                    text!("{}", preview)
+                ])
+                .padding(5)
+                .style(move |_| container::Style {
+                    background: Some(color.into()),
+                    border: border::rounded(5),
+                    ..Default::default()
+                }),
+            )
+            .padding(padding::bottom(8))
+            .into()
+        }
+        HtmlNode::Quote(content) => {
+            let color = theme.colors.foreground_surface;
+
+            container(row![
+                text("| ").color(theme.colors.demo_text),
+                render_html_node(theme, *content)
+            ])
+            .padding(5)
+            .style(move |_| container::Style {
+                background: Some(color.into()),
+                border: border::rounded(4),
+                ..Default::default()
+            })
+            .into()
+        }
+        HtmlNode::InlineCode(code) => {
+            let color = theme.colors.foreground_surface;
+
+            let lines_count = code.lines().count();
+
+            let lines: Vec<Element<Message>> = code
+                .lines()
+                .map(|line| {
+                    selectable_text(line.to_string())
+                        .style(move |_| selectable_text::Style {
+                            color: None,
+                            selection_color,
+                        })
+                        .into()
+                })
+                .collect();
+
+            if lines_count > 1 {
+                container(container(column(lines)).padding(10).style(move |_| {
+                    container::Style {
+                        background: Some(color.into()),
+                        border: border::rounded(5),
+                        ..Default::default()
+                    }
+                }))
+                .padding(padding::vertical(6))
+                .into()
+            } else {
+                container(column(lines))
+                    .padding(3)
+                    .style(move |_| container::Style {
+                        background: Some(color.into()),
+                        border: border::rounded(3),
+                        ..Default::default()
+                    })
+                    .into()
+            }
+        }
+        HtmlNode::CodeBlock(language, code) => c_code_block(theme, language, code),
+        HtmlNode::List(start, items) => {
+            let marker_color = theme.colors.text;
+
+            let mut list = column![].spacing(2);
+            for (index, item) in items.into_iter().enumerate() {
+                let marker: Element<Message> = match start {
+                    Some(start) => {
+                        row![space().width(10).height(1), text!("{}. ", start + index)].into()
+                    }
+                    None => container(circle(3.0, marker_color))
+                        .padding(Padding {
+                            top: 9.0,
+                            bottom: 0.0,
+                            left: 1.0,
+                            right: 4.0,
+                        })
+                        .into(),
+                };
 
-            for word in text_content.split_inclusive(" ") {
-                let spans = vec![if !link_href.is_none() {
-                    span::<(), Font>(word.to_string())
-                        .font(font_text)
-                        .color(color)
-                        .link(())
-                        .underline(underline)
-                        .strikethrough(strikethrough)
-                } else {
-                    span::<(), Font>(word.to_string())
-                        .font(font_text)
-                        .color(color)
-                        .underline(underline)
-                        .strikethrough(strikethrough)
-                }];
-
-                if let Some(href) = link_href.clone() {
-                    dynamic_container = dynamic_container.push(
-                        selectable_rich_text(spans)
-                            .on_link(move |_| Message::LinkClicked(href.clone()))
-                            .style(move |_| selectable_text::Style {
-                                color: None,
-                                selection_color: selection_color,
-                            })
-                            .into(),
-                    );
-                } else {
-                    dynamic_container = dynamic_container.push(
-                        selectable_rich_text(spans)
-                            .style(move |_| selectable_text::Style {
-                                color: None,
-                                selection_color: selection_color,
-                            })
-                            .into(),
+                list = list.push(
+                    row![marker, render_html_node(theme, item)]
+                        .spacing(6)
+                        .align_y(Alignment::Start),
+                );
+            }
+
+            list.into()
+        }
+        HtmlNode::Table(rows) => {
+            let line = theme.colors.line;
+            let header_background = theme.colors.foreground_surface;
+
+            // Rows with fewer cells are padded so the columns stay aligned
+            let column_count = rows.iter().map(|(_, cells)| cells.len()).max().unwrap_or(0);
+
+            let mut table = column![];
+            for (is_header, cells) in rows {
+                let cell_count = cells.len();
+
+                let mut table_row = row![];
+                for cell in cells {
+                    table_row = table_row.push(
+                        container(render_html_node(theme, cell))
+                            .width(Length::FillPortion(1))
+                            .padding(6)
+                            .style(move |_| container::Style {
+                                background: is_header.then(|| header_background.into()),
+                                border: Border {
+                                    color: line,
+                                    width: 1.0,
+                                    radius: 0.0.into(),
+                                },
+                                ..Default::default()
+                            }),
                     );
                 }
+                for _ in cell_count..column_count {
+                    table_row = table_row.push(space().width(Length::FillPortion(1)));
+                }
+
+                table = table.push(table_row);
             }
+
+            container(table).padding(padding::vertical(6)).into()
+        }
+        HtmlNode::Heading(_, content) => container(render_html_node(theme, *content))
+            .padding(padding::vertical(4))
+            .into(),
+        HtmlNode::Rule => {
+            let line = theme.colors.line;
+
+            container(
+                container(space().width(Length::Fill).height(1)).style(move |_| {
+                    container::Style {
+                        background: Some(line.into()),
+                        ..Default::default()
+                    }
+                }),
+            )
+            .padding(padding::vertical(6))
+            .into()
         }
     }
-
-    dynamic_container.wrap() // If it is a row, it needs to be wrapping
 }
 
 pub fn parse_message_html<'a>(
     theme: &style::Theme,
     content: String,
 ) -> Result<Element<'a, Message>, String> {
-    let content = content.trim_end().trim_end_matches("<p>&nbsp;</p>"); //Remove tailing space
-    let document = Html::parse_document(content);
-
-    let selector = Selector::parse("body").unwrap();
-    if let Some(root_element) = document.select(&selector).next() {
-        Ok(transform_html(theme, root_element, HashMap::new()).into_element())
+    if let Some(html_tree) = parse_html_tree(&content) {
+        Ok(render_html_node(theme, html_tree))
     } else {
         Err("Couldn't get body from message html".to_string())
     }
@@ -709,3 +1125,213 @@ pub fn get_html_preview(html: &str) -> String {
         .collect::<Vec<_>>()
         .join(" ") // Join with space
 }
+
+// The message quoted at the top of a reply
+pub fn get_reply_target(html: &str) -> Option<ReplyTarget> {
+    let document = Html::parse_document(html);
+    let reply_selector =
+        Selector::parse(r#"blockquote[itemtype="http://schema.skype.com/Reply"]"#).unwrap();
+    let name_selector = Selector::parse(r#"[itemprop="mri"]"#).unwrap();
+    let preview_selector = Selector::parse(r#"[itemprop="preview"]"#).unwrap();
+
+    let reply = document.select(&reply_selector).next()?;
+    let item_text = |selector: &Selector| {
+        reply
+            .select(selector)
+            .next()
+            .map(|element| element.text().collect::<String>().trim().to_string())
+            .unwrap_or_default()
+    };
+
+    Some(ReplyTarget {
+        message_id: reply.value().attr("itemid").unwrap_or("0").to_string(),
+        name: item_text(&name_selector),
+        preview: item_text(&preview_selector),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn hex(color: &Color) -> String {
+        let [r, g, b, _] = color.into_rgba8();
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    fn size(size: &Option<f32>) -> String {
+        size.map(|size| size.to_string()).unwrap_or("?".to_string())
+    }
+
+    // One node per line, indented by depth
+    fn outline(node: &HtmlNode, depth: usize, out: &mut String) {
+        let indent = "  ".repeat(depth);
+
+        let line = match node {
+            HtmlNode::Column(_) => "column".to_string(),
+            HtmlNode::Flow(_) => "flow".to_string(),
+            HtmlNode::Text(text_content, style) => {
+                let mut line = format!("text {:?}", text_content);
+                for (flag, name) in [
+                    (style.bold, "bold"),
+                    (style.italic, "italic"),
+                    (style.underline, "underline"),
+                    (style.strikethrough, "strikethrough"),
+                    (style.mention, "mention"),
+                ] {
+                    if flag {
+                        line.push_str(&format!(" {}", name));
+                    }
+                }
+                if let Some(link) = &style.link {
+                    line.push_str(&format!(" link={}", link));
+                }
+                if let Some(color) = &style.color {
+                    line.push_str(&format!(" color={}", hex(color)));
+                }
+                if let Some(highlight) = &style.highlight {
+                    line.push_str(&format!(" highlight={}", hex(highlight)));
+                }
+                if let Some(text_size) = style.size {
+                    line.push_str(&format!(" size={}", text_size));
+                }
+                line
+            }
+            HtmlNode::LineBreak => "br".to_string(),
+            HtmlNode::Emoji(alt) => format!("emoji {:?}", alt),
+            HtmlNode::CustomEmoji(image_url) => format!("custom-emoji {}", image_url),
+            HtmlNode::Image(image_url, width, height) => {
+                format!("image {} {}x{}", image_url, size(width), size(height))
+            }
+            HtmlNode::Gif(image_url, width, height) => {
+                format!("gif {} {}x{}", image_url, size(width), size(height))
+            }
+            HtmlNode::Media(media) => format!("media {:?} {}", media.kind, media.url),
+            HtmlNode::Reply(name, preview) => format!("reply {:?} {:?}", name, preview),
+            HtmlNode::Quote(_) => "quote".to_string(),
+            HtmlNode::InlineCode(code) => format!("code {:?}", code),
+            HtmlNode::CodeBlock(language, code) => format!(
+                "code-block {} {:?}",
+                language.clone().unwrap_or("none".to_string()),
+                code
+            ),
+            HtmlNode::List(Some(start), _) => format!("list from {}", start),
+            HtmlNode::List(None, _) => "list".to_string(),
+            HtmlNode::Table(_) => "table".to_string(),
+            HtmlNode::Heading(level, _) => format!("heading {}", level),
+            HtmlNode::Rule => "rule".to_string(),
+        };
+        out.push_str(&format!("{}{}\n", indent, line));
+
+        match node {
+            HtmlNode::Column(children) | HtmlNode::Flow(children) => {
+                for child in children {
+                    outline(child, depth + 1, out);
+                }
+            }
+            HtmlNode::Quote(content) | HtmlNode::Heading(_, content) => {
+                outline(content, depth + 1, out);
+            }
+            HtmlNode::List(_, items) => {
+                for item in items {
+                    out.push_str(&format!("{}  item\n", indent));
+                    outline(item, depth + 2, out);
+                }
+            }
+            HtmlNode::Table(rows) => {
+                for (is_header, cells) in rows {
+                    let row_name = if *is_header { "header-row" } else { "row" };
+                    out.push_str(&format!("{}  {}\n", indent, row_name));
+                    for cell in cells {
+                        out.push_str(&format!("{}    cell\n", indent));
+                        outline(cell, depth + 3, out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn html_outline(html: &str) -> String {
+        let mut out = String::new();
+        outline(&parse_html_tree(html).unwrap(), 0, &mut out);
+        out
+    }
+
+    // Run with UPDATE_SNAPSHOTS=1 to rewrite the .snap files after an intended change
+    #[test]
+    fn html_fixtures_match_snapshots() {
+        let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/html");
+
+        let mut fixtures: Vec<_> = fs::read_dir(&fixtures_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "html"))
+            .collect();
+        fixtures.sort();
+        assert!(!fixtures.is_empty());
+
+        let mut mismatches = vec![];
+        for fixture in fixtures {
+            let actual = html_outline(&fs::read_to_string(&fixture).unwrap());
+            let snapshot = fixture.with_extension("snap");
+
+            if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+                fs::write(&snapshot, &actual).unwrap();
+                continue;
+            }
+
+            let expected = fs::read_to_string(&snapshot).unwrap_or_default();
+            if actual != expected {
+                mismatches.push(format!("{}:\n{}", fixture.display(), actual));
+            }
+        }
+
+        assert!(mismatches.is_empty(), "Snapshots differ:\n{}", mismatches.join("\n"));
+    }
+
+    #[test]
+    fn sent_markdown_tables_are_rendered_as_tables() {
+        let html = parse_message_markdown("| A | B |\n| --- | --- |\n| 1 | 2 |".to_string());
+
+        let expected = [
+            "column",
+            "  table",
+            "    header-row",
+            "      cell",
+            "        flow",
+            "          text \"A\" bold",
+            "      cell",
+            "        flow",
+            "          text \"B\" bold",
+            "    row",
+            "      cell",
+            "        flow",
+            "          text \"1\"",
+            "      cell",
+            "        flow",
+            "          text \"2\"",
+        ];
+        assert_eq!(html_outline(&html), expected.join("\n") + "\n");
+    }
+
+    #[test]
+    fn office_default_colors_are_left_to_the_theme() {
+        assert_eq!(parse_css_color("rgb(36, 36, 36)"), None);
+        assert_eq!(parse_css_color("windowtext"), None);
+        assert_eq!(parse_css_color("rgba(0, 0, 0, 0)"), None);
+        assert_eq!(parse_css_color("black"), Some(Color::from_rgb8(0, 0, 0)));
+        assert_eq!(parse_css_color("#FFFFFF"), Some(Color::from_rgb8(255, 255, 255)));
+        assert_eq!(parse_css_color("#C00000"), Some(Color::from_rgb8(192, 0, 0)));
+        assert_eq!(
+            parse_css_color("yellow none repeat scroll 0% 0%"),
+            Some(Color::from_rgb8(255, 255, 0))
+        );
+        assert_eq!(
+            parse_css_color("rgb(196, 49, 75) !important"),
+            Some(Color::from_rgb8(196, 49, 75))
+        );
+    }
+}
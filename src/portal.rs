@@ -0,0 +1,112 @@
+use futures::sink::SinkExt;
+use futures::stream::{Stream, StreamExt};
+use iced::futures;
+use iced::stream;
+use std::collections::HashMap;
+use zbus::{
+    Connection, Proxy,
+    zvariant::{OwnedValue, Value},
+};
+
+// Sends the desktop's color scheme preference, then every change of it.
+// Ends when there is no session bus or portal
+pub fn color_scheme_changes() -> impl Stream<Item = Option<bool>> {
+    stream::channel(
+        10,
+        |mut output: futures::channel::mpsc::Sender<Option<bool>>| async move {
+            let Ok(connection) = Connection::session().await else {
+                return;
+            };
+            let Ok(proxy) = Proxy::new(
+                &connection,
+                "org.freedesktop.portal.Desktop",
+                "/org/freedesktop/portal/desktop",
+                "org.freedesktop.portal.Settings",
+            )
+            .await
+            else {
+                return;
+            };
+
+            // Subscribed before reading, so no change is missed in between
+            let Ok(mut changes) = proxy.receive_signal("SettingChanged").await else {
+                return;
+            };
+
+            let _ = output.send(read_prefers_dark(&proxy).await).await;
+
+            // Api: XDG desktop portal, Settings.SettingChanged
+            while let Some(message) = changes.next().await {
+                let Ok((namespace, key, value)) =
+                    message.body().deserialize::<(String, String, OwnedValue)>()
+                else {
+                    continue;
+                };
+
+                if namespace == "org.freedesktop.appearance" && key == "color-scheme" {
+                    let _ = output.send(prefers_dark(value)).await;
+                }
+            }
+        },
+    )
+}
+
+// Api: XDG desktop portal, Settings.ReadOne
+// Returns None when the desktop has no preference
+async fn read_prefers_dark(proxy: &Proxy<'_>) -> Option<bool> {
+    let reply = proxy
+        .call_method("ReadOne", &("org.freedesktop.appearance", "color-scheme"))
+        .await
+        .ok()?;
+
+    prefers_dark(reply.body().deserialize().ok()?)
+}
+
+fn prefers_dark(value: OwnedValue) -> Option<bool> {
+    // 0: no preference, 1: prefer dark, 2: prefer light
+    match u32::try_from(value).ok()? {
+        1 => Some(true),
+        2 => Some(false),
+        _ => None,
+    }
+}
+
+// Api: freedesktop notifications, Notify
+// The sound is played by the notification server, so it matches the desktop's message sound
+pub async fn show_notification(summary: String, body: String, sound: bool) -> Result<(), String> {
+    let connection = Connection::session().await.map_err(|err| err.to_string())?;
+    let proxy = Proxy::new(
+        &connection,
+        "org.freedesktop.Notifications",
+        "/org/freedesktop/Notifications",
+        "org.freedesktop.Notifications",
+    )
+    .await
+    .map_err(|err| err.to_string())?;
+
+    let mut hints: HashMap<&str, Value> = HashMap::new();
+    if sound {
+        hints.insert("sound-name", Value::from("message-new-instant"));
+    } else {
+        hints.insert("suppress-sound", Value::from(true));
+    }
+
+    proxy
+        .call_method(
+            "Notify",
+            &(
+                "Squads",
+                0u32, // Not replacing an earlier notification
+                "squads",
+                summary.as_str(),
+                body.as_str(),
+                Vec::<&str>::new(),
+                hints,
+                -1i32, // The server's default timeout
+            ),
+        )
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
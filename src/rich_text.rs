@@ -0,0 +1,1043 @@
+use crate::editing::INDENT;
+use iced::advanced::text::Highlighter;
+use iced::advanced::text::highlighter::Format;
+use iced::widget::text_editor::{Action, Content, Cursor, Edit, Position};
+use iced::{Color, Font, Theme, font};
+use regex::Regex;
+use scraper::{ElementRef, Html};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::LazyLock;
+
+// Lists and quotes are shown with a marker at the start of their lines
+pub const BULLET_MARKER: &str = "• ";
+pub const QUOTE_MARKER: &str = "▎ ";
+
+static ORDERED_MARKER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d+\. ").unwrap());
+static BARE_URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"https?://[^\s<]+").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InlineStyle {
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Code,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CharFormat {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub code: bool,
+    pub link: Option<String>,
+}
+
+impl CharFormat {
+    fn has(&self, style: InlineStyle) -> bool {
+        match style {
+            InlineStyle::Bold => self.bold,
+            InlineStyle::Italic => self.italic,
+            InlineStyle::Underline => self.underline,
+            InlineStyle::Strikethrough => self.strikethrough,
+            InlineStyle::Code => self.code,
+        }
+    }
+
+    fn set(&mut self, style: InlineStyle, value: bool) {
+        match style {
+            InlineStyle::Bold => self.bold = value,
+            InlineStyle::Italic => self.italic = value,
+            InlineStyle::Underline => self.underline = value,
+            InlineStyle::Strikethrough => self.strikethrough = value,
+            InlineStyle::Code => self.code = value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineBlock {
+    Bullet,
+    Ordered,
+    Quote,
+}
+
+// The block of the line with the length of its indentation and marker, in chars
+fn line_block(line: &str) -> Option<(LineBlock, usize, usize)> {
+    let content = line.trim_start_matches(' ');
+    let indentation = line.len() - content.len();
+
+    if content.starts_with(BULLET_MARKER) {
+        Some((LineBlock::Bullet, indentation, BULLET_MARKER.chars().count()))
+    } else if let Some(marker) = ORDERED_MARKER.find(content) {
+        Some((LineBlock::Ordered, indentation, marker.end()))
+    } else if content.starts_with(QUOTE_MARKER) {
+        Some((LineBlock::Quote, indentation, QUOTE_MARKER.chars().count()))
+    } else {
+        None
+    }
+}
+
+fn block_kind(line: &str) -> Option<LineBlock> {
+    line_block(line).map(|(block, _, _)| block)
+}
+
+// Indented items are nested in the list of the item above them
+fn list_html(items: &[(usize, LineBlock, String)]) -> String {
+    let tag = |block: &LineBlock| if *block == LineBlock::Ordered { "ol" } else { "ul" };
+
+    let mut html = String::new();
+    let mut open_lists: Vec<(usize, LineBlock)> = vec![];
+
+    for (level, block, content) in items {
+        while let Some((open_level, open_block)) = open_lists.last()
+            && (open_level > level || (open_level == level && open_block != block))
+        {
+            html.push_str(&format!("</li></{}>", tag(open_block)));
+            open_lists.pop();
+        }
+
+        if open_lists.last().is_some_and(|(open_level, _)| open_level == level) {
+            html.push_str("</li>");
+        } else {
+            html.push_str(&format!("<{}>", tag(block)));
+            open_lists.push((*level, *block));
+        }
+
+        html.push_str(&format!("<li>{}", content));
+    }
+
+    while let Some((_, block)) = open_lists.pop() {
+        html.push_str(&format!("</li></{}>", tag(&block)));
+    }
+
+    html
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Links from pasted or received html can have any scheme, like javascript:
+fn is_allowed_link(link: &str) -> bool {
+    let link = link.trim().to_lowercase();
+    ["http://", "https://", "mailto:"]
+        .iter()
+        .any(|scheme| link.starts_with(scheme))
+}
+
+fn push_run(html: &mut String, run: &str, format: &CharFormat) {
+    let mut content = escape_html(run);
+    let link = format.link.as_deref().filter(|link| is_allowed_link(link));
+
+    if link.is_none() && !format.code {
+        content = BARE_URL
+            .replace_all(&content, "<a href=\"$0\">$0</a>")
+            .to_string();
+    }
+    if format.code {
+        content = format!("<code>{}</code>", content);
+    }
+    if format.strikethrough {
+        content = format!("<s>{}</s>", content);
+    }
+    if format.underline {
+        content = format!("<u>{}</u>", content);
+    }
+    if format.italic {
+        content = format!("<em>{}</em>", content);
+    }
+    if format.bold {
+        content = format!("<strong>{}</strong>", content);
+    }
+    if let Some(link) = link {
+        content = format!("<a href=\"{}\">{}</a>", escape_html(link.trim()), content);
+    }
+
+    html.push_str(&content);
+}
+
+// Emphasis markers only apply when they touch the text, so the run's spaces stay outside them
+fn push_markdown_run(markdown: &mut String, run: &str, format: &CharFormat) {
+    let content = run.trim();
+    if content.is_empty() || *format == CharFormat::default() {
+        markdown.push_str(run);
+        return;
+    }
+
+    let mut content = content.to_string();
+
+    if format.code {
+        content = format!("`{}`", content);
+    }
+    if format.strikethrough {
+        content = format!("~~{}~~", content);
+    }
+    if format.underline {
+        content = format!("<u>{}</u>", content);
+    }
+    if format.italic {
+        content = format!("*{}*", content);
+    }
+    if format.bold {
+        content = format!("**{}**", content);
+    }
+    if let Some(link) = &format.link
+        && link != run.trim()
+    {
+        content = format!("[{}]({})", content, link);
+    }
+
+    let leading = &run[..run.len() - run.trim_start().len()];
+    let trailing = &run[run.trim_end().len()..];
+    markdown.push_str(&format!("{}{}{}", leading, content, trailing));
+}
+
+fn inline_runs(
+    line: &str,
+    formats: &[CharFormat],
+    push: fn(&mut String, &str, &CharFormat),
+) -> String {
+    let mut output = String::new();
+    let mut run = String::new();
+    let mut run_format: Option<&CharFormat> = None;
+
+    for (char, format) in line.chars().zip(formats) {
+        if let Some(previous_format) = run_format
+            && previous_format != format
+        {
+            push(&mut output, &run, previous_format);
+            run.clear();
+        }
+        run_format = Some(format);
+        run.push(char);
+    }
+
+    if let Some(format) = run_format {
+        push(&mut output, &run, format);
+    }
+
+    output
+}
+
+fn inline_html(line: &str, formats: &[CharFormat]) -> String {
+    inline_runs(line, formats, push_run)
+}
+
+// Reads a sent message body back into composer lines, replies are kept out of the text
+#[derive(Default)]
+struct HtmlReader {
+    text: String,
+    formats: Vec<CharFormat>,
+    quote: bool,
+    list_depth: usize,
+    marker: Option<String>, // Of the list item whose first line hasn't been written yet
+}
+
+impl HtmlReader {
+    fn at_line_start(&self) -> bool {
+        self.text.is_empty() || self.text.ends_with('\n')
+    }
+
+    fn push_raw(&mut self, text: &str, format: &CharFormat) {
+        self.text.push_str(text);
+        self.formats
+            .extend(std::iter::repeat_n(format.clone(), text.chars().count()));
+    }
+
+    fn push(&mut self, text: &str, format: &CharFormat) {
+        let mut text = text;
+
+        if self.at_line_start() {
+            text = text.trim_start();
+            if text.is_empty() {
+                return;
+            }
+
+            let mut prefix = String::new();
+            if self.quote {
+                prefix.push_str(QUOTE_MARKER);
+            }
+            if let Some(marker) = self.marker.take() {
+                prefix.push_str(&marker);
+            }
+            self.push_raw(&prefix, &CharFormat::default());
+        }
+
+        self.push_raw(text, format);
+    }
+
+    fn end_line(&mut self) {
+        if !self.at_line_start() {
+            self.push_raw("\n", &CharFormat::default());
+        }
+    }
+
+    fn read_children(&mut self, element: ElementRef, format: &CharFormat) {
+        for child in element.children() {
+            if let Some(text) = child.value().as_text() {
+                // Indentation between the tags of formatted html
+                if text.contains('\n') && text.trim().is_empty() {
+                    continue;
+                }
+                let text = text
+                    .replace("\r\n", " ")
+                    .replace(['\n', '\r', '\u{a0}'], " ");
+                self.push(&text, format);
+            } else if let Some(child_element) = ElementRef::wrap(child) {
+                self.read(child_element, format);
+            }
+        }
+    }
+
+    fn read(&mut self, element: ElementRef, parent_format: &CharFormat) {
+        let value = element.value();
+        let mut format = parent_format.clone();
+
+        match value.name() {
+            "strong" | "b" => format.bold = true,
+            "em" | "i" => format.italic = true,
+            "u" => format.underline = true,
+            "s" | "strike" | "del" => format.strikethrough = true,
+            "code" => format.code = true,
+            "a" => format.link = value.attr("href").map(str::to_string),
+            "br" => {
+                self.push_raw("\n", &CharFormat::default());
+                return;
+            }
+            // Emojis are images with the emoji as their alt text
+            "img" => {
+                if let Some(alt) = value.attr("alt") {
+                    self.push(alt, &format);
+                }
+                return;
+            }
+            "pre" => {
+                let language = value
+                    .attr("class")
+                    .or_else(|| {
+                        element
+                            .child_elements()
+                            .next()
+                            .and_then(|code| code.value().attr("class"))
+                    })
+                    .and_then(|class| class.strip_prefix("language-"))
+                    .unwrap_or("plaintext");
+                let code = element.text().collect::<String>();
+
+                self.end_line();
+                self.push_raw(
+                    &format!("```{}\n{}\n```", language, code.trim_end_matches('\n')),
+                    &CharFormat::default(),
+                );
+                self.end_line();
+                return;
+            }
+            "blockquote" => {
+                if value.attr("itemtype") != Some("http://schema.skype.com/Reply") {
+                    self.end_line();
+                    self.quote = true;
+                    self.read_children(element, &format);
+                    self.quote = false;
+                    self.end_line();
+                }
+                return;
+            }
+            "ul" | "ol" => {
+                self.end_line();
+                self.list_depth += 1;
+
+                for (index, item) in element.child_elements().enumerate() {
+                    let marker = if value.name() == "ol" {
+                        format!("{}. ", index + 1)
+                    } else {
+                        BULLET_MARKER.to_string()
+                    };
+
+                    self.end_line();
+                    self.marker = Some(format!("{}{}", INDENT.repeat(self.list_depth - 1), marker));
+                    self.read_children(item, &format);
+                    self.marker = None;
+                }
+
+                self.list_depth -= 1;
+                self.end_line();
+                return;
+            }
+            _ => {}
+        }
+
+        let is_block = matches!(
+            value.name(),
+            "p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "table" | "tr"
+        );
+        if !is_block {
+            self.read_children(element, &format);
+            return;
+        }
+
+        self.end_line();
+        let length = self.text.len();
+        self.read_children(element, &format);
+
+        // An empty paragraph is a blank line
+        if self.text.len() == length && !self.text.is_empty() {
+            self.push_raw("\n", &CharFormat::default());
+        }
+        self.end_line();
+    }
+}
+
+// The composer text with a format for every char, kept in step with the text editor
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RichDocument {
+    text: String,
+    formats: Vec<CharFormat>,
+    // Used for the next char typed at the offset, set when toggling a style without a selection
+    pending: Option<(usize, CharFormat)>,
+}
+
+impl RichDocument {
+    // Restores a saved document, formats that don't match the text are dropped
+    pub fn with_formats(text: &str, formats: Vec<CharFormat>) -> Self {
+        let formats = if formats.len() == text.chars().count() {
+            formats
+        } else {
+            vec![CharFormat::default(); text.chars().count()]
+        };
+
+        RichDocument {
+            text: text.to_string(),
+            formats,
+            pending: None,
+        }
+    }
+
+    // Formats that can't be written in the composer, like colors and sizes, are dropped
+    pub fn from_html(html: &str) -> Self {
+        let document = Html::parse_document(html);
+        let mut reader = HtmlReader::default();
+        reader.read(document.root_element(), &CharFormat::default());
+
+        while reader.text.ends_with('\n') {
+            reader.text.pop();
+            reader.formats.pop();
+        }
+
+        RichDocument::with_formats(&reader.text, reader.formats)
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn formats(&self) -> &[CharFormat] {
+        &self.formats
+    }
+
+    // Only the part of the text that changed gains or loses formats, so any edit can be followed
+    pub fn sync(&mut self, text: &str) {
+        self.sync_with(text, None);
+    }
+
+    fn sync_with(&mut self, text: &str, inserted_format: Option<CharFormat>) {
+        if text == self.text {
+            return;
+        }
+
+        let old: Vec<char> = self.text.chars().collect();
+        let new: Vec<char> = text.chars().collect();
+
+        if self.formats.len() != old.len() {
+            self.formats = vec![CharFormat::default(); old.len()];
+        }
+
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let inserted = new.len() - prefix - suffix;
+
+        let format = match (inserted_format, self.pending.take()) {
+            (Some(format), _) => format,
+            (None, Some((offset, format))) if offset == prefix => format,
+            _ => self.format_before(prefix),
+        };
+
+        self.formats.splice(
+            prefix..old.len() - suffix,
+            std::iter::repeat_n(format, inserted),
+        );
+        self.text = text.to_string();
+    }
+
+    // Typing after a link doesn't extend it
+    fn format_before(&self, offset: usize) -> CharFormat {
+        offset
+            .checked_sub(1)
+            .and_then(|index| self.formats.get(index))
+            .map(|format| CharFormat {
+                link: None,
+                ..format.clone()
+            })
+            .unwrap_or_default()
+    }
+
+    fn offset(&self, position: &Position) -> usize {
+        let mut offset = 0;
+
+        for (index, line) in self.text.split('\n').enumerate() {
+            let length = line.chars().count();
+            if index == position.line {
+                return offset + position.column.min(length);
+            }
+            offset += length + 1;
+        }
+
+        self.formats.len()
+    }
+
+    // Char range of the selection, empty at the cursor when nothing is selected
+    pub fn selection(&self, cursor: &Cursor) -> Range<usize> {
+        let position = self.offset(&cursor.position);
+        let anchor = cursor
+            .selection
+            .as_ref()
+            .map_or(position, |selection| self.offset(selection));
+
+        position.min(anchor)..position.max(anchor)
+    }
+
+    // Applies the style to the range, or removes it when the whole range already has it
+    pub fn toggle(&mut self, range: Range<usize>, style: InlineStyle) {
+        if range.is_empty() {
+            let mut format = match self.pending.take() {
+                Some((offset, format)) if offset == range.start => format,
+                _ => self.format_before(range.start),
+            };
+            format.set(style, !format.has(style));
+            self.pending = Some((range.start, format));
+            return;
+        }
+
+        let end = range.end.min(self.formats.len());
+        let formats = &mut self.formats[range.start.min(end)..end];
+        let value = !formats.iter().all(|format| format.has(style));
+
+        for format in formats {
+            format.set(style, value);
+        }
+    }
+
+    pub fn set_link(&mut self, range: Range<usize>, url: &str) {
+        let end = range.end.min(self.formats.len());
+
+        for format in &mut self.formats[range.start.min(end)..end] {
+            format.link = Some(url.to_string());
+        }
+    }
+
+    fn lines(&self) -> Vec<(&str, &[CharFormat])> {
+        let mut lines = vec![];
+        let mut offset = 0;
+
+        for line in self.text.split('\n') {
+            let length = line.chars().count();
+            let end = (offset + length).min(self.formats.len());
+            lines.push((line, &self.formats[offset.min(end)..end]));
+            offset += length + 1;
+        }
+
+        lines
+    }
+
+    // Byte ranges of the formatted runs in every line
+    pub fn line_spans(&self) -> Vec<Vec<(Range<usize>, CharFormat)>> {
+        self.lines()
+            .into_iter()
+            .map(|(line, formats)| {
+                let mut spans: Vec<(Range<usize>, CharFormat)> = vec![];
+
+                for ((start, char), format) in line.char_indices().zip(formats) {
+                    let end = start + char.len_utf8();
+
+                    if let Some((range, span_format)) = spans.last_mut()
+                        && range.end == start
+                        && *span_format == *format
+                    {
+                        range.end = end;
+                    } else if *format != CharFormat::default() {
+                        spans.push((start..end, format.clone()));
+                    }
+                }
+
+                spans
+            })
+            .collect()
+    }
+
+    // Serialized straight to the html body Teams expects, without going through markdown
+    pub fn to_html(&self) -> String {
+        let mut lines = self.lines();
+        while lines.last().is_some_and(|(line, _)| line.trim().is_empty()) {
+            lines.pop();
+        }
+
+        let mut html = String::new();
+        let mut index = 0;
+
+        while index < lines.len() {
+            let (line, formats) = lines[index];
+
+            if let Some(info) = line.strip_prefix("```") {
+                let language = info.split_whitespace().next().unwrap_or("plaintext");
+                let mut code = vec![];
+
+                index += 1;
+                while index < lines.len() && lines[index].0.trim_end() != "```" {
+                    code.push(lines[index].0);
+                    index += 1;
+                }
+                index += 1;
+
+                html.push_str(&format!(
+                    "<pre class=\"language-{}\"><code>{}</code></pre>",
+                    escape_html(&language.to_lowercase()),
+                    escape_html(&code.join("\n"))
+                ));
+                continue;
+            }
+
+            let Some(block) = block_kind(line) else {
+                if line.trim().is_empty() {
+                    html.push_str("<p>&nbsp;</p>");
+                } else {
+                    html.push_str(&format!("<p>{}</p>", inline_html(line, formats)));
+                }
+                index += 1;
+                continue;
+            };
+
+            let is_list = block != LineBlock::Quote;
+            let mut items = vec![];
+
+            while index < lines.len()
+                && let Some((item_block, indentation, marker_length)) = line_block(lines[index].0)
+                && (item_block != LineBlock::Quote) == is_list
+            {
+                let (line, formats) = lines[index];
+                let prefix_length = indentation + marker_length;
+                let content: String = line.chars().skip(prefix_length).collect();
+                let formats = &formats[prefix_length.min(formats.len())..];
+
+                items.push((
+                    indentation / INDENT.len(),
+                    item_block,
+                    inline_html(&content, formats),
+                ));
+                index += 1;
+            }
+
+            if is_list {
+                html.push_str(&list_html(&items));
+            } else {
+                html.push_str("<blockquote>");
+                for (_, _, content) in items {
+                    html.push_str(&format!("<p>{}</p>", content));
+                }
+                html.push_str("</blockquote>");
+            }
+        }
+
+        html
+    }
+
+    // The same lines as markdown, for the plain composer
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = vec![];
+        let mut in_code_block = false;
+
+        for (line, formats) in self.lines() {
+            if line.starts_with("```") {
+                in_code_block = !in_code_block;
+            }
+            if in_code_block || line.starts_with("```") {
+                markdown.push(line.to_string());
+                continue;
+            }
+
+            let Some((block, indentation, marker_length)) = line_block(line) else {
+                markdown.push(inline_runs(line, formats, push_markdown_run));
+                continue;
+            };
+
+            let marker = match block {
+                LineBlock::Bullet => "- ".to_string(),
+                LineBlock::Quote => "> ".to_string(),
+                LineBlock::Ordered => line.chars().skip(indentation).take(marker_length).collect(),
+            };
+            let prefix_length = indentation + marker_length;
+            let content: String = line.chars().skip(prefix_length).collect();
+            let formats = &formats[prefix_length.min(formats.len())..];
+
+            markdown.push(format!(
+                "{}{}{}",
+                " ".repeat(indentation),
+                marker,
+                inline_runs(&content, formats, push_markdown_run)
+            ));
+        }
+
+        markdown.join("\n")
+    }
+}
+
+// Adds the marker to the selected lines, or removes it when they all have it already
+pub fn toggle_line_block(content: &mut Content, document: &mut RichDocument, block: LineBlock) {
+    let cursor = content.cursor();
+    let anchor_line = cursor
+        .selection
+        .as_ref()
+        .map_or(cursor.position.line, |selection| selection.line);
+
+    let text = content.text();
+    let lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+    let first_line = cursor.position.line.min(anchor_line);
+    let last_line = cursor.position.line.max(anchor_line).min(lines.len() - 1);
+
+    let remove = lines[first_line..=last_line]
+        .iter()
+        .all(|line| block_kind(line) == Some(block));
+
+    // Bottom up so the positions of the lines above stay the same
+    for index in (first_line..=last_line).rev() {
+        let line = &lines[index];
+
+        // Nested items keep their indentation
+        let indentation = line.len() - line.trim_start_matches(' ').len();
+
+        if let Some((_, indentation, marker_length)) = line_block(line) {
+            content.move_to(Cursor {
+                position: Position {
+                    line: index,
+                    column: indentation + marker_length,
+                },
+                selection: Some(Position {
+                    line: index,
+                    column: indentation,
+                }),
+            });
+            content.perform(Action::Edit(Edit::Backspace));
+            document.sync(&content.text());
+        }
+
+        if !remove {
+            let marker = match block {
+                LineBlock::Bullet => BULLET_MARKER.to_string(),
+                LineBlock::Ordered => format!("{}. ", index - first_line + 1),
+                LineBlock::Quote => QUOTE_MARKER.to_string(),
+            };
+
+            content.move_to(Cursor {
+                position: Position {
+                    line: index,
+                    column: indentation,
+                },
+                selection: None,
+            });
+            for char in marker.chars() {
+                content.perform(Action::Edit(Edit::Insert(char)));
+            }
+            // Markers are never formatted
+            document.sync_with(&content.text(), Some(CharFormat::default()));
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighlightSettings {
+    pub spans: Vec<Vec<(Range<usize>, CharFormat)>>,
+    pub link_color: Color,
+    pub underline_color: Color,
+    pub strikethrough_color: Color,
+    pub quote_color: Color,
+    // Byte ranges of the misspelled words of every line
+    pub misspelled: Vec<Vec<Range<usize>>>,
+    pub misspelled_color: Color,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RichHighlight {
+    bold: bool,
+    italic: bool,
+    monospace: bool,
+    color: Option<Color>,
+}
+
+// The highlighter format of the iced 0.14 editor only has a font and a color, so underline and
+// strikethrough are told apart by their color here. They are drawn as real underlines and
+// strikethroughs in the preview and the sent message. Misspelled words are only colored when the
+// spelling underlines can't be drawn over the editor
+
+pub struct RichHighlighter {
+    settings: HighlightSettings,
+    current_line: usize,
+}
+
+impl Highlighter for RichHighlighter {
+    type Settings = HighlightSettings;
+    type Highlight = RichHighlight;
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, RichHighlight)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        RichHighlighter {
+            settings: settings.clone(),
+            current_line: 0,
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        self.settings = new_settings.clone();
+        self.current_line = 0;
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.current_line = self.current_line.min(line);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        let settings = &self.settings;
+        let quote_color = line.starts_with(QUOTE_MARKER).then_some(settings.quote_color);
+
+        let mut highlights = vec![];
+        if let Some(color) = quote_color {
+            highlights.push((
+                0..line.len(),
+                RichHighlight {
+                    color: Some(color),
+                    ..Default::default()
+                },
+            ));
+        }
+
+        // The spans can be behind the editor for a frame after its text was replaced
+        let fits = |range: &Range<usize>| {
+            range.end <= line.len()
+                && line.is_char_boundary(range.start)
+                && line.is_char_boundary(range.end)
+        };
+
+        let spans = settings.spans.get(self.current_line).into_iter().flatten();
+        for (range, format) in spans.filter(|(range, _)| fits(range)) {
+            let color = if format.link.is_some() {
+                Some(settings.link_color)
+            } else if format.strikethrough {
+                Some(settings.strikethrough_color)
+            } else if format.underline {
+                Some(settings.underline_color)
+            } else {
+                quote_color
+            };
+
+            highlights.push((
+                range.clone(),
+                RichHighlight {
+                    bold: format.bold,
+                    italic: format.italic,
+                    monospace: format.code,
+                    color,
+                },
+            ));
+        }
+
+        // Later highlights replace the ones below them, so misspelled words keep their font
+        let misspelled = settings.misspelled.get(self.current_line).into_iter().flatten();
+        for range in misspelled.filter(|range| fits(range)) {
+            let highlight = highlights
+                .iter()
+                .rev()
+                .find(|(span, _)| span.start <= range.start && range.start < span.end)
+                .map(|(_, highlight)| *highlight)
+                .unwrap_or_default();
+
+            highlights.push((
+                range.clone(),
+                RichHighlight {
+                    color: Some(settings.misspelled_color),
+                    ..highlight
+                },
+            ));
+        }
+
+        self.current_line += 1;
+        highlights.into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.current_line
+    }
+}
+
+pub fn to_format(highlight: &RichHighlight, _theme: &Theme) -> Format<Font> {
+    let font = if highlight.monospace {
+        Font::MONOSPACE
+    } else {
+        Font::default()
+    };
+
+    Format {
+        color: highlight.color,
+        font: Some(Font {
+            weight: if highlight.bold {
+                font::Weight::Bold
+            } else {
+                font::Weight::Normal
+            },
+            style: if highlight.italic {
+                font::Style::Italic
+            } else {
+                font::Style::Normal
+            },
+            ..font
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bold() -> CharFormat {
+        CharFormat {
+            bold: true,
+            ..Default::default()
+        }
+    }
+
+    fn link(url: &str) -> CharFormat {
+        CharFormat {
+            link: Some(url.to_string()),
+            ..Default::default()
+        }
+    }
+
+    // The format applies to the chars in the range, the rest are unformatted
+    fn document(text: &str, range: Range<usize>, format: CharFormat) -> RichDocument {
+        let formats = (0..text.chars().count())
+            .map(|index| {
+                if range.contains(&index) {
+                    format.clone()
+                } else {
+                    CharFormat::default()
+                }
+            })
+            .collect();
+        RichDocument::with_formats(text, formats)
+    }
+
+    fn bold_chars(document: &RichDocument) -> String {
+        document
+            .text()
+            .chars()
+            .zip(document.formats())
+            .map(|(char, format)| if format.bold { char } else { '_' })
+            .collect()
+    }
+
+    #[test]
+    fn sync_only_changes_the_edited_formats() {
+        let mut document = document("hello world", 0..5, bold());
+
+        document.sync("hello big world");
+        assert_eq!(bold_chars(&document), "hello__________");
+
+        document.sync("hllo big world");
+        assert_eq!(bold_chars(&document), "hllo__________");
+
+        // Typed at the end of the bold run
+        document.sync("hllooo big world");
+        assert_eq!(bold_chars(&document), "hllooo__________");
+    }
+
+    #[test]
+    fn sync_uses_the_pending_format() {
+        let mut document = RichDocument::default();
+
+        document.toggle(0..0, InlineStyle::Bold);
+        document.sync("a");
+        document.toggle(1..1, InlineStyle::Bold);
+        document.sync("ab");
+
+        assert_eq!(bold_chars(&document), "a_");
+    }
+
+    #[test]
+    fn typing_after_a_link_does_not_extend_it() {
+        let mut document = document("link", 0..4, link("https://example.com"));
+        document.sync("links");
+
+        assert_eq!(document.formats()[3].link.as_deref(), Some("https://example.com"));
+        assert_eq!(document.formats()[4].link, None);
+    }
+
+    #[test]
+    fn html_round_trips() {
+        let text = "hello world\n• one\n    • two\n1. three\n▎ quote";
+        let document = document(text, 0..5, bold());
+
+        let html = document.to_html();
+        assert_eq!(
+            html,
+            "<p><strong>hello</strong> world</p><ul><li>one<ul><li>two</li></ul></li></ul>\
+             <ol><li>three</li></ol><blockquote><p>quote</p></blockquote>"
+        );
+        assert_eq!(RichDocument::from_html(&html), document);
+    }
+
+    #[test]
+    fn code_blocks_round_trip() {
+        let document =
+            RichDocument::from_html("<p>a</p><pre class=\"language-rust\">fn a() {}</pre>");
+
+        assert_eq!(document.text(), "a\n```rust\nfn a() {}\n```");
+        assert_eq!(
+            document.to_html(),
+            "<p>a</p><pre class=\"language-rust\"><code>fn a() {}</code></pre>"
+        );
+    }
+
+    #[test]
+    fn only_web_and_mail_links_are_sent() {
+        let html = |url: &str| document("click", 0..5, link(url)).to_html();
+
+        assert_eq!(
+            html("https://example.com"),
+            "<p><a href=\"https://example.com\">click</a></p>"
+        );
+        assert_eq!(
+            html("mailto:a@example.com"),
+            "<p><a href=\"mailto:a@example.com\">click</a></p>"
+        );
+        assert_eq!(html("javascript:alert(1)"), "<p>click</p>");
+        assert_eq!(html(" JavaScript:alert(1)"), "<p>click</p>");
+        assert_eq!(html("file:///etc/passwd"), "<p>click</p>");
+    }
+
+    #[test]
+    fn bare_urls_are_linked() {
+        let document = RichDocument::with_formats("see https://example.com", vec![]);
+
+        assert_eq!(
+            document.to_html(),
+            "<p>see <a href=\"https://example.com\">https://example.com</a></p>"
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use crate::utils::{get_config, get_config_dir, save_to_config};
+use regex::Regex;
+use spellbook::Dictionary;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use unicode_segmentation::UnicodeSegmentation;
+
+const PERSONAL_DICTIONARY: &str = "personal_dictionary.json";
+const SUGGESTIONS_LIMIT: usize = 6;
+
+// Links, inline code, html tags, emails and emoji shortcodes aren't prose
+static SKIPPED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"https?://\S+|www\.\S+|`[^`]*`|<[^>]*>|\S+@\S+|:[\w+-]+:").unwrap()
+});
+
+// The misspelled word the context menu was opened on, columns are in chars
+#[derive(Debug, Clone)]
+pub struct SpellingMenu {
+    pub line: usize,
+    pub columns: Range<usize>,
+    pub word: String,
+    pub suggestions: Vec<String>,
+}
+
+// Hunspell dictionaries are a <language>.aff and <language>.dic pair, the user's own come first
+fn dictionary_dirs() -> Vec<PathBuf> {
+    vec![
+        get_config_dir().join("dictionaries"),
+        PathBuf::from("/usr/share/hunspell"),
+        PathBuf::from("/usr/share/myspell"),
+        PathBuf::from("/usr/share/myspell/dicts"),
+    ]
+}
+
+pub fn available_languages() -> Vec<String> {
+    let mut languages: Vec<String> = dictionary_dirs()
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != "dic" || !path.with_extension("aff").exists() {
+                return None;
+            }
+            Some(path.file_stem()?.to_string_lossy().to_string())
+        })
+        .collect();
+
+    languages.sort();
+    languages.dedup();
+    languages
+}
+
+pub struct SpellChecker {
+    dictionary: Dictionary,
+    // Words added from the message area, shared by every language
+    personal_dictionary: BTreeSet<String>,
+}
+
+// The dictionary itself is too large to print
+impl fmt::Debug for SpellChecker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpellChecker")
+            .field("personal_dictionary", &self.personal_dictionary)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SpellChecker {
+    pub fn load(language: &str) -> Option<Self> {
+        let dir = dictionary_dirs().into_iter().find(|dir| {
+            dir.join(format!("{}.dic", language)).exists()
+                && dir.join(format!("{}.aff", language)).exists()
+        })?;
+
+        // Dictionaries in legacy encodings have to be converted to UTF-8 first
+        let aff = fs::read_to_string(dir.join(format!("{}.aff", language))).ok()?;
+        let dic = fs::read_to_string(dir.join(format!("{}.dic", language))).ok()?;
+
+        let dictionary = match Dictionary::new(&aff, &dic) {
+            Ok(dictionary) => dictionary,
+            Err(error) => {
+                eprintln!("Failed to load the {} dictionary: {}", language, error);
+                return None;
+            }
+        };
+
+        Some(SpellChecker {
+            dictionary,
+            personal_dictionary: get_config(PERSONAL_DICTIONARY).unwrap_or_default(),
+        })
+    }
+
+    fn check(&self, word: &str) -> bool {
+        self.personal_dictionary.contains(word) || self.dictionary.check(word)
+    }
+
+    fn suggest(&self, word: &str) -> Vec<String> {
+        let mut suggestions = vec![];
+        self.dictionary.suggest(word, &mut suggestions);
+        suggestions.truncate(SUGGESTIONS_LIMIT);
+        suggestions
+    }
+
+    pub fn add_word(&mut self, word: &str) {
+        self.personal_dictionary.insert(word.to_string());
+        save_to_config(PERSONAL_DICTIONARY, &self.personal_dictionary);
+    }
+
+    pub fn personal_dictionary_size(&self) -> usize {
+        self.personal_dictionary.len()
+    }
+
+    pub fn clear_personal_dictionary(&mut self) {
+        self.personal_dictionary.clear();
+        save_to_config(PERSONAL_DICTIONARY, &self.personal_dictionary);
+    }
+
+    // Byte ranges of the misspelled words in the line
+    fn misspelled(&self, line: &str) -> Vec<Range<usize>> {
+        let skipped: Vec<Range<usize>> = SKIPPED.find_iter(line).map(|skip| skip.range()).collect();
+
+        line.unicode_word_indices()
+            .filter(|(start, _)| !skipped.iter().any(|range| range.contains(start)))
+            .filter(|(_, word)| !word.chars().any(|char| char.is_numeric() || char == '_'))
+            .filter(|(_, word)| !self.check(word))
+            .map(|(start, word)| start..start + word.len())
+            .collect()
+    }
+
+    // For every line of the text, code blocks are skipped
+    pub fn misspelled_lines(&self, text: &str) -> Vec<Vec<Range<usize>>> {
+        let mut in_code_block = false;
+
+        text.split('\n')
+            .map(|line| {
+                if line.trim_start().starts_with("```") {
+                    in_code_block = !in_code_block;
+                    return vec![];
+                }
+
+                if in_code_block {
+                    vec![]
+                } else {
+                    self.misspelled(line)
+                }
+            })
+            .collect()
+    }
+
+    pub fn menu_at(&self, text: &str, line_index: usize, column: usize) -> Option<SpellingMenu> {
+        let line = text.split('\n').nth(line_index)?;
+        let offset = line
+            .char_indices()
+            .nth(column)
+            .map_or(line.len(), |(offset, _)| offset);
+
+        let range = self
+            .misspelled(line)
+            .into_iter()
+            .find(|range| range.start <= offset && offset <= range.end)?;
+        let word = line[range.clone()].to_string();
+        let start = line[..range.start].chars().count();
+
+        Some(SpellingMenu {
+            line: line_index,
+            columns: start..start + word.chars().count(),
+            suggestions: self.suggest(&word),
+            word,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spell_checker() -> SpellChecker {
+        let dictionary = Dictionary::new("SET UTF-8\n", "4\nhello\nworld\nsee\nthe\n").unwrap();
+
+        SpellChecker {
+            dictionary,
+            personal_dictionary: BTreeSet::from(["café".to_string()]),
+        }
+    }
+
+    #[test]
+    fn misspelled_words_are_byte_ranges_per_line() {
+        let lines = spell_checker().misspelled_lines("hello wrold\n\ncafé wrold the");
+
+        assert_eq!(lines, vec![vec![6..11], vec![], vec![6..11]]);
+    }
+
+    #[test]
+    fn code_links_and_shortcodes_are_skipped() {
+        let text =
+            "see `wrold` https://wrold.com a@wrold.com :wrold: wrold2\n```\nwrold\n```\nwrold";
+        let lines = spell_checker().misspelled_lines(text);
+
+        assert_eq!(lines, vec![vec![], vec![], vec![], vec![], vec![0..5]]);
+    }
+
+    #[test]
+    fn menu_is_opened_on_the_misspelled_word() {
+        let spell_checker = spell_checker();
+
+        let menu = spell_checker.menu_at("hello\ncafé wrold", 1, 7).unwrap();
+        assert_eq!(menu.line, 1);
+        assert_eq!(menu.columns, 5..10);
+        assert_eq!(menu.word, "wrold");
+        assert!(menu.suggestions.contains(&"world".to_string()));
+
+        assert!(spell_checker.menu_at("hello\ncafé wrold", 1, 2).is_none());
+    }
+}
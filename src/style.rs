@@ -1,4 +1,9 @@
-use std::str::FromStr;
+use std::{collections::HashMap, fs, path::PathBuf, str::FromStr, time::SystemTime};
+
+use serde::Deserialize;
+
+use crate::types::ThemePreference;
+use crate::utils::get_config_dir;
 
 use iced::{
     Color, Shadow, border,
@@ -41,43 +46,73 @@ pub struct Colors {
     pub notification: Color,
     pub emotion_selected: Color,
     pub emotion_selected_line: Color,
+    pub image_placeholder: Color,
+    pub warning: Color,
 }
 
 #[derive(Debug)]
 pub struct Theme {
+    pub name: String,
     pub colors: Colors,
     pub stylesheet: Stylesheet,
 }
 
-pub fn squads_dark() -> Theme {
-    let colors = Colors {
-        not_set: Color::from_rgb(1.0, 0.0, 0.0), // To be used as a palceholder for colors that are not yet choosen
-        background: Color::from_str("#211F1F").expect("Color is invalid."),
-        background_button: Color::from_str("#2B2929").expect("Color is invalid."),
-        foreground: Color::from_str("#1B1A1A").expect("Color is invalid."),
-        foreground_button: Color::from_str("#232222").expect("Color is invalid."),
-        foreground_button_nobg_hovered: Color::from_str("#201F1F").expect("Color is invalid."),
-        foreground_button_nobg_selected: Color::from_str("#323030").expect("Color is invalid."),
-        foreground_surface: Color::from_str("#332E2E").expect("Color is invalid."),
-        foreground_alt: Color::from_str("#272525").expect("Color is invalid."),
-        line: Color::from_str("#393939").expect("Color is invalid."),
-        tooltip: Color::from_str("#29292A").expect("Color is invalid."),
-        status_available: Color::from_str("#4db255").expect("Color is invalid."),
-        status_offline: Color::from_str("#696c65").expect("Color is invalid."),
-        status_busy: Color::from_str("#a92622").expect("Color is invalid."),
-        status_away: Color::from_str("#ed9612").expect("Color is invalid."),
-        message_hovered: Color::from_str("#2A2929").expect("Color is invalid."),
-        text: Color::WHITE,
-        text_link: Color::from_str("#767df5").expect("Color is invalid."),
-        demo_text: Color::from_str("#c1c1c1").expect("Color is invalid."),
-        text_selection: Color::from_str("#0824bb").expect("Color is invalid."),
-        accent: Color::WHITE,
-        notification: Color::WHITE,
-        emotion_selected: Color::from_str("#24323D").expect("Color is invalid."),
-        emotion_selected_line: Color::from_str("#5057D7").expect("Color is invalid."),
-    };
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeBase {
+    #[default]
+    Dark,
+    Light,
+}
 
-    let stylesheet = Stylesheet {
+// Theme files only need to list the colors that differ from their base theme, e.g.
+// { "name": "Midnight", "base": "dark", "colors": { "background": "#101018" } }
+#[derive(Deserialize, Debug)]
+pub struct ThemeFile {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub base: ThemeBase,
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+}
+
+impl Colors {
+    fn set(&mut self, name: &str, color: Color) -> bool {
+        let field = match name {
+            "background" => &mut self.background,
+            "background_button" => &mut self.background_button,
+            "foreground" => &mut self.foreground,
+            "foreground_button" => &mut self.foreground_button,
+            "foreground_button_nobg_hovered" => &mut self.foreground_button_nobg_hovered,
+            "foreground_button_nobg_selected" => &mut self.foreground_button_nobg_selected,
+            "foreground_surface" => &mut self.foreground_surface,
+            "foreground_alt" => &mut self.foreground_alt,
+            "line" => &mut self.line,
+            "tooltip" => &mut self.tooltip,
+            "status_available" => &mut self.status_available,
+            "status_offline" => &mut self.status_offline,
+            "status_busy" => &mut self.status_busy,
+            "status_away" => &mut self.status_away,
+            "message_hovered" => &mut self.message_hovered,
+            "text" => &mut self.text,
+            "text_link" => &mut self.text_link,
+            "demo_text" => &mut self.demo_text,
+            "text_selection" => &mut self.text_selection,
+            "accent" => &mut self.accent,
+            "notification" => &mut self.notification,
+            "emotion_selected" => &mut self.emotion_selected,
+            "emotion_selected_line" => &mut self.emotion_selected_line,
+            "image_placeholder" => &mut self.image_placeholder,
+            "warning" => &mut self.warning,
+            _ => return false,
+        };
+        *field = color;
+        true
+    }
+}
+
+fn stylesheet(colors: &Colors) -> Stylesheet {
+    Stylesheet {
         input: text_input::Style {
             background: colors.foreground_surface.into(),
             border: border::rounded(6),
@@ -117,11 +152,139 @@ pub fn squads_dark() -> Theme {
                 },
             },
         },
+    }
+}
+
+fn squads_dark_colors() -> Colors {
+    Colors {
+        not_set: Color::from_rgb(1.0, 0.0, 0.0), // To be used as a palceholder for colors that are not yet choosen
+        background: Color::from_str("#211F1F").expect("Color is invalid."),
+        background_button: Color::from_str("#2B2929").expect("Color is invalid."),
+        foreground: Color::from_str("#1B1A1A").expect("Color is invalid."),
+        foreground_button: Color::from_str("#232222").expect("Color is invalid."),
+        foreground_button_nobg_hovered: Color::from_str("#201F1F").expect("Color is invalid."),
+        foreground_button_nobg_selected: Color::from_str("#323030").expect("Color is invalid."),
+        foreground_surface: Color::from_str("#332E2E").expect("Color is invalid."),
+        foreground_alt: Color::from_str("#272525").expect("Color is invalid."),
+        line: Color::from_str("#393939").expect("Color is invalid."),
+        tooltip: Color::from_str("#29292A").expect("Color is invalid."),
+        status_available: Color::from_str("#4db255").expect("Color is invalid."),
+        status_offline: Color::from_str("#696c65").expect("Color is invalid."),
+        status_busy: Color::from_str("#a92622").expect("Color is invalid."),
+        status_away: Color::from_str("#ed9612").expect("Color is invalid."),
+        message_hovered: Color::from_str("#2A2929").expect("Color is invalid."),
+        text: Color::WHITE,
+        text_link: Color::from_str("#767df5").expect("Color is invalid."),
+        demo_text: Color::from_str("#c1c1c1").expect("Color is invalid."),
+        text_selection: Color::from_str("#0824bb").expect("Color is invalid."),
+        accent: Color::WHITE,
+        notification: Color::WHITE,
+        emotion_selected: Color::from_str("#24323D").expect("Color is invalid."),
+        emotion_selected_line: Color::from_str("#5057D7").expect("Color is invalid."),
+        image_placeholder: Color::from_str("#b8b4b4").expect("Color is invalid."),
+        warning: Color::from_str("#ff9933").expect("Color is invalid."),
+    }
+}
+
+fn squads_light_colors() -> Colors {
+    Colors {
+        not_set: Color::from_rgb(1.0, 0.0, 0.0),
+        background: Color::from_str("#F5F5F5").expect("Color is invalid."),
+        background_button: Color::from_str("#E8E6E6").expect("Color is invalid."),
+        foreground: Color::from_str("#FFFFFF").expect("Color is invalid."),
+        foreground_button: Color::from_str("#F0EFEF").expect("Color is invalid."),
+        foreground_button_nobg_hovered: Color::from_str("#F2F1F1").expect("Color is invalid."),
+        foreground_button_nobg_selected: Color::from_str("#E3E1E1").expect("Color is invalid."),
+        foreground_surface: Color::from_str("#E6E3E3").expect("Color is invalid."),
+        foreground_alt: Color::from_str("#FAFAFA").expect("Color is invalid."),
+        line: Color::from_str("#D6D6D6").expect("Color is invalid."),
+        tooltip: Color::from_str("#EDEDEE").expect("Color is invalid."),
+        status_available: Color::from_str("#4db255").expect("Color is invalid."),
+        status_offline: Color::from_str("#8a8d86").expect("Color is invalid."),
+        status_busy: Color::from_str("#c4314b").expect("Color is invalid."),
+        status_away: Color::from_str("#e0850b").expect("Color is invalid."),
+        message_hovered: Color::from_str("#EFEEEE").expect("Color is invalid."),
+        text: Color::from_str("#242424").expect("Color is invalid."),
+        text_link: Color::from_str("#4f52b2").expect("Color is invalid."),
+        demo_text: Color::from_str("#616161").expect("Color is invalid."),
+        text_selection: Color::from_str("#a8b8f5").expect("Color is invalid."),
+        accent: Color::from_str("#424242").expect("Color is invalid."),
+        notification: Color::from_str("#242424").expect("Color is invalid."),
+        emotion_selected: Color::from_str("#E8EBFA").expect("Color is invalid."),
+        emotion_selected_line: Color::from_str("#5B5FC7").expect("Color is invalid."),
+        image_placeholder: Color::from_str("#d6d2d2").expect("Color is invalid."),
+        warning: Color::from_str("#c4600a").expect("Color is invalid."),
+    }
+}
+
+impl Theme {
+    fn new(name: &str, colors: Colors) -> Theme {
+        Theme {
+            name: name.to_string(),
+            stylesheet: stylesheet(&colors),
+            colors,
+        }
+    }
+}
+
+pub fn squads_dark() -> Theme {
+    Theme::new("Squads Dark", squads_dark_colors())
+}
+
+pub fn squads_light() -> Theme {
+    Theme::new("Squads Light", squads_light_colors())
+}
+
+pub fn theme_file_path(file_name: &str) -> PathBuf {
+    get_config_dir().join("themes").join(file_name)
+}
+
+// Used to hot reload the theme file when it is edited
+pub fn theme_file_modified(file_name: &str) -> Option<SystemTime> {
+    fs::metadata(theme_file_path(file_name))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+pub fn load_theme_file(file_name: &str) -> Result<Theme, String> {
+    let content = fs::read_to_string(theme_file_path(file_name)).map_err(|err| err.to_string())?;
+    let theme_file: ThemeFile = serde_json::from_str(&content).map_err(|err| err.to_string())?;
+
+    let mut colors = match theme_file.base {
+        ThemeBase::Dark => squads_dark_colors(),
+        ThemeBase::Light => squads_light_colors(),
     };
 
-    Theme { colors, stylesheet }
+    for (name, value) in &theme_file.colors {
+        match Color::from_str(value) {
+            Ok(color) => {
+                if !colors.set(name, color) {
+                    eprintln!("Theme {}: unknown color \"{}\"", file_name, name);
+                }
+            }
+            Err(_) => eprintln!("Theme {}: invalid color \"{}\" for {}", file_name, value, name),
+        }
+    }
+
+    let name = theme_file.name.unwrap_or(file_name.trim_end_matches(".json").to_string());
+
+    Ok(Theme::new(&name, colors))
 }
 
-pub fn global_theme() -> Theme {
-    squads_dark()
+pub fn global_theme(preference: &ThemePreference, system_prefers_dark: bool) -> Theme {
+    match preference {
+        ThemePreference::Dark => squads_dark(),
+        ThemePreference::Light => squads_light(),
+        ThemePreference::System => {
+            if system_prefers_dark {
+                squads_dark()
+            } else {
+                squads_light()
+            }
+        }
+        ThemePreference::File(file_name) => load_theme_file(file_name).unwrap_or_else(|err| {
+            eprintln!("Failed to load theme {}: {}", file_name, err);
+            squads_dark()
+        }),
+    }
 }
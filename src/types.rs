@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::api::{Channel, Link, Team, TeamMember};
+use crate::rich_text::CharFormat;
+
 #[derive(Debug, Clone)]
 pub enum MessageAreaAction {
     Bold,
@@ -7,6 +12,7 @@ pub enum MessageAreaAction {
     Underline,
     Striketrough,
     Code,
+    CodeBlock(String), // Language
     Blockquote,
     Link,
     Image,
@@ -14,11 +20,378 @@ pub enum MessageAreaAction {
     OrderedList,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaKind {
+    Audio,
+    Video,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaAttachment {
+    pub kind: MediaKind,
+    pub url: String, // The AMS object, or a view of it
+    pub thumbnail_url: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub name: Option<String>,
+}
+
+impl MediaAttachment {
+    pub fn view_url(&self) -> String {
+        if self.url.contains("/views/") {
+            return self.url.clone();
+        }
+        match self.kind {
+            MediaKind::Audio => format!("{}/views/audio", self.url),
+            MediaKind::Video => format!("{}/views/video", self.url),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PendingImage {
+    pub identifier: String, // The image is stored in the image cache under this identifier
+    pub width: u32,
+    pub height: u32,
+}
+
+// What an optimistic team change puts back when its request fails
+#[derive(Debug, Clone)]
+pub enum TeamRollback {
+    ChannelName(String, String),            // Channel id, previous name
+    Channel(String, usize, Channel),        // Team id, position, channel
+    Member(String, usize, TeamMember),      // Team id, position, member
+    MemberRoles(String, String, Vec<String>), // Team id, membership id, previous roles
+    Team(usize, Team, Option<Vec<TeamMember>>), // Position, team, its loaded members
+}
+
+// The message a chat message is quoted from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReplyTarget {
+    pub message_id: String,
+    pub name: String,
+    pub preview: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Draft {
+    pub text: String,
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub images: Vec<PendingImage>,
+    #[serde(default)]
+    pub formats: Vec<CharFormat>, // Of every char of the text, in the rich text composer
+    #[serde(default)]
+    pub reply_to: Option<ReplyTarget>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduledMessage {
+    pub id: String,
+    pub conversation_id: String,
+    pub text: String,
+    #[serde(default)]
+    pub html: Option<String>, // Set when written in the rich text composer
+    #[serde(default)]
+    pub subject: Option<String>, // Channel posts only
+    pub send_at: i64,            // Epoch in seconds
+    #[serde(default)]
+    pub pending_images: Vec<PendingImage>,
+    #[serde(default)]
+    pub links: Vec<Link>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum OutboxState {
+    Pending,
+    Sent,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OutboxMessage {
+    pub client_message_id: String,
+    pub conversation_id: String,
+    pub html: String,
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub ams_references: Vec<String>,
+    pub timestamp: String,
+    pub state: OutboxState,
+    #[serde(default)]
+    pub post_options: PostOptions,
+    #[serde(default)]
+    pub links: Vec<Link>,
+    // Uploaded before the message is sent, kept so a failed upload can be retried
+    #[serde(default)]
+    pub pending_images: Vec<PendingImage>,
+    #[serde(default = "default_outbox_message_type")]
+    pub message_type: String,
+}
+
+fn default_outbox_message_type() -> String {
+    "RichText/Html".to_string()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReplyPolicy {
+    #[default]
+    Everyone,
+    Moderators,
+}
+
+// Options for channel posts, set from the team composer
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PostOptions {
+    pub announcement: bool,
+    pub headline: String,
+    pub banner: Option<PendingImage>,
+    pub cross_post_channels: Vec<String>, // Channel ids besides the one being posted in
+    pub reply_policy: ReplyPolicy,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum FilesSortColumn {
+    Name,
+    Size,
+    Modified,
+    ModifiedBy,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Emoji {
     pub unicode: String,
     pub category: String,
     pub keywords: Vec<String>,
+    #[serde(default)]
+    pub image_url: Option<String>, // Only set for tenant custom emojis
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum SkinTone {
+    #[default]
+    Default,
+    Light,
+    MediumLight,
+    Medium,
+    MediumDark,
+    Dark,
+}
+
+impl SkinTone {
+    pub const ALL: [SkinTone; 6] = [
+        SkinTone::Default,
+        SkinTone::Light,
+        SkinTone::MediumLight,
+        SkinTone::Medium,
+        SkinTone::MediumDark,
+        SkinTone::Dark,
+    ];
+
+    pub fn modifier(&self) -> Option<char> {
+        match self {
+            SkinTone::Default => None,
+            SkinTone::Light => Some('\u{1F3FB}'),
+            SkinTone::MediumLight => Some('\u{1F3FC}'),
+            SkinTone::Medium => Some('\u{1F3FD}'),
+            SkinTone::MediumDark => Some('\u{1F3FE}'),
+            SkinTone::Dark => Some('\u{1F3FF}'),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct EmojiUsage {
+    pub count: u32,
+    pub last_used: u64, // Epoch seconds
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub enum ThemePreference {
+    #[default]
+    Dark,
+    Light,
+    System, // Follows the light/dark preference of the desktop
+    File(String), // A theme file in the themes config directory
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum MessageDensity {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+impl MessageDensity {
+    // Multiplier for the spacing between and around messages
+    pub fn scale(&self) -> f32 {
+        match self {
+            MessageDensity::Comfortable => 1.0,
+            MessageDensity::Compact => 0.4,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SettingsSection {
+    Account,
+    Appearance,
+    Notifications,
+    Privacy,
+    Storage,
+    Keyboard,
+    Spelling,
+}
+
+impl SettingsSection {
+    pub const ALL: [SettingsSection; 7] = [
+        SettingsSection::Account,
+        SettingsSection::Appearance,
+        SettingsSection::Notifications,
+        SettingsSection::Privacy,
+        SettingsSection::Storage,
+        SettingsSection::Keyboard,
+        SettingsSection::Spelling,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            SettingsSection::Account => "Account",
+            SettingsSection::Appearance => "Appearance",
+            SettingsSection::Notifications => "Notifications",
+            SettingsSection::Privacy => "Privacy",
+            SettingsSection::Storage => "Storage",
+            SettingsSection::Keyboard => "Keyboard",
+            SettingsSection::Spelling => "Spelling",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shortcut {
+    QuickSwitcher,
+    PreviousConversation,
+    NextConversation,
+    OpenActivity,
+    EditLastMessage,
+    Search,
+    ShowShortcuts,
+    Undo,
+    Redo,
+}
+
+impl Shortcut {
+    pub const ALL: [Shortcut; 9] = [
+        Shortcut::QuickSwitcher,
+        Shortcut::PreviousConversation,
+        Shortcut::NextConversation,
+        Shortcut::OpenActivity,
+        Shortcut::EditLastMessage,
+        Shortcut::Search,
+        Shortcut::ShowShortcuts,
+        Shortcut::Undo,
+        Shortcut::Redo,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Shortcut::QuickSwitcher => "Jump to a chat, channel or person",
+            Shortcut::PreviousConversation => "Go to the previous chat or channel",
+            Shortcut::NextConversation => "Go to the next chat or channel",
+            Shortcut::OpenActivity => "Open activity",
+            Shortcut::EditLastMessage => "Edit your last message (in an empty message area)",
+            Shortcut::Search => "Search chats",
+            Shortcut::ShowShortcuts => "Show keyboard shortcuts",
+            Shortcut::Undo => "Undo in the message area",
+            Shortcut::Redo => "Redo in the message area",
+        }
+    }
+
+    pub fn default_binding(&self) -> &'static str {
+        match self {
+            Shortcut::QuickSwitcher => "Ctrl+K",
+            Shortcut::PreviousConversation => "Alt+ArrowUp",
+            Shortcut::NextConversation => "Alt+ArrowDown",
+            Shortcut::OpenActivity => "Ctrl+Shift+A",
+            Shortcut::EditLastMessage => "ArrowUp",
+            Shortcut::Search => "Ctrl+F",
+            Shortcut::ShowShortcuts => "?",
+            Shortcut::Undo => "Ctrl+Z",
+            Shortcut::Redo => "Ctrl+Shift+Z",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SwitcherTarget {
+    Chat(String),            // Chat id
+    Channel(String, String), // Team id, channel id
+    Person(String),          // User id
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitcherItem {
+    pub title: String,
+    pub subtitle: String,
+    pub target: SwitcherTarget,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    // Appearance
+    pub skin_tone: SkinTone,
+    pub theme: ThemePreference,
+    pub message_density: MessageDensity,
+    pub window_width: f32, // The window size on startup
+    pub window_height: f32,
+    // Notifications
+    pub unread_badges: bool,
+    pub desktop_notifications: bool,
+    pub notification_sound: bool,
+    pub notify_mentions_only: bool, // Channel posts only ever notify when they mention you
+    // Privacy
+    pub send_read_receipts: bool,
+    pub send_typing_indicator: bool,
+    // Storage
+    pub image_cache_limit_mb: u64,
+    // Keyboard
+    pub send_on_enter: bool, // Otherwise Shift + Enter sends
+    pub rich_text_composer: bool, // Formatting is shown in the message area instead of as markdown
+    pub keybindings: HashMap<Shortcut, String>, // Shortcuts missing here use their default
+    // Spelling
+    pub spell_check_language: Option<String>, // Name of the Hunspell dictionary, None disables it
+}
+
+impl Settings {
+    pub fn binding(&self, shortcut: &Shortcut) -> &str {
+        self.keybindings
+            .get(shortcut)
+            .map(String::as_str)
+            .unwrap_or(shortcut.default_binding())
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            skin_tone: SkinTone::default(),
+            theme: ThemePreference::default(),
+            message_density: MessageDensity::default(),
+            window_width: 1240.0,
+            window_height: 780.0,
+            unread_badges: true,
+            desktop_notifications: true,
+            notification_sound: true,
+            notify_mentions_only: false,
+            send_read_receipts: true,
+            send_typing_indicator: true,
+            image_cache_limit_mb: 500,
+            send_on_enter: true,
+            rich_text_composer: false,
+            keybindings: HashMap::new(),
+            spell_check_language: None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -59,6 +432,8 @@ pub struct Properties<'a> {
     pub files: &'a str,
     pub policy_violation: Option<&'a str>,
     pub format_variant: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_restriction: Option<&'a str>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -83,3 +458,9 @@ pub struct Thread {
     pub members: Vec<ThreadMember>,
     pub properties: Option<ThreadProperties>,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ChannelSettings {
+    pub favourite: bool,
+    pub hidden: bool,
+}
@@ -1,10 +1,22 @@
+use bytes::Bytes;
+use chrono::{Local, NaiveDateTime, TimeZone};
 use directories::ProjectDirs;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use iced::keyboard::key::Named;
+use iced::keyboard::{Key, Modifiers};
+use iced::widget::{Svg, svg};
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
 use std::{
-    env, fs, io::Write, path::{PathBuf}, time::{SystemTime, UNIX_EPOCH}
+    env, fs, io::{Cursor, Write}, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}
 };
 
+use crate::api::TeamsTab;
+use crate::components::cached_image::save_cached_image;
+use crate::style;
+use crate::types::{Emoji, EmojiUsage, PendingImage, SkinTone};
+
 pub fn truncate_name(name: String, max_length: usize) -> String {
     if name.len() > max_length {
         let cutoff = max_length.saturating_sub(3);
@@ -61,6 +73,25 @@ pub fn delete_cache(filename: &str) {
     }
 }
 
+pub fn save_to_config<T>(filename: &str, content: &T)
+where
+    T: Serialize,
+{
+    let mut config_dir = get_config_dir();
+    fs::create_dir_all(config_dir.clone()).expect("Failed to create config directory");
+
+    config_dir.push(filename);
+
+    let json = serde_json::to_string_pretty(content).expect("Failed to serialize content");
+    let mut file = fs::File::create(config_dir).unwrap();
+    file.write_all(json.as_bytes()).unwrap();
+}
+
+pub fn get_config<T: DeserializeOwned>(filename: &str) -> Option<T> {
+    let file_content = fs::read_to_string(get_config_dir().join(filename)).ok()?;
+    serde_json::from_str(&file_content).ok()
+}
+
 pub fn get_epoch_s() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -75,10 +106,401 @@ pub fn get_epoch_ms() -> u128 {
         .as_millis()
 }
 
+pub fn get_config_dir() -> PathBuf {
+    let project_dirs = ProjectDirs::from("", "ianterzo", "squads");
+    project_dirs.unwrap().config_dir().to_path_buf()
+}
+
 pub fn get_image_dir() -> PathBuf {
 	PathBuf::from(env::var("SQUADS_IMAGE_DIR").unwrap_or("images".to_string()))
 }
 
+// The icon files are drawn in white and recolored to the theme's text color
+pub fn icon<'a>(theme: &style::Theme, file_name: &str) -> Svg<'a> {
+    let color = theme.colors.text;
+    svg(get_image_dir().join(file_name)).style(move |_, _| svg::Style { color: Some(color) })
+}
+
 pub fn get_resource_dir() -> PathBuf {
 	PathBuf::from(env::var("SQUADS_RESOURCE_DIR").unwrap_or("resources".to_string()))
 }
+
+pub fn message_link(thread_id: &str, message_id: &str) -> String {
+    let context_type = if thread_id.contains("@thread.tacv") {
+        "channel"
+    } else {
+        "chat"
+    };
+
+    format!(
+        "https://teams.microsoft.com/l/message/{}/{}?context={}",
+        urlencoding::encode(thread_id),
+        message_id,
+        urlencoding::encode(&format!("{{\"contextType\":\"{}\"}}", context_type))
+    )
+}
+
+pub fn parse_local_time(input: &str) -> Option<i64> {
+    let naive_time = NaiveDateTime::parse_from_str(input.trim(), "%Y-%m-%d %H:%M").ok()?;
+    naive_time
+        .and_local_timezone(Local)
+        .single()
+        .map(|time| time.timestamp())
+}
+
+pub fn format_local_time(epoch_s: i64) -> String {
+    Local
+        .timestamp_opt(epoch_s, 0)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or("n/a".to_string())
+}
+
+fn save_pending_image(bytes: Vec<u8>, width: u32, height: u32) -> PendingImage {
+    let identifier = format!("pending{}", rand::random::<u64>());
+    save_cached_image(identifier.clone(), Bytes::from(bytes));
+
+    PendingImage {
+        identifier,
+        width,
+        height,
+    }
+}
+
+pub fn read_clipboard_image() -> Option<PendingImage> {
+    let mut clipboard = arboard::Clipboard::new().ok()?;
+    let image_data = clipboard.get_image().ok()?;
+
+    let width = image_data.width as u32;
+    let height = image_data.height as u32;
+
+    let buffer = image::RgbaImage::from_raw(width, height, image_data.bytes.into_owned())?;
+
+    let mut bytes = Cursor::new(Vec::new());
+    buffer.write_to(&mut bytes, image::ImageFormat::Png).ok()?;
+
+    Some(save_pending_image(bytes.into_inner(), width, height))
+}
+
+pub fn load_pending_image(path: &Path) -> Option<PendingImage> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    if !["png", "jpg", "jpeg", "gif", "webp", "bmp"].contains(&extension.as_str()) {
+        return None;
+    }
+
+    let (width, height) = image::image_dimensions(path).ok()?;
+    let bytes = fs::read(path).ok()?;
+
+    Some(save_pending_image(bytes, width, height))
+}
+
+pub fn format_file_size(size: i64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", size, units[unit])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+fn tab_app_id(tab: &TeamsTab) -> &str {
+    tab.teams_app.as_ref().map(|app| app.id.as_str()).unwrap_or("")
+}
+
+pub fn tab_plan_id(tab: &TeamsTab) -> Option<String> {
+    if tab_app_id(tab) != "com.microsoft.teamspace.tab.planner" {
+        return None;
+    }
+
+    let configuration = tab.configuration.as_ref()?;
+
+    // The plan id is in the content url query, older tabs only have it in the entity id
+    // which looks like tt.c_{channel}_p_{plan}_h_{time}
+    if let Some(content_url) = &configuration.content_url
+        && let Some(plan_id) = content_url
+            .split(['?', '&'])
+            .find_map(|param| param.strip_prefix("planId="))
+    {
+        return Some(plan_id.to_string());
+    }
+
+    let entity_id = configuration.entity_id.as_ref()?;
+    let plan_id = entity_id.split("_p_").nth(1)?.split("_h_").next()?;
+    Some(plan_id.to_string())
+}
+
+pub fn is_notes_tab(tab: &TeamsTab) -> bool {
+    // OneNote
+    tab_app_id(tab) == "0d820ecd-def2-4297-adad-78056cde7c78"
+}
+
+// A notes tab shows one section of the team notebook, usually the one of its channel
+pub fn tab_notes_section_id(tab: &TeamsTab) -> Option<String> {
+    if !is_notes_tab(tab) {
+        return None;
+    }
+
+    let content_url = tab.configuration.as_ref()?.content_url.as_ref()?;
+    let section_id = content_url
+        .split(['?', '&'])
+        .find_map(|param| param.strip_prefix("sectionId="))?;
+
+    urlencoding::decode(section_id)
+        .ok()
+        .map(|section_id| section_id.to_string())
+        .filter(|section_id| !section_id.is_empty())
+}
+
+pub fn tab_url(tab: &TeamsTab) -> Option<String> {
+    let configuration = tab.configuration.as_ref();
+
+    configuration
+        .and_then(|configuration| configuration.website_url.clone())
+        .or(tab.web_url.clone())
+        .or(configuration.and_then(|configuration| configuration.content_url.clone()))
+}
+
+// Unicode Emoji_Modifier_Base ranges, the only emojis that take a skin tone
+const SKIN_TONE_BASES: [(u32, u32); 40] = [
+    (0x261D, 0x261D),
+    (0x26F9, 0x26F9),
+    (0x270A, 0x270D),
+    (0x1F385, 0x1F385),
+    (0x1F3C2, 0x1F3C4),
+    (0x1F3C7, 0x1F3C7),
+    (0x1F3CA, 0x1F3CC),
+    (0x1F442, 0x1F443),
+    (0x1F446, 0x1F450),
+    (0x1F466, 0x1F478),
+    (0x1F47C, 0x1F47C),
+    (0x1F481, 0x1F483),
+    (0x1F485, 0x1F487),
+    (0x1F48F, 0x1F48F),
+    (0x1F491, 0x1F491),
+    (0x1F4AA, 0x1F4AA),
+    (0x1F574, 0x1F575),
+    (0x1F57A, 0x1F57A),
+    (0x1F590, 0x1F590),
+    (0x1F595, 0x1F596),
+    (0x1F645, 0x1F647),
+    (0x1F64B, 0x1F64F),
+    (0x1F6A3, 0x1F6A3),
+    (0x1F6B4, 0x1F6B6),
+    (0x1F6C0, 0x1F6C0),
+    (0x1F6CC, 0x1F6CC),
+    (0x1F90C, 0x1F90C),
+    (0x1F90F, 0x1F90F),
+    (0x1F918, 0x1F91F),
+    (0x1F926, 0x1F926),
+    (0x1F930, 0x1F939),
+    (0x1F93C, 0x1F93E),
+    (0x1F977, 0x1F977),
+    (0x1F9B5, 0x1F9B6),
+    (0x1F9B8, 0x1F9B9),
+    (0x1F9BB, 0x1F9BB),
+    (0x1F9CD, 0x1F9CF),
+    (0x1F9D1, 0x1F9DD),
+    (0x1FAC3, 0x1FAC5),
+    (0x1FAF0, 0x1FAF8),
+];
+
+pub fn apply_skin_tone(unicode: &str, skin_tone: &SkinTone) -> String {
+    let Some(modifier) = skin_tone.modifier() else {
+        return unicode.to_string();
+    };
+
+    let mut chars = unicode.chars();
+    let Some(first) = chars.next() else {
+        return unicode.to_string();
+    };
+
+    let code = first as u32;
+    if !SKIN_TONE_BASES
+        .iter()
+        .any(|(start, end)| code >= *start && code <= *end)
+    {
+        return unicode.to_string();
+    }
+
+    // The modifier replaces the emoji presentation selector
+    let rest: String = chars.as_str().trim_start_matches('\u{FE0F}').to_string();
+    format!("{}{}{}", first, modifier, rest)
+}
+
+// Cache identifier of an AMS image, derived from its url
+pub fn ams_image_identifier(image_url: &str) -> String {
+    image_url
+        .replace("https:", "")
+        .replace("/", "")
+        .replace(":", "") // Windows
+}
+
+// Frequency weighted by recency, an emoji used often last month ranks below one used today
+fn emoji_score(emoji_usage: Option<&EmojiUsage>) -> f64 {
+    let Some(emoji_usage) = emoji_usage else {
+        return 0.0;
+    };
+
+    let days_since = get_epoch_s().saturating_sub(emoji_usage.last_used) as f64 / 86400.0;
+    emoji_usage.count as f64 / (1.0 + days_since)
+}
+
+pub fn frequent_emojis<'a>(
+    emoji_map: &'a IndexMap<String, Emoji>,
+    emoji_usage: &HashMap<String, EmojiUsage>,
+    limit: usize,
+) -> Vec<(&'a String, &'a Emoji)> {
+    let mut frequent: Vec<(&String, &Emoji)> = emoji_usage
+        .keys()
+        .filter_map(|emoji_id| emoji_map.get_key_value(emoji_id))
+        .collect();
+
+    frequent.sort_by(|a, b| {
+        emoji_score(emoji_usage.get(b.0)).total_cmp(&emoji_score(emoji_usage.get(a.0)))
+    });
+    frequent.truncate(limit);
+    frequent
+}
+
+// Used emojis rank first, then keyword prefix matches. Aliases of an emoji are only listed once
+pub fn search_emojis<'a>(
+    emoji_map: &'a IndexMap<String, Emoji>,
+    emoji_usage: &HashMap<String, EmojiUsage>,
+    query: &str,
+    limit: Option<usize>,
+) -> Vec<(&'a String, &'a Emoji)> {
+    let query = query.to_lowercase();
+    let mut seen_unicodes = HashSet::new();
+
+    let mut results: Vec<(&String, &Emoji, bool)> = emoji_map
+        .iter()
+        .filter_map(|(emoji_id, emoji)| {
+            let keywords: Vec<String> = emoji.keywords.iter().map(|s| s.to_lowercase()).collect();
+            if !keywords.iter().any(|keyword| keyword.contains(&query)) {
+                return None;
+            }
+            let is_prefix = keywords.iter().any(|keyword| keyword.starts_with(&query));
+            Some((emoji_id, emoji, is_prefix))
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        emoji_score(emoji_usage.get(b.0))
+            .total_cmp(&emoji_score(emoji_usage.get(a.0)))
+            .then(b.2.cmp(&a.2))
+    });
+
+    let results = results
+        .into_iter()
+        .filter(|(_, emoji, _)| seen_unicodes.insert(emoji.unicode.clone()))
+        .map(|(emoji_id, emoji, _)| (emoji_id, emoji));
+
+    match limit {
+        Some(limit) => results.take(limit).collect(),
+        None => results.collect(),
+    }
+}
+
+// The partially typed :shortcode right before the cursor, if any
+pub fn emoji_shortcode_query(line: &str, cursor_column: usize) -> Option<String> {
+    let before_cursor: String = line.chars().take(cursor_column).collect();
+    let (prefix, query) = before_cursor.rsplit_once(':')?;
+
+    if query.chars().count() < 2
+        || !query
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '+')
+    {
+        return None;
+    }
+
+    // Times like 12:30 aren't shortcodes
+    if prefix.chars().last().is_some_and(|c| !c.is_whitespace()) {
+        return None;
+    }
+
+    Some(query.to_string())
+}
+
+// Formats a key press like the bindings in the settings, e.g. "Ctrl+Shift+A" or "Alt+ArrowUp"
+pub fn key_binding(key: &Key, modifiers: Modifiers) -> Option<String> {
+    let (key_name, shift_matters) = match key {
+        Key::Named(Named::Control | Named::Shift | Named::Alt | Named::Super) => return None,
+        Key::Named(named) => (format!("{:?}", named), true),
+        Key::Character(char) => (
+            char.to_uppercase(),
+            char.chars().all(|char| char.is_alphabetic()),
+        ),
+        _ => return None,
+    };
+
+    let mut binding = String::new();
+    if modifiers.control() {
+        binding.push_str("Ctrl+");
+    }
+    if modifiers.alt() {
+        binding.push_str("Alt+");
+    }
+    // Shift is already part of characters like "?"
+    if modifiers.shift() && shift_matters {
+        binding.push_str("Shift+");
+    }
+    binding.push_str(&key_name);
+
+    Some(binding)
+}
+
+pub fn binding_matches(binding: &str, pressed: &str) -> bool {
+    binding.replace(' ', "").eq_ignore_ascii_case(pressed)
+}
+
+// Subsequence match of the query in the candidate, favoring consecutive characters and word starts
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for query_char in query.to_lowercase().chars().filter(|char| !char.is_whitespace()) {
+        let index = position + candidate[position..].iter().position(|char| *char == query_char)?;
+
+        score += 10;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += 15;
+        }
+        if index == 0 || !candidate[index - 1].is_alphanumeric() {
+            score += 20;
+        }
+        score -= (index - position) as i64;
+
+        previous_match = Some(index);
+        position = index + 1;
+    }
+
+    Some(score)
+}
+
+// The first url of the message, only once it is followed by whitespace unless it was pasted
+pub fn first_link(text: &str, include_last: bool) -> Option<String> {
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+    if !include_last && !text.ends_with(char::is_whitespace) {
+        words.pop();
+    }
+
+    words.into_iter().find_map(|word| {
+        let start = word.find("https://").or(word.find("http://"))?;
+        let link = word[start..].trim_end_matches(|c: char| ")]>.,;:!?\"'".contains(c));
+        url::Url::parse(link)
+            .ok()
+            .filter(|parsed| parsed.host_str().is_some_and(|host| host.contains('.')))
+            .map(|_| link.to_string())
+    })
+}
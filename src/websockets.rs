@@ -33,6 +33,7 @@ struct WebsocketResponseWrapper {
 pub enum WebsocketResponse {
     Connected(ConnectionInfo),
     Message(WebsocketMessage),
+    ThreadUpdate(String), // Thread id
     Presences(Presences),
     AuthExpired,
     Other(String),
@@ -98,6 +99,18 @@ pub struct WebsocketMessage {
     pub isactive: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadUpdateEvent {
+    resource_type: String,
+    resource: ThreadUpdateResource,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ThreadUpdateResource {
+    id: String,
+}
+
 struct TrouterConnectionInfo {
     socketio: String,
     surl: String,
@@ -487,7 +500,12 @@ pub fn connect(
                                             if let Ok(wrapper) = serde_json::from_str::<WebsocketResponseWrapper>(json_content) {
                                                 let json_body = wrapper.body;
 
-                                                if let Ok(message_t) = serde_json::from_str(&json_body) {
+                                                if let Ok(event) = serde_json::from_str::<ThreadUpdateEvent>(&json_body)
+                                                    && event.resource_type == "ThreadUpdate"
+                                                {
+                                                    let _ = output.send(WebsocketResponse::ThreadUpdate(event.resource.id)).await;
+                                                }
+                                                else if let Ok(message_t) = serde_json::from_str(&json_body) {
                                                     let _ = output.send(WebsocketResponse::Message(message_t)).await;
                                                 }
                                                 else if let Ok(presences) = serde_json::from_str(&json_body) {
@@ -15,6 +15,7 @@ pub fn click_area<'a, Message: 'a>(
 pub struct ClickArea<'a, Message> {
     content: Element<'a, Message>,
     on_press: Option<Message>,
+    on_right_press: Option<Box<dyn Fn(Point) -> Message + 'a>>,
     on_enter: Option<Message>,
     on_exit: Option<Message>,
     interaction: Option<mouse::Interaction>,
@@ -33,6 +34,7 @@ impl<'a, Message> ClickArea<'a, Message> {
         ClickArea {
             content: content.into(),
             on_press: None,
+            on_right_press: None,
             on_enter: None,
             on_exit: None,
             interaction: None,
@@ -44,6 +46,12 @@ impl<'a, Message> ClickArea<'a, Message> {
         self
     }
 
+    // Gets the position of the cursor relative to the area
+    pub fn on_right_press(mut self, on_right_press: impl Fn(Point) -> Message + 'a) -> Self {
+        self.on_right_press = Some(Box::new(on_right_press));
+        self
+    }
+
     pub fn on_enter(mut self, message: Message) -> Self {
         self.on_enter = Some(message);
         self
@@ -162,6 +170,14 @@ where
                     shell.capture_event();
                 }
             }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                if let Some(on_right_press) = &self.on_right_press
+                    && let Some(position) = cursor.position_in(bounds)
+                {
+                    shell.publish(on_right_press(position));
+                    shell.capture_event();
+                }
+            }
             Event::Mouse(mouse::Event::CursorMoved { .. }) => {
                 if state.is_pressed && !cursor.is_over(bounds) {
                     state.is_pressed = false;
@@ -187,12 +203,23 @@ where
 
     fn mouse_interaction(
         &self,
-        _tree: &widget::Tree,
+        tree: &widget::Tree,
         layout: Layout<'_>,
         cursor: mouse::Cursor,
-        _viewport: &Rectangle,
-        _renderer: &Renderer,
+        viewport: &Rectangle,
+        renderer: &Renderer,
     ) -> mouse::Interaction {
+        // Only right clicks are handled, so the content keeps its own cursor
+        if self.on_press.is_none() && self.interaction.is_none() && self.on_right_press.is_some() {
+            return self.content.as_widget().mouse_interaction(
+                &tree.children[0],
+                layout,
+                cursor,
+                viewport,
+                renderer,
+            );
+        }
+
         if let Some(interaction) = self.interaction {
             if cursor.is_over(layout.bounds()) {
                 return interaction;
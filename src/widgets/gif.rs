@@ -5,21 +5,217 @@ use iced::advanced::{Clipboard, Layout, Shell, Widget, layout, renderer};
 use iced::border::Radius;
 use iced::{ContentFit, Element, Event, Length, Point, Rectangle, Rotation, Size, Vector, window};
 use image_rs::codecs::gif;
-use image_rs::{AnimationDecoder, Frame, Frames};
+use image_rs::AnimationDecoder;
+use image_rs::imageops::{self, FilterType};
+use std::collections::HashMap;
 use std::fs;
 use std::io;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, mpsc};
+use std::thread;
 use std::time::{Duration, Instant};
-struct State<'a> {
-    index: usize,
-    frames: Frames<'a>,
-    collecte_frames: Vec<Frame>,
-    current: Current,
+use xxhash_rust::xxh3::xxh3_64;
+
+const ANIMATION_CACHE_LIMIT: usize = 256_000_000; // Bytes of decoded frames
+const DECODE_THREADS: usize = 2;
+
+// A hash of the file content and the size decoded to, so the same gif at several paths
+// is only decoded once
+type AnimationKey = (u64, Option<(u32, u32)>);
+
+// Decoded animations shared by every gif widget
+static ANIMATIONS: LazyLock<Mutex<HashMap<AnimationKey, CachedAnimation>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// At most DECODE_THREADS gifs are decoded at once, the others wait in the queue
+static DECODE_QUEUE: LazyLock<mpsc::Sender<(AnimationKey, Vec<u8>)>> = LazyLock::new(|| {
+    let (sender, receiver) = mpsc::channel::<(AnimationKey, Vec<u8>)>();
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    for _ in 0..DECODE_THREADS {
+        let receiver = receiver.clone();
+        thread::spawn(move || {
+            while let Ok((key, bytes)) = receiver.lock().unwrap().recv() {
+                store_animation(key, decode_animation(bytes, key.1));
+            }
+        });
+    }
+
+    sender
+});
+
+static WINDOW_FOCUSED: AtomicBool = AtomicBool::new(true);
+
+enum CachedAnimation {
+    Decoding,
+    Decoded {
+        animation: Arc<Animation>,
+        last_used: Instant,
+    },
+    Invalid, // The file isn't a gif
+}
+
+struct Animation {
+    frames: Vec<AnimationFrame>,
+    width: u32,
+    height: u32,
+    size: usize, // In bytes
+}
+
+struct AnimationFrame {
+    handle: Handle, // Kept so the renderer only uploads each frame once
+    delay: Duration,
 }
 
-struct Current {
-    frame: Frame,
+struct State {
+    path: PathBuf,
+    key: Option<AnimationKey>, // None when the file can't be read
+    animation: Option<Arc<Animation>>, // None while decoding or when the file isn't a valid gif
+    decoding: bool,
+    index: usize,
     started: Instant,
+    paused: bool,
+}
+
+fn decode_animation(bytes: Vec<u8>, max_size: Option<(u32, u32)>) -> Option<Animation> {
+    let decoder = gif::GifDecoder::new(io::Cursor::new(bytes)).ok()?;
+    let decoded_frames = decoder.into_frames().collect_frames().ok()?;
+
+    let mut frames = Vec::with_capacity(decoded_frames.len());
+    let (mut width, mut height) = (0, 0);
+    let mut size = 0;
+
+    for frame in decoded_frames {
+        // Like browsers, treat very short delays as the default gif speed
+        let delay: Duration = frame.delay().into();
+        let delay = if delay < Duration::from_millis(20) {
+            Duration::from_millis(100)
+        } else {
+            delay
+        };
+
+        let mut buffer = frame.into_buffer();
+
+        if let Some((max_width, max_height)) = max_size
+            && (buffer.width() > max_width || buffer.height() > max_height)
+        {
+            let scale = f32::min(
+                max_width as f32 / buffer.width() as f32,
+                max_height as f32 / buffer.height() as f32,
+            );
+            let scaled_width = ((buffer.width() as f32 * scale) as u32).max(1);
+            let scaled_height = ((buffer.height() as f32 * scale) as u32).max(1);
+            buffer = imageops::resize(&buffer, scaled_width, scaled_height, FilterType::Triangle);
+        }
+
+        (width, height) = buffer.dimensions();
+        size += buffer.as_raw().len();
+
+        frames.push(AnimationFrame {
+            handle: Handle::from_rgba(width, height, buffer.into_raw()),
+            delay,
+        });
+    }
+
+    if frames.is_empty() {
+        return None;
+    }
+
+    Some(Animation {
+        frames,
+        width,
+        height,
+        size,
+    })
+}
+
+// Returns None while decoding and when the file isn't a valid gif
+fn cached_animation(key: &AnimationKey) -> Option<Arc<Animation>> {
+    let mut animations = ANIMATIONS.lock().unwrap();
+    match animations.get_mut(key) {
+        Some(CachedAnimation::Decoded {
+            animation,
+            last_used,
+        }) => {
+            *last_used = Instant::now();
+            Some(animation.clone())
+        }
+        _ => None,
+    }
+}
+
+// Frames are decoded in the background the first time an animation is requested
+fn load_animation(key: AnimationKey, bytes: Vec<u8>) -> Option<Arc<Animation>> {
+    if let Some(animation) = cached_animation(&key) {
+        return Some(animation);
+    }
+
+    // Still decoding or not a valid gif
+    let mut animations = ANIMATIONS.lock().unwrap();
+    if animations.contains_key(&key) {
+        return None;
+    }
+
+    animations.insert(key, CachedAnimation::Decoding);
+    let _ = DECODE_QUEUE.send((key, bytes));
+
+    None
+}
+
+fn store_animation(key: AnimationKey, animation: Option<Animation>) {
+    let mut animations = ANIMATIONS.lock().unwrap();
+    let Some(animation) = animation else {
+        animations.insert(key, CachedAnimation::Invalid);
+        return;
+    };
+
+    animations.insert(
+        key,
+        CachedAnimation::Decoded {
+            animation: Arc::new(animation),
+            last_used: Instant::now(),
+        },
+    );
+    evict_animations(&mut animations, &key);
+}
+
+fn is_decoding(key: &AnimationKey) -> bool {
+    matches!(
+        ANIMATIONS.lock().unwrap().get(key),
+        Some(CachedAnimation::Decoding)
+    )
+}
+
+// Widgets still showing an evicted animation keep their own reference to it
+fn evict_animations(
+    animations: &mut HashMap<AnimationKey, CachedAnimation>,
+    keep: &AnimationKey,
+) {
+    let size = |cached: &CachedAnimation| match cached {
+        CachedAnimation::Decoded { animation, .. } => animation.size,
+        _ => 0,
+    };
+
+    let mut total_size: usize = animations.values().map(size).sum();
+    while total_size > ANIMATION_CACHE_LIMIT {
+        let Some(oldest_key) = animations
+            .iter()
+            .filter(|(cached_key, _)| *cached_key != keep)
+            .filter_map(|(cached_key, cached)| match cached {
+                CachedAnimation::Decoded { last_used, .. } => Some((cached_key, last_used)),
+                _ => None,
+            })
+            .min_by_key(|(_, last_used)| **last_used)
+            .map(|(cached_key, _)| *cached_key)
+        else {
+            break;
+        };
+
+        if let Some(evicted) = animations.remove(&oldest_key) {
+            total_size -= size(&evicted);
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -101,6 +297,32 @@ impl<'a> Gif {
         self.crop = Some(region);
         self
     }
+
+    // Frames are decoded at twice a fixed size so they stay sharp on scaled displays
+    fn max_size(&self) -> Option<(u32, u32)> {
+        match (self.width, self.height) {
+            (Length::Fixed(width), Length::Fixed(height)) => {
+                Some(((width * 2.0) as u32, (height * 2.0) as u32))
+            }
+            _ => None,
+        }
+    }
+
+    fn new_state(&self) -> State {
+        let bytes = fs::read(&self.path).ok();
+        let key = bytes.as_ref().map(|bytes| (xxh3_64(bytes), self.max_size()));
+        let animation = key.zip(bytes).and_then(|(key, bytes)| load_animation(key, bytes));
+
+        State {
+            decoding: animation.is_none() && key.as_ref().is_some_and(is_decoding),
+            path: self.path.clone(),
+            key,
+            animation,
+            index: 0,
+            started: Instant::now(),
+            paused: true,
+        }
+    }
 }
 
 fn crop(size: Size<u32>, region: Option<Rectangle<u32>>) -> Size<f32> {
@@ -196,40 +418,31 @@ where
     }
 
     fn state(&self) -> tree::State {
-        let bytes = fs::read(self.path.clone()).unwrap();
-        let decoder = gif::GifDecoder::new(io::Cursor::new(bytes)).unwrap();
-        let mut frames = decoder.into_frames();
-
-        let frame = frames.by_ref().nth(0).unwrap().unwrap();
+        tree::State::new(self.new_state())
+    }
 
-        tree::State::new(State {
-            index: 0,
-            frames,
-            collecte_frames: Vec::new(),
-            current: Current {
-                frame,
-                started: Instant::now(),
-            },
-        })
+    fn diff(&self, tree: &mut Tree) {
+        // The same position in the widget tree can show another gif after a rebuild
+        if tree.state.downcast_ref::<State>().path != self.path {
+            tree.state = tree::State::new(self.new_state());
+        }
     }
 
     fn layout(
         &mut self,
         tree: &mut Tree,
-        renderer: &Renderer,
+        _renderer: &Renderer,
         limits: &layout::Limits,
     ) -> layout::Node {
-        let state = tree.state.downcast_mut::<State>();
+        let state = tree.state.downcast_ref::<State>();
 
-        let current = state.current.frame.clone();
-        let (width, height) = current.buffer().dimensions();
-        let handle = image::Handle::from_rgba(width, height, current.into_buffer().into_vec());
+        let Some(animation) = &state.animation else {
+            return layout::Node::new(Size::ZERO);
+        };
+        let (width, height) = (animation.width, animation.height);
 
         // The raw w/h of the underlying image
-        let image_size = crop(
-            renderer.measure_image(&handle).unwrap_or_default(),
-            self.crop,
-        );
+        let image_size = crop(Size::new(animation.width, animation.height), self.crop);
 
         // The rotated size of the image
         let rotated_size = self.rotation.apply(image_size);
@@ -266,27 +479,60 @@ where
     ) {
         let state = tree.state.downcast_mut::<State>();
 
+        match event {
+            Event::Window(window::Event::Focused) => WINDOW_FOCUSED.store(true, Ordering::Relaxed),
+            Event::Window(window::Event::Unfocused) => {
+                WINDOW_FOCUSED.store(false, Ordering::Relaxed)
+            }
+            _ => {}
+        }
+
+        // Checked again on every redraw until the frames are decoded
+        if state.decoding
+            && let Some(key) = &state.key
+        {
+            state.animation = cached_animation(key);
+            state.decoding = state.animation.is_none() && is_decoding(key);
+
+            if state.animation.is_some() {
+                shell.invalidate_layout();
+                shell.request_redraw();
+            } else if state.decoding {
+                shell.request_redraw_at(window::RedrawRequest::At(
+                    Instant::now() + Duration::from_millis(50),
+                ));
+            }
+        }
+
+        let Some(animation) = state.animation.clone() else {
+            return;
+        };
+        if animation.frames.len() < 2 {
+            return;
+        }
+
+        // Stop requesting redraws in the background. Scrolled out of view, the gif stops getting
+        // redraws from the ViewportHandler around it
+        if !WINDOW_FOCUSED.load(Ordering::Relaxed) {
+            state.paused = true;
+            return;
+        }
+
+        if state.paused {
+            state.paused = false;
+            state.started = Instant::now();
+            shell.request_redraw();
+            return;
+        }
+
         if let Event::Window(window::Event::RedrawRequested(now)) = event {
-            let elapsed = now.duration_since(state.current.started);
-            let delay: Duration = state.current.frame.delay().into();
+            let elapsed = now.duration_since(state.started);
+            let delay = animation.frames[state.index].delay;
             if elapsed > delay {
-                // Take all the frames during the first run
-                let next_frame = if let Some(frame_result) = state.frames.next() {
-                    let frame = frame_result.unwrap();
-                    state.collecte_frames.push(frame.clone());
-                    frame
-                } else {
-                    state.index = (state.index + 1) % state.collecte_frames.len();
-                    state.collecte_frames[state.index].clone()
-                };
-
-                let delay: Duration = next_frame.delay().into();
-
-                state.current = Current {
-                    frame: next_frame,
-                    started: Instant::now(),
-                };
+                state.index = (state.index + 1) % animation.frames.len();
+                state.started = *now;
 
+                let delay = animation.frames[state.index].delay;
                 shell.request_redraw_at(window::RedrawRequest::At(*now + delay));
             } else {
                 let remaining = delay - elapsed;
@@ -310,10 +556,8 @@ where
         // Pulled from iced_native::widget::<Image as Widget>::draw
         //
         // TODO: export iced_native::widget::image::draw as standalone function
-        {
-            let frame = state.current.frame.clone();
-            let (width, height) = frame.buffer().dimensions();
-            let handle = image::Handle::from_rgba(width, height, frame.into_buffer().into_vec());
+        if let Some(animation) = &state.animation {
+            let handle = animation.frames[state.index].handle.clone();
 
             let bounds = layout.bounds();
 
@@ -5,4 +5,5 @@ pub mod click_area;
 pub mod gif;
 pub mod selectable_rich_text;
 pub mod selectable_text;
+pub mod spelling_underline;
 pub mod viewport;
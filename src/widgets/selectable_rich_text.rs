@@ -142,26 +142,6 @@ where
         self
     }
 
-    /// Sets the default [`Color`] of the [`Rich`] text.
-    pub fn color(self, color: impl Into<Color>) -> Self
-    where
-        Theme::Class<'a>: From<StyleFn<'a, Theme>>,
-    {
-        self.color_maybe(Some(color))
-    }
-
-    /// Sets the default [`Color`] of the [`Rich`] text, if `Some`.
-    pub fn color_maybe(self, color: Option<impl Into<Color>>) -> Self
-    where
-        Theme::Class<'a>: From<StyleFn<'a, Theme>>,
-    {
-        let color = color.map(Into::into);
-
-        self.style(move |_theme| Style {
-            color,
-            ..Style::default()
-        })
-    }
 
     /// Sets the message handler for link clicks on the [`Rich`] text.
     pub fn on_link(mut self, on_link: impl Fn(Link) -> Message + 'a) -> Self {
@@ -515,15 +515,6 @@ pub struct Style {
     pub selection_color: Color,
 }
 
-impl Default for Style {
-    fn default() -> Self {
-        Self {
-            color: None,
-            selection_color: Color::WHITE,
-        }
-    }
-}
-
 /// The theme catalog of a [`Text`].
 pub trait Catalog: Sized {
     /// The item class of this [`Catalog`].
@@ -545,7 +536,10 @@ impl Catalog for iced::Theme {
     type Class<'a> = StyleFn<'a, Self>;
 
     fn default<'a>() -> Self::Class<'a> {
-        Box::new(|_theme| Style::default())
+        Box::new(|theme| Style {
+            color: None,
+            selection_color: theme.palette().primary,
+        })
     }
 
     fn style(&self, class: &Self::Class<'_>) -> Style {
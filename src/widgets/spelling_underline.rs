@@ -0,0 +1,264 @@
+use iced::advanced::text::editor::{Cursor, Editor as _, Position, Selection};
+use iced::advanced::text::highlighter::Highlighter as _;
+use iced::advanced::text::{LineHeight, Renderer as TextRenderer, Wrapping};
+use iced::advanced::Renderer as _;
+use iced::advanced::{
+    Clipboard, Layout, Shell, Widget, layout, mouse, overlay, renderer, widget,
+};
+use iced::{Background, Color, Element, Event, Length, Point, Rectangle, Renderer, Size, Vector};
+use std::cell::RefCell;
+use std::ops::Range;
+
+use crate::Theme;
+use crate::rich_text::{HighlightSettings, RichHighlighter, to_format};
+
+// The padding of the text editor
+const EDITOR_PADDING: f32 = 5.0;
+
+// The iced editor can't underline its text, so misspelled words are underlined on top of it.
+// They are found in a second editor laid out like the one shown, which has to be unscrolled
+pub fn spelling_underline<'a, Message: 'a>(
+    editor: impl Into<Element<'a, Message>>,
+    text: String,
+    settings: HighlightSettings,
+    misspelled: Vec<Vec<Range<usize>>>,
+    color: Color,
+) -> SpellingUnderline<'a, Message> {
+    SpellingUnderline {
+        editor: editor.into(),
+        text,
+        settings,
+        misspelled,
+        color,
+    }
+}
+
+pub struct SpellingUnderline<'a, Message> {
+    editor: Element<'a, Message>,
+    text: String,
+    settings: HighlightSettings,
+    misspelled: Vec<Vec<Range<usize>>>,
+    color: Color,
+}
+
+#[derive(PartialEq)]
+struct Key {
+    text: String,
+    settings: HighlightSettings,
+    misspelled: Vec<Vec<Range<usize>>>,
+    size: Size,
+}
+
+// The underlines are only laid out again when the text or the editor size changes
+#[derive(Default)]
+struct State {
+    underlines: RefCell<Option<(Key, Vec<Rectangle>)>>,
+}
+
+impl<Message> SpellingUnderline<'_, Message> {
+    fn underlines(&self, renderer: &Renderer, theme: &Theme, size: Size) -> Vec<Rectangle> {
+        let mut editor = <Renderer as TextRenderer>::Editor::with_text(&self.text);
+        let mut highlighter = RichHighlighter::new(&self.settings);
+        let font = renderer.default_font();
+
+        editor.update(
+            size,
+            font,
+            renderer.default_size(),
+            LineHeight::default(),
+            Wrapping::default(),
+            &mut highlighter,
+        );
+        editor.highlight(font, &mut highlighter, |highlight| to_format(highlight, theme));
+
+        let mut underlines = vec![];
+        for (line, ranges) in self.misspelled.iter().enumerate() {
+            for range in ranges {
+                editor.move_to(Cursor {
+                    position: Position {
+                        line,
+                        column: range.end,
+                    },
+                    selection: Some(Position {
+                        line,
+                        column: range.start,
+                    }),
+                });
+
+                if let Selection::Range(rectangles) = editor.selection() {
+                    underlines.extend(rectangles.into_iter().map(|rectangle| Rectangle {
+                        y: rectangle.y + rectangle.height - 2.0,
+                        height: 1.0,
+                        ..rectangle
+                    }));
+                }
+            }
+        }
+
+        underlines
+    }
+}
+
+impl<Message> Widget<Message, Theme, Renderer> for SpellingUnderline<'_, Message> {
+    fn tag(&self) -> widget::tree::Tag {
+        widget::tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> widget::tree::State {
+        widget::tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<widget::Tree> {
+        vec![widget::Tree::new(&self.editor)]
+    }
+
+    fn diff(&self, tree: &mut widget::Tree) {
+        tree.diff_children(std::slice::from_ref(&self.editor));
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.editor.as_widget().size()
+    }
+
+    fn layout(
+        &mut self,
+        tree: &mut widget::Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.editor
+            .as_widget_mut()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &mut self,
+        tree: &mut widget::Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn widget::Operation<()>,
+    ) {
+        self.editor
+            .as_widget_mut()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn update(
+        &mut self,
+        tree: &mut widget::Tree,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) {
+        self.editor.as_widget_mut().update(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &widget::Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.editor.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &widget::Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.editor.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+
+        let text_bounds = layout.bounds().shrink(EDITOR_PADDING);
+        let key = Key {
+            text: self.text.clone(),
+            settings: self.settings.clone(),
+            misspelled: self.misspelled.clone(),
+            size: text_bounds.size(),
+        };
+
+        let state = tree.state.downcast_ref::<State>();
+        let mut underlines = state.underlines.borrow_mut();
+        if underlines.as_ref().is_none_or(|(cached_key, _)| *cached_key != key) {
+            let rectangles = self.underlines(renderer, theme, key.size);
+            *underlines = Some((key, rectangles));
+        }
+
+        let Some((_, rectangles)) = underlines.as_ref() else {
+            return;
+        };
+
+        let translation = text_bounds.position() - Point::ORIGIN;
+        for rectangle in rectangles {
+            if let Some(bounds) = text_bounds.intersection(&(*rectangle + translation)) {
+                renderer.fill_quad(
+                    renderer::Quad {
+                        bounds,
+                        ..renderer::Quad::default()
+                    },
+                    Background::Color(self.color),
+                );
+            }
+        }
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut widget::Tree,
+        layout: Layout<'b>,
+        renderer: &Renderer,
+        viewport: &Rectangle,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        self.editor.as_widget_mut().overlay(
+            &mut tree.children[0],
+            layout,
+            renderer,
+            viewport,
+            translation,
+        )
+    }
+}
+
+impl<'a, Message> From<SpellingUnderline<'a, Message>> for Element<'a, Message>
+where
+    Message: 'a,
+{
+    fn from(spelling_underline: SpellingUnderline<'a, Message>) -> Self {
+        Element::new(spelling_underline)
+    }
+}
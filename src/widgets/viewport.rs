@@ -15,6 +15,12 @@ pub struct ViewportHandler<'a, Message, Theme, Renderer> {
     content: Element<'a, Message, Theme, Renderer>,
     identifier: Option<String>,
     on_enter: Option<Message>,
+    pause_when_hidden: bool,
+}
+
+// Lets the message of the identifier be called again, e.g. for an image that was removed
+pub fn forget_identifier(identifier: &str) {
+    IDENTIFIERS.lock().unwrap().remove(identifier);
 }
 
 impl<'a, Message, Theme, Renderer> ViewportHandler<'a, Message, Theme, Renderer> {
@@ -23,9 +29,16 @@ impl<'a, Message, Theme, Renderer> ViewportHandler<'a, Message, Theme, Renderer>
             content: content.into(),
             identifier: None,
             on_enter: None,
+            pause_when_hidden: false,
         }
     }
 
+    // Withhold redraws from the content while it is out of the viewport, which pauses animations
+    pub fn pause_when_hidden(mut self) -> Self {
+        self.pause_when_hidden = true;
+        self
+    }
+
     // Call a message once for all widgets with the identifier when the widget enters the viewport
     pub fn on_enter_unique(mut self, identifier: String, message: Message) -> Self {
         self.identifier = Some(identifier.clone());
@@ -122,6 +135,13 @@ where
             }
         }
 
+        if self.pause_when_hidden
+            && let iced::Event::Window(iced::window::Event::RedrawRequested(_)) = event
+            && !layout.bounds().intersects(viewport)
+        {
+            return;
+        }
+
         self.content.as_widget_mut().update(
             tree,
             event,